@@ -0,0 +1,234 @@
+//! Benchmarks of the comparison, the sort keys and the sorting, by comparisons and by radix, of
+//! the corpora of `collate_rs::test_support`, against `str::cmp` and, with the `bench-icu`
+//! feature, the collator of ICU4X, and of the loading of the DUCET. The comparisons of `str`s are
+//! also measured against the ones of the same strings in UTF-16 and in UTF-8 bytes to validate.
+//!
+//! Run `cargo bench --features test-support -- --save-baseline main` before a change and
+//! `cargo bench --features test-support -- --baseline main` after it to report the regressions.
+
+use collate_rs::algorithm::Collator;
+use collate_rs::bulk;
+use collate_rs::data_files::parse_allkeys;
+use collate_rs::ducet;
+use collate_rs::test_support::Corpus;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+/// The number of words of each corpus.
+const WORDS: usize = 1000;
+
+const SEED: u64 = 0x5EED;
+
+fn corpora() -> impl Iterator<Item = (Corpus, Vec<String>)> {
+    Corpus::ALL
+        .into_iter()
+        .map(|corpus| (corpus, corpus.generate(WORDS, SEED)))
+}
+
+/// Compares each word with the next one.
+fn bench_compare(c: &mut Criterion) {
+    let collator = Collator::try_new("und").unwrap();
+    #[cfg(feature = "bench-icu")]
+    let icu = icu_collator::Collator::try_new(Default::default(), Default::default()).unwrap();
+    let mut group = c.benchmark_group("compare");
+    for (corpus, words) in corpora() {
+        group.throughput(Throughput::Elements(words.len() as u64 - 1));
+        group.bench_with_input(
+            BenchmarkId::new("collate-rs", corpus.name()),
+            &words,
+            |b, words| {
+                b.iter(|| {
+                    for pair in words.windows(2) {
+                        criterion::black_box(collator.compare(&pair[0], &pair[1]));
+                    }
+                })
+            },
+        );
+        let utf16: Vec<Vec<u16>> = words.iter().map(|w| w.encode_utf16().collect()).collect();
+        group.bench_with_input(
+            BenchmarkId::new("compare_utf16", corpus.name()),
+            &utf16,
+            |b, words| {
+                b.iter(|| {
+                    for pair in words.windows(2) {
+                        criterion::black_box(collator.compare_utf16(&pair[0], &pair[1]).unwrap());
+                    }
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("compare_utf8", corpus.name()),
+            &words,
+            |b, words| {
+                b.iter(|| {
+                    for pair in words.windows(2) {
+                        let (a, b) = (pair[0].as_bytes(), pair[1].as_bytes());
+                        criterion::black_box(collator.compare_utf8(a, b).unwrap());
+                    }
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("str::cmp", corpus.name()),
+            &words,
+            |b, words| {
+                b.iter(|| {
+                    for pair in words.windows(2) {
+                        criterion::black_box(pair[0].cmp(&pair[1]));
+                    }
+                })
+            },
+        );
+        #[cfg(feature = "bench-icu")]
+        group.bench_with_input(
+            BenchmarkId::new("icu_collator", corpus.name()),
+            &words,
+            |b, words| {
+                b.iter(|| {
+                    for pair in words.windows(2) {
+                        criterion::black_box(icu.compare(&pair[0], &pair[1]));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_sort_key(c: &mut Criterion) {
+    let collator = Collator::try_new("und").unwrap();
+    let mut group = c.benchmark_group("sort_key");
+    for (corpus, words) in corpora() {
+        group.throughput(Throughput::Elements(words.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("collate-rs", corpus.name()),
+            &words,
+            |b, words| {
+                b.iter(|| {
+                    for word in words {
+                        criterion::black_box(collator.sort_key(word));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_sort(c: &mut Criterion) {
+    let collator = Collator::try_new("und").unwrap();
+    #[cfg(feature = "bench-icu")]
+    let icu = icu_collator::Collator::try_new(Default::default(), Default::default()).unwrap();
+    let mut group = c.benchmark_group("sort");
+    group.sample_size(20);
+    for (corpus, words) in corpora() {
+        group.throughput(Throughput::Elements(words.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("collate-rs", corpus.name()),
+            &words,
+            |b, words| {
+                b.iter_batched_ref(
+                    || words.clone(),
+                    |words| collator.sort_slice(words),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("str::cmp", corpus.name()),
+            &words,
+            |b, words| {
+                b.iter_batched_ref(
+                    || words.clone(),
+                    |words| words.sort(),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+        #[cfg(feature = "bench-icu")]
+        group.bench_with_input(
+            BenchmarkId::new("icu_collator", corpus.name()),
+            &words,
+            |b, words| {
+                b.iter_batched_ref(
+                    || words.clone(),
+                    |words| words.sort_by(|a, b| icu.compare(a, b)),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Sorts growing numbers of words, and of their sort keys, by comparisons and by a radix sort of
+/// the bytes of the keys, to locate the crossover of the two.
+fn bench_radix_sort(c: &mut Criterion) {
+    let collator = Collator::try_new("und").unwrap();
+    let mut group = c.benchmark_group("radix_sort");
+    group.sample_size(10);
+    for len in [16, 64, 256, 1_000, 10_000, 100_000] {
+        let words = Corpus::Latin1.generate(len, SEED);
+        let keys: Vec<Vec<u8>> = words
+            .iter()
+            .map(|word| collator.sort_key(word).into_bytes())
+            .collect();
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::new("sort_slice", len), &words, |b, words| {
+            b.iter_batched_ref(
+                || words.clone(),
+                |words| collator.sort_slice(words),
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(
+            BenchmarkId::new("radix_sort_slice", len),
+            &words,
+            |b, words| {
+                b.iter_batched_ref(
+                    || words.clone(),
+                    |words| bulk::radix_sort_slice(&collator, words),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("keys/sort", len), &keys, |b, keys| {
+            b.iter_batched_ref(|| keys.clone(), |keys| keys.sort(), BatchSize::LargeInput)
+        });
+        group.bench_with_input(
+            BenchmarkId::new("keys/radix_sort_keys", len),
+            &keys,
+            |b, keys| {
+                b.iter_batched_ref(
+                    || keys.clone(),
+                    |keys| bulk::radix_sort_keys(keys),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Parses allkeys.txt, decodes the bundled binary table and builds a collator of the DUCET.
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load");
+    group.sample_size(10);
+    group.bench_function("parse_allkeys", |b| {
+        b.iter(|| parse_allkeys(ducet::ALLKEYS).unwrap())
+    });
+    group.bench_function("ducet::table", |b| b.iter(ducet::table));
+    group.bench_function("Collator::new", |b| {
+        b.iter(|| Collator::new(ducet::table()))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_compare,
+    bench_sort_key,
+    bench_sort,
+    bench_radix_sort,
+    bench_load
+);
+criterion_main!(benches);
@@ -0,0 +1,432 @@
+//! Data generation pipeline of the `datagen` feature.
+//!
+//! With the `datagen` feature, the Unicode data baked into the crate is generated at build time
+//! from the files of the directory named by `COLLATE_RS_UCD_DIR`:
+//!
+//! - `allkeys.txt`, copied to `$OUT_DIR/allkeys.txt` and used as the DUCET;
+//! - `DerivedCombiningClass.txt` and `UnicodeData.txt`, from which the canonical combining classes,
+//!   the full canonical decompositions and the general categories are written as Rust source in
+//!   `$OUT_DIR/ucd_tables.rs`;
+//! - `PropList.txt` and `Blocks.txt`, from which the ranges of the implicit weights are written
+//!   there too: the Unified_Ideograph code points and the core Han blocks, next to the siniform
+//!   scripts of the `@implicitweights` lines of `allkeys.txt`.
+//!
+//! The version of `allkeys.txt` must be one of the `UnicodeVersion`s of the crate, and becomes the
+//! version of the bundled DUCET. Setting `COLLATE_RS_UNICODE_VERSION` (e.g. `15.1.0`) pins the
+//! Unicode version of all the data: the build fails if the version declared by any of the files
+//! differs from it.
+//!
+//! Without the feature, the pregenerated `src/data_files/ucd_tables.rs` and `data/allkeys.txt`
+//! are used instead. To refresh them, build with the feature and copy the generated files over.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The versions of `collate_rs::ducet::UnicodeVersion`.
+const SUPPORTED_VERSIONS: [&str; 5] = ["13.0.0", "14.0.0", "15.0.0", "15.1.0", "16.0.0"];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_DATAGEN").is_none() {
+        return;
+    }
+    println!("cargo:rerun-if-env-changed=COLLATE_RS_UCD_DIR");
+    println!("cargo:rerun-if-env-changed=COLLATE_RS_UNICODE_VERSION");
+    if let Err(e) = generate() {
+        panic!("collate-rs datagen failed: {}", e);
+    }
+}
+
+fn generate() -> Result<(), String> {
+    let ucd_dir = PathBuf::from(env::var("COLLATE_RS_UCD_DIR").map_err(|_| {
+        "the datagen feature requires COLLATE_RS_UCD_DIR to name a directory with allkeys.txt, \
+         DerivedCombiningClass.txt, UnicodeData.txt, PropList.txt and Blocks.txt"
+            .to_string()
+    })?);
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("Set by cargo."));
+    let pinned = env::var("COLLATE_RS_UNICODE_VERSION").ok();
+
+    let allkeys = read(&ucd_dir.join("allkeys.txt"))?;
+    let allkeys_version = allkeys
+        .lines()
+        .find_map(|line| line.strip_prefix("@version "))
+        .map(|v| v.trim().to_string())
+        .ok_or("allkeys.txt has no @version line")?;
+
+    if !SUPPORTED_VERSIONS.contains(&allkeys_version.as_str()) {
+        return Err(format!(
+            "allkeys.txt is version {}, which is not one of the supported versions {:?}",
+            allkeys_version, SUPPORTED_VERSIONS
+        ));
+    }
+
+    let combining_classes = read(&ucd_dir.join("DerivedCombiningClass.txt"))?;
+    let ucd_version = header_version(&combining_classes, "DerivedCombiningClass")?;
+    let prop_list = read(&ucd_dir.join("PropList.txt"))?;
+    let blocks = read(&ucd_dir.join("Blocks.txt"))?;
+
+    if let Some(pinned) = &pinned {
+        for (file, version) in [
+            ("allkeys.txt", &allkeys_version),
+            ("DerivedCombiningClass.txt", &ucd_version),
+            ("PropList.txt", &header_version(&prop_list, "PropList")?),
+            ("Blocks.txt", &header_version(&blocks, "Blocks")?),
+        ] {
+            if version != pinned {
+                return Err(format!(
+                    "{} is version {}, but COLLATE_RS_UNICODE_VERSION pins {}",
+                    file, version, pinned
+                ));
+            }
+        }
+    } else if allkeys_version != ucd_version {
+        println!(
+            "cargo:warning=allkeys.txt is version {} but the UCD files are version {}",
+            allkeys_version, ucd_version
+        );
+    }
+
+    let unicode_data = read(&ucd_dir.join("UnicodeData.txt"))?;
+    let tables = ucd_tables(
+        &ucd_version,
+        &parse_combining_classes(&combining_classes)?,
+        &parse_decompositions(&unicode_data)?,
+        &parse_general_categories(&unicode_data)?,
+        &ImplicitRanges {
+            unified_ideographs: parse_unified_ideographs(&prop_list)?,
+            core_han_blocks: parse_core_han_blocks(&blocks)?,
+            siniform_scripts: parse_implicit_weights(&allkeys)?,
+        },
+    );
+
+    write(&out_dir.join("allkeys.txt"), &allkeys)?;
+    println!(
+        "cargo:rustc-env=COLLATE_RS_DUCET_VERSION={}",
+        allkeys_version
+    );
+    write(&out_dir.join("ucd_tables.rs"), &tables)?;
+    for file in [
+        "allkeys.txt",
+        "DerivedCombiningClass.txt",
+        "UnicodeData.txt",
+        "PropList.txt",
+        "Blocks.txt",
+    ] {
+        println!("cargo:rerun-if-changed={}", ucd_dir.join(file).display());
+    }
+    Ok(())
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path.display(), e))
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("could not write {}: {}", path.display(), e))
+}
+
+fn parse_code_point(s: &str) -> Result<u32, String> {
+    u32::from_str_radix(s.trim(), 16).map_err(|_| format!("invalid code point {:?}", s))
+}
+
+/// Parses a `<first>..<last>` range of code points, or a single code point.
+fn parse_range(s: &str) -> Result<(u32, u32), String> {
+    match s.split_once("..") {
+        Some((first, last)) => Ok((parse_code_point(first)?, parse_code_point(last)?)),
+        None => Ok((parse_code_point(s)?, parse_code_point(s)?)),
+    }
+}
+
+/// The version of the `# <name>-<version>.txt` first line of a file of the UCD.
+fn header_version(s: &str, name: &str) -> Result<String, String> {
+    s.lines()
+        .next()
+        .and_then(|line| line.trim().strip_prefix("# "))
+        .and_then(|line| line.strip_prefix(name))
+        .and_then(|line| line.strip_prefix('-'))
+        .and_then(|line| line.strip_suffix(".txt"))
+        .map(str::to_string)
+        .ok_or_else(|| format!("{}.txt has no version header", name))
+}
+
+/// The ranges of code points of the implicit weights (UTS #10, 10.1.3).
+struct ImplicitRanges {
+    unified_ideographs: Vec<(u32, u32)>,
+    core_han_blocks: Vec<(u32, u32)>,
+    /// `(first, last, base, offset)`, the offset being the first code point of the first range of
+    /// the base.
+    siniform_scripts: Vec<(u32, u32, u32, u32)>,
+}
+
+/// Parses the `<code point or range> ; Unified_Ideograph` lines of PropList.txt, merging the
+/// adjacent ranges.
+fn parse_unified_ideographs(s: &str) -> Result<Vec<(u32, u32)>, String> {
+    let mut ranges: Vec<(u32, u32)> = vec![];
+    for line in s.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((range, property)) = line.split_once(';') else {
+            continue;
+        };
+        if property.trim() != "Unified_Ideograph" {
+            continue;
+        }
+        ranges.push(parse_range(range)?);
+    }
+    ranges.sort_unstable();
+    ranges.dedup_by(|(first, last), (_, end)| {
+        let adjacent = *end + 1 == *first;
+        if adjacent {
+            *end = *last;
+        }
+        adjacent
+    });
+    if ranges.is_empty() {
+        return Err("PropList.txt has no Unified_Ideograph line".to_string());
+    }
+    Ok(ranges)
+}
+
+/// Parses the ranges of the CJK Unified Ideographs and CJK Compatibility Ideographs blocks of
+/// Blocks.txt.
+fn parse_core_han_blocks(s: &str) -> Result<Vec<(u32, u32)>, String> {
+    let mut ranges = vec![];
+    for line in s.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((range, name)) = line.split_once(';') else {
+            continue;
+        };
+        if matches!(
+            name.trim(),
+            "CJK Unified Ideographs" | "CJK Compatibility Ideographs"
+        ) {
+            ranges.push(parse_range(range)?);
+        }
+    }
+    match ranges.len() {
+        2 => Ok(ranges),
+        _ => Err("Blocks.txt lacks a core Han block".to_string()),
+    }
+}
+
+/// Parses the `@implicitweights <range>; <base>` lines of allkeys.txt.
+fn parse_implicit_weights(s: &str) -> Result<Vec<(u32, u32, u32, u32)>, String> {
+    let mut scripts: Vec<(u32, u32, u32, u32)> = vec![];
+    for line in s.lines() {
+        let Some(line) = line.strip_prefix("@implicitweights ") else {
+            continue;
+        };
+        let line = line.split('#').next().unwrap_or_default();
+        let Some((range, base)) = line.split_once(';') else {
+            return Err(format!("ill-formed @implicitweights line {:?}", line));
+        };
+        let (first, last) = parse_range(range)?;
+        let base = parse_code_point(base)?;
+        let offset = scripts
+            .iter()
+            .find(|script| script.2 == base)
+            .map_or(first, |script| script.3);
+        scripts.push((first, last, base, offset));
+    }
+    Ok(scripts)
+}
+
+/// Parses the `<code point or range> ; <class>` lines of DerivedCombiningClass.txt, keeping the
+/// non-zero classes.
+fn parse_combining_classes(s: &str) -> Result<BTreeMap<u32, u8>, String> {
+    let mut classes = BTreeMap::new();
+    for line in s.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((range, class)) = line.split_once(';') else {
+            continue;
+        };
+        let class: u8 = class
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid combining class {:?}", class))?;
+        let (first, last) = parse_range(range)?;
+        if class != 0 {
+            classes.extend((first..=last).map(|cp| (cp, class)));
+        }
+    }
+    Ok(classes)
+}
+
+/// Parses the canonical decompositions (field 5 without a `<tag>`) of UnicodeData.txt and
+/// decomposes them fully.
+fn parse_decompositions(s: &str) -> Result<BTreeMap<u32, Vec<u32>>, String> {
+    let mut single = BTreeMap::new();
+    for line in s.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split(';').collect();
+        let (Some(cp), Some(decomposition)) = (fields.first(), fields.get(5)) else {
+            return Err(format!("ill-formed UnicodeData.txt line {:?}", line));
+        };
+        if decomposition.is_empty() || decomposition.starts_with('<') {
+            continue;
+        }
+        let decomposition = decomposition
+            .split_whitespace()
+            .map(parse_code_point)
+            .collect::<Result<Vec<_>, _>>()?;
+        single.insert(parse_code_point(cp)?, decomposition);
+    }
+    fn decompose(cp: u32, single: &BTreeMap<u32, Vec<u32>>, full: &mut Vec<u32>) {
+        match single.get(&cp) {
+            Some(decomposition) => decomposition
+                .iter()
+                .for_each(|&cp| decompose(cp, single, full)),
+            None => full.push(cp),
+        }
+    }
+    Ok(single
+        .keys()
+        .map(|&cp| {
+            let mut full = vec![];
+            decompose(cp, &single, &mut full);
+            (cp, full)
+        })
+        .collect())
+}
+
+/// Parses the general categories (field 2) of UnicodeData.txt into ranges of code points, the
+/// ranges written as `<..., First>` and `<..., Last>` lines included. The unassigned code points
+/// (`Cn`) are left out.
+fn parse_general_categories(s: &str) -> Result<Vec<(u32, u32, String)>, String> {
+    let mut ranges: Vec<(u32, u32, String)> = vec![];
+    for line in s.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split(';').collect();
+        let (Some(cp), Some(name), Some(category)) = (fields.first(), fields.get(1), fields.get(2))
+        else {
+            return Err(format!("ill-formed UnicodeData.txt line {:?}", line));
+        };
+        let cp = parse_code_point(cp)?;
+        match ranges.last_mut() {
+            Some((_, last, c))
+                if c == category && (*last + 1 == cp || name.ends_with(", Last>")) =>
+            {
+                *last = cp
+            }
+            _ => ranges.push((cp, cp, category.to_string())),
+        }
+    }
+    Ok(ranges)
+}
+
+fn ucd_tables(
+    version: &str,
+    classes: &BTreeMap<u32, u8>,
+    decompositions: &BTreeMap<u32, Vec<u32>>,
+    categories: &[(u32, u32, String)],
+    implicit: &ImplicitRanges,
+) -> String {
+    let mut ranges: Vec<(u32, u32, u8)> = vec![];
+    for (&cp, &class) in classes {
+        match ranges.last_mut() {
+            Some((_, last, c)) if *last + 1 == cp && *c == class => *last = cp,
+            _ => ranges.push((cp, cp, class)),
+        }
+    }
+
+    let mut s = String::new();
+    writeln!(
+        s,
+        "// Generated by build.rs from the Unicode Character Database {}. Do not edit.\n",
+        version
+    )
+    .unwrap();
+    writeln!(
+        s,
+        "/// Version of the Unicode Character Database of the tables."
+    )
+    .unwrap();
+    writeln!(s, "pub const UNICODE_VERSION: &str = {:?};\n", version).unwrap();
+    writeln!(
+        s,
+        "/// Ranges of code points with a non-zero canonical combining class: `(first, last, class)`."
+    )
+    .unwrap();
+    writeln!(
+        s,
+        "pub(crate) const CANONICAL_COMBINING_CLASSES: &[(u32, u32, u8)] = &["
+    )
+    .unwrap();
+    for (first, last, class) in ranges {
+        writeln!(s, "    (0x{:04X}, 0x{:04X}, {}),", first, last, class).unwrap();
+    }
+    writeln!(s, "];\n").unwrap();
+    writeln!(
+        s,
+        "/// Full canonical decompositions, excluding the Hangul syllables."
+    )
+    .unwrap();
+    writeln!(
+        s,
+        "pub(crate) const CANONICAL_DECOMPOSITIONS: &[(u32, &[u32])] = &["
+    )
+    .unwrap();
+    for (cp, decomposition) in decompositions {
+        let decomposition: Vec<String> = decomposition
+            .iter()
+            .map(|cp| format!("0x{:04X}", cp))
+            .collect();
+        writeln!(s, "    (0x{:04X}, &[{}]),", cp, decomposition.join(", ")).unwrap();
+    }
+    writeln!(s, "];\n").unwrap();
+    writeln!(
+        s,
+        "/// Ranges of code points of the same general category, but `Cn`: `(first, last, category)`."
+    )
+    .unwrap();
+    writeln!(
+        s,
+        "pub(crate) const GENERAL_CATEGORIES: &[(u32, u32, &str)] = &["
+    )
+    .unwrap();
+    for (first, last, category) in categories {
+        writeln!(s, "    (0x{:04X}, 0x{:04X}, {:?}),", first, last, category).unwrap();
+    }
+    writeln!(s, "];\n").unwrap();
+    writeln!(
+        s,
+        "/// Ranges of code points with the Unified_Ideograph property: `(first, last)`."
+    )
+    .unwrap();
+    writeln!(s, "pub(crate) const UNIFIED_IDEOGRAPHS: &[(u32, u32)] = &[").unwrap();
+    for (first, last) in &implicit.unified_ideographs {
+        writeln!(s, "    (0x{:04X}, 0x{:04X}),", first, last).unwrap();
+    }
+    writeln!(s, "];\n").unwrap();
+    writeln!(
+        s,
+        "/// Ranges of the CJK Unified Ideographs and CJK Compatibility Ideographs blocks: `(first, last)`."
+    )
+    .unwrap();
+    writeln!(s, "pub(crate) const CORE_HAN_BLOCKS: &[(u32, u32)] = &[").unwrap();
+    for (first, last) in &implicit.core_han_blocks {
+        writeln!(s, "    (0x{:04X}, 0x{:04X}),", first, last).unwrap();
+    }
+    writeln!(s, "];\n").unwrap();
+    writeln!(
+        s,
+        "/// Siniform ideographic scripts with their own implicit primary weight base, as listed by the\n\
+         /// `@implicitweights` lines of allkeys.txt: `(first, last, base, offset)`."
+    )
+    .unwrap();
+    writeln!(
+        s,
+        "pub(crate) const SINIFORM_SCRIPTS: &[(u32, u32, u32, u32)] = &["
+    )
+    .unwrap();
+    for (first, last, base, offset) in &implicit.siniform_scripts {
+        writeln!(
+            s,
+            "    (0x{:04X}, 0x{:04X}, 0x{:04X}, 0x{:04X}),",
+            first, last, base, offset
+        )
+        .unwrap();
+    }
+    writeln!(s, "];").unwrap();
+    s
+}
@@ -0,0 +1,66 @@
+//! Client-side collation for PostgreSQL: the strings are stored with their sort keys, in a
+//! `bytea` column whose binary order is the collation order, and with the
+//! [`CollationVersion`](collate_rs::algorithm::CollationVersion) of the collator that computed
+//! them, so that the keys made stale by an upgrade of the data are detected and recomputed.
+//!
+//! The example reads strings from the standard input, one per line, and writes the SQL upserting
+//! them, to be piped into `psql`:
+//!
+//! ```text
+//! cargo run --example postgres -- sv < names.txt | psql mydb
+//! ```
+//!
+//! The rows are then sorted with `ORDER BY sort_key`. After an upgrade, the rows of
+//! `SELECT name FROM collated_names WHERE collation_version <> '<version>'` must be fed to the
+//! example again, with the version of the new collator, before their keys are compared to the new
+//! ones. The keys are truncated to fit in the entries of a B-tree index of PostgreSQL, so that
+//! `name` breaks the ties of the truncated keys.
+
+use collate_rs::algorithm::Collator;
+use std::fmt::Write;
+use std::io::{self, BufRead};
+
+/// The largest key kept, well under the limit of about 2700 bytes of the entries of a B-tree index.
+const MAX_KEY_LEN: usize = 1024;
+
+/// `s` quoted as an SQL string literal.
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn main() -> io::Result<()> {
+    let locale = std::env::args().nth(1).unwrap_or_else(|| "und".to_string());
+    let collator = Collator::try_new(&locale).map_err(io::Error::other)?;
+    let version = collator.version();
+
+    println!(
+        "CREATE TABLE IF NOT EXISTS collated_names (\n    \
+             name text PRIMARY KEY,\n    \
+             sort_key bytea NOT NULL,\n    \
+             collation_version text NOT NULL\n\
+         );"
+    );
+    println!("CREATE INDEX IF NOT EXISTS collated_names_order ON collated_names (sort_key, name);");
+    for line in io::stdin().lock().lines() {
+        let name = line?;
+        let key = collator.sort_key(&name).truncate_safe(MAX_KEY_LEN);
+        let mut hex = String::with_capacity(2 * key.as_bytes().len());
+        for byte in key.as_bytes() {
+            write!(hex, "{:02x}", byte).unwrap();
+        }
+        println!(
+            "INSERT INTO collated_names VALUES ({}, '\\x{}', {})\n    \
+             ON CONFLICT (name) DO UPDATE\n    \
+             SET sort_key = EXCLUDED.sort_key, collation_version = EXCLUDED.collation_version;",
+            quote(&name),
+            hex,
+            quote(&version.to_string())
+        );
+    }
+    println!(
+        "-- Rows whose keys were computed by another collator, to be recomputed:\n\
+         SELECT name FROM collated_names WHERE collation_version <> {};",
+        quote(&version.to_string())
+    );
+    Ok(())
+}
@@ -0,0 +1,15 @@
+//! Reads arbitrary binary tables, and looks up and decodes their mappings.
+
+#![no_main]
+
+use collate_rs::algorithm::Collator;
+use collate_rs::data_files::binary::BinaryTable;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(binary) = BinaryTable::new(data) {
+        let table = binary.to_table();
+        let collator = Collator::new(table);
+        let _ = collator.sort_key("abc");
+    }
+});
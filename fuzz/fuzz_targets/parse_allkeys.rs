@@ -0,0 +1,19 @@
+//! Parses arbitrary allkeys.txt files, and collates their first entries with the tables parsed.
+
+#![no_main]
+
+use collate_rs::algorithm::Collator;
+use collate_rs::data_files::parse_allkeys;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(allkeys) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(table) = parse_allkeys(allkeys) {
+        let collator = Collator::new(table);
+        for line in allkeys.lines().take(8) {
+            let _ = collator.sort_key(line);
+        }
+    }
+});
@@ -0,0 +1,29 @@
+//! Parses arbitrary tailorings, and applies them to the DUCET.
+
+#![no_main]
+
+use collate_rs::algorithm::Collator;
+use collate_rs::definitions::collation_element_tables::CollationElementTable;
+use collate_rs::tailoring::Tailoring;
+use collate_rs::{default_collator, ducet};
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+
+fn root() -> &'static CollationElementTable {
+    static ROOT: OnceLock<CollationElementTable> = OnceLock::new();
+    ROOT.get_or_init(ducet::table)
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(rules) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(tailoring) = Tailoring::parse(rules) {
+        let collator = Collator::new(tailoring.apply(root()));
+        for mapping in tailoring.mappings().iter().take(8) {
+            let s: String = mapping.characters().iter().collect();
+            let _ = collator.compare(&s, "a");
+            let _ = default_collator().compare(&s, "a");
+        }
+    }
+});
@@ -0,0 +1,57 @@
+//! Builds the sort keys of arbitrary strings with the options picked by the first byte, checking
+//! that they agree with the comparisons.
+
+#![no_main]
+
+use collate_rs::algorithm::{CaseFirst, CollatorOptions, KeyEncoding, Strength};
+use collate_rs::default_collator;
+use collate_rs::definitions::variable_weighting::VariableWeighting;
+use libfuzzer_sys::fuzz_target;
+
+fn options(bits: u8) -> CollatorOptions {
+    CollatorOptions {
+        key_encoding: if bits & 1 != 0 {
+            KeyEncoding::Fractional
+        } else {
+            KeyEncoding::U16BigEndian
+        },
+        strength: [
+            Strength::Primary,
+            Strength::Tertiary,
+            Strength::Quaternary,
+            Strength::All,
+        ][usize::from(bits >> 1 & 3)],
+        backward_secondary: bits & 8 != 0,
+        variable_weighting: if bits & 16 != 0 {
+            VariableWeighting::Shifted
+        } else {
+            VariableWeighting::NonIgnorable
+        },
+        case_level: bits & 32 != 0,
+        case_first: if bits & 64 != 0 {
+            CaseFirst::Upper
+        } else {
+            CaseFirst::Off
+        },
+        numeric: bits & 128 != 0,
+        ..Default::default()
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&bits, rest)) = data.split_first() else {
+        return;
+    };
+    let collator = default_collator().clone_with_options(options(bits));
+    let s = String::from_utf8_lossy(rest);
+    let (a, b) = s.split_at(
+        s.char_indices()
+            .nth(s.chars().count() / 2)
+            .map_or(0, |(i, _)| i),
+    );
+    let keys = collator
+        .sort_key(a)
+        .as_bytes()
+        .cmp(collator.sort_key(b).as_bytes());
+    assert_eq!(collator.compare(a, b), keys);
+});
@@ -0,0 +1,16 @@
+//! Reads arbitrary bytes as stored sort keys, and decodes and displays those accepted.
+
+#![no_main]
+
+use collate_rs::algorithm::SortKey;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(key) = SortKey::from_bytes(data) {
+        assert_eq!(key.as_bytes(), data);
+        let levels = key.levels();
+        assert!(!key.weights().is_empty() || levels.is_empty());
+        let _ = format!("{} {:?}", key, key);
+        let _ = key.truncate_safe(data.len() / 2).levels();
+    }
+});
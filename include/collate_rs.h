@@ -0,0 +1,42 @@
+/*
+ * C API of collate-rs, built with `cargo build --release --features ffi`.
+ *
+ * Strings are UTF-8, given as a pointer and a length in bytes, or a length of -1 for
+ * NUL-terminated strings. Fallible functions report their outcome through `status`.
+ */
+#ifndef COLLATE_RS_H
+#define COLLATE_RS_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define COLLATE_OK 0
+#define COLLATE_NULL_ARGUMENT 1
+#define COLLATE_INVALID_UTF8 2
+#define COLLATE_UNKNOWN_LOCALE 3
+#define COLLATE_INVALID_LENGTH 4
+
+typedef struct CollateCollator CollateCollator;
+
+/* Opens a collator for a bundled locale ("und" or NULL for the root collation); NULL on failure. */
+CollateCollator *collate_open(const char *locale, int32_t *status);
+
+/* Returns -1, 0 or 1 as `a` sorts before, equal to or after `b`. */
+int32_t collate_strcoll(const CollateCollator *coll, const char *a, int32_t a_len, const char *b,
+                        int32_t b_len, int32_t *status);
+
+/* Returns the length of the sort key of `s`, writing it to `result` if it fits in `result_len`. */
+int32_t collate_getSortKey(const CollateCollator *coll, const char *s, int32_t len,
+                           uint8_t *result, int32_t result_len, int32_t *status);
+
+/* Releases a collator; NULL is ignored. */
+void collate_close(CollateCollator *coll);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* COLLATE_RS_H */
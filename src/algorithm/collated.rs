@@ -0,0 +1,119 @@
+use crate::algorithm::Collator;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// A string paired with the [`Collator`] ordering it, so that it can be used where [`Ord`] is
+/// required, e.g. as a key of a [`BTreeMap`](std::collections::BTreeMap) or in a
+/// [`BinaryHeap`](std::collections::BinaryHeap).
+///
+/// Two `Collated` values are equal when their strings are canonically equivalent for the collator
+/// (their sort keys have the same weights), and hash consistently with that equality. Comparing
+/// values wrapped by different collators compares them by the collator of the left-hand side.
+pub struct Collated<'c, S: ?Sized> {
+    collator: &'c Collator,
+    value: &'c S,
+}
+
+impl<'c, S: ?Sized + AsRef<str>> Collated<'c, S> {
+    pub fn new(collator: &'c Collator, value: &'c S) -> Self {
+        Self { collator, value }
+    }
+
+    pub fn get(&self) -> &'c S {
+        self.value
+    }
+
+    pub fn collator(&self) -> &'c Collator {
+        self.collator
+    }
+}
+
+impl<S: ?Sized> Clone for Collated<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: ?Sized> Copy for Collated<'_, S> {}
+
+impl<S: ?Sized + fmt::Debug> fmt::Debug for Collated<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Collated").field(&self.value).finish()
+    }
+}
+
+impl<S: ?Sized + fmt::Display> fmt::Display for Collated<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<S: ?Sized + AsRef<str>> PartialEq for Collated<'_, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S: ?Sized + AsRef<str>> Eq for Collated<'_, S> {}
+
+impl<S: ?Sized + AsRef<str>> PartialOrd for Collated<'_, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: ?Sized + AsRef<str>> Ord for Collated<'_, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.collator
+            .compare(self.value.as_ref(), other.value.as_ref())
+    }
+}
+
+impl<S: ?Sized + AsRef<str>> Hash for Collated<'_, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.collator.collation_hash(self.value.as_ref(), state);
+    }
+}
+
+impl Collator {
+    /// Wraps `s` in a [`Collated`] ordered by this collator.
+    pub fn wrap<'c>(&'c self, s: &'c str) -> Collated<'c, str> {
+        Collated::new(self, s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::tests::table;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{BTreeMap, BinaryHeap};
+
+    #[test]
+    fn test_collated() {
+        let collator = Collator::new(table());
+        let mut map = BTreeMap::new();
+        for (i, s) in ["ch", "b", "A", "ca", "a"].into_iter().enumerate() {
+            map.insert(collator.wrap(s), i);
+        }
+        let keys: Vec<&str> = map.keys().map(Collated::get).collect();
+        assert_eq!(keys, ["a", "A", "b", "ca", "ch"]);
+
+        let owned = [String::from("b"), String::from("ch")];
+        let mut heap: BinaryHeap<Collated<String>> =
+            owned.iter().map(|s| Collated::new(&collator, s)).collect();
+        assert_eq!(heap.pop().unwrap().get(), "ch");
+
+        // Canonically equivalent strings are equal and hash alike.
+        let hash = |c: Collated<str>| {
+            let mut hasher = DefaultHasher::new();
+            c.hash(&mut hasher);
+            hasher.finish()
+        };
+        let (composed, decomposed) = (collator.wrap("\u{E1}"), collator.wrap("a\u{301}"));
+        assert_eq!(composed, decomposed);
+        assert_eq!(hash(composed), hash(decomposed));
+        assert_ne!(collator.wrap("a"), collator.wrap("A"));
+    }
+}
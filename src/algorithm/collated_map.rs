@@ -0,0 +1,194 @@
+use crate::algorithm::Collator;
+use std::collections::btree_map::{self, BTreeMap};
+use std::fmt;
+
+/// A map of string keys kept in the order of a [`Collator`], e.g. for the entries of a dropdown
+/// or of an index kept sorted as they are inserted.
+///
+/// The entries are stored by the sort keys of their keys, computed once on insertion, so the
+/// lookups compute a single sort key and compare bytes. Like
+/// [`Collated`](crate::algorithm::Collated) keys, the keys equal for the collator, e.g.
+/// canonically equivalent, are the same key: inserting one replaces the value of the other, which
+/// keeps its key.
+///
+/// ```
+/// use collate_rs::algorithm::{CollatedBTreeMap, Collator};
+///
+/// let mut cities = CollatedBTreeMap::new(Collator::try_new("und").unwrap());
+/// cities.insert("Zürich", 421_878);
+/// cities.insert("Bâle", 173_863);
+/// cities.insert("zoug", 30_934);
+/// assert_eq!(cities.keys().collect::<Vec<_>>(), [&"Bâle", &"zoug", &"Zürich"]);
+/// assert_eq!(cities.get("Ba\u{302}le"), Some(&173_863));
+/// ```
+#[derive(Clone)]
+pub struct CollatedBTreeMap<K, V> {
+    collator: Collator,
+    map: BTreeMap<Vec<u8>, (K, V)>,
+}
+
+impl<K: AsRef<str>, V> CollatedBTreeMap<K, V> {
+    pub fn new(collator: Collator) -> Self {
+        Self {
+            collator,
+            map: BTreeMap::new(),
+        }
+    }
+
+    pub fn collator(&self) -> &Collator {
+        &self.collator
+    }
+
+    /// Inserts a value, returning the value of the key equal to `key` for the collator, if any.
+    /// That key is kept.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.map.entry(self.key(key.as_ref())) {
+            btree_map::Entry::Occupied(mut entry) => {
+                Some(std::mem::replace(&mut entry.get_mut().1, value))
+            }
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.map.get(&self.key(key)).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value(&self, key: &str) -> Option<(&K, &V)> {
+        self.map.get(&self.key(key)).map(|(k, v)| (k, v))
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let key = self.key(key);
+        self.map.get_mut(&key).map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(&self.key(key))
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry(&mut self, key: &str) -> Option<(K, V)> {
+        let key = self.key(key);
+        self.map.remove(&key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear()
+    }
+
+    /// The entries, in the collation order of their keys.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.map.values().map(|(k, v)| (k, v))
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.map.values().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.map.values().map(|(_, v)| v)
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.map.values().next().map(|(k, v)| (k, v))
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.map.values().next_back().map(|(k, v)| (k, v))
+    }
+
+    /// The entries whose keys follow `key` in the collation order, starting from `key` itself if
+    /// present, e.g. to complete a prefix typed in a dropdown.
+    pub fn iter_from(&self, key: &str) -> impl DoubleEndedIterator<Item = (&K, &V)> {
+        self.map.range(self.key(key)..).map(|(_, (k, v))| (k, v))
+    }
+
+    /// The number of keys less than `key` for the collator, its rank among the keys.
+    pub fn rank(&self, key: &str) -> usize {
+        self.map.range(..self.key(key)).count()
+    }
+
+    fn key(&self, s: &str) -> Vec<u8> {
+        self.collator.sort_key(s).into_bytes()
+    }
+}
+
+impl<K: AsRef<str>, V> Extend<(K, V)> for CollatedBTreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for CollatedBTreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = btree_map::IntoValues<Vec<u8>, (K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.map.into_values()
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for CollatedBTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.map.values().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::tests::table;
+
+    #[test]
+    fn test_collated_map() {
+        let mut map = CollatedBTreeMap::new(Collator::new(table()));
+        assert!(map.is_empty());
+        map.extend([("ch", 1), ("b", 2), ("A", 3), ("ca", 4), ("a", 5)]);
+        assert_eq!(
+            map.keys().copied().collect::<Vec<_>>(),
+            ["a", "A", "b", "ca", "ch"]
+        );
+        assert_eq!(map.first_key_value(), Some((&"a", &5)));
+        assert_eq!(map.last_key_value(), Some((&"ch", &1)));
+        assert_eq!(
+            format!("{:?}", map),
+            r#"{"a": 5, "A": 3, "b": 2, "ca": 4, "ch": 1}"#
+        );
+
+        // Canonically equivalent keys are the same key, which keeps its first spelling.
+        assert_eq!(map.insert("\u{E1}", 6), None);
+        assert_eq!(map.insert("a\u{301}", 7), Some(6));
+        assert_eq!(map.get_key_value("\u{E1}"), Some((&"\u{E1}", &7)));
+
+        assert_eq!(map.rank("b"), 3);
+        assert_eq!(map.rank("c"), 4);
+        let from: Vec<&str> = map.iter_from("c").map(|(k, _)| *k).collect();
+        assert_eq!(from, ["ca", "ch"]);
+
+        *map.get_mut("b").unwrap() += 10;
+        assert_eq!(map.remove("b"), Some(12));
+        assert!(!map.contains_key("b"));
+        assert_eq!(map.len(), 5);
+        let values: Vec<i32> = map.into_iter().map(|(_, v)| v).collect();
+        assert_eq!(values, [5, 3, 7, 4, 1]);
+    }
+}
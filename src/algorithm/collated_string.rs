@@ -0,0 +1,237 @@
+use crate::algorithm::{default_collator, CollationVersion, Collator, SortKey};
+use crate::error::{CollateError, Result};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::OnceLock;
+
+/// The collator of [`CollatedString::new`], set once by [`CollatedString::set_global_collator`].
+static GLOBAL_COLLATOR: OnceLock<Collator> = OnceLock::new();
+
+/// An owned string stored with its sort key, so that it can be used where [`Ord`] is required,
+/// e.g. as a key of a [`BTreeMap`](std::collections::BTreeMap), without a borrowed collator as
+/// [`Collated`](crate::algorithm::Collated) strings have.
+///
+/// The values are ordered, equal and hashed by their sort keys, so the values of different
+/// collators should not be compared; each keeps the [`CollationVersion`] of its collator. With
+/// the `serde` feature, the values are serialized with their sort key and version, e.g. in the
+/// configuration files of sorted structures, and their sort key is recomputed by the global
+/// collator when they are deserialized if its version differs.
+///
+/// ```
+/// use collate_rs::algorithm::CollatedString;
+/// use std::collections::BTreeSet;
+///
+/// let names: BTreeSet<CollatedString> = ["Zoë", "zoe", "Álvaro", "alice"]
+///     .into_iter()
+///     .map(CollatedString::new)
+///     .collect();
+/// let names: Vec<&str> = names.iter().map(CollatedString::as_str).collect();
+/// assert_eq!(names, ["alice", "Álvaro", "zoe", "Zoë"]);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(into = "StoredCollatedString", from = "StoredCollatedString")
+)]
+pub struct CollatedString {
+    value: String,
+    key: SortKey,
+    version: CollationVersion,
+}
+
+impl CollatedString {
+    /// The string ordered by the global collator: the one set by
+    /// [`CollatedString::set_global_collator`], or else the
+    /// [`default_collator`](crate::default_collator).
+    pub fn new(value: impl Into<String>) -> Self {
+        Self::with_collator(Self::global_collator(), value)
+    }
+
+    pub fn with_collator(collator: &Collator, value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            key: collator.sort_key(&value),
+            version: collator.version(),
+            value,
+        }
+    }
+
+    /// Sets the collator of [`CollatedString::new`] and of the deserialization, e.g. from the
+    /// configuration of the application at startup. Fails if it is already set, or if a value has
+    /// already been created by the default collator, as the values of both could not be compared.
+    pub fn set_global_collator(collator: Collator) -> Result<()> {
+        GLOBAL_COLLATOR.set(collator).map_err(|_| {
+            CollateError::InvalidOption(
+                "The global collator of CollatedString is already set.".to_string(),
+            )
+        })
+    }
+
+    /// The collator of [`CollatedString::new`].
+    pub fn global_collator() -> &'static Collator {
+        GLOBAL_COLLATOR.get_or_init(|| default_collator().clone())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    pub fn into_string(self) -> String {
+        self.value
+    }
+
+    pub fn sort_key(&self) -> &SortKey {
+        &self.key
+    }
+
+    /// The version of the collator of the sort key.
+    pub fn version(&self) -> CollationVersion {
+        self.version
+    }
+}
+
+impl From<&str> for CollatedString {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for CollatedString {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl AsRef<str> for CollatedString {
+    fn as_ref(&self) -> &str {
+        &self.value
+    }
+}
+
+impl fmt::Debug for CollatedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CollatedString").field(&self.value).finish()
+    }
+}
+
+impl fmt::Display for CollatedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl PartialEq for CollatedString {
+    fn eq(&self, other: &Self) -> bool {
+        self.key.as_bytes() == other.key.as_bytes()
+    }
+}
+
+impl Eq for CollatedString {}
+
+impl PartialOrd for CollatedString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CollatedString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.as_bytes().cmp(other.key.as_bytes())
+    }
+}
+
+impl Hash for CollatedString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.as_bytes().hash(state);
+    }
+}
+
+/// The serialized form of a [`CollatedString`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StoredCollatedString {
+    value: String,
+    version: CollationVersion,
+    key: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl From<CollatedString> for StoredCollatedString {
+    fn from(s: CollatedString) -> Self {
+        Self {
+            value: s.value,
+            version: s.version,
+            key: s.key.into_bytes(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<StoredCollatedString> for CollatedString {
+    fn from(stored: StoredCollatedString) -> Self {
+        let collator = Self::global_collator();
+        match SortKey::from_bytes(&stored.key) {
+            Ok(key) if stored.version == collator.version() => Self {
+                value: stored.value,
+                key,
+                version: stored.version,
+            },
+            _ => Self::with_collator(collator, stored.value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::tests::table;
+
+    #[test]
+    fn test_collated_string() {
+        let collator = Collator::new(table());
+        let (composed, decomposed) = (
+            CollatedString::with_collator(&collator, "\u{E1}"),
+            CollatedString::with_collator(&collator, String::from("a\u{301}")),
+        );
+        assert_eq!(composed, decomposed);
+        assert_eq!(composed.version(), collator.version());
+        assert_eq!(composed.sort_key(), &collator.sort_key("\u{E1}"));
+        let mut strings: Vec<CollatedString> = ["ch", "b", "A", "ca", "a"]
+            .into_iter()
+            .map(|s| CollatedString::with_collator(&collator, s))
+            .collect();
+        strings.sort();
+        let strings: Vec<&str> = strings.iter().map(CollatedString::as_str).collect();
+        assert_eq!(strings, ["a", "A", "b", "ca", "ch"]);
+
+        // The global collator is the default one once used.
+        assert_eq!(
+            CollatedString::from("a").version(),
+            default_collator().version()
+        );
+        assert!(CollatedString::set_global_collator(collator).is_err());
+        assert_eq!(format!("{:?}", composed), r#"CollatedString("á")"#);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_collated_string_serde() {
+        let s = CollatedString::new("Zoë");
+        let json = serde_json::to_string(&s).unwrap();
+        assert!(json.starts_with(&format!(
+            r#"{{"value":"Zoë","version":"{}","key":[1,"#,
+            s.version()
+        )));
+        let parsed: CollatedString = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, s);
+        assert_eq!(parsed.sort_key(), s.sort_key());
+
+        // The keys of other versions are recomputed.
+        let stale = r#"{"value":"Zoë","version":"0000000000000000","key":[1,0,1]}"#;
+        let parsed: CollatedString = serde_json::from_str(stale).unwrap();
+        assert_eq!(parsed.sort_key(), s.sort_key());
+        assert_eq!(parsed.version(), s.version());
+    }
+}
@@ -0,0 +1,448 @@
+use crate::algorithm::normalize::{to_nfd, to_nfd_with_offsets};
+use crate::algorithm::packed::{NarrowElement, PackedElement};
+use crate::algorithm::trie::{Elements, MappingTrie};
+use crate::algorithm::{Collator, CollatorScratch, QuaternarySource};
+use crate::data_files::ucd::canonical_decomposition;
+use crate::definitions::collation_element_tables::MappingOrigin;
+use crate::definitions::collation_wel::{CollationElement, CollationWeight, Weighted, MAX_WEIGHT};
+use crate::definitions::variable_weighting::VariableWeighting;
+use std::ops::Range;
+
+/// The collation elements of the Latin-1 characters collated without looking up the
+/// [`MappingTrie`], in the manner of the Latin-1 fast path of ICU: those without a canonical
+/// decomposition whose only mapping starting with them is their own, to a single collation element
+/// that is not variable.
+#[derive(Debug, Clone)]
+pub(crate) struct Latin1FastPath {
+    /// The bitset of the characters of the fast path.
+    simple: [u64; 4],
+    /// The collation element of each character of the fast path, by code point.
+    elements: Vec<PackedElement>,
+}
+
+impl Latin1FastPath {
+    pub(crate) fn new(trie: &MappingTrie) -> Self {
+        let mut fast_path = Self {
+            simple: [0; 4],
+            elements: vec![PackedElement::from(&CollationElement::new(vec![])); 256],
+        };
+        for c in (0..=0xFF_u8).map(char::from) {
+            if canonical_decomposition(c).is_some() {
+                continue;
+            }
+            if let Some(ce) = trie.simple_match(c).and_then(Elements::single) {
+                if !ce.is_variable() {
+                    fast_path.simple[c as usize / 64] |= 1 << (c as usize % 64);
+                    fast_path.elements[c as usize] = ce;
+                }
+            }
+        }
+        fast_path
+    }
+
+    pub(crate) fn get(&self, c: char) -> Option<&PackedElement> {
+        let cp = c as usize;
+        let simple = cp < 256 && self.simple[cp / 64] & (1 << (cp % 64)) != 0;
+        simple.then(|| &self.elements[cp])
+    }
+}
+
+impl Collator {
+    /// > S2. Produce an array of collation elements for each string.
+    /// >
+    /// > S2.1 Find the longest initial substring S at each point that has a match in the collation
+    /// > element table.
+    /// >
+    /// > S2.2 Fetch the corresponding collation element(s) from the table if there is a match. If
+    /// > there is no match, synthesize a collation element as described in Section 10.1, Derived
+    /// > Collation Elements.
+    /// >
+    /// > S2.3 Process collation elements according to the variable-weight setting, as described in
+    /// > Section 4, Variable Weighting.
+    /// >
+    /// > S2.4 Append the collation element(s) to the collation element array.
+    /// >
+    /// > S2.5 Proceed to the next point in the string (past S).
+    /// >
+    /// > S2.6 Loop until the end of the string is reached.
+    ///
+    /// The string is first normalized as in S1 (see [`to_nfd`]). A match is extended with the
+    /// unblocked non-starters following it as in S2.1.1-S2.1.3, e.g. the contraction of "и" and
+    /// U+0306 COMBINING BREVE past U+0323 COMBINING DOT BELOW in "и\u{323}\u{306}". The prevowels of Thai, Lao, Tai Viet and New Tai Lue,
+    /// written before the consonant they follow in speech, need no reordering: the DUCET has a
+    /// contraction of each of them with each consonant, weighted as the consonant followed by the
+    /// prevowel.
+    ///
+    /// With [`CollatorOptions::numeric`](crate::algorithm::CollatorOptions::numeric), each
+    /// sequence of the digits `0` to `9` loses its leading zeros and is preceded by collation elements
+    /// encoding its length, so that longer numbers sort after shorter ones.
+    pub fn collation_element_array(&self, s: &str) -> Vec<CollationElement> {
+        let mut scratch = CollatorScratch::new();
+        self.collation_elements_into(s, &mut scratch);
+        scratch.elements.into_vec()
+    }
+
+    /// Like [`Collator::collation_element_array`], each collation element with the byte range of
+    /// `s` it comes from, e.g. to highlight the characters matched by a search or to point at
+    /// the characters of a diagnostic.
+    ///
+    /// The collation elements of an expansion share the range of its character, and the ones of a
+    /// contraction the range of all its characters. As the ranges are the ones of the characters
+    /// of `s` before S1, the collation elements of a character decomposed in S1 share its range,
+    /// and a contraction of characters reordered in S1 spans from the first to the last of them.
+    /// With [`CollatorOptions::numeric`](crate::algorithm::CollatorOptions::numeric), the
+    /// collation elements of a number have the range of all its digits.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let ranges: Vec<_> = collator
+    ///     .collation_element_array_with_offsets("\u{E6}\u{E1}")
+    ///     .into_iter()
+    ///     .map(|(_, range)| range)
+    ///     .collect();
+    /// // "æ" expands to three collation elements, and "á" decomposes into two characters.
+    /// assert_eq!(ranges, [0..2, 0..2, 0..2, 2..4, 2..4]);
+    /// ```
+    pub fn collation_element_array_with_offsets(
+        &self,
+        s: &str,
+    ) -> Vec<(CollationElement, Range<usize>)> {
+        let (chars, offsets) = to_nfd_with_offsets(s);
+        let mut array = ElementBuffer::default();
+        let mut spans = vec![];
+        self.elements_of_nfd(&chars, &mut array, Some(&mut spans));
+        array
+            .as_slice()
+            .iter()
+            .zip(spans)
+            .map(|(ce, span)| {
+                let sources = &offsets[span];
+                let start = sources.iter().map(|range| range.start).min().unwrap_or(0);
+                let end = sources.iter().map(|range| range.end).max().unwrap_or(0);
+                (ce.to_element(), start..end)
+            })
+            .collect()
+    }
+
+    /// Like [`Collator::collation_element_array`], into the buffers of `scratch`: the collation
+    /// elements of the [`CodePointSource`](crate::algorithm::source::CodePointSource) of the
+    /// characters of `s`, which has no ill-formed sequence.
+    pub(crate) fn collation_elements_into(&self, s: &str, scratch: &mut CollatorScratch) {
+        self.source_elements_into(s.chars(), scratch)
+            .expect("A str has no ill-formed sequence.");
+    }
+
+    /// The collation elements of the normalized characters `chars`, into `array`, and with
+    /// `spans` the range of the indexes in `chars` of the characters of each collation element.
+    fn elements_of_nfd(
+        &self,
+        chars: &[char],
+        array: &mut ElementBuffer,
+        spans: Option<&mut Vec<Range<usize>>>,
+    ) {
+        array.clear();
+        self.append_elements_of_nfd(chars, array, spans);
+        self.apply_variable_weighting(array);
+        self.apply_quaternary_source(chars, array);
+    }
+
+    /// Appends the collation elements of `chars` to `array` as [`Collator::elements_of_nfd`],
+    /// before S2.3.
+    pub(crate) fn append_elements_of_nfd(
+        &self,
+        chars: &[char],
+        array: &mut ElementBuffer,
+        spans: Option<&mut Vec<Range<usize>>>,
+    ) {
+        let mut spans = Spans(spans);
+        let digits = self.options.numeric.then(|| self.digits()).flatten();
+        let mut number = vec![];
+        // The index of the first digit of `number`.
+        let mut number_start = 0;
+        // The indexes past `i` of the characters removed by discontiguous matches (S2.1.3), in
+        // order, and the characters from `i` on without them, with their indexes.
+        let mut removed: Vec<usize> = vec![];
+        let (mut rest, mut positions) = (vec![], vec![]);
+        let mut i = 0;
+        while i < chars.len() {
+            if removed.first() == Some(&i) {
+                removed.remove(0);
+                i += 1;
+                continue;
+            }
+            if let Some(ce) = self.data.latin1.get(chars[i]) {
+                if digits.is_none() || !chars[i].is_ascii_digit() {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(character = ?chars[i], fast_path = true, "S2.2 lookup");
+                    push_number(array, &mut number, digits.as_ref());
+                    spans.mark(array, number_start..i);
+                    array.push(ce);
+                    spans.mark(array, i..i + 1);
+                    i += 1;
+                    continue;
+                }
+            }
+            let input = if removed.is_empty() {
+                &chars[i..]
+            } else {
+                positions.clear();
+                positions.extend((i..chars.len()).filter(|j| removed.binary_search(j).is_err()));
+                rest.clear();
+                rest.extend(positions.iter().map(|&j| chars[j]));
+                &rest[..]
+            };
+            if let Some((len, mut elements)) = self.data.trie.longest_match(&chars[..i], input) {
+                let mut end = i + len;
+                let discontiguous = self.data.trie.discontiguous_match(input, len);
+                if !removed.is_empty() || discontiguous.is_some() {
+                    let added = discontiguous.as_ref().map_or(&[][..], |(added, _)| added);
+                    let at = |k: usize| {
+                        if removed.is_empty() {
+                            i + k
+                        } else {
+                            positions[k]
+                        }
+                    };
+                    let taken: Vec<usize> = (1..len).chain(added.iter().copied()).map(at).collect();
+                    end = taken.iter().fold(i, |end, &j| end.max(j)) + 1;
+                    for j in taken {
+                        if let Err(position) = removed.binary_search(&j) {
+                            removed.insert(position, j);
+                        }
+                    }
+                }
+                if let Some((_, longer)) = discontiguous {
+                    elements = longer;
+                }
+                #[cfg(feature = "tracing")]
+                match end - i {
+                    1 => tracing::trace!(character = ?chars[i], "S2.2 lookup"),
+                    _ => tracing::trace!(
+                        characters = %chars[i..end].iter().collect::<String>(),
+                        "S2.1 contraction"
+                    ),
+                }
+                let value = digits.as_ref().and_then(|digits| {
+                    let ce = elements.single()?;
+                    digits.iter().position(|digit| *digit == ce)
+                });
+                match value {
+                    Some(value) => {
+                        if number.is_empty() {
+                            number_start = i;
+                        }
+                        number.push(value)
+                    }
+                    None => {
+                        push_number(array, &mut number, digits.as_ref());
+                        spans.mark(array, number_start..i);
+                        array.push_elements(elements);
+                        spans.mark(array, i..end);
+                    }
+                }
+                i = if removed.is_empty() { end } else { i + 1 };
+            } else {
+                push_number(array, &mut number, digits.as_ref());
+                spans.mark(array, number_start..i);
+                #[cfg(feature = "tracing")]
+                tracing::trace!(character = ?chars[i], "S2.2 implicit weights");
+                array.push_implicit(self.weight_derivation().implicit_primaries(chars[i].into()));
+                spans.mark(array, i..i + 1);
+                i += 1;
+            }
+        }
+        push_number(array, &mut number, digits.as_ref());
+        spans.mark(array, number_start..i);
+    }
+
+    /// S2.3 on the collation elements of `array`.
+    pub(crate) fn apply_variable_weighting(&self, array: &mut ElementBuffer) {
+        let array = array.as_mut_slice();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            weighting = ?self.options.variable_weighting,
+            max_variable = ?self.options.max_variable,
+            elements = array.len(),
+            "S2.3 variable weighting"
+        );
+        match (self.options.variable_weighting, self.options.max_variable) {
+            (VariableWeighting::NonIgnorable, _) => {}
+            (weighting, None) => weighting.apply_in_place(array, PackedElement::is_variable),
+            (weighting, Some(max_variable)) => {
+                let top = self.data.max_variable_top(max_variable);
+                weighting.apply_in_place(array, |ce| {
+                    let primary = ce.weight(1);
+                    primary != 0 && Some(CollationWeight(primary)) <= top
+                })
+            }
+        }
+    }
+
+    /// Replaces the fourth weights of `array` by the code points of the normalized characters
+    /// `chars`, or by ignorable weights, as set by the [`QuaternarySource`] of the collator.
+    ///
+    /// The code points are appended as quaternary collation elements (UTS10-D12), ignorable
+    /// at the other levels, of the weights `1 + cp >> 15` and `1 + cp & 7FFF`.
+    pub(crate) fn apply_quaternary_source(&self, chars: &[char], array: &mut ElementBuffer) {
+        if self.options.quaternary == QuaternarySource::Weights {
+            return;
+        }
+        for ce in array.as_mut_slice() {
+            if ce.len_levels() >= 4 && ce.weight(4) != 0 {
+                ce.set_weight(4, 0);
+            }
+        }
+        if self.options.quaternary == QuaternarySource::CodePoints {
+            for cp in chars.iter().map(|&c| u32::from(c)) {
+                for weight in [1 + (cp >> 15), 1 + (cp & 0x7FFF)] {
+                    let mut ce = PackedElement::Narrow(NarrowElement::new(0, 0, 0, false));
+                    ce.set_weight(4, weight);
+                    array.push(&ce);
+                }
+            }
+        }
+    }
+
+    /// The collation elements of the digits `0` to `9`, if each of them maps to a single one.
+    fn digits(&self) -> Option<Vec<PackedElement>> {
+        ('0'..='9')
+            .map(|c| self.data.trie.longest_match(&[], &[c])?.1.single())
+            .collect()
+    }
+
+    /// The substrings of the normalized `s` matched in S2.1, with the origin of their mappings, to
+    /// audit which characters a table (e.g. a tailoring) covers explicitly.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use collate_rs::definitions::collation_element_tables::MappingOrigin;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// assert_eq!(
+    ///     collator.mapping_origins("a\u{4E00}"),
+    ///     [
+    ///         ("a".to_string(), MappingOrigin::Explicit),
+    ///         ("\u{4E00}".to_string(), MappingOrigin::Implicit),
+    ///     ]
+    /// );
+    /// ```
+    pub fn mapping_origins(&self, s: &str) -> Vec<(String, MappingOrigin)> {
+        let chars = to_nfd(s);
+        let mut origins = vec![];
+        let mut i = 0;
+        while i < chars.len() {
+            let (len, origin) = match self.data.trie.longest_match(&chars[..i], &chars[i..]) {
+                Some((len, _)) => (len, MappingOrigin::Explicit),
+                None => (1, MappingOrigin::Implicit),
+            };
+            origins.push((chars[i..i + len].iter().collect(), origin));
+            i += len;
+        }
+        origins
+    }
+
+    /// The collation element array of `s` in the notation of UTS10, e.g.
+    /// `[.1FA2.0020.0002][.0000.0024.0002]` for `"á"`, to diagnose why strings sort as they do.
+    pub fn explain(&self, s: &str) -> String {
+        self.collation_element_array(s)
+            .iter()
+            .map(CollationElement::to_string)
+            .collect()
+    }
+}
+
+/// Appends the collation elements of the digits of `number`, by value, to `array`, without their
+/// leading zeros and preceded by the collation elements of the digits encoding its length `L`:
+/// `(L - 1) / 9` nines followed by the digit `(L - 1) % 9`.
+fn push_number(
+    array: &mut ElementBuffer,
+    number: &mut Vec<usize>,
+    digits: Option<&Vec<PackedElement>>,
+) {
+    let Some(digits) = digits.filter(|_| !number.is_empty()) else {
+        return;
+    };
+    let start = number
+        .iter()
+        .position(|&value| value != 0)
+        .unwrap_or(number.len() - 1);
+    let len = number.len() - start;
+    for _ in 0..(len - 1) / 9 {
+        array.push(&digits[9]);
+    }
+    array.push(&digits[(len - 1) % 9]);
+    for value in number.drain(..).skip(start) {
+        array.push(&digits[value]);
+    }
+}
+
+/// The ranges of the characters of the collation elements of an array, if they are recorded.
+struct Spans<'a>(Option<&'a mut Vec<Range<usize>>>);
+
+impl Spans<'_> {
+    /// Records `range` as the range of the collation elements of `array` past the recorded ones.
+    fn mark(&mut self, array: &ElementBuffer, range: Range<usize>) {
+        if let Some(spans) = &mut self.0 {
+            spans.resize(array.elements.len(), range);
+        }
+    }
+}
+
+/// A collation element array reused from one string to the next, of packed collation elements.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ElementBuffer {
+    elements: Vec<PackedElement>,
+}
+
+impl ElementBuffer {
+    pub(crate) fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    pub(crate) fn push(&mut self, ce: &PackedElement) {
+        self.elements.push(ce.clone());
+    }
+
+    /// Pushes the two implicit collation elements of the primary weights `AAAA` and `BBBB`,
+    /// saturated to `0001..=FFFF`.
+    pub(crate) fn push_implicit(&mut self, (aaaa, bbbb): (u32, u32)) {
+        debug_assert!(
+            [aaaa, bbbb]
+                .iter()
+                .all(|w| (0x0001..=MAX_WEIGHT).contains(w)),
+            "Implicit primary weights {:04X} and {:04X} out of 0001..=FFFF.",
+            aaaa,
+            bbbb
+        );
+        let [aaaa, bbbb] = [aaaa, bbbb].map(|w| w.clamp(0x0001, MAX_WEIGHT));
+        let elements = [(aaaa, 0x20, 0x02), (bbbb, 0, 0)];
+        self.elements
+            .extend(elements.map(|(primary, secondary, tertiary)| {
+                PackedElement::Narrow(NarrowElement::new(primary, secondary, tertiary, false))
+            }));
+    }
+
+    fn push_elements(&mut self, elements: Elements) {
+        match elements {
+            Elements::Simple(payload) => self.elements.push(Elements::unpack(payload)),
+            Elements::Expansion(ces) => self.elements.extend_from_slice(ces),
+        }
+    }
+
+    pub(crate) fn as_slice(&self) -> &[PackedElement] {
+        &self.elements
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [PackedElement] {
+        &mut self.elements
+    }
+
+    pub(crate) fn into_vec(self) -> Vec<CollationElement> {
+        self.elements
+            .iter()
+            .map(PackedElement::to_element)
+            .collect()
+    }
+}
@@ -0,0 +1,121 @@
+use crate::algorithm::scratch::with_thread_scratch;
+use crate::algorithm::Collator;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+impl Collator {
+    /// > S4. Compare the sort keys for each of the input strings, using a binary comparison. This
+    /// > means that:
+    /// >
+    /// > - Level 3 differences are ignored if there are any Level 1 or 2 differences.
+    /// > - Level 2 differences are ignored if there are any Level 1 differences.
+    /// > - Level 1 differences are never ignored.
+    ///
+    /// The sort keys are written to scratch buffers kept by each thread, so comparisons do not
+    /// allocate once the buffers have grown to the size of the strings compared (see
+    /// [`CollatorScratch`](crate::algorithm::CollatorScratch)).
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        with_thread_scratch(|scratch| self.compare_with(a, b, scratch))
+    }
+
+    /// Compares the strings of each pair of `pairs`, as [`Collator::compare`], reusing the same
+    /// buffers for all of them.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let orderings = collator.compare_many(&[("a", "b"), ("b", "a"), ("\u{E9}", "e\u{301}")]);
+    /// assert_eq!(orderings, [Ordering::Less, Ordering::Greater, Ordering::Equal]);
+    /// ```
+    pub fn compare_many(&self, pairs: &[(&str, &str)]) -> Vec<Ordering> {
+        with_thread_scratch(|scratch| {
+            pairs
+                .iter()
+                .map(|(a, b)| self.compare_with(a, b, scratch))
+                .collect()
+        })
+    }
+
+    /// Compares `needle` with each of `haystacks`, as [`Collator::compare`], e.g. a query with
+    /// the candidates of a search: the needle is normalized and its sort key written only once.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorOptions, Strength};
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap().clone_with_options(CollatorOptions {
+    ///     strength: Strength::Primary,
+    ///     ..Default::default()
+    /// });
+    /// let orderings = collator.compare_one_to_many("resume", &["Résumé", "result", "rhyme"]);
+    /// assert_eq!(orderings, [Ordering::Equal, Ordering::Greater, Ordering::Less]);
+    /// ```
+    pub fn compare_one_to_many<S: AsRef<str>>(
+        &self,
+        needle: &str,
+        haystacks: &[S],
+    ) -> Vec<Ordering> {
+        with_thread_scratch(|scratch| {
+            self.sort_key_into_slot(needle, scratch, 0);
+            haystacks
+                .iter()
+                .map(|haystack| {
+                    self.sort_key_into_slot(haystack.as_ref(), scratch, 1);
+                    scratch.keys[0].cmp(&scratch.keys[1])
+                })
+                .collect()
+        })
+    }
+
+    /// Whether `a` and `b` are equal at the strength of the collator, e.g. regardless of accents
+    /// and case at [`Strength::Primary`](crate::algorithm::Strength::Primary).
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorOptions, Strength};
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let primary = collator.clone_with_options(CollatorOptions {
+    ///     strength: Strength::Primary,
+    ///     ..Default::default()
+    /// });
+    /// assert!(primary.eq_str("Élodie", "elodie"));
+    /// assert!(!collator.eq_str("Élodie", "elodie"));
+    /// assert!(collator.eq_str("\u{E9}", "e\u{301}"));
+    /// ```
+    pub fn eq_str(&self, a: &str, b: &str) -> bool {
+        self.compare(a, b) == Ordering::Equal
+    }
+
+    /// Feeds `s` to `state` so that the strings equal by [`Collator::eq_str`] hash alike, e.g. to
+    /// deduplicate names in a [`HashSet`](std::collections::HashSet) of
+    /// [`Collated`](crate::algorithm::Collated) strings, which hash this way.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorOptions, Strength};
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::collections::HashSet;
+    /// use std::hash::Hasher;
+    ///
+    /// let primary = Collator::try_new("und").unwrap().clone_with_options(CollatorOptions {
+    ///     strength: Strength::Primary,
+    ///     ..Default::default()
+    /// });
+    /// let hash = |s| {
+    ///     let mut hasher = DefaultHasher::new();
+    ///     primary.collation_hash(s, &mut hasher);
+    ///     hasher.finish()
+    /// };
+    /// assert_eq!(hash("José"), hash("jose"));
+    ///
+    /// let names: HashSet<_> = ["José", "jose", "JOSE", "Josef"]
+    ///     .into_iter()
+    ///     .map(|name| primary.wrap(name))
+    ///     .collect();
+    /// assert_eq!(names.len(), 2);
+    /// ```
+    pub fn collation_hash<H: Hasher>(&self, s: &str, state: &mut H) {
+        with_thread_scratch(|scratch| self.sort_key_with(s, scratch)[1..].hash(state));
+    }
+}
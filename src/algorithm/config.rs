@@ -0,0 +1,276 @@
+use crate::algorithm::{CaseFirst, Collator, Strength};
+use crate::data_files::delimited::parse_delimited;
+use crate::data_files::parse_allkeys;
+use crate::definitions::variable_weighting::{MaxVariable, VariableWeighting};
+use crate::error::{CollateError, Result};
+use crate::tailoring::Tailoring;
+use std::path::{Path, PathBuf};
+
+/// A complete configuration of a collator, e.g. read from a configuration file of an application
+/// with [`CollatorConfig::from_toml`] or [`CollatorConfig::from_json`], so that its users can
+/// choose the order of its lists:
+///
+/// ```toml
+/// locale = "de-u-co-phonebk"
+/// tailoringFile = "names.txt"
+/// strength = "secondary"
+/// alternate = "shifted"
+/// numeric = true
+/// caseFirst = "upper"
+/// ```
+///
+/// Every field is optional. The [`Collator`] of [`Collator::from_config`] is built in order:
+///
+/// 1. the collator of [`Collator::for_locale`] for [`CollatorConfig::locale`], `und` by default,
+///    the keywords of the tag included;
+/// 2. its table replaced by the one of [`CollatorConfig::table`], if any;
+/// 3. the table tailored by [`CollatorConfig::tailoring`] and then by
+///    [`CollatorConfig::tailoring_file`];
+/// 4. its options overridden by the other fields.
+///
+/// The names and values of the settings are those of the tailoring rules of CLDR, e.g.
+/// `alternate = "shifted"` for `[alternate shifted]`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+pub struct CollatorConfig {
+    /// The BCP 47 language tag of the base collator, e.g. `sv-SE` or `de-u-co-phonebk-kn`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// A file replacing the table of the locale: in the allkeys.txt format, or in the
+    /// [delimited format](crate::data_files::delimited::parse_delimited) if its extension is
+    /// `.tsv` or `.csv`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<PathBuf>,
+    /// The entries of a tailoring, in the allkeys.txt format (see [`Tailoring::parse`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tailoring: Option<String>,
+    /// A file of the entries of a tailoring, in the allkeys.txt format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tailoring_file: Option<PathBuf>,
+    /// `primary`, `secondary`, `tertiary`, `quaternary`, `quinary`, `all` or the level number
+    /// (see [`Strength`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strength: Option<String>,
+    /// `non-ignorable` or `shifted` (see [`VariableWeighting`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alternate: Option<String>,
+    /// `space`, `punct`, `symbol` or `currency` (see [`MaxVariable`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_variable: Option<String>,
+    /// Whether the secondary weights are compared from the end of the strings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backwards: Option<bool>,
+    /// Whether the case level is inserted before the tertiary level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case_level: Option<bool>,
+    /// `upper`, `lower` or `off` (see [`CaseFirst`]).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case_first: Option<String>,
+    /// Whether the digits are compared by their numeric values.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeric: Option<bool>,
+    /// The order of the scripts, as the `kr` keyword: only `others` (or `Zzzz`), the default order
+    /// of the table, is supported.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reorder: Vec<String>,
+}
+
+impl CollatorConfig {
+    /// Parses a configuration written in TOML.
+    pub fn from_toml(document: &str) -> Result<Self> {
+        toml::from_str(document).map_err(|e| CollateError::parse(e.to_string()))
+    }
+
+    /// Parses a configuration written in JSON, e.g. `{"locale": "sv", "strength": "primary"}`.
+    pub fn from_json(document: &str) -> Result<Self> {
+        serde_json::from_str(document).map_err(|e| CollateError::parse(e.to_string()))
+    }
+
+    /// Reads a configuration from a file, in JSON if its extension is `.json` and in TOML
+    /// otherwise. The relative paths of the files of the configuration are resolved from the
+    /// directory of the file.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let document = read_to_string(path)?;
+        let mut config = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&document)?,
+            _ => Self::from_toml(&document)?,
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for file in [&mut config.table, &mut config.tailoring_file]
+            .into_iter()
+            .flatten()
+        {
+            *file = dir.join(&*file);
+        }
+        Ok(config)
+    }
+}
+
+impl Collator {
+    /// Creates the collator of a [`CollatorConfig`].
+    ///
+    /// Fails if a file cannot be read or parsed, or if a setting has an unknown value.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::config::CollatorConfig;
+    /// use collate_rs::algorithm::Collator;
+    /// use std::cmp::Ordering;
+    ///
+    /// let config = CollatorConfig::from_toml(
+    ///     r#"
+    ///     locale = "sv"
+    ///     tailoring = "0078 ; [.2500.0020.0002]"
+    ///     strength = "primary"
+    ///     numeric = true
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// let collator = Collator::from_config(&config).unwrap();
+    /// assert_eq!(collator.compare("ö", "z"), Ordering::Greater);
+    /// assert_eq!(collator.compare("x", "z"), Ordering::Greater);
+    /// assert_eq!(collator.compare("File2", "file10"), Ordering::Less);
+    ///
+    /// let config = CollatorConfig::from_json(r#"{"alternate": "ignore"}"#).unwrap();
+    /// assert!(Collator::from_config(&config).is_err());
+    /// ```
+    pub fn from_config(config: &CollatorConfig) -> Result<Self> {
+        let mut collator = Self::for_locale(config.locale.as_deref().unwrap_or("und"))?;
+        let mut options = collator.options().clone();
+        if let Some(path) = &config.table {
+            let data = read_to_string(path)?;
+            let table = match path.extension().and_then(|e| e.to_str()) {
+                Some("tsv" | "csv") => parse_delimited(&data)?,
+                _ => parse_allkeys(&data)?,
+            };
+            collator = Self::new(table);
+        }
+        let tailorings = [
+            config.tailoring.clone(),
+            config
+                .tailoring_file
+                .as_deref()
+                .map(read_to_string)
+                .transpose()?,
+        ];
+        for entries in tailorings.into_iter().flatten() {
+            let tailoring = Tailoring::parse(&entries)?;
+            collator = Self::new(tailoring.apply(&collator.table()));
+        }
+
+        let invalid = |setting: &str, value: &str| {
+            CollateError::InvalidOption(format!(
+                "Unsupported value {:?} for the setting {:?}.",
+                value, setting
+            ))
+        };
+        if let Some(strength) = &config.strength {
+            options.strength = strength.parse::<Strength>()?;
+        }
+        if let Some(alternate) = &config.alternate {
+            options.variable_weighting = match alternate.as_str() {
+                "non-ignorable" => VariableWeighting::NonIgnorable,
+                "shifted" => VariableWeighting::Shifted,
+                _ => return Err(invalid("alternate", alternate)),
+            };
+        }
+        if let Some(max_variable) = &config.max_variable {
+            options.max_variable = Some(match max_variable.as_str() {
+                "space" => MaxVariable::Space,
+                "punct" => MaxVariable::Punct,
+                "symbol" => MaxVariable::Symbol,
+                "currency" => MaxVariable::Currency,
+                _ => return Err(invalid("maxVariable", max_variable)),
+            });
+        }
+        if let Some(case_first) = &config.case_first {
+            options.case_first = match case_first.as_str() {
+                "upper" => CaseFirst::Upper,
+                "lower" => CaseFirst::Lower,
+                "off" => CaseFirst::Off,
+                _ => return Err(invalid("caseFirst", case_first)),
+            };
+        }
+        if let Some(script) = config
+            .reorder
+            .iter()
+            .find(|script| !matches!(script.as_str(), "others" | "Zzzz"))
+        {
+            return Err(invalid("reorder", script));
+        }
+        options.backward_secondary = config.backwards.unwrap_or(options.backward_secondary);
+        options.case_level = config.case_level.unwrap_or(options.case_level);
+        options.numeric = config.numeric.unwrap_or(options.numeric);
+        Ok(collator.clone_with_options(options))
+    }
+
+    /// Creates the collator of the configuration file `path`, read by [`CollatorConfig::read`].
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_config(&CollatorConfig::read(path)?)
+    }
+}
+
+fn read_to_string(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|source| CollateError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_config() {
+        let dir = std::env::temp_dir().join(format!("collate-rs-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("table.tsv"),
+            "a\t2000\nA\t2000.0020.0008\nb\t1000\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("tailoring.txt"), "0063 ; [.0500.0020.0002]\n").unwrap();
+        std::fs::write(
+            dir.join("collator.toml"),
+            "table = \"table.tsv\"\n\
+             tailoringFile = \"tailoring.txt\"\n\
+             caseFirst = \"upper\"\n\
+             reorder = [\"others\"]\n",
+        )
+        .unwrap();
+        let collator = Collator::from_config_file(dir.join("collator.toml")).unwrap();
+        assert_eq!(collator.compare("b", "a"), Ordering::Less);
+        assert_eq!(collator.compare("c", "b"), Ordering::Less);
+        assert_eq!(collator.compare("A", "a"), Ordering::Less);
+
+        std::fs::write(
+            dir.join("collator.json"),
+            r#"{"locale": "en-u-kn", "strength": "2", "backwards": true}"#,
+        )
+        .unwrap();
+        let config = CollatorConfig::read(dir.join("collator.json")).unwrap();
+        let collator = Collator::from_config(&config).unwrap();
+        assert_eq!(collator.options().strength, Strength::Secondary);
+        assert!(collator.options().numeric && collator.options().backward_secondary);
+        assert_eq!(
+            CollatorConfig::from_json(&serde_json::to_string(&config).unwrap()).unwrap(),
+            config
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(CollatorConfig::from_toml("strenght = \"primary\"")
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field"));
+        for config in [
+            r#"{"reorder": ["Grek"]}"#,
+            r#"{"caseFirst": "first"}"#,
+            r#"{"tailoringFile": "/nonexistent/tailoring.txt"}"#,
+        ] {
+            let config = CollatorConfig::from_json(config).unwrap();
+            assert!(Collator::from_config(&config).is_err());
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use crate::algorithm::SortKey;
+use crate::error::{CollateError, Result};
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+impl SortKey {
+    /// A 64-bit hash of the weights of the level `n` (counted from 1) of the key, as decoded by
+    /// [`SortKey::levels`], the hash of no weights past the last level.
+    ///
+    /// The hash is stable: it is the FNV-1a hash of the weights, each as 4 little-endian bytes,
+    /// whatever the platform, the build or the [`KeyEncoding`](crate::algorithm::KeyEncoding) of
+    /// the key, so that it can be persisted or computed by different programs. It changes with
+    /// the weights, e.g. from one [`UnicodeVersion`](crate::ducet::UnicodeVersion) of the DUCET
+    /// to the next.
+    pub fn hash_level(&self, n: usize) -> u64 {
+        let levels = self.levels();
+        let weights = n
+            .checked_sub(1)
+            .and_then(|i| levels.get(i))
+            .map_or(&[][..], Vec::as_slice);
+        weights
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// The [hash](SortKey::hash_level) of the primary weights of the key, the same for the strings
+    /// equal at [`Strength::Primary`](crate::algorithm::Strength::Primary), e.g. to find the
+    /// near-duplicates differing by accents or case.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let (a, b) = (collator.sort_key("Crème Brûlée"), collator.sort_key("creme brulee"));
+    /// assert_eq!(a.hash_primary(), b.hash_primary());
+    /// assert_ne!(a.hash_level(2), b.hash_level(2));
+    /// assert_eq!(a.hash_primary(), 0x68EF_5AC6_A33C_C9F5);
+    /// ```
+    pub fn hash_primary(&self) -> u64 {
+        self.hash_level(1)
+    }
+}
+
+/// A Bloom filter of the sort keys by the [hash](SortKey::hash_level) of one of their levels, to
+/// find the keys seen before at that level among many, e.g. the near-duplicates of a corpus at
+/// the primary level, in a fixed amount of memory.
+///
+/// The filter has no false negatives: a key inserted is always contained. A key not inserted may
+/// be reported as contained, at the rate given to [`LevelBloomFilter::new`] once the filter holds
+/// as many keys as its capacity. The bits set depend only on the stable hashes, so that filters
+/// built by different programs can be compared.
+///
+/// ```
+/// use collate_rs::algorithm::{Collator, LevelBloomFilter};
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// let mut seen = LevelBloomFilter::new(1, 1000, 0.01).unwrap();
+/// let duplicates: Vec<&str> = ["Résumé", "naïve", "resume", "NAIVE", "cafe"]
+///     .into_iter()
+///     .filter(|s| seen.insert(&collator.sort_key(s)))
+///     .collect();
+/// assert_eq!(duplicates, ["resume", "NAIVE"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelBloomFilter {
+    level: usize,
+    bits: Vec<u64>,
+    hashes: u32,
+}
+
+impl LevelBloomFilter {
+    /// A filter of the keys by their level `level` (counted from 1), sized for `capacity` keys
+    /// with a rate of false positives of `false_positive_rate`.
+    ///
+    /// Fails with [`CollateError::InvalidOption`] if the rate is not strictly between 0 and 1.
+    pub fn new(level: usize, capacity: usize, false_positive_rate: f64) -> Result<Self> {
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(CollateError::InvalidOption(format!(
+                "The false positive rate {} is not between 0 and 1.",
+                false_positive_rate
+            )));
+        }
+        let ln2 = std::f64::consts::LN_2;
+        let capacity = capacity.max(1) as f64;
+        let len_bits = (-capacity * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        let hashes = (len_bits / capacity * ln2).round().max(1.0) as u32;
+        Ok(Self {
+            level,
+            bits: vec![0; (len_bits as usize).div_ceil(64)],
+            hashes,
+        })
+    }
+
+    /// The level of the keys the filter hashes.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// The number of bits of the filter.
+    pub fn len_bits(&self) -> usize {
+        self.bits.len() * 64
+    }
+
+    /// The positions of the bits of `key`, by double hashing of the halves of its hash.
+    fn positions(&self, key: &SortKey) -> impl Iterator<Item = usize> {
+        let hash = key.hash_level(self.level);
+        let (h1, h2) = (hash & 0xFFFF_FFFF, hash >> 32 | 1);
+        let len_bits = self.len_bits() as u64;
+        (0..u64::from(self.hashes))
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len_bits) as usize)
+    }
+
+    /// Adds `key` to the filter, returning whether it may have been contained already.
+    pub fn insert(&mut self, key: &SortKey) -> bool {
+        let positions: Vec<usize> = self.positions(key).collect();
+        let mut contained = true;
+        for i in positions {
+            let (word, bit) = (&mut self.bits[i / 64], 1 << (i % 64));
+            contained &= *word & bit != 0;
+            *word |= bit;
+        }
+        contained
+    }
+
+    /// Whether `key` may have been inserted: always if it was, rarely if it was not.
+    pub fn may_contain(&self, key: &SortKey) -> bool {
+        self.positions(key)
+            .all(|i| self.bits[i / 64] & 1 << (i % 64) != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{Collator, CollatorOptions, KeyEncoding};
+
+    #[test]
+    fn test_level_hashes() {
+        let collator = Collator::try_new("und").unwrap();
+        let fractional = collator.clone_with_options(CollatorOptions {
+            key_encoding: KeyEncoding::Fractional,
+            ..Default::default()
+        });
+        let key = collator.sort_key("ab");
+        // FNV-1a of 1FA2 and 1FBC as little-endian u32s, and of no bytes.
+        assert_eq!(key.hash_primary(), 0xD7B6_1F38_5192_E393);
+        assert_eq!(key.hash_level(4), FNV_OFFSET_BASIS);
+        assert_eq!(key.hash_level(0), FNV_OFFSET_BASIS);
+        for n in 1..=3 {
+            assert_eq!(key.hash_level(n), fractional.sort_key("ab").hash_level(n));
+        }
+        assert_eq!(
+            key.hash_primary(),
+            collator.sort_key("A\u{301}B").hash_primary()
+        );
+        assert_ne!(key.hash_level(3), collator.sort_key("AB").hash_level(3));
+    }
+
+    #[test]
+    fn test_level_bloom_filter() {
+        let collator = Collator::try_new("und").unwrap();
+        assert!(LevelBloomFilter::new(1, 10, 0.0).is_err());
+        assert!(LevelBloomFilter::new(1, 10, 1.0).is_err());
+        let mut filter = LevelBloomFilter::new(1, 1000, 0.01).unwrap();
+        assert_eq!(
+            (filter.level(), filter.len_bits(), filter.hashes),
+            (1, 9600, 7)
+        );
+        let key = |i: usize| collator.sort_key(&format!("word {}", i));
+        for i in 0..1000 {
+            filter.insert(&key(i));
+        }
+        assert!((0..1000).all(|i| filter.may_contain(&key(i))));
+        assert!(filter.may_contain(&collator.sort_key("WORD 1")));
+        let false_positives = (1000..11000)
+            .filter(|&i| filter.may_contain(&key(i)))
+            .count();
+        assert!(false_positives < 200, "{}", false_positives);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::algorithm::Collator;
+
+/// Extends the iterators with adapters ordering their items by a [`Collator`], so that pipelines
+/// need not build the sort keys themselves.
+///
+/// ```
+/// use collate_rs::algorithm::{CollateIteratorExt, Collator};
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// let words = ["pêche", "Péché", "peach", "péché"];
+/// let sorted: Vec<_> = words.iter().sorted_by_collator(&collator).collect();
+/// assert_eq!(sorted, [&"peach", &"péché", &"Péché", &"pêche"]);
+/// assert_eq!(words.iter().max_by_collator(&collator), Some(&"pêche"));
+/// assert_eq!(words.iter().min_by_collator(&collator), Some(&"peach"));
+/// ```
+pub trait CollateIteratorExt: Iterator + Sized {
+    /// The items in the order of [`Collator::compare`], sorted stably as by
+    /// [`Collator::sort_slice`].
+    fn sorted_by_collator(self, collator: &Collator) -> std::vec::IntoIter<Self::Item>
+    where
+        Self::Item: AsRef<str>,
+    {
+        let mut items: Vec<_> = self.collect();
+        collator.sort_slice(&mut items);
+        items.into_iter()
+    }
+
+    /// The items in the collation order of the string extracted from each of them by `f`, sorted
+    /// stably as by [`Collator::sort_by_cached_key`].
+    fn sorted_by_collator_key<F>(self, collator: &Collator, f: F) -> std::vec::IntoIter<Self::Item>
+    where
+        F: Fn(&Self::Item) -> &str,
+    {
+        let mut items: Vec<_> = self.collect();
+        collator.sort_by_cached_key(&mut items, f);
+        items.into_iter()
+    }
+
+    /// The greatest item for the collator, the last one if several are equal, as
+    /// [`Iterator::max_by`].
+    fn max_by_collator(self, collator: &Collator) -> Option<Self::Item>
+    where
+        Self::Item: AsRef<str>,
+    {
+        self.max_by(|a, b| collator.compare(a.as_ref(), b.as_ref()))
+    }
+
+    /// The least item for the collator, the first one if several are equal, as
+    /// [`Iterator::min_by`].
+    fn min_by_collator(self, collator: &Collator) -> Option<Self::Item>
+    where
+        Self::Item: AsRef<str>,
+    {
+        self.min_by(|a, b| collator.compare(a.as_ref(), b.as_ref()))
+    }
+}
+
+impl<I: Iterator> CollateIteratorExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::tests::table;
+
+    #[test]
+    fn test_iterator_adapters() {
+        let collator = Collator::new(table());
+        let strings = ["ch", "b", "A", "ca", "a\u{301}", "a"];
+        let sorted: Vec<_> = strings.into_iter().sorted_by_collator(&collator).collect();
+        assert_eq!(sorted, ["a", "A", "a\u{301}", "b", "ca", "ch"]);
+
+        let records = [(1, "b"), (2, "a"), (3, "a\u{301}"), (4, "\u{E1}"), (5, "a")];
+        let ids: Vec<i32> = records
+            .into_iter()
+            .sorted_by_collator_key(&collator, |(_, s)| s)
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(ids, [2, 5, 3, 4, 1]);
+
+        // Among equal strings, the last is the greatest and the first the least.
+        let owned: Vec<String> = ["a", "ch", "\u{E1}", "ch", "a\u{301}"]
+            .map(String::from)
+            .to_vec();
+        assert!(std::ptr::eq(
+            owned.iter().max_by_collator(&collator).unwrap(),
+            &owned[3]
+        ));
+        assert_eq!(owned.iter().min_by_collator(&collator), Some(&owned[0]));
+        assert_eq!(std::iter::empty::<&str>().max_by_collator(&collator), None);
+    }
+}
@@ -1,2 +1,1158 @@
+use crate::algorithm::collation_element_array::Latin1FastPath;
+use crate::algorithm::trie::MappingTrie;
+use crate::data_files::{parse_allkeys, parse_version};
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::collation_wel::CollationWeight;
+use crate::definitions::variable_weighting::{MaxVariable, VariableWeighting};
+use crate::ducet::{self, UnicodeVersion};
+use crate::error::{CollateError, Result};
+use crate::weight_derivation::{UcaWeightDerivation, WeightDerivation};
+use std::cmp::Ordering;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 
+/// Defines the normalization of input strings (Covering UTS10-S1).
+pub mod normalize;
 
+/// Defines the production of collation element arrays (Covering UTS10-S2).
+pub mod collation_element_array;
+
+/// Defines the [`SortKey`](`sort_key::SortKey`) and its [`KeyEncoding`](`sort_key::KeyEncoding`)s
+/// (Covering UTS10-S3).
+pub mod sort_key;
+
+/// Defines the comparison of strings (Covering UTS10-S4).
+pub mod compare;
+
+/// Defines the sorting of slices of strings by a collator.
+pub mod sort;
+
+/// Defines the [`ComparisonTrace`](`trace::ComparisonTrace`) of the steps of the collation of two
+/// strings.
+pub mod trace;
+
+/// Defines the stable hashes of the levels of sort keys and the
+/// [`LevelBloomFilter`](`dedup::LevelBloomFilter`) finding the duplicates at a level.
+pub mod dedup;
+
+/// Defines the [`CollateIteratorExt`](`iter::CollateIteratorExt`) adapters ordering the items of
+/// iterators by a collator (feature `iter`).
+#[cfg(feature = "iter")]
+pub mod iter;
+
+/// Defines the [`Collated`](`collated::Collated`) wrapper ordering strings by a collator.
+pub mod collated;
+
+/// Defines the [`CollatedString`](`collated_string::CollatedString`) owning a string and its sort
+/// key.
+pub mod collated_string;
+
+/// Defines the [`CollatedBTreeMap`](`collated_map::CollatedBTreeMap`) of string keys kept in the
+/// order of a collator.
+pub mod collated_map;
+
+/// Defines the [`CollationVersion`](`version::CollationVersion`) identifying the sort keys of a
+/// collator.
+pub mod version;
+
+/// Defines the [`CodePointSource`](`source::CodePointSource`)s decoding the strings of other
+/// encodings than `str`, e.g. UTF-8 bytes to validate, as they are collated.
+pub mod source;
+
+/// Defines the comparison of UTF-16 strings, e.g. of Windows or JavaScript, and the handling of
+/// their [`IllFormedInput`](`utf16::IllFormedInput`).
+pub mod utf16;
+
+/// Defines the [`CollatorConfig`](`config::CollatorConfig`) of the collators configured by TOML or
+/// JSON documents (feature `config`).
+#[cfg(feature = "config")]
+pub mod config;
+
+/// Defines the [`ResolvedOptions`](`resolved::ResolvedOptions`) reporting the effective settings
+/// of a collator.
+pub mod resolved;
+
+/// Defines the two-stage trie the mappings are looked up in.
+mod trie;
+
+/// Defines the packed collation elements of the algorithm.
+mod packed;
+
+/// Defines the [`CollatorScratch`](`scratch::CollatorScratch`) buffers reused from one collation
+/// to the next.
+pub mod scratch;
+
+pub use collated::Collated;
+pub use collated_map::CollatedBTreeMap;
+pub use collated_string::CollatedString;
+pub use dedup::LevelBloomFilter;
+#[cfg(feature = "iter")]
+pub use iter::CollateIteratorExt;
+pub use resolved::ResolvedOptions;
+pub use scratch::CollatorScratch;
+pub use sort_key::{KeyEncoding, SortKey};
+pub use trace::{ComparisonTrace, StringTrace};
+pub use utf16::IllFormedInput;
+pub use version::CollationVersion;
+
+/// The number of levels compared by a [`Collator`]: the sort keys of a collator of strength L only
+/// contain the weights of the levels 1 to L.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Strength {
+    /// Base letters only: `a` = `á` = `A`.
+    Primary,
+    /// Base letters and accents: `a` = `A` < `á`.
+    Secondary,
+    /// Base letters, accents and case: `a` < `A` < `á`.
+    #[default]
+    Tertiary,
+    /// Up to the fourth level, e.g. the distinction of hiragana and katakana in Japanese.
+    Quaternary,
+    /// Up to the fifth level, which only some tailorings define, e.g. the width of the kana in
+    /// Japanese: `ｱ` (halfwidth) > `ア`.
+    Quinary,
+    /// All the levels of the collation elements, however many the table defines.
+    All,
+}
+
+impl Strength {
+    /// The maximum level of the sort keys.
+    pub fn level(&self) -> usize {
+        match self {
+            Self::Primary => 1,
+            Self::Secondary => 2,
+            Self::Tertiary => 3,
+            Self::Quaternary => 4,
+            Self::Quinary => 5,
+            Self::All => usize::MAX,
+        }
+    }
+}
+
+impl FromStr for Strength {
+    type Err = CollateError;
+
+    /// Parses `primary`, `secondary`, `tertiary`, `quaternary`, `quinary` and `all`, or the level
+    /// number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "primary" | "1" => Ok(Self::Primary),
+            "secondary" | "2" => Ok(Self::Secondary),
+            "tertiary" | "3" => Ok(Self::Tertiary),
+            "quaternary" | "4" => Ok(Self::Quaternary),
+            "quinary" | "5" => Ok(Self::Quinary),
+            "all" => Ok(Self::All),
+            _ => Err(CollateError::InvalidOption(format!(
+                "Unknown strength {:?}.",
+                s
+            ))),
+        }
+    }
+}
+
+/// Which case sorts first among strings differing only by case, as in the `kf` keyword of the
+/// Unicode locale extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CaseFirst {
+    /// The order of the tertiary weights of the table: lowercase first in the DUCET.
+    #[default]
+    Off,
+    /// Lowercase first: `a` < `A`.
+    Lower,
+    /// Uppercase first: `A` < `a`.
+    Upper,
+}
+
+/// What the fourth level of the sort keys carries, at [`Strength::Quaternary`] and above, as the
+/// behaviors of ICU: the shifted weights of its `alternate=shifted` setting, or the code points of
+/// its identical level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuaternarySource {
+    /// The fourth weights of the collation elements: with [`VariableWeighting::Shifted`], the
+    /// primary weights of the shifted collation elements, so that `de-ath` < `death`.
+    #[default]
+    Weights,
+    /// The code points of the NFD of the string, in place of the fourth weights, so that only the
+    /// canonically equivalent strings are equal. Each code point is weighted by two weights, as
+    /// they do not fit a primary weight of the [`KeyEncoding::U16BigEndian`] otherwise.
+    CodePoints,
+    /// Nothing: the fourth weights are made ignorable, so that the strings differ by their first
+    /// three levels only, even at [`Strength::Quaternary`].
+    Ignored,
+}
+
+/// Options of a [`Collator`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CollatorOptions {
+    /// Byte encoding of the sort keys produced by the collator.
+    pub key_encoding: KeyEncoding,
+    /// Maximum level of the sort keys, and thus of the comparisons.
+    pub strength: Strength,
+    /// Whether the secondary weights are compared from the end of the strings, as accents are in
+    /// French (e.g. `cote` < `côte` < `coté` < `côté`).
+    pub backward_secondary: bool,
+    /// How variable collation elements, e.g. spaces and punctuation, are weighted (S2.3).
+    pub variable_weighting: VariableWeighting,
+    /// Which collation elements are variable: those of the groups up to `max_variable`, or those
+    /// marked as variable by the table if `None`.
+    pub max_variable: Option<MaxVariable>,
+    /// Whether a level distinguishing uppercase from lowercase is inserted between the secondary
+    /// and the tertiary level, so that case differences can be considered at strength
+    /// [`Strength::Primary`] or [`Strength::Secondary`].
+    pub case_level: bool,
+    /// Which case sorts first, at the case level if any and otherwise at the tertiary level.
+    pub case_first: CaseFirst,
+    /// Whether sequences of decimal digits are compared by their numeric value, e.g. `2` < `10`.
+    pub numeric: bool,
+    /// How the unpaired surrogates of the UTF-16 strings are collated.
+    pub ill_formed_input: IllFormedInput,
+    /// What the fourth level of the sort keys carries.
+    pub quaternary: QuaternarySource,
+    /// Whether the strings equal at every level of the collator are ordered by their code points,
+    /// as a last level of the sort keys, so that only identical strings are equal, e.g. for an
+    /// `ORDER BY` of a database that must be deterministic even at [`Strength::Primary`].
+    ///
+    /// Unlike the identical level of [`QuaternarySource::CodePoints`], which compares the code
+    /// points of the NFD of the strings at the fourth level, the tiebreak compares their code
+    /// points as given, after all the levels: canonically equivalent strings are still ordered,
+    /// e.g. `e\u{301}` before `\u{E9}`, as U+0065 < U+00E9. The ill-formed sequences of the
+    /// [`CodePointSource`](crate::algorithm::source::CodePointSource)s are compared as U+FFFD.
+    pub code_point_tiebreak: bool,
+}
+
+impl CollatorOptions {
+    /// The options ignoring the spaces and punctuation entirely if `ignore`, e.g. `death` =
+    /// `de-ath`, and otherwise weighing them as letters before the digits, e.g. `de-ath` <
+    /// `death` < `de-aths`.
+    ///
+    /// Ignoring them sets the [`VariableWeighting::Shifted`] weighting of the spaces and
+    /// punctuation ([`MaxVariable::Punct`]), and lowers the strength to [`Strength::Tertiary`] if
+    /// it is higher: the shifted collation elements are still weighted at the quaternary level,
+    /// where they break the ties of the strings equal up to the tertiary level. To sort such
+    /// strings by their punctuation, e.g. `de-ath` < `death` < `de-aths`, set the strength to
+    /// [`Strength::Quaternary`] afterwards.
+    ///
+    /// Not ignoring them sets the [`VariableWeighting::NonIgnorable`] weighting.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorOptions, Strength};
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let ignoring = collator.clone_with_options(CollatorOptions::default().ignore_punctuation(true));
+    /// assert_eq!(ignoring.compare("de-ath", "death"), Ordering::Equal);
+    /// let shifted = collator.clone_with_options(CollatorOptions {
+    ///     strength: Strength::Quaternary,
+    ///     ..CollatorOptions::default().ignore_punctuation(true)
+    /// });
+    /// assert_eq!(shifted.compare("de-ath", "death"), Ordering::Less);
+    /// assert_eq!(shifted.compare("de-aths", "death"), Ordering::Greater);
+    /// assert_eq!(collator.compare("de-aths", "death"), Ordering::Less);
+    /// ```
+    pub fn ignore_punctuation(self, ignore: bool) -> Self {
+        if ignore {
+            Self {
+                variable_weighting: VariableWeighting::Shifted,
+                max_variable: Some(MaxVariable::Punct),
+                strength: self.strength.min(Strength::Tertiary),
+                ..self
+            }
+        } else {
+            Self {
+                variable_weighting: VariableWeighting::NonIgnorable,
+                ..self
+            }
+        }
+    }
+}
+
+/// A collation element table in the [`MappingTrie`] used to look it up.
+#[derive(Debug)]
+pub(crate) struct TableData {
+    pub(crate) trie: MappingTrie,
+    pub(crate) latin1: Latin1FastPath,
+    /// Version of the DUCET the table is, or is derived from, if known.
+    pub(crate) version: Option<UnicodeVersion>,
+    /// The [`CollationElementTable::max_variable_top`] of each [`MaxVariable`], in order.
+    max_variable_tops: [Option<CollationWeight>; 4],
+    /// The hash of the table hashed into the [`CollationVersion`] of the collators.
+    pub(crate) fingerprint: u64,
+}
+
+impl TableData {
+    pub(crate) fn new(table: CollationElementTable) -> Self {
+        let trie = MappingTrie::new(&table);
+        let latin1 = Latin1FastPath::new(&trie);
+        let max_variable_tops = [
+            MaxVariable::Space,
+            MaxVariable::Punct,
+            MaxVariable::Symbol,
+            MaxVariable::Currency,
+        ]
+        .map(|max_variable| table.max_variable_top(max_variable));
+        Self {
+            trie,
+            latin1,
+            version: None,
+            max_variable_tops,
+            fingerprint: version::fingerprint(&table),
+        }
+    }
+
+    /// The table of the trie, rebuilt in the order of its mappings.
+    pub(crate) fn table(&self) -> CollationElementTable {
+        self.trie.to_table()
+    }
+
+    pub(crate) fn max_variable_top(&self, max_variable: MaxVariable) -> Option<CollationWeight> {
+        self.max_variable_tops[max_variable as usize]
+    }
+
+    /// The data of a table of the DUCET of `version`, which must be the version of the Unicode data
+    /// of the crate (see [`ducet::check_version`]).
+    pub(crate) fn versioned(table: CollationElementTable, version: UnicodeVersion) -> Result<Self> {
+        ducet::check_version(version)?;
+        Ok(Self {
+            version: Some(version),
+            ..Self::new(table)
+        })
+    }
+}
+
+/// Applies the main algorithm to strings using a given [`CollationElementTable`].
+///
+/// The table, packed in a trie, is shared immutably by all the clones of a collator, so cloning is
+/// O(1) and a collator can be sent to or shared between threads. Use
+/// [`Collator::clone_with_options`] to get collators with different options over the same table.
+#[derive(Debug, Clone)]
+pub struct Collator {
+    pub(crate) data: Arc<TableData>,
+    options: CollatorOptions,
+    /// The policy of [`Collator::with_weight_derivation`], or `None` for the weights of 10.1.3.
+    weight_derivation: Option<Arc<dyn WeightDerivation>>,
+}
+
+impl Collator {
+    pub fn new(table: CollationElementTable) -> Self {
+        Self::with_options(table, CollatorOptions::default())
+    }
+
+    pub fn with_options(table: CollationElementTable, options: CollatorOptions) -> Self {
+        Self::from_data(Arc::new(TableData::new(table)), options)
+    }
+
+    /// Creates a collator for the DUCET of `version` (see [`ducet::table_for`]), so that an
+    /// application checks that the orderings are the ones of the version it expects.
+    ///
+    /// Fails with [`CollateError::UnsupportedVersion`] unless `version` is the one of the Unicode
+    /// data of the crate ([`ducet::version`]), whose canonical decompositions and implicit weights
+    /// the collators use: to reproduce the orderings of another version, build the crate with its
+    /// data (feature `datagen`, see `build.rs`).
+    pub fn for_unicode_version(version: UnicodeVersion) -> Result<Self> {
+        ducet::table_data(version).map(|data| Self::from_data(data, CollatorOptions::default()))
+    }
+
+    pub(crate) fn from_data(data: Arc<TableData>, options: CollatorOptions) -> Self {
+        Self {
+            data,
+            options,
+            weight_derivation: None,
+        }
+    }
+
+    /// A collator with other `options` sharing the table and the [`WeightDerivation`] of this
+    /// one.
+    pub fn clone_with_options(&self, options: CollatorOptions) -> Self {
+        Self {
+            options,
+            ..self.clone()
+        }
+    }
+
+    /// The collator deriving the implicit weights of the code points missing from its table by
+    /// `derivation` instead of by 10.1.3 (see [`WeightDerivation`]).
+    pub fn with_weight_derivation(self, derivation: impl WeightDerivation + 'static) -> Self {
+        Self {
+            weight_derivation: Some(Arc::new(derivation)),
+            ..self
+        }
+    }
+
+    /// The policy deriving the implicit weights of the collator: [`UcaWeightDerivation`] unless
+    /// another one was given to [`Collator::with_weight_derivation`].
+    pub fn weight_derivation(&self) -> &dyn WeightDerivation {
+        match &self.weight_derivation {
+            Some(derivation) => derivation.as_ref(),
+            None => &UcaWeightDerivation,
+        }
+    }
+
+    /// Whether both collators share the same table, e.g. because one is a clone of the other.
+    pub fn shares_table_with(&self, other: &Collator) -> bool {
+        Arc::ptr_eq(&self.data, &other.data)
+    }
+
+    /// The table of the collator, rebuilt from the packed form the collator looks it up in, which
+    /// does not keep the mappings themselves.
+    pub fn table(&self) -> CollationElementTable {
+        self.data.table()
+    }
+
+    pub fn options(&self) -> &CollatorOptions {
+        &self.options
+    }
+
+    /// Version of the DUCET the table of the collator is, or is derived from, or `None` for the
+    /// tables given to [`Collator::new`].
+    pub fn unicode_version(&self) -> Option<UnicodeVersion> {
+        self.data.version
+    }
+
+    /// Version of the UCA the collator applies: the version of the Unicode data bundled with the
+    /// crate ([`ducet::version`]), whose canonical decompositions and implicit weights it uses,
+    /// which is also the [`Collator::unicode_version`] of its table if known.
+    ///
+    /// An application expecting the orderings of a given version compares it with this one, e.g.
+    /// to rebuild the sort keys it stored when the data of the crate is updated.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use collate_rs::ducet::{self, UnicodeVersion};
+    ///
+    /// let allkeys = format!("@version {}\n0061 ; [.2075.0020.0002]\n", ducet::version());
+    /// let collator = Collator::from_allkeys(&allkeys).unwrap();
+    /// assert_eq!(collator.unicode_version(), Some(ducet::version()));
+    /// assert_eq!(collator.uca_version(), ducet::version());
+    /// let collator = Collator::new(collator.table());
+    /// assert_eq!(collator.unicode_version(), None);
+    /// assert_eq!(collator.uca_version(), ducet::version());
+    /// ```
+    pub fn uca_version(&self) -> UnicodeVersion {
+        ducet::version()
+    }
+
+    /// Creates a collator for a table in the allkeys.txt format, of the version declared by its
+    /// `@version` line, if any (see [`parse_version`]).
+    ///
+    /// Fails if the file is ill-formed or declares another version than the one of the Unicode
+    /// data of the crate ([`ducet::version`]).
+    pub fn from_allkeys(allkeys: &str) -> Result<Self> {
+        let table = parse_allkeys(allkeys)?;
+        let data = match parse_version(allkeys)? {
+            Some(version) => TableData::versioned(table, version)?,
+            None => TableData::new(table),
+        };
+        Ok(Self::from_data(Arc::new(data), CollatorOptions::default()))
+    }
+}
+
+/// The collator of the bundled DUCET with the default options, created the first time it is
+/// requested and shared by all the threads afterwards, for the programs that need no tailoring.
+///
+/// ```
+/// use collate_rs::default_collator;
+/// use std::cmp::Ordering;
+///
+/// assert_eq!(default_collator().compare("résumé", "resume"), Ordering::Greater);
+/// assert!(std::ptr::eq(default_collator(), default_collator()));
+/// ```
+pub fn default_collator() -> &'static Collator {
+    static DEFAULT: OnceLock<Collator> = OnceLock::new();
+    DEFAULT.get_or_init(|| {
+        Collator::for_unicode_version(ducet::version())
+            .expect("The bundled DUCET is always available.")
+    })
+}
+
+/// Compares `a` and `b` with the [`default_collator`].
+///
+/// ```
+/// let mut words = ["peach", "Péché", "pêche", "péché"];
+/// words.sort_by(|a, b| collate_rs::cmp(a, b));
+/// assert_eq!(words, ["peach", "péché", "Péché", "pêche"]);
+/// ```
+pub fn cmp(a: &str, b: &str) -> Ordering {
+    default_collator().compare(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::collation_wel::CollationElement;
+    use crate::definitions::mappings::CollationElementMapping;
+    use std::cmp::Ordering;
+
+    /// A small table shared by the tests of the submodules.
+    pub(crate) fn table() -> CollationElementTable {
+        let mapping = |c: &str, ce: &str| {
+            CollationElementMapping::new(
+                &c.chars().collect::<Vec<_>>(),
+                &[CollationElement::try_from(ce).unwrap()],
+            )
+            .unwrap()
+        };
+        CollationElementTable::new(vec![
+            mapping("a", "[.1C47.0020.0002]"),
+            mapping("A", "[.1C47.0020.0008]"),
+            mapping("b", "[.1C60.0020.0002]"),
+            mapping("c", "[.1C7A.0020.0002]"),
+            mapping("h", "[.1D18.0020.0002]"),
+            mapping("ch", "[.1D19.0020.0002]"),
+            mapping("\u{301}", "[.0000.0024.0002]"),
+        ])
+    }
+
+    #[test]
+    fn test_7_2() {
+        let collator = Collator::new(table());
+        assert_eq!(
+            collator.collation_element_array("cha\u{301}"),
+            vec![
+                CollationElement::try_from("[.1D19.0020.0002]").unwrap(),
+                CollationElement::try_from("[.1C47.0020.0002]").unwrap(),
+                CollationElement::try_from("[.0000.0024.0002]").unwrap(),
+            ]
+        );
+        assert_eq!(
+            collator.explain("cha\u{301}"),
+            "[.1D19.0020.0002][.1C47.0020.0002][.0000.0024.0002]"
+        );
+        // Characters missing from the table get implicit weights.
+        assert_eq!(collator.collation_element_array("\u{4E00}").len(), 2);
+
+        // The offsets of the collation elements of contractions, decompositions and implicit
+        // weights, and of the numbers.
+        let with_offsets = collator.collation_element_array_with_offsets("ch\u{E1}\u{4E00}");
+        let (elements, ranges): (Vec<_>, Vec<_>) = with_offsets.into_iter().unzip();
+        assert_eq!(
+            elements,
+            collator.collation_element_array("ch\u{E1}\u{4E00}")
+        );
+        assert_eq!(ranges, [0..2, 2..4, 2..4, 4..7, 4..7]);
+        let numeric = Collator::try_new("und")
+            .unwrap()
+            .clone_with_options(CollatorOptions {
+                numeric: true,
+                ..Default::default()
+            });
+        let ranges: Vec<_> = numeric
+            .collation_element_array_with_offsets("a0012-")
+            .into_iter()
+            .map(|(_, range)| range)
+            .collect();
+        assert_eq!(ranges, [0..1, 1..5, 1..5, 1..5, 5..6]);
+    }
+
+    #[test]
+    fn test_discontiguous_contractions() {
+        let collator = Collator::try_new("und").unwrap();
+        let ce = |s: &str| collator.collation_element_array(s);
+        // CYRILLIC SMALL LETTER I, COMBINING DOT BELOW, COMBINING BREVE: the contraction of "и"
+        // and the breve skips the dot below, of a lower class, as in CollationTest.
+        assert_eq!(
+            ce("\u{438}\u{323}\u{306}"),
+            [ce("\u{439}"), ce("\u{323}")].concat()
+        );
+        assert_eq!(
+            collator.compare("\u{439}\u{323}", "\u{438}\u{323}\u{306}"),
+            Ordering::Equal
+        );
+        // The acute accent, of the class of the breve, blocks it.
+        assert_eq!(
+            ce("\u{438}\u{301}\u{306}"),
+            [ce("\u{438}"), ce("\u{301}"), ce("\u{306}")].concat()
+        );
+        // A starter ends the non-starters.
+        assert_eq!(
+            ce("\u{438}\u{323}a\u{306}"),
+            [ce("\u{438}"), ce("\u{323}"), ce("a"), ce("\u{306}")].concat()
+        );
+        let ranges: Vec<_> = collator
+            .collation_element_array_with_offsets("\u{438}\u{323}\u{306}")
+            .into_iter()
+            .map(|(_, range)| range)
+            .collect();
+        assert_eq!(ranges, [0..6, 2..4]);
+        let characters: Vec<_> = collator
+            .trace("\u{438}\u{323}\u{306}x")
+            .mappings
+            .into_iter()
+            .map(|mapping| mapping.characters)
+            .collect();
+        assert_eq!(characters, ["\u{438}\u{323}\u{306}", "\u{323}", "x"]);
+    }
+
+    #[test]
+    fn test_thai_lao_prevowels() {
+        // The prevowels of Thai and Lao, written before the consonant they follow in speech, sort
+        // after it: the DUCET maps each prevowel and consonant to the collation elements of the
+        // consonant and then of the prevowel. The order is the one of ICU 78 (th, and root).
+        let sorted = [
+            "ๆ",
+            "ก",
+            "กก",
+            "กา",
+            "ก่า",
+            "กำ",
+            "เก",
+            "เก่",
+            "เกก",
+            "เกา",
+            "แก",
+            "โก",
+            "ใก",
+            "ไก",
+            "ไก่",
+            "ข",
+            "ขาว",
+            "ข้าว",
+            "เขา",
+            "เข้า",
+            "ไข่",
+            "นำ",
+            "น้ำ",
+            "มา",
+            "ม้า",
+            "แม่",
+            "ไม้",
+            "หมา",
+            "เหมา",
+            "ใหม่",
+            "ไหม",
+            "ກ",
+            "ເກ",
+            "ແກ",
+            "ໂກ",
+            "ໃກ",
+            "ໄກ",
+            "ຂ",
+            "ຂ້າວ",
+            "ເຂົ້າ",
+            "ປາ",
+            "ເປັນ",
+            "ໄປ",
+        ];
+        let collator = Collator::try_new("und").unwrap();
+        let mut words = sorted.to_vec();
+        words.reverse();
+        words.rotate_left(sorted.len() / 3);
+        collator.sort_slice(&mut words);
+        assert_eq!(words, sorted);
+        assert!(sorted
+            .windows(2)
+            .all(|pair| collator.compare(pair[0], pair[1]) == Ordering::Less));
+
+        let (consonant, prevowel) = (collator.explain("ก"), collator.explain("เ"));
+        assert_eq!(collator.explain("เก"), consonant + &prevowel);
+        let ranges: Vec<_> = collator
+            .collation_element_array_with_offsets("เกา")
+            .into_iter()
+            .map(|(_, range)| range)
+            .collect();
+        assert_eq!(ranges, [0..6, 0..6, 6..9]);
+    }
+
+    #[test]
+    fn test_7_3() {
+        let u16_collator = Collator::new(table());
+        let key = u16_collator.sort_key("ab");
+        assert_eq!(key.encoding(), KeyEncoding::U16BigEndian);
+        assert_eq!(
+            key.weights(),
+            [
+                0x1C, 0x47, 0x1C, 0x60, 0x00, 0x00, 0x00, 0x20, 0x00, 0x20, 0x00, 0x00, 0x00, 0x02,
+                0x00, 0x02
+            ]
+        );
+
+        let fractional_collator = Collator::with_options(
+            table(),
+            CollatorOptions {
+                key_encoding: KeyEncoding::Fractional,
+                ..Default::default()
+            },
+        );
+        let fractional_key = fractional_collator.sort_key("ab");
+        assert_eq!(fractional_key.encoding(), KeyEncoding::Fractional);
+        assert!(fractional_key.as_bytes().len() < key.as_bytes().len());
+
+        assert_eq!(SortKey::from_bytes(key.as_bytes()).unwrap(), key);
+        // The truncated keys, and the bytes of no weight, are rejected.
+        let bytes = key.as_bytes();
+        assert!(SortKey::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(SortKey::from_bytes(&bytes[..2]).is_err());
+        assert!(SortKey::from_bytes(&bytes[..3]).is_ok());
+        let bytes = fractional_key.as_bytes();
+        assert!(SortKey::from_bytes(bytes).is_ok());
+        assert!(SortKey::from_bytes(&[bytes[0], 0x80]).is_err());
+        assert!(SortKey::from_bytes(&[bytes[0], 0xF1, 0, 0, 0, 0]).is_err());
+        assert!(SortKey::from_bytes(&[bytes[0], 0xF0, 0, 0, 0]).is_err());
+        assert!(SortKey::from_bytes(&[bytes[0], 0x00]).is_err());
+        assert_eq!(key.to_string(), "[1C47 1C60 | 0020 0020 | 0002 0002]");
+        assert_eq!(fractional_key.levels(), key.levels());
+        assert_eq!(
+            format!("{:?}", fractional_key),
+            "SortKey(Fractional, [1C47 1C60 | 0020 0020 | 0002 0002])"
+        );
+        let wide = CollationElement::new(vec![0x1C47.into(), 0x607F.into(), 0x2_0000.into()]);
+        let wide_key = SortKey::new(&[wide], KeyEncoding::Fractional);
+        assert_eq!(wide_key.levels(), [[0x1C47], [0x607F], [0x2_0000]]);
+        assert!(key.try_cmp(&fractional_key).is_err());
+        assert_eq!(key.partial_cmp(&fractional_key), None);
+
+        // The truncated keys are cut between the weights and the level separators.
+        assert_eq!(key.truncate_safe(4).levels(), [[0x1C47]]);
+        assert_eq!(
+            key.truncate_safe(7).levels(),
+            [vec![0x1C47, 0x1C60], vec![]]
+        );
+        assert_eq!(key.truncate_safe(100), key);
+        assert_eq!(key.truncate_safe(0).as_bytes(), [0x01]);
+        assert_eq!(fractional_key.truncate_safe(4).as_bytes().len(), 3);
+        assert_eq!(
+            fractional_key.truncate_safe(7).levels(),
+            [vec![0x1C47, 0x1C60], vec![0x20]]
+        );
+        assert_eq!(
+            wide_key.truncate_safe(10).levels(),
+            [vec![0x1C47], vec![0x607F], vec![]]
+        );
+        assert!(
+            fractional_collator.sort_key("ch").truncate_safe(4) > fractional_key.truncate_safe(4)
+        );
+
+        // The folding keys are the keys of the first levels, without the case level.
+        let case_level = Collator::with_options(
+            table(),
+            CollatorOptions {
+                case_level: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(case_level.primary_key("Ab").to_string(), "[1C47 1C60]");
+        assert_eq!(
+            case_level.secondary_key("A\u{301}b").to_string(),
+            "[1C47 1C60 | 0020 0024 0020]"
+        );
+        assert_eq!(
+            u16_collator.primary_key("Ab"),
+            u16_collator.primary_key("a\u{301}b")
+        );
+    }
+
+    #[test]
+    fn test_7_4() {
+        for key_encoding in [KeyEncoding::U16BigEndian, KeyEncoding::Fractional] {
+            let collator = Collator::with_options(
+                table(),
+                CollatorOptions {
+                    key_encoding,
+                    ..Default::default()
+                },
+            );
+            assert_eq!(collator.compare("a", "b"), Ordering::Less);
+            assert_eq!(collator.compare("a", "A"), Ordering::Less);
+            assert_eq!(collator.compare("A", "b"), Ordering::Less);
+            assert_eq!(collator.compare("ab", "a\u{301}b"), Ordering::Less);
+            assert_eq!(collator.compare("ch", "ca"), Ordering::Greater);
+            assert_eq!(collator.compare("b", "\u{4E00}"), Ordering::Less);
+            assert_eq!(collator.compare("a", "a"), Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn test_shared_table() {
+        let collator = Collator::new(table());
+        let clone = collator.clone();
+        assert!(clone.shares_table_with(&collator));
+
+        let fractional = collator.clone_with_options(CollatorOptions {
+            key_encoding: KeyEncoding::Fractional,
+            ..Default::default()
+        });
+        assert!(fractional.shares_table_with(&collator));
+        assert_eq!(fractional.sort_key("a").encoding(), KeyEncoding::Fractional);
+        assert_eq!(collator.sort_key("a").encoding(), KeyEncoding::U16BigEndian);
+        assert!(!Collator::new(table()).shares_table_with(&collator));
+
+        let primary = collator.clone_with_options(CollatorOptions {
+            strength: Strength::Primary,
+            ..Default::default()
+        });
+        assert_eq!(primary.compare("a", "A\u{301}"), Ordering::Equal);
+        assert_eq!(primary.compare("a", "b"), Ordering::Less);
+        let secondary = collator.clone_with_options(CollatorOptions {
+            strength: "2".parse().unwrap(),
+            ..Default::default()
+        });
+        assert_eq!(secondary.compare("a", "A"), Ordering::Equal);
+        assert_eq!(secondary.compare("A", "a\u{301}"), Ordering::Less);
+
+        // The sort keys of a collator of strength `All` have every level of the table.
+        let six_levels = Collator::with_options(
+            CollationElementTable::new(vec![CollationElementMapping::new(
+                &['a'],
+                &[CollationElement::try_from("[.1C47.0020.0002.0001.0001.0003]").unwrap()],
+            )
+            .unwrap()]),
+            CollatorOptions {
+                strength: "all".parse().unwrap(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(six_levels.sort_key("a").levels().len(), 6);
+        assert_eq!(collator.sort_key("a").levels().len(), 3);
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let collator = collator.clone();
+                std::thread::spawn(move || collator.compare("ch", "ca"))
+            })
+            .collect();
+        assert!(threads
+            .into_iter()
+            .all(|t| t.join().unwrap() == Ordering::Greater));
+    }
+
+    #[test]
+    fn test_case_options() {
+        let collator = Collator::new(table());
+        let upper_first = collator.clone_with_options(CollatorOptions {
+            case_first: CaseFirst::Upper,
+            ..Default::default()
+        });
+        assert_eq!(collator.compare("a", "A"), Ordering::Less);
+        assert_eq!(upper_first.compare("a", "A"), Ordering::Greater);
+        assert_eq!(upper_first.compare("Ab", "ab"), Ordering::Less);
+        assert_eq!(upper_first.compare("A", "a\u{301}"), Ordering::Less);
+
+        // The case level distinguishes "a" and "A" even at the primary strength.
+        let case_level = collator.clone_with_options(CollatorOptions {
+            strength: Strength::Primary,
+            case_level: true,
+            ..Default::default()
+        });
+        assert_eq!(case_level.compare("a", "A"), Ordering::Less);
+        assert_eq!(case_level.compare("a", "a\u{301}"), Ordering::Equal);
+        assert_eq!(
+            case_level.sort_key("aA").to_string(),
+            "[1C47 1C47 | 0001 0002]"
+        );
+        let upper_first = case_level.clone_with_options(CollatorOptions {
+            case_first: CaseFirst::Upper,
+            ..case_level.options().clone()
+        });
+        assert_eq!(upper_first.compare("a", "A"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_ignore_punctuation() {
+        let collator = Collator::try_new("und").unwrap();
+        let sorted = |options: CollatorOptions| {
+            let collator = collator.clone_with_options(options);
+            let mut words = ["deaths", "de-aths", "death", "de-ath", "de ath", "de$ath"];
+            collator.sort_slice(&mut words);
+            let equal: Vec<bool> = words
+                .windows(2)
+                .map(|pair| collator.compare(pair[0], pair[1]) == Ordering::Equal)
+                .collect();
+            (words, equal)
+        };
+
+        // Weighed as letters, the spaces and punctuation sort before the letters.
+        let options = CollatorOptions::default().ignore_punctuation(false);
+        assert_eq!(options.variable_weighting, VariableWeighting::NonIgnorable);
+        let (words, equal) = sorted(options);
+        assert_eq!(
+            words,
+            ["de ath", "de-ath", "de-aths", "de$ath", "death", "deaths"]
+        );
+        assert!(!equal.contains(&true));
+
+        // Ignored, they do not distinguish the strings, even at the quaternary strength, but the
+        // symbols are not punctuation.
+        let options = CollatorOptions {
+            strength: Strength::Quaternary,
+            ..Default::default()
+        }
+        .ignore_punctuation(true);
+        assert_eq!(options.strength, Strength::Tertiary);
+        let (words, equal) = sorted(options.clone());
+        assert_eq!(
+            words,
+            ["de$ath", "death", "de-ath", "de ath", "deaths", "de-aths"]
+        );
+        assert_eq!(equal, [false, true, true, false, true]);
+
+        // Shifted with the quaternary strength, they only break the ties of the equal strings.
+        let (words, equal) = sorted(CollatorOptions {
+            strength: Strength::Quaternary,
+            ..options
+        });
+        assert_eq!(
+            words,
+            ["de$ath", "de ath", "de-ath", "death", "de-aths", "deaths"]
+        );
+        assert!(!equal.contains(&true));
+    }
+
+    #[test]
+    fn test_sort_key_writers() {
+        struct Full;
+        impl std::io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::WriteZero.into())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let collator = Collator::new(table());
+        let options = [
+            CollatorOptions::default(),
+            CollatorOptions {
+                key_encoding: KeyEncoding::Fractional,
+                backward_secondary: true,
+                ..Default::default()
+            },
+            CollatorOptions {
+                case_level: true,
+                case_first: CaseFirst::Upper,
+                ..Default::default()
+            },
+        ];
+        for options in options {
+            let collator = collator.clone_with_options(options);
+            let mut buffer = vec![0xFF];
+            collator.append_sort_key("cha\u{301}A", &mut buffer);
+            let key = collator.sort_key("cha\u{301}A");
+            assert_eq!(buffer[1..], *key.as_bytes());
+            let mut written = vec![];
+            let len = collator.write_sort_key_to("cha\u{301}A", &mut written);
+            assert_eq!(len.unwrap(), key.as_bytes().len());
+            assert_eq!(written, key.as_bytes());
+        }
+        assert!(collator.write_sort_key_to("a", &mut Full).is_err());
+    }
+
+    #[test]
+    fn test_quaternary_source() {
+        let collator = Collator::try_new("und").unwrap();
+        let with = |variable_weighting, quaternary| {
+            collator.clone_with_options(CollatorOptions {
+                strength: Strength::Quaternary,
+                variable_weighting,
+                quaternary,
+                ..Default::default()
+            })
+        };
+        let weights = with(VariableWeighting::Shifted, QuaternarySource::Weights);
+        let ignored = with(VariableWeighting::Shifted, QuaternarySource::Ignored);
+        let code_points = with(
+            VariableWeighting::NonIgnorable,
+            QuaternarySource::CodePoints,
+        );
+        assert_eq!(weights.compare("de-ath", "death"), Ordering::Less);
+        assert_eq!(ignored.compare("de-ath", "death"), Ordering::Equal);
+        assert!(ignored.sort_key("de-ath").levels()[3].is_empty());
+
+        // Completely ignorable, U+0000 only differs by its code point.
+        assert_eq!(weights.compare("a", "a\u{0}"), Ordering::Equal);
+        assert_eq!(code_points.compare("a", "a\u{0}"), Ordering::Less);
+        assert_eq!(
+            code_points.sort_key("a\u{10000}").levels()[3],
+            [1, 0x62, 3, 1]
+        );
+        // ANGSTROM SIGN, LATIN CAPITAL LETTER A WITH RING ABOVE
+        assert_eq!(code_points.compare("\u{212B}", "\u{C5}"), Ordering::Equal);
+        let shifted = with(VariableWeighting::Shifted, QuaternarySource::CodePoints);
+        assert_eq!(shifted.compare("de-ath", "death"), Ordering::Less);
+        assert_eq!(shifted.compare("de\u{0}ath", "death"), Ordering::Less);
+        // The code points following an unpaired surrogate are kept.
+        let implicit = shifted.clone_with_options(CollatorOptions {
+            ill_formed_input: IllFormedInput::Implicit,
+            ..shifted.options().clone()
+        });
+        let ordering = implicit.compare_utf16(&[0x61, 0xD800, 0x0], &[0x61, 0xD800]);
+        assert_eq!(ordering.unwrap(), Ordering::Greater);
+
+        for collator in [&ignored, &code_points] {
+            assert_ne!(collator.version(), weights.version());
+        }
+    }
+
+    #[test]
+    fn test_code_point_tiebreak() {
+        let primary = Collator::try_new("und")
+            .unwrap()
+            .clone_with_options(CollatorOptions {
+                strength: Strength::Primary,
+                ..Default::default()
+            });
+        let tiebreak = primary.clone_with_options(CollatorOptions {
+            code_point_tiebreak: true,
+            ..primary.options().clone()
+        });
+        assert_eq!(primary.compare("Résumé", "resume"), Ordering::Equal);
+        assert_eq!(tiebreak.compare("Résumé", "resume"), Ordering::Less);
+        assert_eq!(tiebreak.compare("resume", "résumé"), Ordering::Less);
+        // The levels come first, whatever the code points.
+        assert_eq!(tiebreak.compare("b", "A"), Ordering::Greater);
+        assert_eq!(tiebreak.compare("resume", "resumes"), Ordering::Less);
+        // Canonically equivalent strings are ordered too, and only identical strings are equal.
+        assert_eq!(tiebreak.compare("\u{E9}", "e\u{301}"), Ordering::Greater);
+        assert_eq!(tiebreak.compare("é", "é"), Ordering::Equal);
+        assert_eq!(tiebreak.compare("", "\u{0}"), Ordering::Less);
+
+        let mut words = vec![
+            "resume",
+            "Resume",
+            "résumé",
+            "RESUME",
+            "re\u{301}sume\u{301}",
+        ];
+        let mut sorted = words.clone();
+        tiebreak.sort_slice(&mut words);
+        sorted.sort_by(|a, b| tiebreak.compare(a, b));
+        assert_eq!(words, sorted);
+        let groups = tiebreak.group_by_key(words.iter().rev(), Strength::Primary);
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].1.iter().copied().eq(words.iter()));
+        assert_eq!(
+            words,
+            [
+                "RESUME",
+                "Resume",
+                "resume",
+                "re\u{301}sume\u{301}",
+                "résumé"
+            ]
+        );
+        let key = tiebreak.sort_key("Resume");
+        assert_eq!(key.levels()[1][..2], [1, 0x53]);
+        assert_eq!(tiebreak.sort_key_utf8(b"Resume").unwrap(), key);
+        assert_eq!(
+            tiebreak.compare_to_key("Resume", &key).unwrap(),
+            Ordering::Equal
+        );
+        assert_eq!(
+            tiebreak.compare_to_key("resume", &key).unwrap(),
+            Ordering::Greater
+        );
+        assert_ne!(tiebreak.version(), primary.version());
+    }
+
+    #[test]
+    fn test_compare_to_key() {
+        let collator = Collator::new(table());
+        let strings = [
+            "", "a", "A", "ab", "a\u{301}", "\u{E1}b", "b", "-a", "\u{FFFF}",
+        ];
+        let options = [
+            CollatorOptions::default(),
+            CollatorOptions {
+                key_encoding: KeyEncoding::Fractional,
+                backward_secondary: true,
+                variable_weighting: VariableWeighting::Shifted,
+                ..Default::default()
+            },
+            CollatorOptions {
+                case_level: true,
+                strength: Strength::Primary,
+                ..Default::default()
+            },
+        ];
+        for options in options {
+            let collator = collator.clone_with_options(options);
+            for a in strings {
+                for b in strings {
+                    let key = collator.sort_key(b);
+                    assert_eq!(
+                        collator.compare_to_key(a, &key).unwrap(),
+                        collator.compare(a, b),
+                        "{:?} {:?} {:?}",
+                        a,
+                        b,
+                        collator.options()
+                    );
+                }
+            }
+        }
+
+        // Only the levels of both keys are compared.
+        let primary = collator.clone_with_options(CollatorOptions {
+            strength: Strength::Primary,
+            ..Default::default()
+        });
+        let (key, primary_key) = (collator.sort_key("ab"), primary.sort_key("ab"));
+        assert_eq!(
+            primary.compare_to_key("a\u{301}b", &key).unwrap(),
+            Ordering::Equal
+        );
+        assert_eq!(
+            collator.compare_to_key("a\u{301}b", &primary_key).unwrap(),
+            Ordering::Equal
+        );
+        assert_eq!(
+            collator.compare_to_key("a", &primary_key).unwrap(),
+            Ordering::Less
+        );
+        assert_eq!(
+            primary.compare_to_key("abc", &key).unwrap(),
+            Ordering::Greater
+        );
+
+        let fractional = collator.clone_with_options(CollatorOptions {
+            key_encoding: KeyEncoding::Fractional,
+            ..Default::default()
+        });
+        assert!(matches!(
+            fractional.compare_to_key("ab", &key),
+            Err(CollateError::IncompatibleSortKeys { .. })
+        ));
+    }
+
+    #[test]
+    fn test_latin1_fast_path() {
+        let mut mappings = table().0;
+        mappings.push(
+            CollationElementMapping::new(
+                &['d', 'z'],
+                &[CollationElement::try_from("[.1D00.0020.0002]").unwrap()],
+            )
+            .unwrap(),
+        );
+        let data = TableData::new(CollationElementTable::new(mappings));
+        // "c" starts the contraction "ch", "d" only maps as the start of "dz".
+        assert!(data.latin1.get('a').is_some());
+        assert!(data.latin1.get('h').is_some());
+        assert!(data.latin1.get('c').is_none());
+        assert!(data.latin1.get('d').is_none());
+        assert!(data.latin1.get('\u{301}').is_none());
+        let collator = Collator::from_data(Arc::new(data), CollatorOptions::default());
+        assert_eq!(
+            collator.explain("chdza\u{301}"),
+            "[.1D19.0020.0002][.1D00.0020.0002][.1C47.0020.0002][.0000.0024.0002]"
+        );
+    }
+
+    #[test]
+    fn test_default_collator() {
+        let collators: Vec<&'static Collator> = (0..4)
+            .map(|_| std::thread::spawn(default_collator))
+            .map(|thread| thread.join().unwrap())
+            .collect();
+        assert!(collators
+            .iter()
+            .all(|c| std::ptr::eq(*c, default_collator())));
+        assert!(default_collator().shares_table_with(&Collator::try_new("und").unwrap()));
+        assert_eq!(cmp("a", "B"), Ordering::Less);
+        assert_eq!(cmp("a", "a"), Ordering::Equal);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::data_files::ucd::{canonical_combining_class, canonical_decomposition};
+use std::ops::Range;
+
+const S_BASE: u32 = 0xAC00;
+const L_BASE: u32 = 0x1100;
+const V_BASE: u32 = 0x1161;
+const T_BASE: u32 = 0x11A7;
+const V_COUNT: u32 = 21;
+const T_COUNT: u32 = 28;
+const N_COUNT: u32 = V_COUNT * T_COUNT;
+const S_COUNT: u32 = 19 * N_COUNT;
+
+/// The Canonical_Combining_Class of `c` in the Unicode data of the crate: `0` for the starters,
+/// and the class by which the non-starters are put in canonical order otherwise, e.g. when the
+/// unblocked non-starters of a discontiguous match are looked for (S2.1.2).
+///
+/// ```
+/// use collate_rs::algorithm::normalize::ccc;
+///
+/// assert_eq!(ccc('a'), 0);
+/// // COMBINING DOT BELOW, COMBINING ACUTE ACCENT
+/// assert_eq!(ccc('\u{323}'), 220);
+/// assert_eq!(ccc('\u{301}'), 230);
+/// ```
+pub fn ccc(c: char) -> u8 {
+    canonical_combining_class(c)
+}
+
+/// Appends the canonical decomposition of `c` to `decomposed`, decomposing the Hangul syllables
+/// algorithmically (The Unicode Standard, Section 3.12).
+pub(crate) fn decompose(c: char, decomposed: &mut Vec<char>) {
+    let cp = c as u32;
+    if (S_BASE..S_BASE + S_COUNT).contains(&cp) {
+        let s_index = cp - S_BASE;
+        let jamo = [
+            L_BASE + s_index / N_COUNT,
+            V_BASE + (s_index % N_COUNT) / T_COUNT,
+            T_BASE + s_index % T_COUNT,
+        ];
+        let len = if jamo[2] == T_BASE { 2 } else { 3 };
+        decomposed.extend(jamo[..len].iter().filter_map(|&cp| char::from_u32(cp)));
+    } else if let Some(decomposition) = canonical_decomposition(c) {
+        decomposed.extend(decomposition.iter().filter_map(|&cp| char::from_u32(cp)));
+    } else {
+        decomposed.push(c);
+    }
+}
+
+/// > S1.1 Convert the input string to NFD.
+///
+/// Every character is replaced by its full canonical decomposition, then every sequence of
+/// non-starters is put in canonical order (stably sorted by canonical combining class).
+pub fn to_nfd(s: &str) -> Vec<char> {
+    let mut decomposed = Vec::with_capacity(s.len());
+    to_nfd_into(s, &mut decomposed);
+    decomposed
+}
+
+/// Like [`to_nfd`], replacing the contents of `decomposed`, to reuse its allocation.
+pub(crate) fn to_nfd_into(s: &str, decomposed: &mut Vec<char>) {
+    decomposed.clear();
+    for c in s.chars() {
+        decompose(c, decomposed);
+    }
+    canonical_reorder(decomposed);
+}
+
+/// Puts every sequence of non-starters of the decomposed characters in canonical order: stably
+/// sorted by [`ccc`], as in the Canonical Ordering Algorithm (The Unicode Standard, Section 3.11).
+///
+/// ```
+/// use collate_rs::algorithm::normalize::canonical_reorder;
+///
+/// let mut chars = ['a', '\u{301}', '\u{323}', '\u{302}', 'b'];
+/// canonical_reorder(&mut chars);
+/// assert_eq!(chars, ['a', '\u{323}', '\u{301}', '\u{302}', 'b']);
+/// ```
+pub fn canonical_reorder(decomposed: &mut [char]) {
+    let mut start = 0;
+    while start < decomposed.len() {
+        let len = decomposed[start..]
+            .iter()
+            .take_while(|&&c| canonical_combining_class(c) != 0)
+            .count();
+        if len > 1 {
+            decomposed[start..start + len].sort_by_key(|&c| canonical_combining_class(c));
+        }
+        start += len.max(1);
+    }
+}
+
+/// Like [`to_nfd`], with the byte range in `s` of the character each character of the NFD comes
+/// from.
+pub(crate) fn to_nfd_with_offsets(s: &str) -> (Vec<char>, Vec<Range<usize>>) {
+    let mut decomposed = vec![];
+    let mut offsets = vec![];
+    for (i, c) in s.char_indices() {
+        decompose(c, &mut decomposed);
+        offsets.resize(decomposed.len(), i..i + c.len_utf8());
+    }
+    let mut start = 0;
+    while start < decomposed.len() {
+        let len = decomposed[start..]
+            .iter()
+            .take_while(|&&c| canonical_combining_class(c) != 0)
+            .count();
+        if len > 1 {
+            let mut marks: Vec<(char, Range<usize>)> = decomposed[start..start + len]
+                .iter()
+                .copied()
+                .zip(offsets[start..start + len].iter().cloned())
+                .collect();
+            marks.sort_by_key(|&(c, _)| canonical_combining_class(c));
+            for (j, (c, range)) in marks.into_iter().enumerate() {
+                decomposed[start + j] = c;
+                offsets[start + j] = range;
+            }
+        }
+        start += len.max(1);
+    }
+    (decomposed, offsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_7_1() {
+        // LATIN CAPITAL LETTER C WITH CEDILLA AND ACUTE
+        assert_eq!(to_nfd("\u{1E08}"), ['C', '\u{327}', '\u{301}']);
+        // HANGUL SYLLABLE GAG, HANGUL SYLLABLE GA
+        assert_eq!(
+            to_nfd("\u{AC01}\u{AC00}"),
+            ['\u{1100}', '\u{1161}', '\u{11A8}', '\u{1100}', '\u{1161}']
+        );
+        // COMBINING ACUTE ACCENT (230) is reordered after COMBINING DOT BELOW (220).
+        assert_eq!(to_nfd("a\u{301}\u{323}b"), ['a', '\u{323}', '\u{301}', 'b']);
+        assert_eq!(to_nfd("abc"), ['a', 'b', 'c']);
+        // The classes of the Hangul jamo, the Hebrew points and the CJK tone marks.
+        assert_eq!(
+            ['\u{1161}', '\u{5B0}', '\u{302A}', '\u{1D165}'].map(ccc),
+            [0, 10, 218, 216]
+        );
+
+        let (decomposed, offsets) = to_nfd_with_offsets("\u{E1}\u{301}\u{323}b");
+        assert_eq!(decomposed, ['a', '\u{323}', '\u{301}', '\u{301}', 'b']);
+        assert_eq!(offsets, [0..2, 4..6, 0..2, 2..4, 6..7]);
+    }
+}
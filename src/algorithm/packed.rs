@@ -0,0 +1,198 @@
+use crate::definitions::collation_wel::{CollationElement, CollationWeight, Weighted};
+use std::sync::Arc;
+
+/// A collation element of the algorithm, packed so that the collation element arrays and the
+/// expansions of the tables need no allocation per collation element: the primary, secondary and
+/// tertiary weights in a `u64`, as in the 64-bit collation elements of ICU, with the quaternary and
+/// quinary weights beside them.
+///
+/// The collation elements that do not fit, of more than five levels or with weights too large for
+/// their level, are kept as they are behind an [`Arc`], cloned without allocating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PackedElement {
+    Narrow(NarrowElement),
+    Wide(Arc<CollationElement>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct NarrowElement {
+    /// `primary << 32 | secondary << 16 | tertiary`.
+    weights: u64,
+    quaternary: u32,
+    quinary: u16,
+    levels: u8,
+    variable: bool,
+}
+
+/// The largest weight of each level of a [`NarrowElement`].
+const NARROW_MAX: [u32; 5] = [u32::MAX, 0xFFFF, 0xFFFF, u32::MAX, 0xFFFF];
+
+impl NarrowElement {
+    /// A collation element of three levels, as the simple mappings of the tables.
+    pub(crate) fn new(primary: u32, secondary: u16, tertiary: u16, variable: bool) -> Self {
+        Self {
+            weights: u64::from(primary) << 32 | u64::from(secondary) << 16 | u64::from(tertiary),
+            quaternary: 0,
+            quinary: 0,
+            levels: 3,
+            variable,
+        }
+    }
+
+    #[inline]
+    /// The weights past the last level are always ignorable, as the levels are only added.
+    fn weight(&self, n: usize) -> u32 {
+        match n {
+            1 => (self.weights >> 32) as u32,
+            2 => (self.weights >> 16 & 0xFFFF) as u32,
+            3 => (self.weights & 0xFFFF) as u32,
+            4 => self.quaternary,
+            5 => u32::from(self.quinary),
+            _ => 0,
+        }
+    }
+
+    /// Sets the weight at level `n`, which must fit.
+    fn set_weight(&mut self, n: usize, weight: u32) {
+        match n {
+            1 => self.weights = self.weights & 0xFFFF_FFFF | u64::from(weight) << 32,
+            2 => self.weights = self.weights & !0xFFFF_0000 | u64::from(weight) << 16,
+            3 => self.weights = self.weights & !0xFFFF | u64::from(weight),
+            4 => self.quaternary = weight,
+            _ => self.quinary = weight as u16,
+        }
+        self.levels = self.levels.max(n as u8);
+    }
+}
+
+impl PackedElement {
+    pub(crate) fn is_variable(&self) -> bool {
+        match self {
+            Self::Narrow(ce) => ce.variable,
+            Self::Wide(ce) => ce.is_variable(),
+        }
+    }
+
+    pub(crate) fn to_element(&self) -> CollationElement {
+        match self {
+            Self::Narrow(ce) => {
+                let weights = (1..=usize::from(ce.levels))
+                    .map(|n| CollationWeight(ce.weight(n)))
+                    .collect();
+                CollationElement(weights, ce.variable)
+            }
+            Self::Wide(ce) => CollationElement::clone(ce),
+        }
+    }
+}
+
+impl From<&CollationElement> for PackedElement {
+    fn from(ce: &CollationElement) -> Self {
+        let fits = ce.0.len() <= NARROW_MAX.len()
+            && ce.0.iter().zip(NARROW_MAX).all(|(w, max)| w.0 <= max);
+        if !fits {
+            return Self::Wide(Arc::new(ce.clone()));
+        }
+        let mut narrow = NarrowElement::new(0, 0, 0, ce.1);
+        narrow.levels = 0;
+        for (n, w) in ce.0.iter().enumerate() {
+            narrow.set_weight(n + 1, w.0);
+        }
+        Self::Narrow(narrow)
+    }
+}
+
+impl Weighted for PackedElement {
+    #[inline]
+    fn len_levels(&self) -> usize {
+        match self {
+            Self::Narrow(ce) => usize::from(ce.levels),
+            Self::Wide(ce) => ce.len_levels(),
+        }
+    }
+
+    #[inline]
+    fn weight(&self, n: usize) -> u32 {
+        match self {
+            Self::Narrow(ce) => ce.weight(n),
+            Self::Wide(ce) => Weighted::weight(ce.as_ref(), n),
+        }
+    }
+
+    /// Sets the weight at level `n`, moving the collation element behind an [`Arc`] if it no
+    /// longer fits.
+    fn set_weight(&mut self, n: usize, weight: u32) {
+        match self {
+            Self::Narrow(ce) if NARROW_MAX.get(n - 1).is_some_and(|&max| weight <= max) => {
+                ce.set_weight(n, weight)
+            }
+            Self::Narrow(_) => {
+                let mut wide = self.to_element();
+                Weighted::set_weight(&mut wide, n, weight);
+                *self = Self::Wide(Arc::new(wide));
+            }
+            Self::Wide(ce) => Weighted::set_weight(Arc::make_mut(ce), n, weight),
+        }
+    }
+
+    fn clear_variable(&mut self) {
+        match self {
+            Self::Narrow(ce) => ce.variable = false,
+            Self::Wide(ce) if ce.1 => Arc::make_mut(ce).clear_variable(),
+            Self::Wide(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::variable_weighting::VariableWeighting;
+
+    #[test]
+    fn test_packed_element() {
+        assert_eq!(std::mem::size_of::<PackedElement>(), 16);
+        let ce = |s| CollationElement::try_from(s).unwrap();
+        for s in [
+            "[.1FA2.0020.0002]",
+            "[*0209.0020.0002]",
+            "[.0000.0000.0000]",
+            "[.1C47.0020.0002.FFFF]",
+            // The kana of the tailoring of Japanese have a fifth level.
+            "[.3D5A.0020.000E.0000.0002]",
+        ] {
+            let packed = PackedElement::from(&ce(s));
+            assert!(matches!(packed, PackedElement::Narrow(_)), "{s}");
+            assert_eq!(packed.to_element(), ce(s));
+        }
+        let empty = PackedElement::from(&CollationElement::new(vec![]));
+        assert_eq!(Weighted::len_levels(&empty), 0);
+        let six = ce("[.1C47.0020.0002.0001.0001.0003]");
+        let packed = PackedElement::from(&six);
+        assert!(matches!(packed, PackedElement::Wide(_)));
+        assert_eq!(packed.to_element(), six);
+        assert_eq!(Weighted::weight(&packed, 6), 3);
+        assert_eq!(Weighted::weight(&packed, 7), 0);
+
+        // The variable weighting agrees with the one of the collation elements.
+        let array = [
+            ce("[*0209.0020.0002]"),
+            ce("[.0000.0024.0002]"),
+            ce("[.1FA2.0020.0002]"),
+            six,
+        ];
+        for weighting in [
+            VariableWeighting::Blanked,
+            VariableWeighting::Shifted,
+            VariableWeighting::ShiftTrimmed,
+        ] {
+            let mut packed: Vec<_> = array.iter().map(PackedElement::from).collect();
+            weighting.apply_in_place(&mut packed, PackedElement::is_variable);
+            let packed: Vec<_> = packed.iter().map(PackedElement::to_element).collect();
+            assert_eq!(
+                packed,
+                weighting.apply(&array, CollationElement::is_variable)
+            );
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::algorithm::{CaseFirst, CollationVersion, Collator, Strength};
+use crate::definitions::variable_weighting::{MaxVariable, VariableWeighting};
+use crate::ducet::UnicodeVersion;
+use std::fmt;
+
+/// The effective settings of a [`Collator`], once the options of its locale, the keywords of its
+/// language tag and the overrides of the application are resolved, as returned by
+/// [`Collator::resolved_options`], like `Intl.Collator.prototype.resolvedOptions()` in
+/// JavaScript.
+///
+/// The names of the fields are those of the settings of CLDR, in which they display, e.g.
+/// `strength=secondary, alternate=shifted, numeric=on, …`, and serialize with the `serde`
+/// feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ResolvedOptions {
+    pub strength: Strength,
+    pub alternate: VariableWeighting,
+    /// The last group of variable collation elements, or `None` if they are those marked as
+    /// variable by the table.
+    pub max_variable: Option<MaxVariable>,
+    /// The scripts reordered, as the codes of the `kr` keyword: always empty, as the scripts
+    /// keep the order of the table.
+    pub reorder: Vec<String>,
+    pub numeric: bool,
+    pub backwards: bool,
+    pub case_level: bool,
+    pub case_first: CaseFirst,
+    /// The [`Collator::uca_version`] of the collator.
+    pub version: UnicodeVersion,
+    /// The [`Collator::version`] of the sort keys of the collator.
+    pub collation_version: CollationVersion,
+}
+
+impl Collator {
+    /// The effective settings of the collator, e.g. to display them to the users or to check what
+    /// a locale and its keywords resolve to.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, Strength};
+    /// use collate_rs::definitions::variable_weighting::VariableWeighting;
+    ///
+    /// let collator = Collator::for_locale("de-AT-u-ka-shifted-kn-ks-level2").unwrap();
+    /// let resolved = collator.resolved_options();
+    /// assert_eq!(resolved.strength, Strength::Secondary);
+    /// assert_eq!(resolved.alternate, VariableWeighting::Shifted);
+    /// assert!(resolved.numeric && !resolved.backwards);
+    /// assert!(resolved.to_string().starts_with(
+    ///     "strength=secondary, alternate=shifted, reorder=others, numeric=on, backwards=off,"
+    /// ));
+    /// ```
+    pub fn resolved_options(&self) -> ResolvedOptions {
+        let options = self.options();
+        ResolvedOptions {
+            strength: options.strength,
+            alternate: options.variable_weighting,
+            max_variable: options.max_variable,
+            reorder: vec![],
+            numeric: options.numeric,
+            backwards: options.backward_secondary,
+            case_level: options.case_level,
+            case_first: options.case_first,
+            version: self.uca_version(),
+            collation_version: self.version(),
+        }
+    }
+}
+
+/// The settings in the notation of CLDR, separated by commas.
+impl fmt::Display for ResolvedOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        let strength = match self.strength {
+            Strength::Primary => "primary",
+            Strength::Secondary => "secondary",
+            Strength::Tertiary => "tertiary",
+            Strength::Quaternary => "quaternary",
+            Strength::Quinary => "quinary",
+            Strength::All => "all",
+        };
+        let alternate = match self.alternate {
+            VariableWeighting::NonIgnorable => "non-ignorable",
+            VariableWeighting::Blanked => "blanked",
+            VariableWeighting::Shifted => "shifted",
+            VariableWeighting::ShiftTrimmed => "shift-trimmed",
+        };
+        write!(f, "strength={}, alternate={}", strength, alternate)?;
+        if let Some(max_variable) = self.max_variable {
+            let max_variable = match max_variable {
+                MaxVariable::Space => "space",
+                MaxVariable::Punct => "punct",
+                MaxVariable::Symbol => "symbol",
+                MaxVariable::Currency => "currency",
+            };
+            write!(f, ", maxVariable={}", max_variable)?;
+        }
+        let reorder = if self.reorder.is_empty() {
+            "others".to_string()
+        } else {
+            self.reorder.join(" ")
+        };
+        let case_first = match self.case_first {
+            CaseFirst::Off => "off",
+            CaseFirst::Lower => "lower",
+            CaseFirst::Upper => "upper",
+        };
+        write!(
+            f,
+            ", reorder={}, numeric={}, backwards={}, caseLevel={}, caseFirst={}, version={}",
+            reorder,
+            on_off(self.numeric),
+            on_off(self.backwards),
+            on_off(self.case_level),
+            case_first,
+            self.version
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::CollatorOptions;
+    use crate::ducet;
+
+    #[test]
+    fn test_resolved_options() {
+        let collator = Collator::try_new("und").unwrap();
+        let resolved = collator.resolved_options();
+        assert_eq!(resolved.strength, Strength::Tertiary);
+        assert_eq!(resolved.version, ducet::version());
+        assert_eq!(resolved.collation_version, collator.version());
+        assert_eq!(
+            resolved.to_string(),
+            format!(
+                "strength=tertiary, alternate=non-ignorable, reorder=others, numeric=off, \
+                 backwards=off, caseLevel=off, caseFirst=off, version={}",
+                ducet::version()
+            )
+        );
+
+        let overridden = collator.clone_with_options(CollatorOptions {
+            max_variable: Some(MaxVariable::Punct),
+            case_level: true,
+            case_first: CaseFirst::Upper,
+            ..collator.options().clone()
+        });
+        let resolved = overridden.resolved_options();
+        assert!(resolved.case_level);
+        assert_ne!(resolved.collation_version, collator.version());
+        assert!(resolved.to_string().contains(
+            "maxVariable=punct, reorder=others, numeric=off, backwards=off, caseLevel=on, \
+             caseFirst=upper"
+        ));
+    }
+}
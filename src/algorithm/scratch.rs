@@ -0,0 +1,105 @@
+use crate::algorithm::collation_element_array::ElementBuffer;
+use crate::algorithm::Collator;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+/// Buffers reused to collate many strings without allocating: the normalized characters, the
+/// collation element array and the sort keys of the last strings collated.
+///
+/// [`Collator::compare`] and [`Collator::sort_key`] already reuse scratch buffers kept by each
+/// thread; a `CollatorScratch` makes the reuse explicit, e.g. to keep the memory of the buffers
+/// out of the threads, which keep theirs (as large as the longest string collated) until they
+/// exit.
+#[derive(Debug, Clone, Default)]
+pub struct CollatorScratch {
+    pub(crate) chars: Vec<char>,
+    pub(crate) elements: ElementBuffer,
+    pub(crate) keys: [Vec<u8>; 2],
+    /// The code points of the last string collated, as given, for
+    /// [`CollatorOptions::code_point_tiebreak`](crate::algorithm::CollatorOptions::code_point_tiebreak).
+    pub(crate) code_points: Vec<char>,
+}
+
+impl CollatorScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+thread_local! {
+    static SCRATCH: RefCell<CollatorScratch> = RefCell::default();
+}
+
+/// Calls `f` with the scratch buffers of the thread, or new ones if they are in use.
+pub(crate) fn with_thread_scratch<T>(f: impl FnOnce(&mut CollatorScratch) -> T) -> T {
+    SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+        Ok(mut scratch) => f(&mut scratch),
+        Err(_) => f(&mut CollatorScratch::new()),
+    })
+}
+
+impl Collator {
+    /// Like [`Collator::compare`], with the buffers of `scratch`.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorScratch};
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let mut scratch = CollatorScratch::new();
+    /// let mut names = vec!["Émile", "Zoé", "adèle"];
+    /// names.sort_by(|a, b| collator.compare_with(a, b, &mut scratch));
+    /// assert_eq!(names, ["adèle", "Émile", "Zoé"]);
+    /// ```
+    pub fn compare_with(&self, a: &str, b: &str, scratch: &mut CollatorScratch) -> Ordering {
+        self.sort_key_into_slot(a, scratch, 0);
+        self.sort_key_into_slot(b, scratch, 1);
+        scratch.keys[0].cmp(&scratch.keys[1])
+    }
+
+    /// The bytes of the sort key of `s`, as [`SortKey::as_bytes`](crate::algorithm::SortKey::as_bytes),
+    /// in the buffers of `scratch`.
+    pub fn sort_key_with<'s>(&self, s: &str, scratch: &'s mut CollatorScratch) -> &'s [u8] {
+        self.sort_key_into_slot(s, scratch, 0);
+        &scratch.keys[0]
+    }
+
+    pub(crate) fn sort_key_into_slot(&self, s: &str, scratch: &mut CollatorScratch, slot: usize) {
+        self.collation_elements_into(s, scratch);
+        let key = &mut scratch.keys[slot];
+        key.clear();
+        self.write_sort_key_from_elements(scratch.elements.as_slice(), key);
+        self.write_tiebreak(&scratch.code_points, key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::tests::table;
+    use crate::algorithm::{Collator, CollatorOptions, CollatorScratch};
+    use crate::definitions::variable_weighting::VariableWeighting;
+
+    #[test]
+    fn test_scratch() {
+        let collator = Collator::new(table());
+        let shifted = collator.clone_with_options(CollatorOptions {
+            variable_weighting: VariableWeighting::Shifted,
+            ..Default::default()
+        });
+        // The same buffers hold the shifted collation elements, of four levels, and then the
+        // others, of three.
+        let mut scratch = CollatorScratch::new();
+        for collator in [shifted, collator] {
+            for (a, b) in [("chacha\u{301}", "ab"), ("a", "A"), ("", "b"), ("hc", "ch")] {
+                assert_eq!(
+                    collator.compare_with(a, b, &mut scratch),
+                    collator.compare(a, b)
+                );
+                assert_eq!(
+                    collator.sort_key_with(a, &mut scratch),
+                    collator.sort_key(a).as_bytes()
+                );
+            }
+        }
+    }
+}
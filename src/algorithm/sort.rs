@@ -0,0 +1,426 @@
+use crate::algorithm::scratch::with_thread_scratch;
+use crate::algorithm::{Collator, SortKey, Strength};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Slices up to this length are sorted by comparing their strings, which computes the same number
+/// of sort keys as caching them but allocates nothing.
+const DIRECT_SORT_MAX_LEN: usize = 2;
+
+/// A string selected by [`Collator::k_smallest`] or [`Collator::k_largest`], ordered by its rank:
+/// the selection keeps the strings of the `k` smallest ranks, in a heap whose top is the string to
+/// be replaced first.
+struct Selected<S, const LARGEST: bool> {
+    key: Vec<u8>,
+    index: usize,
+    value: S,
+}
+
+impl<S, const LARGEST: bool> Selected<S, LARGEST> {
+    fn new(key: Vec<u8>, index: usize, value: S) -> Self {
+        Self { key, index, value }
+    }
+
+    /// Compares the ranks of two strings by their sort keys, reversed for the largest strings,
+    /// and then by their indices, so that of equal strings the first are kept.
+    fn rank(key: &[u8], index: usize, other_key: &[u8], other_index: usize) -> Ordering {
+        let by_key = if LARGEST {
+            other_key.cmp(key)
+        } else {
+            key.cmp(other_key)
+        };
+        by_key.then(index.cmp(&other_index))
+    }
+}
+
+impl<S, const LARGEST: bool> Ord for Selected<S, LARGEST> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Self::rank(&self.key, self.index, &other.key, other.index)
+    }
+}
+
+impl<S, const LARGEST: bool> PartialOrd for Selected<S, LARGEST> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, const LARGEST: bool> PartialEq for Selected<S, LARGEST> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S, const LARGEST: bool> Eq for Selected<S, LARGEST> {}
+
+impl Collator {
+    /// Sorts a slice of strings, stably, in the order of [`Collator::compare`].
+    pub fn sort_slice<S: AsRef<str>>(&self, slice: &mut [S]) {
+        self.sort_by_cached_key(slice, |s| s.as_ref())
+    }
+
+    /// Sorts a slice, stably, by the collation order of the string extracted from each element by
+    /// `f`.
+    ///
+    /// The sort key of each element is computed once and cached for the duration of the sort,
+    /// instead of twice per comparison as with `slice.sort_by(|a, b| collator.compare(..))`.
+    pub fn sort_by_cached_key<T, F>(&self, slice: &mut [T], f: F)
+    where
+        F: Fn(&T) -> &str,
+    {
+        if slice.len() <= DIRECT_SORT_MAX_LEN {
+            slice.sort_by(|a, b| self.compare(f(a), f(b)));
+        } else {
+            slice.sort_by_cached_key(|t| self.sort_key(f(t)).into_bytes());
+        }
+    }
+
+    /// Computes the sort key of every string.
+    pub fn sort_keys<S: AsRef<str>>(&self, strings: &[S]) -> Vec<SortKey> {
+        strings.iter().map(|s| self.sort_key(s.as_ref())).collect()
+    }
+
+    /// The index at which to insert `needle` into `sorted`, sorted in the order of
+    /// [`Collator::compare`], to keep it sorted: after the strings less than or equal to it, so
+    /// that the insertions of equal strings are stable. Divided by the length of `sorted`, it is
+    /// the percentile rank of `needle`.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let mut names = vec!["Ada", "Émile", "zoe"];
+    /// let i = collator.binary_search_insert_position(&names, "eve");
+    /// names.insert(i, "eve");
+    /// assert_eq!(names, ["Ada", "Émile", "eve", "zoe"]);
+    /// ```
+    pub fn binary_search_insert_position<S: AsRef<str>>(
+        &self,
+        sorted: &[S],
+        needle: &str,
+    ) -> usize {
+        sorted.partition_point(|s| self.compare(s.as_ref(), needle) != Ordering::Greater)
+    }
+
+    /// The permutation sorting `strings`, stably, in the order of [`Collator::compare`], with the
+    /// missing strings first or last: the sort indices of a column of strings, e.g. of the
+    /// `Option<&str>` of the `iter` of an Arrow `StringArray`, to be given to its `take` kernel.
+    ///
+    /// The sort keys are appended to a single buffer, in the layout of the string arrays of
+    /// Arrow, rather than allocated one by one, and compared in place.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let column = [Some("zoë"), None, Some("Zoe"), Some("ángel")];
+    /// assert_eq!(collator.sort_indices(column, true), [1, 3, 2, 0]);
+    /// assert_eq!(collator.sort_indices(column, false), [3, 2, 0, 1]);
+    /// ```
+    ///
+    /// ## Panics
+    /// If there are more than `u32::MAX` strings.
+    pub fn sort_indices<'a, I>(&self, strings: I, nulls_first: bool) -> Vec<u32>
+    where
+        I: IntoIterator<Item = Option<&'a str>>,
+    {
+        let mut keys = vec![];
+        let mut offsets = vec![0];
+        let mut indices = vec![];
+        let mut nulls = vec![];
+        for (i, s) in strings.into_iter().enumerate() {
+            let i = u32::try_from(i).expect("At most u32::MAX strings can be sorted.");
+            match s {
+                Some(s) => {
+                    self.append_sort_key(s, &mut keys);
+                    offsets.push(keys.len());
+                    indices.push(i);
+                }
+                None => nulls.push(i),
+            }
+        }
+        let key = |k: usize| &keys[offsets[k]..offsets[k + 1]];
+        let mut order: Vec<usize> = (0..indices.len()).collect();
+        order.sort_by(|&a, &b| key(a).cmp(key(b)));
+        let sorted = order.into_iter().map(|k| indices[k]);
+        if nulls_first {
+            nulls.into_iter().chain(sorted).collect()
+        } else {
+            sorted.chain(nulls).collect()
+        }
+    }
+
+    /// Sorts `strings`, stably, and removes all but the first of the strings equal for the
+    /// collator, e.g. the spellings of a name differing only by case at
+    /// [`Strength::Secondary`].
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorOptions, Strength};
+    ///
+    /// let collator = Collator::try_new("und").unwrap().clone_with_options(CollatorOptions {
+    ///     strength: Strength::Secondary,
+    ///     ..Default::default()
+    /// });
+    /// let mut names = vec!["Zoë", "zoe", "ZOË", "Zoe", "zoë"];
+    /// collator.dedup(&mut names);
+    /// assert_eq!(names, ["zoe", "Zoë"]);
+    /// ```
+    pub fn dedup<S: AsRef<str>>(&self, strings: &mut Vec<S>) {
+        let mut keyed: Vec<(Vec<u8>, S)> = strings
+            .drain(..)
+            .map(|s| (self.sort_key(s.as_ref()).into_bytes(), s))
+            .collect();
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        keyed.dedup_by(|a, b| a.0 == b.0);
+        strings.extend(keyed.into_iter().map(|(_, s)| s));
+    }
+
+    /// Sorts the strings and groups them by their initial: the first collation element with a
+    /// primary weight, compared at `strength`. The groups are in collation order, each with the
+    /// sort key of its initial, e.g. to build an index with the headers `A`, `B`, `C`…
+    ///
+    /// The strings without primary weights, e.g. the empty string, are grouped under the empty
+    /// sort key, first.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, Strength};
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let groups = collator.group_by_key(["bob", "Álvaro", "alice", "Bea"], Strength::Primary);
+    /// let groups: Vec<Vec<&str>> = groups.into_iter().map(|(_, group)| group).collect();
+    /// assert_eq!(groups, [vec!["alice", "Álvaro"], vec!["Bea", "bob"]]);
+    /// ```
+    pub fn group_by_key<I, S>(&self, strings: I, strength: Strength) -> Vec<(SortKey, Vec<S>)>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut keyed: Vec<(Vec<u8>, SortKey, S)> = strings
+            .into_iter()
+            .map(|s| {
+                let elements = self.collation_element_array(s.as_ref());
+                let initial = elements
+                    .iter()
+                    .find(|ce| ce.is_primary_collation_element())
+                    .map(std::slice::from_ref)
+                    .unwrap_or_default();
+                let initial =
+                    SortKey::with_max_level(initial, self.options.key_encoding, strength.level());
+                (self.sort_key(s.as_ref()).into_bytes(), initial, s)
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut groups: Vec<(SortKey, Vec<S>)> = vec![];
+        for (_, initial, s) in keyed {
+            match groups.iter_mut().find(|(key, _)| *key == initial) {
+                Some((_, group)) => group.push(s),
+                None => groups.push((initial, vec![s])),
+            }
+        }
+        groups.sort_by(|a, b| a.0.weights().cmp(b.0.weights()));
+        groups
+    }
+
+    /// The `k` smallest strings in the order of [`Collator::compare`], sorted, with the equal
+    /// strings in the order of `strings`: the first `k` strings of `strings` sorted, without
+    /// sorting it, e.g. the first suggestions of an autocompletion among millions of names.
+    ///
+    /// Only the sort keys of the `k` smallest strings seen so far are kept, in a heap; the others
+    /// are written to scratch buffers and dropped.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let names = ["Zoé", "adèle", "Émile", "bob", "Ada"];
+    /// assert_eq!(collator.k_smallest(names, 3), ["Ada", "adèle", "bob"]);
+    /// assert_eq!(collator.k_largest(names, 2), ["Zoé", "Émile"]);
+    /// ```
+    pub fn k_smallest<I, S>(&self, strings: I, k: usize) -> Vec<S>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.select_k::<I, S, false>(strings, k)
+    }
+
+    /// The `k` largest strings in the order of [`Collator::compare`], sorted from the largest,
+    /// with the equal strings in the order of `strings`, as [`Collator::k_smallest`].
+    pub fn k_largest<I, S>(&self, strings: I, k: usize) -> Vec<S>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.select_k::<I, S, true>(strings, k)
+    }
+
+    fn select_k<I, S, const LARGEST: bool>(&self, strings: I, k: usize) -> Vec<S>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        if k == 0 {
+            return vec![];
+        }
+        let mut heap: BinaryHeap<Selected<S, LARGEST>> = BinaryHeap::with_capacity(k);
+        with_thread_scratch(|scratch| {
+            for (index, s) in strings.into_iter().enumerate() {
+                let key = self.sort_key_with(s.as_ref(), scratch);
+                if heap.len() < k {
+                    heap.push(Selected::new(key.to_vec(), index, s));
+                } else if let Some(mut worst) = heap.peek_mut() {
+                    if Selected::<S, LARGEST>::rank(key, index, &worst.key, worst.index)
+                        == Ordering::Less
+                    {
+                        *worst = Selected::new(key.to_vec(), index, s);
+                    }
+                }
+            }
+        });
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|selected| selected.value)
+            .collect()
+    }
+
+    /// Like [`Collator::sort_slice`], computing the sort keys and sorting in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn par_sort_slice<S: AsRef<str> + Send>(&self, slice: &mut [S]) {
+        self.par_sort_by_cached_key(slice, |s| s.as_ref())
+    }
+
+    /// Like [`Collator::sort_by_cached_key`], computing the sort keys and sorting in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn par_sort_by_cached_key<T, F>(&self, slice: &mut [T], f: F)
+    where
+        T: Send,
+        F: Fn(&T) -> &str + Sync,
+    {
+        slice.par_sort_by_cached_key(|t| self.sort_key(f(t)).into_bytes());
+    }
+
+    /// Like [`Collator::sort_keys`], computing the sort keys in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn par_sort_keys<S: AsRef<str> + Sync>(&self, strings: &[S]) -> Vec<SortKey> {
+        strings
+            .par_iter()
+            .map(|s| self.sort_key(s.as_ref()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::tests::table;
+
+    #[test]
+    fn test_sort() {
+        let collator = Collator::new(table());
+        let mut strings = vec!["ch", "b", "A", "ca", "a\u{301}", "a"];
+        collator.sort_slice(&mut strings);
+        assert_eq!(strings, ["a", "A", "a\u{301}", "b", "ca", "ch"]);
+
+        let mut owned = vec![String::from("b"), String::from("a")];
+        collator.sort_slice(&mut owned);
+        assert_eq!(owned, ["a", "b"]);
+
+        // Stable: equal strings keep their relative order.
+        let mut records = vec![(1, "b"), (2, "a"), (3, "a\u{301}"), (4, "\u{E1}"), (5, "a")];
+        collator.sort_by_cached_key(&mut records, |(_, s)| s);
+        let ids: Vec<i32> = records.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [2, 5, 3, 4, 1]);
+
+        let keys = collator.sort_keys(&strings);
+        assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+
+        assert_eq!(collator.binary_search_insert_position(&strings, "b"), 4);
+        assert_eq!(
+            collator.binary_search_insert_position(&strings, "\u{E1}"),
+            3
+        );
+        assert_eq!(collator.binary_search_insert_position(&strings, ""), 0);
+        assert_eq!(collator.binary_search_insert_position(&strings, "d"), 6);
+
+        let column = [
+            Some("b"),
+            Some("a\u{301}"),
+            None,
+            Some("A"),
+            Some("\u{E1}"),
+            None,
+        ];
+        assert_eq!(collator.sort_indices(column, false), [3, 1, 4, 0, 2, 5]);
+        assert_eq!(collator.sort_indices(column, true), [2, 5, 3, 1, 4, 0]);
+        assert!(collator.sort_indices([], true).is_empty());
+    }
+
+    #[test]
+    fn test_dedup_and_groups() {
+        let collator = Collator::new(table());
+        let mut strings = vec!["b", "A", "a", "b", "\u{E1}", "a\u{301}"];
+        collator.dedup(&mut strings);
+        assert_eq!(strings, ["a", "A", "\u{E1}", "b"]);
+
+        let groups =
+            collator.group_by_key(["cha", "A", "ca", "b", "a\u{301}b", ""], Strength::Primary);
+        let groups: Vec<Vec<&str>> = groups.into_iter().map(|(_, group)| group).collect();
+        assert_eq!(
+            groups,
+            [
+                vec![""],
+                vec!["A", "a\u{301}b"],
+                vec!["b"],
+                vec!["ca"],
+                vec!["cha"]
+            ]
+        );
+        // At the tertiary strength, "a" and "A" have their own groups.
+        let groups = collator.group_by_key(["a", "A", "a\u{301}"], Strength::Tertiary);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].1, ["a", "a\u{301}"]);
+    }
+
+    #[test]
+    fn test_k_smallest() {
+        let collator = Collator::new(table());
+        let strings = ["ch", "b", "A", "ca", "a\u{301}", "\u{E1}", "a", "A"];
+        let mut sorted = strings.to_vec();
+        collator.sort_slice(&mut sorted);
+        for k in 0..=strings.len() + 1 {
+            let k_smallest = collator.k_smallest(strings, k);
+            assert_eq!(k_smallest, sorted[..k.min(strings.len())]);
+        }
+        // Of the equal strings, the first are kept, in the order of the input.
+        let strings = ["b", "\u{E1}", "a", "a\u{301}", "ch"];
+        assert_eq!(collator.k_smallest(strings, 2), ["a", "\u{E1}"]);
+        assert_eq!(collator.k_smallest(strings, 3), ["a", "\u{E1}", "a\u{301}"]);
+        assert_eq!(collator.k_largest(strings, 3), ["ch", "b", "\u{E1}"]);
+        assert_eq!(
+            collator.k_largest(strings, 4),
+            ["ch", "b", "\u{E1}", "a\u{301}"]
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_sort() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Collator>();
+
+        let collator = Collator::new(table());
+        let mut strings: Vec<String> = (0..1000)
+            .map(|i| ["ch", "b", "A", "ca", "a\u{301}", "a"][i % 6].repeat(i % 7 + 1))
+            .collect();
+        let mut expected = strings.clone();
+        collator.sort_slice(&mut expected);
+        assert_eq!(
+            collator.par_sort_keys(&strings),
+            collator.sort_keys(&strings)
+        );
+        collator.par_sort_slice(&mut strings);
+        assert_eq!(strings, expected);
+    }
+}
@@ -0,0 +1,752 @@
+use crate::algorithm::packed::PackedElement;
+use crate::algorithm::scratch::with_thread_scratch;
+use crate::algorithm::{CaseFirst, Collator};
+use crate::definitions::collation_wel::{CollationElement, CollationWeight, Weighted};
+use crate::definitions::ignorable::Ignorable;
+use crate::error::{CollateError, Result};
+use std::cmp::Ordering;
+use std::fmt;
+use std::io;
+
+/// Byte encoding of the weights in a [`SortKey`].
+///
+/// Both encodings are order preserving, so keys of the same encoding can be compared with a plain
+/// binary comparison. Keys of different encodings are **not** comparable: the first byte of every
+/// key is a version header identifying its encoding, and [`SortKey::try_cmp`] refuses to compare
+/// keys whose headers differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyEncoding {
+    /// Every weight is written as a 16-bit big-endian word and levels are separated by `0000`, as
+    /// in the examples of UTS10. Easier to read when debugging.
+    #[default]
+    U16BigEndian,
+    /// Every weight is written as a variable-length byte sequence (1 byte for weights up to `007E`,
+    /// 2 bytes up to `607E`, 3 bytes up to `1607E`, 5 bytes otherwise) and levels are separated by
+    /// `01`, in the spirit of the fractional weights of ICU. Secondary and tertiary weights take a
+    /// single byte, so keys are considerably smaller.
+    Fractional,
+}
+
+impl KeyEncoding {
+    /// The version header written as the first byte of every sort key.
+    pub fn header(&self) -> u8 {
+        match self {
+            Self::U16BigEndian => 0x01,
+            Self::Fractional => 0x02,
+        }
+    }
+
+    pub fn from_header(header: u8) -> Option<Self> {
+        match header {
+            0x01 => Some(Self::U16BigEndian),
+            0x02 => Some(Self::Fractional),
+            _ => None,
+        }
+    }
+
+    /// UTS10-D41. Level Separator: the bytes separating the weights of two levels in a sort key,
+    /// lower than the encoding of any weight.
+    pub fn level_separator(&self) -> &'static [u8] {
+        match self {
+            Self::U16BigEndian => &[0x00, 0x00],
+            Self::Fractional => &[0x01],
+        }
+    }
+
+    /// > S3.2 If L is not 1, append a level separator.
+    fn write_level_separator(&self, bytes: &mut impl ByteSink) {
+        bytes.put(self.level_separator());
+    }
+
+    /// The largest number of bytes written for a weight.
+    fn max_weight_len(&self) -> usize {
+        match self {
+            Self::U16BigEndian => 2,
+            Self::Fractional => 5,
+        }
+    }
+
+    /// ## Panics
+    /// If the encoding is [`KeyEncoding::U16BigEndian`] and the weight does not fit in 16 bits.
+    pub(crate) fn write_weight(&self, weight: &CollationWeight, bytes: &mut impl ByteSink) {
+        let w = weight.0;
+        match self {
+            Self::U16BigEndian => {
+                let w = u16::try_from(w)
+                    .unwrap_or_else(|_| panic!("Weight {:04X} does not fit in 16 bits.", w));
+                bytes.put(&w.to_be_bytes());
+            }
+            Self::Fractional => match w {
+                0x0001..=0x007E => bytes.put(&[w as u8 + 0x01]),
+                0x007F..=0x607E => {
+                    let v = w - 0x007F;
+                    bytes.put(&[0x80 | (v >> 8) as u8, v as u8]);
+                }
+                0x607F..=0x1607E => {
+                    let v = w - 0x607F;
+                    bytes.put(&[0xE0 | (v >> 16) as u8, (v >> 8) as u8, v as u8]);
+                }
+                _ => {
+                    let [a, b, c, d] = (w - 0x1607F).to_be_bytes();
+                    bytes.put(&[0xF0, a, b, c, d]);
+                }
+            },
+        }
+    }
+
+    /// The length of the weight or level separator starting with the byte `lead`.
+    pub(crate) fn unit_len(&self, lead: u8) -> usize {
+        match self {
+            Self::U16BigEndian => 2,
+            Self::Fractional => match lead {
+                0x01..=0x7F => 1,
+                0x80..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                _ => 5,
+            },
+        }
+    }
+
+    /// Decodes the weights of each level written by [`KeyEncoding::write_weight`] and
+    /// [`KeyEncoding::write_level_separator`], or `None` if the bytes end inside a weight or hold
+    /// a byte starting no weight nor level separator, e.g. those of a truncated key.
+    fn read_levels(&self, bytes: &[u8]) -> Option<Vec<Vec<u32>>> {
+        let mut levels = vec![];
+        if bytes.is_empty() {
+            return Some(levels);
+        }
+        let mut level = vec![];
+        let mut i = 0;
+        while i < bytes.len() {
+            let (weight, len) = match self {
+                Self::U16BigEndian => {
+                    let unit = bytes.get(i..i + 2)?;
+                    (u32::from(u16::from_be_bytes([unit[0], unit[1]])), 2)
+                }
+                Self::Fractional => match bytes[i] {
+                    0x01 => (0, 1),
+                    lead @ 0x02..=0x7F => (u32::from(lead) - 0x01, 1),
+                    lead @ 0x80..=0xDF => {
+                        let v = u32::from(lead & 0x7F) << 8 | u32::from(*bytes.get(i + 1)?);
+                        (v + 0x007F, 2)
+                    }
+                    0xE0..=0xEF => {
+                        let unit = bytes.get(i..i + 3)?;
+                        let v = u32::from(unit[1]) << 8 | u32::from(unit[2]);
+                        (v + 0x607F, 3)
+                    }
+                    0xF0 => {
+                        let v = u32::from_be_bytes(bytes.get(i + 1..i + 5)?.try_into().unwrap());
+                        (v.checked_add(0x1607F)?, 5)
+                    }
+                    _ => return None,
+                },
+            };
+            if weight == 0 {
+                levels.push(std::mem::take(&mut level));
+            } else {
+                level.push(weight);
+            }
+            i += len;
+        }
+        levels.push(level);
+        Some(levels)
+    }
+}
+
+/// A destination of the bytes of a sort key.
+pub(crate) trait ByteSink {
+    fn put(&mut self, bytes: &[u8]);
+
+    /// Whether the sink needs no more bytes, so that the key stops being written at the next
+    /// level.
+    fn is_done(&self) -> bool {
+        false
+    }
+}
+
+impl ByteSink for Vec<u8> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+}
+
+/// A [`ByteSink`] writing to an [`io::Write`], counting the bytes written and keeping the first
+/// error, after which it writes nothing.
+struct WriteSink<'w, W: io::Write> {
+    writer: &'w mut W,
+    len: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> ByteSink for WriteSink<'_, W> {
+    fn put(&mut self, bytes: &[u8]) {
+        if self.error.is_none() {
+            match self.writer.write_all(bytes) {
+                Ok(()) => self.len += bytes.len(),
+                Err(error) => self.error = Some(error),
+            }
+        }
+    }
+}
+
+/// A [`ByteSink`] comparing the bytes of a sort key, as they are written, with the bytes of a
+/// stored key of the same encoding, over the levels both keys have.
+struct ComparingSink<'k> {
+    key: &'k [u8],
+    separator: &'static [u8],
+    /// The length of the common prefix of `key` and the bytes written.
+    len: usize,
+    ordering: Option<Ordering>,
+}
+
+impl ComparingSink<'_> {
+    /// The order of the key written before the stored one, once all its bytes are written.
+    fn finish(&self) -> Ordering {
+        self.ordering.unwrap_or_else(|| {
+            let rest = &self.key[self.len..];
+            if rest.is_empty() || rest.starts_with(self.separator) {
+                Ordering::Equal
+            } else {
+                Ordering::Less
+            }
+        })
+    }
+}
+
+impl ByteSink for ComparingSink<'_> {
+    fn put(&mut self, bytes: &[u8]) {
+        if self.ordering.is_some() {
+            return;
+        }
+        let rest = &self.key[self.len..];
+        if rest.is_empty() && bytes == self.separator {
+            // The stored key has fewer levels.
+            self.ordering = Some(Ordering::Equal);
+        } else if rest.starts_with(bytes) {
+            self.len += bytes.len();
+        } else {
+            let common = &rest[..bytes.len().min(rest.len())];
+            self.ordering = Some(bytes.cmp(common).then(Ordering::Greater));
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.ordering.is_some()
+    }
+}
+
+/// The options of a [`Collator`] changing the levels of its sort keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LevelOptions {
+    pub(crate) backward_secondary: bool,
+    pub(crate) case_level: bool,
+    pub(crate) case_first: CaseFirst,
+}
+
+/// A level of a sort key: the weights of a level of the collation elements, or the case level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Weights(usize),
+    Case,
+}
+
+/// The tertiary weights of the uppercase characters in the DUCET: uppercase, and its `<wide>`,
+/// `<compat>`, `<font>` and `<circle>` variants.
+const UPPERCASE_TERTIARY_WEIGHTS: [u32; 6] = [0x0008, 0x0009, 0x000A, 0x000B, 0x000C, 0x001D];
+
+/// Whether the tertiary weight of `ce` is the one of an uppercase character.
+fn is_uppercase(ce: &impl Weighted) -> bool {
+    UPPERCASE_TERTIARY_WEIGHTS.contains(&ce.weight(3))
+}
+
+impl CaseFirst {
+    /// The weight of the case level for a primary collation element: `0001` for the case sorting
+    /// first, `0002` for the other.
+    fn case_weight(&self, uppercase: bool) -> CollationWeight {
+        let first = match self {
+            Self::Off | Self::Lower => !uppercase,
+            Self::Upper => uppercase,
+        };
+        CollationWeight(if first { 0x0001 } else { 0x0002 })
+    }
+
+    /// The tertiary weight `weight` prefixed by the rank of its case, e.g. `0108` for an uppercase
+    /// tertiary weight `0008` and `0202` for a lowercase `0002` when uppercase sorts first. The
+    /// tertiary weights of the DUCET fit in a byte.
+    fn tertiary_weight(&self, weight: CollationWeight) -> CollationWeight {
+        match self {
+            Self::Off => weight,
+            Self::Lower | Self::Upper => {
+                let uppercase = UPPERCASE_TERTIARY_WEIGHTS.contains(&weight.0);
+                CollationWeight(self.case_weight(uppercase).0 << 8 | weight.0)
+            }
+        }
+    }
+}
+
+/// A sort key: the weights of a collation element array, level by level, preceded by a version
+/// header identifying its [`KeyEncoding`].
+///
+/// Sort keys are displayed in the notation of UTS10, with the levels separated by `|`, e.g.
+/// `[1C47 1C60 | 0020 0020 | 0002 0002]`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct SortKey(Vec<u8>);
+
+impl SortKey {
+    /// > S3.1 For each weight level L in the collation element array from 1 to the maximum level,
+    /// >
+    /// > S3.2 If L is not 1, append a level separator.
+    /// >
+    /// > S3.3 If the collation element table is forwards at level L,
+    /// >
+    /// > S3.4 For each collation element CE in the array,
+    /// >
+    /// > S3.5 Append CE<sub>L</sub> to the sort key if CE<sub>L</sub> is non-zero.
+    ///
+    /// Collation elements with fewer levels than the maximum level are treated as having ignorable
+    /// weights at the missing levels.
+    pub fn new(collation_elements: &[CollationElement], encoding: KeyEncoding) -> Self {
+        Self::with_max_level(collation_elements, encoding, usize::MAX)
+    }
+
+    /// Like [`SortKey::new`], ignoring the levels above `max_level`.
+    pub fn with_max_level(
+        collation_elements: &[CollationElement],
+        encoding: KeyEncoding,
+        max_level: usize,
+    ) -> Self {
+        Self::build(
+            collation_elements,
+            encoding,
+            max_level,
+            LevelOptions::default(),
+        )
+    }
+
+    /// Like [`SortKey::with_max_level`], with the changes of `options` to the levels of the key.
+    pub(crate) fn build(
+        collation_elements: &[impl Weighted],
+        encoding: KeyEncoding,
+        max_level: usize,
+        options: LevelOptions,
+    ) -> Self {
+        let mut bytes = vec![];
+        Self::write(collation_elements, encoding, max_level, options, &mut bytes);
+        Self(bytes)
+    }
+
+    /// Writes the bytes of the key of [`SortKey::build`] to `bytes`, version header included.
+    fn write<E: Weighted>(
+        collation_elements: &[E],
+        encoding: KeyEncoding,
+        max_level: usize,
+        options: LevelOptions,
+        bytes: &mut impl ByteSink,
+    ) {
+        let max_level = collation_elements
+            .iter()
+            .map(Weighted::len_levels)
+            .max()
+            .unwrap_or(0)
+            .min(max_level);
+        let levels = (1..=max_level.min(2))
+            .map(Level::Weights)
+            .chain((options.case_level && max_level > 0).then_some(Level::Case))
+            .chain((3..=max_level).map(Level::Weights));
+
+        bytes.put(&[encoding.header()]);
+        for (i, level) in levels.enumerate() {
+            if bytes.is_done() {
+                return;
+            }
+            if i != 0 {
+                encoding.write_level_separator(bytes);
+            }
+            let weight = |ce: &E| match level {
+                Level::Weights(l) => Some(CollationWeight(ce.weight(l)))
+                    .filter(|w| !w.is_ignorable())
+                    .map(|w| match l {
+                        3 if !options.case_level => options.case_first.tertiary_weight(w),
+                        _ => w,
+                    }),
+                Level::Case => {
+                    (ce.weight(1) != 0).then(|| options.case_first.case_weight(is_uppercase(ce)))
+                }
+            };
+            if level == Level::Weights(2) && options.backward_secondary {
+                collation_elements
+                    .iter()
+                    .rev()
+                    .filter_map(weight)
+                    .for_each(|w| encoding.write_weight(&w, bytes));
+            } else {
+                collation_elements
+                    .iter()
+                    .filter_map(weight)
+                    .for_each(|w| encoding.write_weight(&w, bytes));
+            }
+        }
+    }
+
+    /// Reconstructs a sort key from the bytes returned by [`SortKey::as_bytes`].
+    ///
+    /// Fails if the bytes do not start with a version header, or if the weights and the level
+    /// separators after it are not whole units of its encoding, e.g. if the key was truncated.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes.first() {
+            Some(&header) => match KeyEncoding::from_header(header) {
+                Some(encoding) if encoding.read_levels(&bytes[1..]).is_some() => {
+                    Ok(Self(bytes.to_vec()))
+                }
+                Some(encoding) => Err(CollateError::InvalidSortKey(format!(
+                    "Ill-formed weights for the {:?} encoding.",
+                    encoding
+                ))),
+                None => Err(CollateError::InvalidSortKey(format!(
+                    "Unknown version header {:02X}.",
+                    header
+                ))),
+            },
+            None => Err(CollateError::InvalidSortKey(
+                "Sort keys must start with a version header.".to_string(),
+            )),
+        }
+    }
+
+    pub fn encoding(&self) -> KeyEncoding {
+        KeyEncoding::from_header(self.0[0]).expect("Sort keys always start with a valid header.")
+    }
+
+    /// The bytes of the sort key, version header included.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Consumes the sort key into its bytes, version header included.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// The encoded weights of the sort key, without the version header.
+    pub fn weights(&self) -> &[u8] {
+        &self.0[1..]
+    }
+
+    /// The key truncated to at most `max_len` bytes, version header included, for the indexes of
+    /// databases limiting the size of their keys. The version header is always kept.
+    ///
+    /// The key is cut between two weights or level separators, never inside one, so that it
+    /// stays made of whole weights. As the keys are written without compressing runs of weights,
+    /// the truncated key is a prefix of the key, which never sorts after it: the truncated keys
+    /// of strings in order are in the same order, but may be equal for strings that are not.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let (short, long) = (collator.sort_key("abc"), collator.sort_key("abd"));
+    /// let (short, long) = (short.truncate_safe(6), long.truncate_safe(6));
+    /// assert_eq!(short.as_bytes().len(), 5);
+    /// assert_eq!(short.levels(), [vec![0x1FA2, 0x1FBC]]);
+    /// assert_eq!(short, long);
+    /// ```
+    pub fn truncate_safe(&self, max_len: usize) -> SortKey {
+        let encoding = self.encoding();
+        let mut len = 1;
+        while len < self.0.len() {
+            let end = len + encoding.unit_len(self.0[len]);
+            if end > max_len {
+                break;
+            }
+            len = end;
+        }
+        SortKey(self.0[..len].to_vec())
+    }
+
+    /// The weights of each level of the sort key, decoded.
+    pub fn levels(&self) -> Vec<Vec<u32>> {
+        self.encoding()
+            .read_levels(self.weights())
+            .expect("The weights of sort keys are well-formed.")
+    }
+
+    /// > S4. Compare the sort keys for each of the input strings, using a binary comparison.
+    ///
+    /// Fails if the keys were produced with different [`KeyEncoding`]s.
+    pub fn try_cmp(&self, other: &Self) -> Result<Ordering> {
+        if self.encoding() != other.encoding() {
+            return Err(CollateError::IncompatibleSortKeys {
+                left: self.encoding(),
+                right: other.encoding(),
+            });
+        }
+        Ok(self.weights().cmp(other.weights()))
+    }
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let levels: Vec<String> = self
+            .levels()
+            .iter()
+            .map(|level| {
+                let weights: Vec<String> = level.iter().map(|w| format!("{:04X}", w)).collect();
+                weights.join(" ")
+            })
+            .collect();
+        write!(f, "[{}]", levels.join(" | "))
+    }
+}
+
+impl fmt::Debug for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SortKey({:?}, {})", self.encoding(), self)
+    }
+}
+
+/// Sort keys of different encodings are unordered.
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.try_cmp(other).ok()
+    }
+}
+
+impl Collator {
+    /// > S3. Produce a sort key for each string from the collation elements.
+    pub fn sort_key(&self, s: &str) -> SortKey {
+        with_thread_scratch(|scratch| {
+            self.collation_elements_into(s, scratch);
+            self.sort_key_of(scratch.elements.as_slice(), &scratch.code_points)
+        })
+    }
+
+    /// The sort key of a collation element array produced by the collator for the string of
+    /// `code_points`, with the tiebreak of
+    /// [`CollatorOptions::code_point_tiebreak`](crate::algorithm::CollatorOptions::code_point_tiebreak).
+    pub(crate) fn sort_key_of(
+        &self,
+        collation_elements: &[PackedElement],
+        code_points: &[char],
+    ) -> SortKey {
+        let mut bytes = vec![];
+        self.write_sort_key_from_elements(collation_elements, &mut bytes);
+        self.write_tiebreak(code_points, &mut bytes);
+        SortKey(bytes)
+    }
+
+    /// With [`CollatorOptions::code_point_tiebreak`](crate::algorithm::CollatorOptions::code_point_tiebreak),
+    /// writes the last level of the sort keys, after a level separator: the code points of the
+    /// string, each weighted by two weights, `1 + cp >> 15` and `1 + cp & 7FFF`, as the ones of
+    /// [`QuaternarySource::CodePoints`](crate::algorithm::QuaternarySource::CodePoints).
+    pub(crate) fn write_tiebreak(&self, code_points: &[char], bytes: &mut impl ByteSink) {
+        if !self.options.code_point_tiebreak || bytes.is_done() {
+            return;
+        }
+        let encoding = self.options.key_encoding;
+        encoding.write_level_separator(bytes);
+        for cp in code_points.iter().map(|&c| u32::from(c)) {
+            for weight in [1 + (cp >> 15), 1 + (cp & 0x7FFF)] {
+                encoding.write_weight(&CollationWeight(weight), bytes);
+            }
+        }
+    }
+
+    /// The sort key of a collation element array produced by the collator.
+    pub(crate) fn sort_key_from_elements(&self, collation_elements: &[impl Weighted]) -> SortKey {
+        SortKey::build(
+            collation_elements,
+            self.options.key_encoding,
+            self.options.strength.level(),
+            self.level_options(),
+        )
+    }
+
+    /// Writes the bytes of the sort key of a collation element array produced by the collator.
+    pub(crate) fn write_sort_key_from_elements(
+        &self,
+        collation_elements: &[PackedElement],
+        bytes: &mut impl ByteSink,
+    ) {
+        SortKey::write(
+            collation_elements,
+            self.options.key_encoding,
+            self.options.strength.level(),
+            self.level_options(),
+            bytes,
+        );
+    }
+
+    fn level_options(&self) -> LevelOptions {
+        LevelOptions {
+            backward_secondary: self.options.backward_secondary,
+            case_level: self.options.case_level,
+            case_first: self.options.case_first,
+        }
+    }
+
+    /// Appends the bytes of the sort key of `s` to `buffer`, without allocating a [`SortKey`], to
+    /// reuse a buffer across many keys.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let mut buffer = vec![];
+    /// collator.append_sort_key("abc", &mut buffer);
+    /// assert_eq!(buffer, collator.sort_key("abc").as_bytes());
+    /// ```
+    pub fn append_sort_key(&self, s: &str, buffer: &mut Vec<u8>) {
+        with_thread_scratch(|scratch| {
+            self.collation_elements_into(s, scratch);
+            self.write_sort_key_from_elements(scratch.elements.as_slice(), buffer);
+            self.write_tiebreak(&scratch.code_points, buffer);
+        })
+    }
+
+    /// The sort key of `s` at level 1 only, whatever the strength of the collator: a key folding
+    /// case and accents, e.g. to group the entries of a search index.
+    ///
+    /// The levels past the first are not written at all, and the case level is left out. The
+    /// variable weighting of the collator still applies.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// assert_eq!(collator.primary_key("Résumé"), collator.primary_key("resume"));
+    /// assert_ne!(collator.primary_key("résumé"), collator.primary_key("resumes"));
+    /// assert_eq!(collator.primary_key("resume").levels().len(), 1);
+    /// ```
+    pub fn primary_key(&self, s: &str) -> SortKey {
+        self.folding_key(s, 1)
+    }
+
+    /// The sort key of `s` at levels 1 and 2 only, whatever the strength of the collator: a key
+    /// folding case but not accents. The secondary weights are backward if the collator's are.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// assert_eq!(collator.secondary_key("Résumé"), collator.secondary_key("résumé"));
+    /// assert_ne!(collator.secondary_key("résumé"), collator.secondary_key("resume"));
+    /// ```
+    pub fn secondary_key(&self, s: &str) -> SortKey {
+        self.folding_key(s, 2)
+    }
+
+    fn folding_key(&self, s: &str, max_level: usize) -> SortKey {
+        let options = LevelOptions {
+            backward_secondary: self.options.backward_secondary,
+            ..Default::default()
+        };
+        with_thread_scratch(|scratch| {
+            self.collation_elements_into(s, scratch);
+            SortKey::build(
+                scratch.elements.as_slice(),
+                self.options.key_encoding,
+                max_level,
+                options,
+            )
+        })
+    }
+
+    /// Writes the bytes of the sort key of `s` to `writer`, without allocating a [`SortKey`], and
+    /// returns their number.
+    ///
+    /// The weights are written one by one, so `writer` should be buffered, e.g. by an
+    /// [`io::BufWriter`]. Nothing more is written after an error of `writer`.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let mut index = std::io::Cursor::new(vec![]);
+    /// let len = collator.write_sort_key_to("abc", &mut index).unwrap();
+    /// assert_eq!(index.into_inner(), collator.sort_key("abc").as_bytes());
+    /// assert_eq!(len, collator.sort_key_len_hint("abc"));
+    /// ```
+    pub fn write_sort_key_to<W: io::Write>(&self, s: &str, writer: &mut W) -> io::Result<usize> {
+        let mut sink = WriteSink {
+            writer,
+            len: 0,
+            error: None,
+        };
+        with_thread_scratch(|scratch| {
+            self.collation_elements_into(s, scratch);
+            self.write_sort_key_from_elements(scratch.elements.as_slice(), &mut sink);
+            self.write_tiebreak(&scratch.code_points, &mut sink);
+        });
+        match sink.error {
+            Some(error) => Err(error),
+            None => Ok(sink.len),
+        }
+    }
+
+    /// Compares `s` with the string of the stored sort `key`, as [`Collator::compare`] compares
+    /// strings, without writing the sort key of `s` past the level where they differ, e.g. to
+    /// probe an index of persisted keys.
+    ///
+    /// `key` must be a key of the collator, or of a collator of the same table and options at
+    /// another strength: only the levels of both keys are compared, so that a key of all the
+    /// levels is equal to the strings equal to its own at the strength of the collator. Fails if
+    /// `key` is not of the [`KeyEncoding`] of the collator.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorOptions, Strength};
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let key = collator.sort_key("résumé");
+    /// assert_eq!(collator.compare_to_key("resume", &key).unwrap(), Ordering::Less);
+    /// assert_eq!(collator.compare_to_key("résumé", &key).unwrap(), Ordering::Equal);
+    /// assert_eq!(collator.compare_to_key("résumés", &key).unwrap(), Ordering::Greater);
+    ///
+    /// let primary = collator.clone_with_options(CollatorOptions {
+    ///     strength: Strength::Primary,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(primary.compare_to_key("RESUME", &key).unwrap(), Ordering::Equal);
+    /// ```
+    pub fn compare_to_key(&self, s: &str, key: &SortKey) -> Result<Ordering> {
+        let encoding = self.options.key_encoding;
+        if key.encoding() != encoding {
+            return Err(CollateError::IncompatibleSortKeys {
+                left: encoding,
+                right: key.encoding(),
+            });
+        }
+        let mut sink = ComparingSink {
+            key: key.as_bytes(),
+            separator: encoding.level_separator(),
+            len: 0,
+            ordering: None,
+        };
+        with_thread_scratch(|scratch| {
+            self.collation_elements_into(s, scratch);
+            self.write_sort_key_from_elements(scratch.elements.as_slice(), &mut sink);
+            self.write_tiebreak(&scratch.code_points, &mut sink);
+        });
+        Ok(sink.finish())
+    }
+
+    /// An estimate of the length in bytes of the sort key of `s`, to reserve buffers, computed
+    /// from the number of characters of `s` without collating it.
+    ///
+    /// The estimate assumes a collation element per character, with non-ignorable weights up to
+    /// the fourth level, and a primary weight of the longest encoding. Expansions, as in `"Æ"`,
+    /// make longer keys, and ignorable weights shorter ones.
+    pub fn sort_key_len_hint(&self, s: &str) -> usize {
+        let encoding = self.options.key_encoding;
+        let levels = self.options.strength.level().min(4);
+        let levels = levels + usize::from(self.options.case_level);
+        let chars = s.chars().count();
+        let weights = match encoding {
+            KeyEncoding::U16BigEndian => chars * levels * encoding.max_weight_len(),
+            KeyEncoding::Fractional => chars * (encoding.max_weight_len() + levels - 1),
+        };
+        1 + weights + (levels - 1) * encoding.level_separator().len()
+    }
+}
@@ -0,0 +1,375 @@
+use crate::algorithm::normalize::{canonical_reorder, decompose};
+use crate::algorithm::scratch::with_thread_scratch;
+use crate::algorithm::{Collator, CollatorScratch, IllFormedInput, SortKey};
+use crate::definitions::collation_wel::CollationElement;
+use crate::error::{CollateError, Result};
+use std::char::DecodeUtf16;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Copied;
+use std::str::{Chars, Utf8Chunks};
+
+/// An ill-formed sequence of the units of a [`CodePointSource`], collated as the
+/// [`IllFormedInput`] option of the collator says.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllFormedSequence {
+    /// The index of the first unit of the sequence in the source, e.g. of its first byte in
+    /// UTF-8.
+    pub index: usize,
+    /// The surrogate, if the sequence is an unpaired surrogate of UTF-16.
+    pub surrogate: Option<u16>,
+}
+
+impl fmt::Display for IllFormedSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.surrogate {
+            Some(surrogate) => write!(
+                f,
+                "Unpaired surrogate {:04X} at index {}",
+                surrogate, self.index
+            ),
+            None => write!(f, "Ill-formed sequence at index {}", self.index),
+        }
+    }
+}
+
+/// A string in some encoding, decoded into code points as it is collated, so that it need not be
+/// transcoded into a `str` first: UTF-8 bytes ([`Utf8Source`]), UTF-16 units ([`Utf16Source`])
+/// or any iterator of characters, e.g. [`str::chars`].
+pub trait CodePointSource {
+    /// The next code point of the string, or the next ill-formed sequence of its units.
+    fn next_code_point(&mut self) -> Option<std::result::Result<char, IllFormedSequence>>;
+}
+
+impl<I: Iterator<Item = char>> CodePointSource for I {
+    fn next_code_point(&mut self) -> Option<std::result::Result<char, IllFormedSequence>> {
+        self.next().map(Ok)
+    }
+}
+
+/// The code points of UTF-8 bytes, validated as they are decoded. Each maximal ill-formed
+/// subsequence is one [`IllFormedSequence`], as it is one U+FFFD in
+/// [`String::from_utf8_lossy`].
+#[derive(Debug, Clone)]
+pub struct Utf8Source<'a> {
+    chunks: Utf8Chunks<'a>,
+    valid: Chars<'a>,
+    invalid: Option<IllFormedSequence>,
+    /// The index of the first byte of the next chunk.
+    index: usize,
+}
+
+impl<'a> Utf8Source<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            chunks: bytes.utf8_chunks(),
+            valid: "".chars(),
+            invalid: None,
+            index: 0,
+        }
+    }
+}
+
+impl CodePointSource for Utf8Source<'_> {
+    fn next_code_point(&mut self) -> Option<std::result::Result<char, IllFormedSequence>> {
+        loop {
+            if let Some(c) = self.valid.next() {
+                return Some(Ok(c));
+            }
+            if let Some(sequence) = self.invalid.take() {
+                return Some(Err(sequence));
+            }
+            let chunk = self.chunks.next()?;
+            let (valid, invalid) = (chunk.valid(), chunk.invalid());
+            self.valid = valid.chars();
+            self.invalid = (!invalid.is_empty()).then_some(IllFormedSequence {
+                index: self.index + valid.len(),
+                surrogate: None,
+            });
+            self.index += valid.len() + invalid.len();
+        }
+    }
+}
+
+/// The code points of UTF-16 units, with their unpaired surrogates as [`IllFormedSequence`]s.
+#[derive(Debug, Clone)]
+pub struct Utf16Source<'a> {
+    units: DecodeUtf16<Copied<std::slice::Iter<'a, u16>>>,
+    /// The index of the next unit.
+    index: usize,
+}
+
+impl<'a> Utf16Source<'a> {
+    pub fn new(units: &'a [u16]) -> Self {
+        Self {
+            units: char::decode_utf16(units.iter().copied()),
+            index: 0,
+        }
+    }
+}
+
+impl CodePointSource for Utf16Source<'_> {
+    fn next_code_point(&mut self) -> Option<std::result::Result<char, IllFormedSequence>> {
+        let decoded = match self.units.next()? {
+            Ok(c) => Ok(c),
+            Err(e) => Err(IllFormedSequence {
+                index: self.index,
+                surrogate: Some(e.unpaired_surrogate()),
+            }),
+        };
+        self.index += decoded.map_or(1, char::len_utf16);
+        Some(decoded)
+    }
+}
+
+impl Collator {
+    /// Compares the strings of two sources, of the same encoding or not. Fails on an ill-formed
+    /// sequence only with [`IllFormedInput::Error`].
+    ///
+    /// ```
+    /// use collate_rs::algorithm::source::{Utf16Source, Utf8Source};
+    /// use collate_rs::algorithm::Collator;
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let utf16: Vec<u16> = "Zoé".encode_utf16().collect();
+    /// let ordering = collator
+    ///     .compare_source(Utf8Source::new("Émile".as_bytes()), Utf16Source::new(&utf16))
+    ///     .unwrap();
+    /// assert_eq!(ordering, Ordering::Less);
+    /// let lowercase = "ZOE".chars().map(|c| c.to_ascii_lowercase());
+    /// let ordering = collator.compare_source("zoe".chars(), lowercase).unwrap();
+    /// assert_eq!(ordering, Ordering::Equal);
+    /// ```
+    pub fn compare_source(
+        &self,
+        a: impl CodePointSource,
+        b: impl CodePointSource,
+    ) -> Result<Ordering> {
+        with_thread_scratch(|scratch| {
+            self.source_key_into_slot(a, scratch, 0)?;
+            self.source_key_into_slot(b, scratch, 1)?;
+            Ok(scratch.keys[0].cmp(&scratch.keys[1]))
+        })
+    }
+
+    /// The sort key of the string of a source, failing on an ill-formed sequence only with
+    /// [`IllFormedInput::Error`].
+    pub fn sort_key_from_source(&self, s: impl CodePointSource) -> Result<SortKey> {
+        with_thread_scratch(|scratch| {
+            self.source_elements_into(s, scratch)?;
+            Ok(self.sort_key_of(scratch.elements.as_slice(), &scratch.code_points))
+        })
+    }
+
+    /// Compares the UTF-8 bytes `a` and `b`, validating them as they are collated rather than
+    /// beforehand. Fails on an ill-formed sequence only with [`IllFormedInput::Error`].
+    pub fn compare_utf8(&self, a: &[u8], b: &[u8]) -> Result<Ordering> {
+        self.compare_source(Utf8Source::new(a), Utf8Source::new(b))
+    }
+
+    /// The sort key of the UTF-8 bytes `s`, failing on an ill-formed sequence only with
+    /// [`IllFormedInput::Error`].
+    pub fn sort_key_utf8(&self, s: &[u8]) -> Result<SortKey> {
+        self.sort_key_from_source(Utf8Source::new(s))
+    }
+
+    /// Compares two strings split into chunks, e.g. the leaves of ropes or the buffers of a
+    /// stream, as the strings they make up, without copying them into a `String` first: the
+    /// chunks are decoded as a single [`CodePointSource`], so that the contractions and the
+    /// canonical reordering of S1 see through their boundaries.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let rope = ["Ame", "\u{301}lie ", "Poulain"];
+    /// assert_eq!(collator.compare_chunks(rope, ["Amélie Poulain"]), Ordering::Equal);
+    /// assert_eq!(collator.compare_chunks(rope, ["Amelie"]), Ordering::Greater);
+    /// ```
+    pub fn compare_chunks<'a, 'b>(
+        &self,
+        a: impl IntoIterator<Item = &'a str>,
+        b: impl IntoIterator<Item = &'b str>,
+    ) -> Ordering {
+        self.compare_source(
+            a.into_iter().flat_map(str::chars),
+            b.into_iter().flat_map(str::chars),
+        )
+        .expect("A str has no ill-formed sequence.")
+    }
+
+    /// The sort key of a string split into chunks, as [`Collator::compare_chunks`] collates it.
+    pub fn sort_key_chunks<'a>(&self, chunks: impl IntoIterator<Item = &'a str>) -> SortKey {
+        self.sort_key_from_source(chunks.into_iter().flat_map(str::chars))
+            .expect("A str has no ill-formed sequence.")
+    }
+
+    /// The collation element array of a string split into chunks, as
+    /// [`Collator::collation_element_array`] of the string they make up.
+    pub fn collation_element_array_chunks<'a>(
+        &self,
+        chunks: impl IntoIterator<Item = &'a str>,
+    ) -> Vec<CollationElement> {
+        let mut scratch = CollatorScratch::new();
+        self.source_elements_into(chunks.into_iter().flat_map(str::chars), &mut scratch)
+            .expect("A str has no ill-formed sequence.");
+        scratch.elements.into_vec()
+    }
+
+    fn source_key_into_slot(
+        &self,
+        s: impl CodePointSource,
+        scratch: &mut CollatorScratch,
+        slot: usize,
+    ) -> Result<()> {
+        self.source_elements_into(s, scratch)?;
+        let key = &mut scratch.keys[slot];
+        key.clear();
+        self.write_sort_key_from_elements(scratch.elements.as_slice(), key);
+        self.write_tiebreak(&scratch.code_points, key);
+        Ok(())
+    }
+
+    /// The collation element array of the string of `source`, into the buffers of `scratch`.
+    /// The whole string is decoded before S1, so that a source of several chunks is collated as
+    /// the string they make up.
+    /// With [`IllFormedInput::Implicit`], the well-formed runs between the unpaired surrogates are
+    /// collated apart, as no contraction nor canonical reordering goes past a surrogate.
+    pub(crate) fn source_elements_into(
+        &self,
+        mut source: impl CodePointSource,
+        scratch: &mut CollatorScratch,
+    ) -> Result<()> {
+        let CollatorScratch {
+            chars,
+            elements: array,
+            code_points,
+            ..
+        } = scratch;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("collation_elements").entered();
+        chars.clear();
+        array.clear();
+        code_points.clear();
+        let tiebreak = self.options.code_point_tiebreak;
+        // The start in `chars` of the run following the last unpaired surrogate.
+        let mut start = 0;
+        while let Some(decoded) = source.next_code_point() {
+            let sequence = match decoded {
+                Ok(c) => {
+                    if tiebreak {
+                        code_points.push(c);
+                    }
+                    decompose(c, chars);
+                    continue;
+                }
+                Err(sequence) => sequence,
+            };
+            if tiebreak {
+                code_points.push(char::REPLACEMENT_CHARACTER);
+            }
+            match (self.options.ill_formed_input, sequence.surrogate) {
+                (IllFormedInput::Error, _) => {
+                    return Err(CollateError::InvalidInput(format!("{}.", sequence)))
+                }
+                (IllFormedInput::Implicit, Some(surrogate)) => {
+                    canonical_reorder(&mut chars[start..]);
+                    self.append_elements_of_nfd(&chars[start..], array, None);
+                    start = chars.len();
+                    array.push_implicit(
+                        self.weight_derivation()
+                            .implicit_primaries(surrogate.into()),
+                    );
+                }
+                _ => chars.push(char::REPLACEMENT_CHARACTER),
+            }
+        }
+        canonical_reorder(&mut chars[start..]);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(nfd = %chars.iter().collect::<String>(), "S1 normalized");
+        self.append_elements_of_nfd(&chars[start..], array, None);
+        self.apply_variable_weighting(array);
+        self.apply_quaternary_source(chars, array);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::tests::table;
+    use crate::algorithm::CollatorOptions;
+
+    #[test]
+    fn test_code_point_sources() {
+        let decode = |mut source: Utf8Source| {
+            std::iter::from_fn(move || source.next_code_point()).collect::<Vec<_>>()
+        };
+        let ill_formed = |index| {
+            Err(IllFormedSequence {
+                index,
+                surrogate: None,
+            })
+        };
+        assert_eq!(
+            decode(Utf8Source::new(b"a\xC3\xA1\xE2\x82b\xFF")),
+            [Ok('a'), Ok('\u{E1}'), ill_formed(3), Ok('b'), ill_formed(6)]
+        );
+        assert_eq!(decode(Utf8Source::new(b"")), []);
+
+        let collator = Collator::new(table());
+        for s in ["cha\u{301}", "", "\u{E1}h", "a\u{301}\u{301}c"] {
+            let key = collator.sort_key(s);
+            assert_eq!(collator.sort_key_utf8(s.as_bytes()).unwrap(), key);
+            assert_eq!(collator.sort_key_from_source(s.chars()).unwrap(), key);
+            let utf16: Vec<u16> = s.encode_utf16().collect();
+            assert_eq!(
+                collator
+                    .sort_key_from_source(Utf16Source::new(&utf16))
+                    .unwrap(),
+                key
+            );
+        }
+        assert_eq!(
+            collator
+                .compare_utf8(b"c\xFFh", "c\u{FFFD}h".as_bytes())
+                .unwrap(),
+            Ordering::Equal
+        );
+
+        // The ill-formed UTF-8 sequences have no code point to weight.
+        let implicit = collator.clone_with_options(CollatorOptions {
+            ill_formed_input: IllFormedInput::Implicit,
+            ..Default::default()
+        });
+        assert_eq!(
+            implicit.sort_key_utf8(b"c\xFFh").unwrap(),
+            collator.sort_key("c\u{FFFD}h")
+        );
+        let error = collator.clone_with_options(CollatorOptions {
+            ill_formed_input: IllFormedInput::Error,
+            ..Default::default()
+        });
+        assert!(matches!(
+            error.compare_utf8(b"a", b"c\xE2\x82h"),
+            Err(CollateError::InvalidInput(message)) if message.contains("sequence at index 1")
+        ));
+
+        // The contractions and the canonical reordering go past the boundaries of the chunks.
+        let chunks = ["c", "", "ha\u{323}", "\u{301}"];
+        let s = "cha\u{323}\u{301}";
+        assert_eq!(collator.sort_key_chunks(chunks), collator.sort_key(s));
+        assert_eq!(
+            collator.collation_element_array_chunks(chunks),
+            collator.collation_element_array(s)
+        );
+        assert_eq!(
+            collator.compare_chunks(chunks, ["ch\u{E1}\u{323}"]),
+            Ordering::Equal
+        );
+        assert_eq!(collator.compare_chunks([], [""]), Ordering::Equal);
+        assert_eq!(collator.compare_chunks(["c"], ["h"]), Ordering::Less);
+    }
+}
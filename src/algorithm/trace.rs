@@ -0,0 +1,264 @@
+use crate::algorithm::collation_element_array::ElementBuffer;
+use crate::algorithm::normalize::to_nfd;
+use crate::algorithm::{Collator, SortKey};
+use crate::definitions::collation_wel::{CollationElement, Weighted};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A lookup of S2.1 and S2.2: the characters matched in the table, more than one for a
+/// contraction, and the collation elements they map to, more than one for an expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingTrace {
+    pub characters: String,
+    pub elements: Vec<CollationElement>,
+}
+
+impl MappingTrace {
+    pub fn is_contraction(&self) -> bool {
+        self.characters.chars().nth(1).is_some()
+    }
+}
+
+/// The collation of a string by a [`Collator`], step by step, as traced by [`Collator::trace`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringTrace {
+    pub input: String,
+    /// The string normalized in S1.
+    pub normalized: String,
+    /// The lookups of the normalized string in the table, in order.
+    pub mappings: Vec<MappingTrace>,
+    /// The collation element array after the variable weighting of S2.3 and the
+    /// [`QuaternarySource`](crate::algorithm::QuaternarySource) of the collator.
+    pub elements: Vec<CollationElement>,
+    /// The indexes in `elements` of the collation elements whose first three levels were changed
+    /// by the variable weighting: the variable ones, and the ignorable ones following them.
+    pub shifted: Vec<usize>,
+    /// The sort key of S3.
+    pub sort_key: SortKey,
+}
+
+/// The comparison of two strings by a [`Collator`], step by step, as traced by
+/// [`Collator::compare_traced`], to debug why they compare as they do. It is displayed as a
+/// report of the steps of both strings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparisonTrace {
+    pub left: StringTrace,
+    pub right: StringTrace,
+    pub ordering: Ordering,
+    /// The first level of the sort keys (counted from 1) where the strings differ, the case level
+    /// included, or `None` if they are equal.
+    pub level: Option<usize>,
+}
+
+impl Collator {
+    /// The steps of the collation of `s`: its normalization, its lookups in the table, its
+    /// collation elements before and after the variable weighting, and its sort key.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorOptions};
+    /// use collate_rs::definitions::variable_weighting::VariableWeighting;
+    ///
+    /// let collator = Collator::try_new("und").unwrap().clone_with_options(CollatorOptions {
+    ///     variable_weighting: VariableWeighting::Shifted,
+    ///     ..Default::default()
+    /// });
+    /// let trace = collator.trace("a-\u{E1}");
+    /// assert_eq!(trace.normalized, "a-a\u{301}");
+    /// let characters: Vec<_> = trace.mappings.iter().map(|m| m.characters.as_str()).collect();
+    /// assert_eq!(characters, ["a", "-", "a", "\u{301}"]);
+    /// assert_eq!(trace.shifted, [1]);
+    /// assert_eq!(trace.elements[1].to_string(), "[.0000.0000.0000.020D]");
+    /// ```
+    pub fn trace(&self, s: &str) -> StringTrace {
+        let chars = to_nfd(s);
+        let mut array = ElementBuffer::default();
+        let mut spans = vec![];
+        self.append_elements_of_nfd(&chars, &mut array, Some(&mut spans));
+        let unweighted = array.as_slice().to_vec();
+        self.apply_variable_weighting(&mut array);
+        self.apply_quaternary_source(&chars, &mut array);
+
+        let mut mappings: Vec<MappingTrace> = vec![];
+        let mut last_span = None;
+        for (ce, span) in unweighted.iter().zip(spans) {
+            match mappings.last_mut() {
+                Some(mapping) if last_span.as_ref() == Some(&span) => {
+                    mapping.elements.push(ce.to_element())
+                }
+                _ => mappings.push(MappingTrace {
+                    characters: chars[span.clone()].iter().collect(),
+                    elements: vec![ce.to_element()],
+                }),
+            }
+            last_span = Some(span);
+        }
+        let shifted = unweighted
+            .iter()
+            .zip(array.as_slice())
+            .enumerate()
+            .filter(|(_, (before, after))| (1..=3).any(|n| before.weight(n) != after.weight(n)))
+            .map(|(i, _)| i)
+            .collect();
+        StringTrace {
+            input: s.to_string(),
+            normalized: chars.iter().collect(),
+            mappings,
+            elements: array.as_slice().iter().map(|ce| ce.to_element()).collect(),
+            shifted,
+            sort_key: self.sort_key_of(array.as_slice(), &s.chars().collect::<Vec<_>>()),
+        }
+    }
+
+    /// Compares `a` and `b` as [`Collator::compare`], tracing the steps of both strings and the
+    /// level where they differ.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let trace = collator.compare_traced("Resume", "résumé");
+    /// assert_eq!((trace.ordering, trace.level), (Ordering::Less, Some(2)));
+    /// assert!(trace.to_string().contains("differ at level 2"));
+    /// ```
+    pub fn compare_traced(&self, a: &str, b: &str) -> ComparisonTrace {
+        let (left, right) = (self.trace(a), self.trace(b));
+        let ordering = left.sort_key.weights().cmp(right.sort_key.weights());
+        let (left_levels, right_levels) = (left.sort_key.levels(), right.sort_key.levels());
+        let level = (ordering != Ordering::Equal).then(|| {
+            let common = left_levels.iter().zip(&right_levels);
+            common.take_while(|(l, r)| l == r).count() + 1
+        });
+        ComparisonTrace {
+            left,
+            right,
+            ordering,
+            level,
+        }
+    }
+}
+
+impl fmt::Display for StringTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:?} (NFD {:?})", self.input, self.normalized)?;
+        for mapping in &self.mappings {
+            let elements: String = mapping.elements.iter().map(|ce| ce.to_string()).collect();
+            let kind = if mapping.is_contraction() {
+                " (contraction)"
+            } else {
+                ""
+            };
+            writeln!(f, "  {:?}{} -> {}", mapping.characters, kind, elements)?;
+        }
+        for &i in &self.shifted {
+            writeln!(f, "  element {} weighted as {}", i, self.elements[i])?;
+        }
+        write!(f, "  sort key {}", self.sort_key)
+    }
+}
+
+impl fmt::Display for ComparisonTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.left)?;
+        writeln!(f, "{}", self.right)?;
+        match self.level {
+            Some(level) => write!(f, "{:?}: the keys differ at level {}", self.ordering, level),
+            None => write!(f, "Equal"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{CollatorOptions, Strength};
+    use crate::definitions::variable_weighting::VariableWeighting;
+
+    #[test]
+    fn test_compare_traced() {
+        let collator = Collator::try_new("und").unwrap();
+        let pairs = [
+            ("a", "b"),
+            ("a", "A"),
+            ("", "a"),
+            ("\u{C5}", "\u{212B}"),
+            ("ab", "a-b"),
+        ];
+        for (a, b) in pairs {
+            let trace = collator.compare_traced(a, b);
+            assert_eq!(trace.ordering, collator.compare(a, b));
+            assert_eq!(trace.left.sort_key, collator.sort_key(a));
+            assert_eq!(trace.left.elements, collator.collation_element_array(a));
+        }
+        assert_eq!(collator.compare_traced("a", "A").level, Some(3));
+        assert_eq!(collator.compare_traced("\u{C5}", "\u{212B}").level, None);
+
+        // LATIN SMALL LETTER L, MIDDLE DOT
+        let trace = collator.trace("l\u{B7}\u{C6}");
+        assert!(trace.mappings[0].is_contraction());
+        assert_eq!(trace.mappings[1].elements.len(), 3);
+        assert!(trace.shifted.is_empty());
+
+        let shifted = collator.clone_with_options(CollatorOptions {
+            variable_weighting: VariableWeighting::Shifted,
+            strength: Strength::Quaternary,
+            ..Default::default()
+        });
+        let trace = shifted.compare_traced("de-ath", "death");
+        assert_eq!((trace.ordering, trace.level), (Ordering::Less, Some(4)));
+        assert_eq!(trace.left.shifted, [2]);
+        let report = trace.to_string();
+        assert!(report.contains("\"-\" -> [*020D.0020.0002]"), "{}", report);
+        assert!(report.contains("element 2 weighted as [.0000.0000.0000.020D]"));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        struct Messages(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for Messages {
+            fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                if field.name() == "message" {
+                    self.0.lock().unwrap().push(format!("{:?}", value));
+                }
+            }
+        }
+
+        impl Subscriber for Messages {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                event.record(&mut Messages(self.0.clone()));
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let collator = Collator::try_new("und").unwrap();
+        tracing::subscriber::with_default(Messages(messages.clone()), || {
+            collator.collation_element_array("l\u{B7}\u{4E00}");
+        });
+        let messages = messages.lock().unwrap();
+        for step in [
+            "S1 normalized",
+            "S2.1 contraction",
+            "S2.2 implicit weights",
+            "S2.3 variable weighting",
+        ] {
+            assert!(messages.iter().any(|m| m == step), "{:?}", messages);
+        }
+    }
+}
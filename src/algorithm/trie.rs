@@ -0,0 +1,535 @@
+use crate::algorithm::normalize::ccc;
+use crate::algorithm::packed::{NarrowElement, PackedElement};
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::collation_wel::CollationElement;
+use crate::definitions::mappings::CollationElementMapping;
+use std::collections::HashMap;
+
+/// The code points of a block of the second stage, as a number of bits.
+const BLOCK_BITS: u32 = 6;
+const BLOCK_LEN: usize = 1 << BLOCK_BITS;
+const CODE_POINTS: usize = 0x11_0000;
+
+/// The tags of the two high bits of an [`Entry`].
+const TAG_SHIFT: u32 = 30;
+const PAYLOAD_MASK: u32 = (1 << TAG_SHIFT) - 1;
+const NONE: u32 = 0;
+/// The payload is a collation element of three levels: a primary weight of 16 bits, a secondary
+/// weight of 8 bits, the variable flag and a tertiary weight of 5 bits.
+const SIMPLE: u32 = 1;
+/// The payload is the position of a span of [`MappingTrie::expansions`].
+const EXPANSION: u32 = 2;
+/// The payload is the position of a [`Contraction`].
+const CONTRACTION: u32 = 3;
+
+/// The mapping of a code point, packed in the manner of the CE32 of ICU.
+type Entry = u32;
+
+fn tag(entry: Entry) -> u32 {
+    entry >> TAG_SHIFT
+}
+
+/// The contractions and the contextual mappings starting with a character, and the mapping of the
+/// character itself.
+#[derive(Debug, Clone)]
+struct Contraction {
+    own: Entry,
+    /// The positions in [`MappingTrie::suffixes`] of the contractions, longest first.
+    suffixes: std::ops::Range<u32>,
+    /// The positions in [`MappingTrie::prefixed`] of the contextual mappings, longest prefix
+    /// first.
+    prefixed: std::ops::Range<u32>,
+}
+
+/// The characters of a contraction, first one included, with their entry.
+#[derive(Debug, Clone)]
+struct Suffix {
+    characters: Box<[char]>,
+    entry: Entry,
+}
+
+/// The prefix and the characters of a contextual mapping, with their entry.
+#[derive(Debug, Clone)]
+struct Prefixed {
+    prefix: Box<[char]>,
+    characters: Box<[char]>,
+    entry: Entry,
+}
+
+/// The characters and the collation elements of a contraction of a table, with its position in
+/// the table, while building a [`MappingTrie`].
+type PendingSuffix<'t> = (&'t [char], &'t [CollationElement], usize);
+
+/// The prefix of a contextual mapping of a table, with the fields of its [`PendingSuffix`].
+type PendingPrefixed<'t> = (&'t [char], PendingSuffix<'t>);
+
+/// The collation elements of a match of [`MappingTrie::longest_match`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Elements<'t> {
+    /// A collation element packed in a [`SIMPLE`] entry.
+    Simple(u32),
+    Expansion(&'t [PackedElement]),
+}
+
+impl Elements<'_> {
+    /// The collation element of a [`Elements::Simple`] payload.
+    pub(crate) fn unpack(payload: u32) -> PackedElement {
+        let (primary, secondary) = (payload >> 14, (payload >> 6 & 0xFF) as u16);
+        let (tertiary, variable) = ((payload & 0x1F) as u16, payload & 0x20 != 0);
+        PackedElement::Narrow(NarrowElement::new(primary, secondary, tertiary, variable))
+    }
+
+    pub(crate) fn to_vec(self) -> Vec<CollationElement> {
+        match self {
+            Self::Simple(payload) => vec![Self::unpack(payload).to_element()],
+            Self::Expansion(ces) => ces.iter().map(PackedElement::to_element).collect(),
+        }
+    }
+
+    /// The collation element, if there is exactly one.
+    pub(crate) fn single(self) -> Option<PackedElement> {
+        match self {
+            Self::Simple(payload) => Some(Self::unpack(payload)),
+            Self::Expansion([ce]) => Some(ce.clone()),
+            Self::Expansion(_) => None,
+        }
+    }
+}
+
+/// The positions of the mappings of a [`CollationElementTable`], to rebuild it in order.
+const ORDER_SUFFIX: u32 = 1 << 31;
+const ORDER_SHADOWED: u32 = 1 << 30;
+const ORDER_PREFIXED: u32 = ORDER_SUFFIX | ORDER_SHADOWED;
+
+/// The mappings of a [`CollationElementTable`] in a two-stage trie from the code points to packed
+/// entries, with side tables for the expansions, the contractions and the contextual mappings, so
+/// that a code point is looked up in constant time and the simple mappings take 4 bytes.
+///
+/// The trie keeps what is needed to rebuild the table, in the order of its mappings, with
+/// [`MappingTrie::to_table`]. The mappings shadowed by an earlier one of the same characters, and
+/// those not in the form of [`CollationElementMapping::new`], are kept aside as they are.
+#[derive(Debug, Clone)]
+pub(crate) struct MappingTrie {
+    /// The position of the block of each range of [`BLOCK_LEN`] code points in `stage2`.
+    stage1: Vec<u16>,
+    /// The entries of the distinct blocks, the first one empty.
+    stage2: Vec<Entry>,
+    /// The spans of `expansions` of the [`EXPANSION`] entries.
+    spans: Vec<(u32, u32)>,
+    expansions: Vec<PackedElement>,
+    contractions: Vec<Contraction>,
+    suffixes: Vec<Suffix>,
+    prefixed: Vec<Prefixed>,
+    /// The code point, suffix, contextual or shadowed mapping at each position of the table.
+    order: Vec<u32>,
+    shadowed: Vec<CollationElementMapping>,
+}
+
+/// Whether `mapping` is the one [`CollationElementMapping::new`] builds from its characters and
+/// collation elements.
+fn is_normal(mapping: &CollationElementMapping) -> bool {
+    let (characters, ces) = (
+        mapping.characters().len(),
+        mapping.collation_elements().len(),
+    );
+    match mapping {
+        CollationElementMapping::SimpleMapping { .. } => true,
+        CollationElementMapping::Expansion { .. } => ces != 1,
+        CollationElementMapping::ManyToOneMapping { .. } => characters > 1,
+        CollationElementMapping::ManyToManyMapping { .. } => characters > 1 && ces != 1,
+        CollationElementMapping::ContextualMapping { prefix, .. } => !prefix.is_empty(),
+    }
+}
+
+impl MappingTrie {
+    pub(crate) fn new(table: &CollationElementTable) -> Self {
+        let mut trie = Self {
+            stage1: vec![],
+            stage2: vec![],
+            spans: vec![],
+            expansions: vec![],
+            contractions: vec![],
+            suffixes: vec![],
+            prefixed: vec![],
+            order: Vec::with_capacity(table.0.len()),
+            shadowed: vec![],
+        };
+        let mut entries: HashMap<char, Entry> = HashMap::new();
+        let mut contractions: HashMap<char, (Vec<PendingSuffix>, Vec<PendingPrefixed>)> =
+            HashMap::new();
+        for mapping in &table.0 {
+            let (prefix, characters) = (mapping.prefix(), mapping.characters());
+            let pending = characters.first().and_then(|c| contractions.get(c));
+            let taken = match characters {
+                [] => true,
+                _ if !prefix.is_empty() => pending.is_some_and(|(_, prefixed)| {
+                    prefixed
+                        .iter()
+                        .any(|p| p.0 == prefix && p.1 .0 == characters)
+                }),
+                [c] => entries.contains_key(c),
+                [_, ..] => {
+                    pending.is_some_and(|(suffixes, _)| suffixes.iter().any(|s| s.0 == characters))
+                }
+            };
+            if taken || !is_normal(mapping) {
+                trie.order.push(ORDER_SHADOWED | trie.shadowed.len() as u32);
+                trie.shadowed.push(mapping.clone());
+                continue;
+            }
+            let pending = (characters, mapping.collation_elements(), trie.order.len());
+            match characters {
+                [c] if prefix.is_empty() => {
+                    let entry = trie.entry_of(mapping.collation_elements());
+                    entries.insert(*c, entry);
+                    trie.order.push(*c as u32);
+                }
+                [c, ..] if prefix.is_empty() => {
+                    contractions.entry(*c).or_default().0.push(pending);
+                    // Patched below, once the suffixes have their positions.
+                    trie.order.push(ORDER_SUFFIX);
+                }
+                [c, ..] => {
+                    contractions
+                        .entry(*c)
+                        .or_default()
+                        .1
+                        .push((prefix, pending));
+                    trie.order.push(ORDER_PREFIXED);
+                }
+                [] => unreachable!("The empty mappings are shadowed."),
+            }
+        }
+        let mut contractions: Vec<_> = contractions.into_iter().collect();
+        contractions.sort_by_key(|(c, _)| *c);
+        for (c, (mut suffixes, mut prefixed)) in contractions {
+            // Stable, so that the contractions of the same length keep their order.
+            suffixes.sort_by_key(|s| std::cmp::Reverse(s.0.len()));
+            let start = trie.suffixes.len() as u32;
+            for (characters, ces, position) in suffixes {
+                trie.order[position] = ORDER_SUFFIX | trie.suffixes.len() as u32;
+                let entry = trie.entry_of(ces);
+                trie.suffixes.push(Suffix {
+                    characters: characters.into(),
+                    entry,
+                });
+            }
+            prefixed.sort_by_key(|(prefix, s)| std::cmp::Reverse((prefix.len(), s.0.len())));
+            let prefixed_start = trie.prefixed.len() as u32;
+            for (prefix, (characters, ces, position)) in prefixed {
+                trie.order[position] = ORDER_PREFIXED | trie.prefixed.len() as u32;
+                let entry = trie.entry_of(ces);
+                trie.prefixed.push(Prefixed {
+                    prefix: prefix.into(),
+                    characters: characters.into(),
+                    entry,
+                });
+            }
+            let own = entries.get(&c).copied().unwrap_or(NONE);
+            entries.insert(c, CONTRACTION << TAG_SHIFT | trie.contractions.len() as u32);
+            trie.contractions.push(Contraction {
+                own,
+                suffixes: start..trie.suffixes.len() as u32,
+                prefixed: prefixed_start..trie.prefixed.len() as u32,
+            });
+        }
+        trie.build_stages(&entries);
+        trie
+    }
+
+    /// Packs the collation elements of a mapping in an entry.
+    fn entry_of(&mut self, ces: &[CollationElement]) -> Entry {
+        if let [CollationElement(weights, variable)] = ces {
+            if let [p, s, t] = weights.as_slice() {
+                if p.0 <= 0xFFFF && s.0 <= 0xFF && t.0 <= 0x1F {
+                    let payload = p.0 << 14 | s.0 << 6 | u32::from(*variable) << 5 | t.0;
+                    return SIMPLE << TAG_SHIFT | payload;
+                }
+            }
+        }
+        let start = self.expansions.len() as u32;
+        self.expansions.extend(ces.iter().map(PackedElement::from));
+        self.spans.push((start, ces.len() as u32));
+        EXPANSION << TAG_SHIFT | (self.spans.len() - 1) as u32
+    }
+
+    /// Fills the two stages with the entry of each code point, sharing the identical blocks.
+    fn build_stages(&mut self, entries: &HashMap<char, Entry>) {
+        let mut blocks: HashMap<Vec<Entry>, u16> = HashMap::new();
+        self.stage2 = vec![NONE; BLOCK_LEN];
+        blocks.insert(vec![NONE; BLOCK_LEN], 0);
+        let mut block_of: HashMap<usize, Vec<Entry>> = HashMap::new();
+        for (&c, &entry) in entries {
+            let cp = c as usize;
+            block_of
+                .entry(cp >> BLOCK_BITS)
+                .or_insert_with(|| vec![NONE; BLOCK_LEN])[cp % BLOCK_LEN] = entry;
+        }
+        let mut numbers: Vec<usize> = block_of.keys().copied().collect();
+        numbers.sort_unstable();
+        self.stage1 = vec![0; CODE_POINTS / BLOCK_LEN];
+        for number in numbers {
+            let block = block_of.remove(&number).expect("The number is a key.");
+            let next = (self.stage2.len() / BLOCK_LEN) as u16;
+            let position = *blocks.entry(block).or_insert_with_key(|block| {
+                self.stage2.extend_from_slice(block);
+                next
+            });
+            self.stage1[number] = position;
+        }
+    }
+
+    fn entry(&self, c: char) -> Entry {
+        let cp = c as usize;
+        let block = usize::from(self.stage1[cp >> BLOCK_BITS]);
+        self.stage2[block * BLOCK_LEN + cp % BLOCK_LEN]
+    }
+
+    fn elements(&self, entry: Entry) -> Option<Elements<'_>> {
+        let payload = entry & PAYLOAD_MASK;
+        match tag(entry) {
+            SIMPLE => Some(Elements::Simple(payload)),
+            EXPANSION => {
+                let (start, len) = self.spans[payload as usize];
+                let (start, len) = (start as usize, len as usize);
+                Some(Elements::Expansion(&self.expansions[start..start + len]))
+            }
+            _ => None,
+        }
+    }
+
+    /// > S2.1 Find the longest initial substring S at each point that has a match in the collation
+    /// > element table.
+    ///
+    /// The length of the match, in characters, with its collation elements. The contextual
+    /// mappings whose prefix ends `before`, the characters preceding `input`, match first.
+    pub(crate) fn longest_match(
+        &self,
+        before: &[char],
+        input: &[char],
+    ) -> Option<(usize, Elements<'_>)> {
+        let entry = self.entry(*input.first()?);
+        if tag(entry) != CONTRACTION {
+            return self.elements(entry).map(|elements| (1, elements));
+        }
+        let contraction = &self.contractions[(entry & PAYLOAD_MASK) as usize];
+        let prefixed = contraction.prefixed.start as usize..contraction.prefixed.end as usize;
+        if let Some(prefixed) = self.prefixed[prefixed]
+            .iter()
+            .find(|p| before.ends_with(&p.prefix) && input.starts_with(&p.characters))
+        {
+            return self
+                .elements(prefixed.entry)
+                .map(|elements| (prefixed.characters.len(), elements));
+        }
+        let suffixes = contraction.suffixes.start as usize..contraction.suffixes.end as usize;
+        match self.suffixes[suffixes]
+            .iter()
+            .find(|suffix| input.starts_with(&suffix.characters))
+        {
+            Some(suffix) => self
+                .elements(suffix.entry)
+                .map(|elements| (suffix.characters.len(), elements)),
+            None => self.elements(contraction.own).map(|elements| (1, elements)),
+        }
+    }
+
+    /// > S2.1.1 If there are any non-starters following S, process each non-starter C.
+    /// >
+    /// > S2.1.2 If C is an unblocked non-starter with respect to S, find if S + C has a match in
+    /// > the collation element table.
+    /// >
+    /// > S2.1.3 If there is a match, replace S by S + C, and remove C.
+    ///
+    /// The indexes in `input` of the characters added to its `len` first ones, S, with the
+    /// collation elements of the longer match, if S + C matches for some C. A non-starter is
+    /// blocked by a starter, or by a non-starter left out of S of a class at least as high.
+    pub(crate) fn discontiguous_match(
+        &self,
+        input: &[char],
+        len: usize,
+    ) -> Option<(Vec<usize>, Elements<'_>)> {
+        let entry = self.entry(*input.first()?);
+        if tag(entry) != CONTRACTION || ccc(*input.get(len)?) == 0 {
+            return None;
+        }
+        let contraction = &self.contractions[(entry & PAYLOAD_MASK) as usize];
+        let suffixes =
+            &self.suffixes[contraction.suffixes.start as usize..contraction.suffixes.end as usize];
+        let mut matched = input[..len].to_vec();
+        let (mut added, mut entry) = (vec![], None);
+        // The highest class of the non-starters left out of S.
+        let mut blocking = 0;
+        for (j, &c) in input.iter().enumerate().skip(len) {
+            let class = ccc(c);
+            if class == 0 {
+                break;
+            }
+            if class > blocking {
+                matched.push(c);
+                if let Some(suffix) = suffixes
+                    .iter()
+                    .find(|suffix| *suffix.characters == *matched)
+                {
+                    added.push(j);
+                    entry = Some(suffix.entry);
+                    continue;
+                }
+                matched.pop();
+            }
+            blocking = blocking.max(class);
+        }
+        Some((added, self.elements(entry?)?))
+    }
+
+    /// The collation elements of the mapping of `c` alone, if `c` starts no contraction.
+    pub(crate) fn simple_match(&self, c: char) -> Option<Elements<'_>> {
+        self.elements(self.entry(c))
+    }
+
+    /// Rebuilds the table of the trie, its mappings in their order.
+    pub(crate) fn to_table(&self) -> CollationElementTable {
+        let mapping = |characters: &[char], entry| {
+            let ces = self.elements(entry).map_or(vec![], Elements::to_vec);
+            CollationElementMapping::new(characters, &ces).expect("The mapping was well-formed.")
+        };
+        let mappings = self.order.iter().map(|&ordinal| {
+            if ordinal & ORDER_PREFIXED == ORDER_PREFIXED {
+                let prefixed = &self.prefixed[(ordinal & !ORDER_PREFIXED) as usize];
+                let ces = self
+                    .elements(prefixed.entry)
+                    .map_or(vec![], Elements::to_vec);
+                CollationElementMapping::with_prefix(&prefixed.prefix, &prefixed.characters, &ces)
+                    .expect("The mapping was well-formed.")
+            } else if ordinal & ORDER_SUFFIX != 0 {
+                let suffix = &self.suffixes[(ordinal & !ORDER_SUFFIX) as usize];
+                mapping(&suffix.characters, suffix.entry)
+            } else if ordinal & ORDER_SHADOWED != 0 {
+                self.shadowed[(ordinal & !ORDER_SHADOWED) as usize].clone()
+            } else {
+                let c = char::from_u32(ordinal).expect("The ordinal is a code point.");
+                let entry = match self.entry(c) {
+                    entry if tag(entry) == CONTRACTION => {
+                        self.contractions[(entry & PAYLOAD_MASK) as usize].own
+                    }
+                    entry => entry,
+                };
+                mapping(&[c], entry)
+            }
+        });
+        CollationElementTable::new(mappings.collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ducet;
+
+    fn mapping(characters: &str, ces: &[&str]) -> CollationElementMapping {
+        let ces: Vec<_> = ces
+            .iter()
+            .map(|ce| CollationElement::try_from(*ce).unwrap())
+            .collect();
+        CollationElementMapping::new(&characters.chars().collect::<Vec<_>>(), &ces).unwrap()
+    }
+
+    #[test]
+    fn test_trie() {
+        let table = CollationElementTable::new(vec![
+            mapping("c", &["[.1FD2.0020.0002]"]),
+            mapping("ch", &["[.1FD3.0020.0002]"]),
+            mapping("chx", &["[.1FD4.0020.0002]"]),
+            mapping("dz", &["[.1FE0.0020.0002]"]),
+            mapping("æ", &["[.1FA2.0020.0004]", "[.0000.0110.0004]"]),
+            mapping("　", &["[*0209.0020.0003]"]),
+            // Weights too large to be packed.
+            mapping("x", &["[.2000.0020.0002.0001]"]),
+            // Shadowed by the first mapping of "c".
+            mapping("c", &["[.0001.0020.0002]"]),
+            CollationElementMapping::ManyToManyMapping {
+                characters: vec!['y'],
+                collation_elements: Default::default(),
+            },
+        ]);
+        let trie = MappingTrie::new(&table);
+        let longest = |s: &str| {
+            let chars: Vec<char> = s.chars().collect();
+            trie.longest_match(&[], &chars)
+                .map(|(len, elements)| (len, elements.to_vec()))
+        };
+        let ce = |s| CollationElement::try_from(s).unwrap();
+        assert_eq!(longest("cha"), Some((2, vec![ce("[.1FD3.0020.0002]")])));
+        assert_eq!(longest("chx"), Some((3, vec![ce("[.1FD4.0020.0002]")])));
+        assert_eq!(longest("ca"), Some((1, vec![ce("[.1FD2.0020.0002]")])));
+        assert_eq!(longest("da"), None);
+        assert_eq!(longest("y"), None);
+        assert!(longest("　").unwrap().1[0].is_variable());
+        assert_eq!(
+            longest("æ").unwrap().1,
+            [ce("[.1FA2.0020.0004]"), ce("[.0000.0110.0004]")]
+        );
+        assert_eq!(longest("x"), Some((1, vec![ce("[.2000.0020.0002.0001]")])));
+        assert_eq!(trie.to_table(), table);
+    }
+
+    #[test]
+    fn test_contextual_trie() {
+        let ce = |s| CollationElement::try_from(s).unwrap();
+        let contextual = |prefix: &str, characters: &str, s| {
+            let chars = |s: &str| s.chars().collect::<Vec<_>>();
+            CollationElementMapping::with_prefix(&chars(prefix), &chars(characters), &[ce(s)])
+                .unwrap()
+        };
+        let table = CollationElementTable::new(vec![
+            mapping("ー", &["[.42D5.0020.0002]"]),
+            contextual("ア", "ー", "[.42DA.0020.0011]"),
+            contextual("カア", "ー", "[.42DB.0020.0011]"),
+            contextual("ア", "ーー", "[.42DC.0020.0011]"),
+            // Shadowed by the first mapping of "ー" after "ア".
+            contextual("ア", "ー", "[.0001.0020.0002]"),
+            // Matched only after "イ", as there is no mapping of "ゝ" alone.
+            contextual("イ", "ゝ", "[.42E0.0020.0002]"),
+        ]);
+        let trie = MappingTrie::new(&table);
+        let longest = |before: &str, s: &str| {
+            let before: Vec<char> = before.chars().collect();
+            let chars: Vec<char> = s.chars().collect();
+            trie.longest_match(&before, &chars)
+                .map(|(len, elements)| (len, elements.to_vec()))
+        };
+        assert_eq!(longest("", "ー"), Some((1, vec![ce("[.42D5.0020.0002]")])));
+        assert_eq!(
+            longest("イ", "ー"),
+            Some((1, vec![ce("[.42D5.0020.0002]")]))
+        );
+        assert_eq!(
+            longest("ア", "ー"),
+            Some((1, vec![ce("[.42DA.0020.0011]")]))
+        );
+        assert_eq!(
+            longest("ア", "ーー"),
+            Some((2, vec![ce("[.42DC.0020.0011]")]))
+        );
+        assert_eq!(
+            longest("カア", "ー"),
+            Some((1, vec![ce("[.42DB.0020.0011]")]))
+        );
+        assert_eq!(longest("ア", "ゝ"), None);
+        assert_eq!(
+            longest("イ", "ゝ"),
+            Some((1, vec![ce("[.42E0.0020.0002]")]))
+        );
+        assert_eq!(trie.to_table(), table);
+    }
+
+    #[test]
+    fn test_ducet_trie() {
+        let table = ducet::table();
+        let trie = MappingTrie::new(&table);
+        assert_eq!(trie.to_table(), table);
+        // Most of the DUCET is packed in the two stages.
+        assert!(trie.stage2.len() * 4 + trie.stage1.len() * 2 < 512 * 1024);
+        assert!(trie.expansions.len() < table.0.len() / 2);
+    }
+}
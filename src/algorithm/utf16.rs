@@ -0,0 +1,122 @@
+use crate::algorithm::source::Utf16Source;
+use crate::algorithm::{Collator, SortKey};
+use crate::error::Result;
+use std::cmp::Ordering;
+
+/// How the ill-formed sequences of the strings of a
+/// [`CodePointSource`](crate::algorithm::source::CodePointSource) are collated, e.g. by
+/// [`Collator::compare_utf16`] the unpaired surrogates of UTF-16 strings, which Windows file names
+/// and JavaScript strings may have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IllFormedInput {
+    /// As U+FFFD REPLACEMENT CHARACTER, as [`String::from_utf16_lossy`] decodes them.
+    #[default]
+    Replace,
+    /// Not at all: the strings are rejected with a [`CollateError::InvalidInput`].
+    Error,
+    /// As the code points missing from the table, with the implicit weights of their code point
+    /// (Section 10.1.3), so that strings with different surrogates are not equal. The ill-formed
+    /// sequences of UTF-8, which have no code point, are replaced.
+    Implicit,
+}
+
+impl Collator {
+    /// Compares the UTF-16 strings `a` and `b`, without transcoding them to `str` first. Fails
+    /// on an unpaired surrogate only with [`IllFormedInput::Error`].
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{Collator, CollatorOptions, IllFormedInput};
+    /// use std::cmp::Ordering;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let utf16 = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+    /// assert_eq!(
+    ///     collator.compare_utf16(&utf16("Émile"), &utf16("Zoé")).unwrap(),
+    ///     Ordering::Less
+    /// );
+    ///
+    /// let (a, b) = ([0x61, 0xD800], [0x61, 0xDC00]);
+    /// assert_eq!(collator.compare_utf16(&a, &b).unwrap(), Ordering::Equal);
+    /// let implicit = collator.clone_with_options(CollatorOptions {
+    ///     ill_formed_input: IllFormedInput::Implicit,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(implicit.compare_utf16(&a, &b).unwrap(), Ordering::Less);
+    /// let strict = collator.clone_with_options(CollatorOptions {
+    ///     ill_formed_input: IllFormedInput::Error,
+    ///     ..Default::default()
+    /// });
+    /// assert!(strict.compare_utf16(&a, &b).is_err());
+    /// ```
+    pub fn compare_utf16(&self, a: &[u16], b: &[u16]) -> Result<Ordering> {
+        self.compare_source(Utf16Source::new(a), Utf16Source::new(b))
+    }
+
+    /// The sort key of the UTF-16 string `s`, failing on an unpaired surrogate only with
+    /// [`IllFormedInput::Error`].
+    pub fn sort_key_utf16(&self, s: &[u16]) -> Result<SortKey> {
+        self.sort_key_from_source(Utf16Source::new(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::tests::table;
+    use crate::algorithm::CollatorOptions;
+    use crate::error::CollateError;
+
+    #[test]
+    fn test_compare_utf16() {
+        let collator = Collator::new(table());
+        let utf16 = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+        for (a, b) in [
+            ("cha\u{301}", "ab"),
+            ("a", "A"),
+            ("", "b"),
+            ("\u{E1}", "a\u{301}"),
+        ] {
+            assert_eq!(
+                collator.compare_utf16(&utf16(a), &utf16(b)).unwrap(),
+                collator.compare(a, b)
+            );
+            assert_eq!(
+                collator.sort_key_utf16(&utf16(a)).unwrap(),
+                collator.sort_key(a)
+            );
+        }
+
+        // The unpaired surrogates, lone or reversed, by policy.
+        let ill_formed = [0x63, 0xDC00, 0x68, 0xD800];
+        assert_eq!(
+            collator.sort_key_utf16(&ill_formed).unwrap(),
+            collator.sort_key("c\u{FFFD}h\u{FFFD}")
+        );
+        let implicit = collator.clone_with_options(CollatorOptions {
+            ill_formed_input: IllFormedInput::Implicit,
+            ..Default::default()
+        });
+        // The surrogate separates "c" from "h", so that they do not contract.
+        let primaries = implicit.sort_key_utf16(&ill_formed).unwrap().levels()[0].clone();
+        let primary = |s| collator.sort_key(s).levels()[0][0];
+        assert_eq!(
+            primaries,
+            [primary("c"), 0xFBC1, 0xDC00, primary("h"), 0xFBC1, 0xD800]
+        );
+        let error = collator.clone_with_options(CollatorOptions {
+            ill_formed_input: IllFormedInput::Error,
+            ..Default::default()
+        });
+        assert!(matches!(
+            error.compare_utf16(&[0x61], &ill_formed),
+            Err(CollateError::InvalidInput(message)) if message.contains("DC00 at index 1")
+        ));
+        assert_eq!(
+            error
+                .compare_utf16(&utf16("\u{1F600}"), &utf16("a"))
+                .unwrap(),
+            collator.compare("\u{1F600}", "a")
+        );
+    }
+}
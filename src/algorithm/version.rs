@@ -0,0 +1,237 @@
+use crate::algorithm::{CaseFirst, Collator, CollatorOptions, IllFormedInput, QuaternarySource};
+use crate::data_files::ucd::UNICODE_VERSION;
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::variable_weighting::{MaxVariable, VariableWeighting};
+use crate::error::CollateError;
+use std::fmt;
+use std::str::FromStr;
+
+/// The version of the format of the sort keys, hashed into every [`CollationVersion`]: bumped by
+/// every change of the algorithm or of the key encodings changing the sort key of some string for
+/// the same table and options.
+const SORT_KEY_FORMAT: u32 = 1;
+
+/// The version of the sort keys of a [`Collator`], as returned by [`Collator::version`]: a hash
+/// of its table, of its options, of the Unicode data normalizing the strings and of the format of
+/// the sort keys.
+///
+/// Displays, and serializes with the `serde` feature, as 16 hexadecimal digits, which
+/// [`CollationVersion::from_str`] parses back, e.g. to store it next to persisted sort keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "String", try_from = "String"))]
+pub struct CollationVersion(u64);
+
+impl CollationVersion {
+    pub fn from_u64(hash: u64) -> Self {
+        Self(hash)
+    }
+
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for CollationVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for CollationVersion {
+    type Err = CollateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match u64::from_str_radix(s, 16) {
+            Ok(hash) if s.len() == 16 && s.bytes().all(|b| b.is_ascii_hexdigit()) => Ok(Self(hash)),
+            _ => Err(CollateError::parse(format!(
+                "Invalid collation version {:?}: expected 16 hexadecimal digits.",
+                s
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<CollationVersion> for String {
+    fn from(version: CollationVersion) -> Self {
+        version.to_string()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for CollationVersion {
+    type Error = CollateError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// The 64-bit FNV-1a hash, whose values, unlike the ones of
+/// [`DefaultHasher`](std::collections::hash_map::DefaultHasher), are the same on every platform
+/// and with every release of Rust.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xCBF2_9CE4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.write(&n.to_le_bytes());
+    }
+}
+
+/// The hash of the mappings of a table.
+pub(crate) fn fingerprint(table: &CollationElementTable) -> u64 {
+    let mut hasher = Fnv::new();
+    for mapping in table.mappings() {
+        for characters in [mapping.prefix(), mapping.characters()] {
+            hasher.write_u32(characters.len() as u32);
+            characters
+                .iter()
+                .for_each(|&c| hasher.write_u32(u32::from(c)));
+        }
+        hasher.write_u32(mapping.collation_elements().len() as u32);
+        for ce in mapping.collation_elements() {
+            hasher.write(&[u8::from(ce.is_variable()), ce.len_levels() as u8]);
+            ce.0.iter().for_each(|w| hasher.write_u32(w.0));
+        }
+    }
+    hasher.0
+}
+
+/// Hashes the options changing the sort keys.
+fn hash_options(hasher: &mut Fnv, options: &CollatorOptions) {
+    let variable_weighting = match options.variable_weighting {
+        VariableWeighting::NonIgnorable => 0,
+        VariableWeighting::Blanked => 1,
+        VariableWeighting::Shifted => 2,
+        VariableWeighting::ShiftTrimmed => 3,
+    };
+    let max_variable = match options.max_variable {
+        None => 0,
+        Some(MaxVariable::Space) => 1,
+        Some(MaxVariable::Punct) => 2,
+        Some(MaxVariable::Symbol) => 3,
+        Some(MaxVariable::Currency) => 4,
+    };
+    let case_first = match options.case_first {
+        CaseFirst::Off => 0,
+        CaseFirst::Lower => 1,
+        CaseFirst::Upper => 2,
+    };
+    let ill_formed_input = match options.ill_formed_input {
+        IllFormedInput::Replace => 0,
+        IllFormedInput::Error => 1,
+        IllFormedInput::Implicit => 2,
+    };
+    hasher.write(&[
+        options.key_encoding.header(),
+        options.strength.level().min(u8::MAX.into()) as u8,
+        u8::from(options.backward_secondary),
+        variable_weighting,
+        max_variable,
+        u8::from(options.case_level),
+        case_first,
+        u8::from(options.numeric),
+        ill_formed_input,
+    ]);
+    // Hashed only if set, so that the option leaves the versions of the other collators as they
+    // were before it existed.
+    match options.quaternary {
+        QuaternarySource::Weights => {}
+        QuaternarySource::CodePoints => hasher.write(&[1]),
+        QuaternarySource::Ignored => hasher.write(&[2]),
+    }
+    if options.code_point_tiebreak {
+        hasher.write(&[3]);
+    }
+}
+
+impl Collator {
+    /// The version of the sort keys of the collator, to detect when the keys persisted by a
+    /// database, e.g. in an index, no longer sort as the strings they were computed from.
+    ///
+    /// Two collators of the same version produce the same sort keys for the same strings, in any
+    /// process, on any platform and with any release of this crate: the version hashes the table,
+    /// the options, the version of the Unicode data normalizing the strings and the version of
+    /// the format of the sort keys, bumped by any change of the algorithm changing some key.
+    /// Conversely, collators of different versions may produce different keys, even where the
+    /// change is unrelated to the stored strings, so the keys of the other versions must be
+    /// recomputed. See `examples/postgres.rs` for the keys stored in a PostgreSQL table.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{CollationVersion, Collator, CollatorOptions, Strength};
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let stored = collator.version().to_string();
+    /// let version: CollationVersion = stored.parse().unwrap();
+    /// assert_eq!(version, Collator::try_new("und").unwrap().version());
+    ///
+    /// let primary = collator.clone_with_options(CollatorOptions {
+    ///     strength: Strength::Primary,
+    ///     ..Default::default()
+    /// });
+    /// assert_ne!(primary.version(), collator.version());
+    /// # #[cfg(feature = "locales")]
+    /// assert_ne!(Collator::try_new("sv").unwrap().version(), collator.version());
+    /// ```
+    pub fn version(&self) -> CollationVersion {
+        let mut hasher = Fnv::new();
+        hasher.write_u32(SORT_KEY_FORMAT);
+        hasher.write(UNICODE_VERSION.as_bytes());
+        hasher.write(&self.data.fingerprint.to_le_bytes());
+        hash_options(&mut hasher, &self.options);
+        // Hashed only if set, as the options above.
+        if let Some(derivation) = &self.weight_derivation {
+            hasher.write(&[4]);
+            hasher.write(&derivation.fingerprint().to_le_bytes());
+        }
+        CollationVersion(hasher.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::tests::table;
+    use crate::algorithm::KeyEncoding;
+
+    #[test]
+    fn test_collation_version() {
+        let mut hasher = Fnv::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.0, 0xAF63_DC4C_8601_EC8C);
+
+        let collator = Collator::new(table());
+        assert_eq!(collator.version(), Collator::new(table()).version());
+        assert_eq!(collator.version(), collator.clone().version());
+        let fractional = collator.clone_with_options(CollatorOptions {
+            key_encoding: KeyEncoding::Fractional,
+            ..Default::default()
+        });
+        assert_ne!(fractional.version(), collator.version());
+        let mut mappings = table().mappings().to_vec();
+        mappings.pop();
+        let smaller = Collator::new(CollationElementTable::new(mappings));
+        assert_ne!(smaller.version(), collator.version());
+
+        let version = collator.version();
+        assert_eq!(version.to_string().len(), 16);
+        assert_eq!(
+            version.to_string().parse::<CollationVersion>().unwrap(),
+            version
+        );
+        assert_eq!(CollationVersion::from_u64(version.as_u64()), version);
+        assert!("12345".parse::<CollationVersion>().is_err());
+        assert!("+123456789abcdef".parse::<CollationVersion>().is_err());
+    }
+}
@@ -0,0 +1,42 @@
+//! Runs a conformance test file of the Unicode Collation Algorithm and reports the lines out of
+//! order, with the collation elements and the sort keys of their strings.
+//!
+//! Usage: `cargo run --bin collate-rs-conformance -- [--json] <CollationTest_SHIFTED.txt>`
+//!
+//! The variable weighting is the one of the name of the file: shifted if it contains `SHIFTED`,
+//! and non-ignorable otherwise. The report is printed as text, or as JSON with `--json`; the
+//! program fails if a line is out of order.
+
+use anyhow::{bail, Context, Result};
+use collate_rs::conformance::{conformance_collator, run_conformance_test};
+use collate_rs::definitions::variable_weighting::VariableWeighting;
+
+const USAGE: &str = "Usage: collate-rs-conformance [--json] <CollationTest_*.txt>";
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (json, path) = match args.as_slice() {
+        [flag, path] if flag == "--json" => (true, path),
+        [path] if !path.starts_with('-') => (false, path),
+        _ => bail!(USAGE),
+    };
+    let test_file =
+        std::fs::read_to_string(path).with_context(|| format!("Could not read {}.", path))?;
+    let variable_weighting = if path.contains("SHIFTED") {
+        VariableWeighting::Shifted
+    } else {
+        VariableWeighting::NonIgnorable
+    };
+    let collator = conformance_collator(variable_weighting);
+    let report = run_conformance_test(&collator, &test_file)
+        .with_context(|| format!("Could not parse {}.", path))?;
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        print!("{}", report);
+    }
+    if !report.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
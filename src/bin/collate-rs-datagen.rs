@@ -0,0 +1,26 @@
+//! Converts an allkeys.txt file into the binary table format of
+//! [`collate_rs::data_files::binary`].
+//!
+//! Usage: `cargo run --bin collate-rs-datagen -- <allkeys.txt> <output.bin>`
+
+use anyhow::{bail, Context, Result};
+use collate_rs::data_files::{binary::write_table, parse_allkeys};
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [input, output] = args.as_slice() else {
+        bail!("Usage: collate-rs-datagen <allkeys.txt> <output.bin>")
+    };
+    let allkeys =
+        std::fs::read_to_string(input).with_context(|| format!("Could not read {}.", input))?;
+    let table = parse_allkeys(&allkeys).with_context(|| format!("Could not parse {}.", input))?;
+    let bytes = write_table(&table)?;
+    std::fs::write(output, &bytes).with_context(|| format!("Could not write {}.", output))?;
+    println!(
+        "Wrote {} mappings ({} bytes) to {}.",
+        table.mappings().len(),
+        bytes.len(),
+        output
+    );
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Sorts the lines of the standard input in the order of the Unicode Collation Algorithm.
+//!
+//! Usage: `collate [OPTIONS] < input.txt`
+//!
+//! - `-l, --locale <ID>`: collate for a BCP 47 language tag, falling back to the closest bundled
+//!   locale (default `und`, the DUCET), e.g. `de-DE-u-co-phonebk-kn`;
+//! - `-t, --tailoring <FILE>`: apply the tailoring of FILE, in the allkeys.txt format, to the table
+//!   of the locale;
+//! - `-s, --strength <LEVEL>`: `primary`, `secondary`, `tertiary` (default), `quaternary`,
+//!   `quinary` or `all`;
+//! - `-a, --alternate <HANDLING>`: how to weight variable collation elements, `non-ignorable`
+//!   (default) or `shifted`;
+//! - `-k, --key`: print the hexadecimal sort key of each line before it, separated by a tab;
+//! - `-e, --external`: sort in sorted runs spilled to temporary files and merged, for inputs
+//!   larger than memory (see `collate_rs::bulk::external_sort`).
+
+use anyhow::{bail, Context, Result};
+use collate_rs::algorithm::{Collator, Strength};
+use collate_rs::bulk::{self, ExternalSortOptions};
+use collate_rs::definitions::variable_weighting::VariableWeighting;
+use collate_rs::tailoring::Tailoring;
+use std::io::{self, BufRead, BufWriter, Write};
+
+const USAGE: &str =
+    "Usage: collate [-l LOCALE] [-t TAILORING] [-s STRENGTH] [-a ALTERNATE] [-k] [-e]";
+
+#[derive(Debug, Default)]
+struct Args {
+    locale: Option<String>,
+    tailoring: Option<String>,
+    strength: Option<Strength>,
+    variable_weighting: Option<VariableWeighting>,
+    key: bool,
+    external: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("{} expects a value.\n{}", arg, USAGE))
+        };
+        match arg.as_str() {
+            "-l" | "--locale" => parsed.locale = Some(value()?),
+            "-t" | "--tailoring" => parsed.tailoring = Some(value()?),
+            "-s" | "--strength" => parsed.strength = Some(value()?.parse()?),
+            "-a" | "--alternate" => match value()?.as_str() {
+                "non-ignorable" => {
+                    parsed.variable_weighting = Some(VariableWeighting::NonIgnorable)
+                }
+                "shifted" => parsed.variable_weighting = Some(VariableWeighting::Shifted),
+                other => bail!("Unknown alternate handling {:?}.", other),
+            },
+            "-k" | "--key" => parsed.key = true,
+            "-e" | "--external" => parsed.external = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => bail!("Unknown argument {:?}.\n{}", arg, USAGE),
+        }
+    }
+    Ok(parsed)
+}
+
+fn collator(args: &Args) -> Result<Collator> {
+    let mut collator = Collator::for_locale(args.locale.as_deref().unwrap_or("und"))?;
+    let mut options = collator.options().clone();
+    if let Some(path) = &args.tailoring {
+        let entries =
+            std::fs::read_to_string(path).with_context(|| format!("Could not read {}.", path))?;
+        let tailoring =
+            Tailoring::parse(&entries).with_context(|| format!("Could not parse {}.", path))?;
+        collator = Collator::new(tailoring.apply(&collator.table()));
+    }
+    if let Some(strength) = args.strength {
+        options.strength = strength;
+    }
+    if let Some(variable_weighting) = args.variable_weighting {
+        options.variable_weighting = variable_weighting;
+    }
+    Ok(collator.clone_with_options(options))
+}
+
+fn write_line(out: &mut impl Write, key: Option<&[u8]>, line: &str) -> io::Result<()> {
+    if let Some(key) = key {
+        for byte in key {
+            write!(out, "{:02X}", byte)?;
+        }
+        write!(out, "\t")?;
+    }
+    writeln!(out, "{}", line)
+}
+
+fn main() -> Result<()> {
+    let args = parse_args(std::env::args().skip(1))?;
+    let collator = collator(&args)?;
+    let mut out = BufWriter::new(io::stdout().lock());
+    if args.external {
+        let lines = io::stdin().lock().lines();
+        bulk::external_sort_with(
+            &collator,
+            lines,
+            &ExternalSortOptions::default(),
+            |key, line| write_line(&mut out, args.key.then_some(key), line),
+        )?;
+    } else {
+        let mut lines = io::stdin()
+            .lock()
+            .lines()
+            .collect::<io::Result<Vec<String>>>()?;
+        collator.sort_slice(&mut lines);
+        for line in &lines {
+            let key = args.key.then(|| collator.sort_key(line));
+            write_line(&mut out, key.as_ref().map(|key| key.as_bytes()), line)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
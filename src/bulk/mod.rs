@@ -0,0 +1,258 @@
+use crate::algorithm::Collator;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod radix;
+
+pub use radix::{radix_sort_by_cached_key, radix_sort_keys, radix_sort_slice};
+
+/// Distinguishes the runs of the sorts of a process, which may run concurrently.
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Options of [`external_sort`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalSortOptions {
+    /// The approximate number of bytes of lines and sort keys held in memory: once the lines read
+    /// exceed it, they are sorted and spilled to a temporary file, a run, to be merged with the
+    /// others. The default is 64 MiB.
+    pub max_run_bytes: usize,
+    /// The directory of the runs, by default [`std::env::temp_dir`].
+    pub temp_dir: Option<PathBuf>,
+}
+
+impl Default for ExternalSortOptions {
+    fn default() -> Self {
+        Self {
+            max_run_bytes: 64 << 20,
+            temp_dir: None,
+        }
+    }
+}
+
+/// Sorts the lines of `input`, stably, in the order of [`Collator::compare`], and writes them to
+/// `output`, each followed by a `\n`, holding at most about
+/// [`ExternalSortOptions::max_run_bytes`] of them in memory, e.g. to sort a corpus of several
+/// gigabytes.
+///
+/// The lines are read as by [`BufRead::lines`], so ending with `\n` or `\r\n`. Each run of lines
+/// fitting in memory is sorted by the cached sort keys of its lines, as by [`radix_sort_slice`],
+/// and written, with the keys, to a temporary file; the runs are then merged by their keys, the
+/// equal lines in the order of their runs, and their files removed.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::bulk::{self, ExternalSortOptions};
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// let options = ExternalSortOptions {
+///     max_run_bytes: 16,
+///     ..Default::default()
+/// };
+/// let mut output = vec![];
+/// bulk::external_sort(&collator, "zoë\nÉmile\nadèle\nemile\n".as_bytes(), &mut output, &options)
+///     .unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "adèle\nemile\nÉmile\nzoë\n");
+/// ```
+pub fn external_sort<R: BufRead, W: Write>(
+    collator: &Collator,
+    input: R,
+    mut output: W,
+    options: &ExternalSortOptions,
+) -> io::Result<()> {
+    external_sort_with(collator, input.lines(), options, |_, line| {
+        writeln!(output, "{}", line)
+    })?;
+    output.flush()
+}
+
+/// Like [`external_sort`], calling `f` with the sort key and the string of each line of `lines`,
+/// in order, e.g. to print the sort keys along with the lines.
+pub fn external_sort_with<I, F>(
+    collator: &Collator,
+    lines: I,
+    options: &ExternalSortOptions,
+    mut f: F,
+) -> io::Result<()>
+where
+    I: IntoIterator<Item = io::Result<String>>,
+    F: FnMut(&[u8], &str) -> io::Result<()>,
+{
+    let dir = options.temp_dir.clone().unwrap_or_else(std::env::temp_dir);
+    let mut runs = vec![];
+    let mut lines_in_memory: Vec<(Vec<u8>, String)> = vec![];
+    let mut bytes_in_memory = 0;
+    for line in lines {
+        let line = line?;
+        let key = collator.sort_key(&line).into_bytes();
+        bytes_in_memory += key.len() + line.len();
+        lines_in_memory.push((key, line));
+        if bytes_in_memory > options.max_run_bytes {
+            runs.push(Run::spill(&dir, &mut lines_in_memory)?);
+            bytes_in_memory = 0;
+        }
+    }
+    if runs.is_empty() {
+        sort_by_keys(&mut lines_in_memory);
+        for (key, line) in &lines_in_memory {
+            f(key, line)?;
+        }
+        return Ok(());
+    }
+    if !lines_in_memory.is_empty() {
+        runs.push(Run::spill(&dir, &mut lines_in_memory)?);
+    }
+    merge(&runs, f)
+}
+
+/// Merges the sorted runs, the equal lines in the order of the runs.
+fn merge<F>(runs: &[Run], mut f: F) -> io::Result<()>
+where
+    F: FnMut(&[u8], &str) -> io::Result<()>,
+{
+    let mut readers = runs
+        .iter()
+        .map(|run| File::open(&run.path).map(BufReader::new))
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut heap = BinaryHeap::with_capacity(readers.len());
+    for (i, reader) in readers.iter_mut().enumerate() {
+        if let Some((key, line)) = read_record(reader)? {
+            heap.push(Reverse((key, i, line)));
+        }
+    }
+    while let Some(Reverse((key, i, line))) = heap.pop() {
+        f(&key, &line)?;
+        if let Some((key, line)) = read_record(&mut readers[i])? {
+            heap.push(Reverse((key, i, line)));
+        }
+    }
+    Ok(())
+}
+
+/// Sorts the lines, stably, by their sort keys, with a radix sort.
+fn sort_by_keys(lines: &mut [(Vec<u8>, String)]) {
+    let order = radix::sort_order(lines.len(), |i| &lines[i].0);
+    radix::permute(lines, order);
+}
+
+/// A sorted run of lines in a temporary file, removed when the run is dropped.
+struct Run {
+    path: PathBuf,
+}
+
+impl Run {
+    /// Sorts `lines`, stably, by their sort keys and writes them to a new run, emptying `lines`.
+    fn spill(dir: &Path, lines: &mut Vec<(Vec<u8>, String)>) -> io::Result<Self> {
+        let path = dir.join(format!(
+            "collate-rs-run-{}-{}",
+            std::process::id(),
+            RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let run = Self { path };
+        sort_by_keys(lines);
+        let mut writer = BufWriter::new(file);
+        for (key, line) in lines.drain(..) {
+            write_bytes(&mut writer, &key)?;
+            write_bytes(&mut writer, line.as_bytes())?;
+        }
+        writer.flush()?;
+        Ok(run)
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Writes the bytes of a record, after their length in 4 little-endian bytes.
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "A line is too long to sort."))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+/// Reads the bytes written by [`write_bytes`], or `None` at the end of the run.
+fn read_bytes(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    if reader.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(bytes))
+}
+
+/// Reads the sort key and the line of the next record of a run.
+fn read_record(reader: &mut impl BufRead) -> io::Result<Option<(Vec<u8>, String)>> {
+    let Some(key) = read_bytes(reader)? else {
+        return Ok(None);
+    };
+    let line = read_bytes(reader)?.ok_or(io::ErrorKind::UnexpectedEof)?;
+    let line =
+        String::from_utf8(line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Some((key, line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_sort() {
+        let dir = std::env::temp_dir().join(format!("collate-rs-bulk-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let collator = Collator::try_new("und").unwrap();
+        let lines: Vec<String> = (0..200)
+            .map(|i| {
+                format!(
+                    "{}{}",
+                    ["ch", "b", "A", "ca", "a\u{301}", "\u{E1}", "a"][i % 7],
+                    i % 3
+                )
+            })
+            .collect();
+        let mut expected = lines.clone();
+        collator.sort_slice(&mut expected);
+        for max_run_bytes in [0, 100, usize::MAX] {
+            let options = ExternalSortOptions {
+                max_run_bytes,
+                temp_dir: Some(dir.clone()),
+            };
+            let mut sorted = vec![];
+            external_sort_with(
+                &collator,
+                lines.iter().cloned().map(Ok),
+                &options,
+                |key, line| {
+                    assert_eq!(key, collator.sort_key(line).as_bytes());
+                    sorted.push(line.to_string());
+                    Ok(())
+                },
+            )
+            .unwrap();
+            // Stable: the equal "a\u{301}" and "\u{E1}" keep their order, as with sort_slice.
+            assert_eq!(sorted, expected);
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        }
+
+        let mut output = vec![];
+        let options = ExternalSortOptions {
+            max_run_bytes: 0,
+            temp_dir: Some(dir.clone()),
+        };
+        external_sort(&collator, "b\r\na\n\nA".as_bytes(), &mut output, &options).unwrap();
+        assert_eq!(output, b"\na\nA\nb\n");
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
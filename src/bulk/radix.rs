@@ -0,0 +1,216 @@
+use crate::algorithm::Collator;
+
+/// Slices of sort keys up to this length, and the buckets of the radix sort up to it, are sorted
+/// by comparing their keys, faster than distributing so few keys in 257 buckets (see the
+/// `radix_sort` group of `benches/collation.rs`).
+const RADIX_SORT_MIN_LEN: usize = 256;
+
+/// Sorts a slice of strings, stably, in the order of [`Collator::compare`], as
+/// [`Collator::sort_slice`], with a radix sort of the bytes of their sort keys.
+///
+/// The sort keys being compared byte by byte, they are distributed by their first byte, then the
+/// keys of each byte by their second byte, and so on until the keys of a bucket are few enough to
+/// be sorted by comparing them. This avoids comparing the long common prefixes of the sort keys,
+/// e.g. their header and the primary weights of the first letters of the words, over and over.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::bulk;
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// let mut names = vec!["Zoé", "adèle", "Émile", "emile"];
+/// bulk::radix_sort_slice(&collator, &mut names);
+/// assert_eq!(names, ["adèle", "emile", "Émile", "Zoé"]);
+/// ```
+pub fn radix_sort_slice<S: AsRef<str>>(collator: &Collator, slice: &mut [S]) {
+    radix_sort_by_cached_key(collator, slice, |s| s.as_ref())
+}
+
+/// Sorts a slice, stably, by the collation order of the string extracted from each element by
+/// `f`, as [`Collator::sort_by_cached_key`], with a radix sort of the bytes of their sort keys
+/// (see [`radix_sort_slice`]).
+///
+/// The sort keys are appended to a single buffer rather than allocated one by one.
+pub fn radix_sort_by_cached_key<T, F>(collator: &Collator, slice: &mut [T], f: F)
+where
+    F: Fn(&T) -> &str,
+{
+    let mut keys = vec![];
+    let mut offsets = Vec::with_capacity(slice.len() + 1);
+    offsets.push(0);
+    for t in slice.iter() {
+        collator.append_sort_key(f(t), &mut keys);
+        offsets.push(keys.len());
+    }
+    let order = sort_order(slice.len(), |i| &keys[offsets[i]..offsets[i + 1]]);
+    permute(slice, order);
+}
+
+/// Sorts byte strings, stably, with the radix sort of [`radix_sort_slice`], e.g. the bytes of
+/// stored sort keys.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::bulk;
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// let mut keys: Vec<Vec<u8>> = ["zoë", "Émile", "adèle"]
+///     .iter()
+///     .map(|s| collator.sort_key(s).into_bytes())
+///     .collect();
+/// bulk::radix_sort_keys(&mut keys);
+/// assert_eq!(keys[0], collator.sort_key("adèle").into_bytes());
+/// ```
+pub fn radix_sort_keys<K: AsRef<[u8]>>(keys: &mut [K]) {
+    if keys.len() <= RADIX_SORT_MIN_LEN {
+        keys.sort_by(|a, b| a.as_ref().cmp(b.as_ref()));
+    } else {
+        let order = sort_order(keys.len(), |i| keys[i].as_ref());
+        permute(keys, order);
+    }
+}
+
+/// The permutation sorting, stably, the `len` byte strings `key(0)`, `key(1)`…: the index of the
+/// first, then of the second…
+pub(crate) fn sort_order<'k>(len: usize, key: impl Fn(usize) -> &'k [u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+    if len <= RADIX_SORT_MIN_LEN {
+        order.sort_by(|&a, &b| key(a).cmp(key(b)));
+    } else {
+        let mut buffer = RadixBuffer {
+            order: vec![0; len],
+            buckets: vec![0; len],
+        };
+        radix_sort(&mut order, &mut buffer.as_mut(), 0, &key);
+    }
+    order
+}
+
+/// The buffers of a radix sort of `order`, as long as it: the indices being distributed and
+/// their buckets.
+struct RadixBuffer<O, B> {
+    order: O,
+    buckets: B,
+}
+
+impl RadixBuffer<Vec<usize>, Vec<u16>> {
+    fn as_mut(&mut self) -> RadixBuffer<&mut [usize], &mut [u16]> {
+        RadixBuffer {
+            order: &mut self.order,
+            buckets: &mut self.buckets,
+        }
+    }
+}
+
+/// Sorts, stably, the indices of `order` by the bytes of their keys from `depth`, the keys
+/// sharing their first `depth` bytes.
+fn radix_sort<'k>(
+    order: &mut [usize],
+    buffer: &mut RadixBuffer<&mut [usize], &mut [u16]>,
+    mut depth: usize,
+    key: &impl Fn(usize) -> &'k [u8],
+) {
+    if order.len() <= RADIX_SORT_MIN_LEN {
+        order.sort_by(|&a, &b| key(a)[depth..].cmp(&key(b)[depth..]));
+        return;
+    }
+    let mut starts = loop {
+        // The keys ending at `depth` are in the first bucket, before the ones of the byte 00.
+        // The bucket of each key is looked up once, the keys being scattered in memory.
+        let mut starts = [0; 258];
+        for (&i, bucket) in order.iter().zip(buffer.buckets.iter_mut()) {
+            *bucket = key(i).get(depth).map_or(0, |&byte| u16::from(byte) + 1);
+            starts[usize::from(*bucket) + 1] += 1;
+        }
+        // The keys sharing their next byte are distributed from the byte after it.
+        if starts[1] == 0 && starts.contains(&order.len()) {
+            depth += 1;
+        } else {
+            break starts;
+        }
+    };
+    for b in 1..starts.len() {
+        starts[b] += starts[b - 1];
+    }
+    let mut next = starts;
+    for (&i, &bucket) in order.iter().zip(buffer.buckets.iter()) {
+        let b = usize::from(bucket);
+        buffer.order[next[b]] = i;
+        next[b] += 1;
+    }
+    order.copy_from_slice(buffer.order);
+    // The keys of the first bucket are equal and stay in their order.
+    for b in 1..257 {
+        let bucket = starts[b]..starts[b + 1];
+        if bucket.len() > 1 {
+            radix_sort(
+                &mut order[bucket.clone()],
+                &mut RadixBuffer {
+                    order: &mut buffer.order[bucket.clone()],
+                    buckets: &mut buffer.buckets[bucket],
+                },
+                depth + 1,
+                key,
+            );
+        }
+    }
+}
+
+/// Reorders `slice` so that its `k`-th element is the `order[k]`-th one before.
+pub(crate) fn permute<T>(slice: &mut [T], mut order: Vec<usize>) {
+    for start in 0..slice.len() {
+        // Follows the cycle of the permutation from `start`, marking its positions as done.
+        let mut i = start;
+        while order[i] != start {
+            let next = order[i];
+            slice.swap(i, next);
+            order[i] = i;
+            i = next;
+        }
+        order[i] = i;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_radix_sort() {
+        let collator = Collator::try_new("und").unwrap();
+        for len in [0, 1, 2, RADIX_SORT_MIN_LEN, 1000] {
+            let strings: Vec<String> = (0..len)
+                .map(|i| {
+                    let word = ["ch", "b", "A", "ca", "a\u{301}", "\u{E1}", "a", ""][i % 8];
+                    word.repeat(i % 5)
+                })
+                .collect();
+            let mut expected = strings.clone();
+            collator.sort_slice(&mut expected);
+            // Stable: the equal "a\u{301}" and "\u{E1}" keep their order, as with sort_slice.
+            let mut sorted = strings.clone();
+            radix_sort_slice(&collator, &mut sorted);
+            assert_eq!(sorted, expected);
+
+            let mut records: Vec<(usize, &str)> =
+                strings.iter().map(String::as_str).enumerate().collect();
+            radix_sort_by_cached_key(&collator, &mut records, |(_, s)| s);
+            let sorted: Vec<&str> = records.iter().map(|(_, s)| *s).collect();
+            assert_eq!(sorted, expected);
+        }
+
+        let mut keys: Vec<Vec<u8>> = (0..1000u32)
+            .map(|i| (i * 7919 % 1000).to_be_bytes()[2..].to_vec())
+            .collect();
+        let mut expected = keys.clone();
+        expected.sort();
+        radix_sort_keys(&mut keys);
+        assert_eq!(keys, expected);
+
+        let keys: [&[u8]; 5] = [b"ba", b"", b"b", b"ab", b"b"];
+        assert_eq!(sort_order(keys.len(), |i| keys[i]), [1, 3, 2, 4, 0]);
+        let mut slice = ['a', 'b', 'c', 'd', 'e'];
+        permute(&mut slice, vec![1, 3, 2, 4, 0]);
+        assert_eq!(slice, ['b', 'd', 'c', 'e', 'a']);
+    }
+}
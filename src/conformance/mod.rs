@@ -0,0 +1,288 @@
+use crate::algorithm::{Collator, CollatorOptions, Strength};
+use crate::definitions::variable_weighting::VariableWeighting;
+use crate::error::{CollateError, Result};
+use std::cmp::Ordering;
+use std::fmt::{self, Write};
+
+/// The collator of a test file: the root collation with the variable weighting of the file, at the
+/// tertiary strength for `NON_IGNORABLE` and at the quaternary strength for `SHIFTED`.
+pub fn conformance_collator(variable_weighting: VariableWeighting) -> Collator {
+    let strength = match variable_weighting {
+        VariableWeighting::Shifted => Strength::Quaternary,
+        _ => Strength::Tertiary,
+    };
+    Collator::try_new("und")
+        .expect("The root locale is always available.")
+        .clone_with_options(CollatorOptions {
+            strength,
+            variable_weighting,
+            ..Default::default()
+        })
+}
+
+/// A string of a line of a test file, with what `collator` computes for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestString {
+    /// The line of the string in the file, counted from 1.
+    pub line: usize,
+    /// The code points of the string, in the hexadecimal notation of the file.
+    pub code_points: String,
+    /// The comment of the line, after its `#`.
+    pub comment: String,
+    /// The collation element array of the string, in the notation of UTS10.
+    pub collation_elements: String,
+    /// The sort key of the string, its weights by level (see
+    /// [`SortKey`](crate::algorithm::SortKey)).
+    pub sort_key: String,
+}
+
+/// A line whose string sorts before the one of the line before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceFailure {
+    pub previous: TestString,
+    pub current: TestString,
+}
+
+/// The report of [`run_conformance_test`], printed as text or [as JSON](ConformanceReport::to_json).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConformanceReport {
+    /// The number of strings compared with the one before them.
+    pub tested: usize,
+    /// The lines whose code points are not all Unicode scalar values, e.g. lone surrogates, which
+    /// `str`s cannot hold.
+    pub skipped: Vec<usize>,
+    /// The lines out of order.
+    pub failures: Vec<ConformanceFailure>,
+}
+
+impl ConformanceReport {
+    /// Whether every line is in order.
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// The report in JSON, e.g. for the tools reporting the tests of continuous integration:
+    ///
+    /// ```json
+    /// {"tested": 2, "skipped": [], "failures": [{"previous": {"line": 1, "codePoints": "0062",
+    ///   "comment": "", "collationElements": "[.1FBC.0020.0002]", "sortKey": "[1FBC | 0020 | 0002]"},
+    ///   "current": {...}}]}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        let skipped: Vec<String> = self.skipped.iter().map(usize::to_string).collect();
+        write!(
+            json,
+            "{{\"tested\": {}, \"skipped\": [{}], \"failures\": [",
+            self.tested,
+            skipped.join(", ")
+        )
+        .unwrap();
+        for (i, failure) in self.failures.iter().enumerate() {
+            if i > 0 {
+                json.push_str(", ");
+            }
+            json.push_str("{\"previous\": ");
+            failure.previous.write_json(&mut json);
+            json.push_str(", \"current\": ");
+            failure.current.write_json(&mut json);
+            json.push('}');
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+impl TestString {
+    fn write_json(&self, json: &mut String) {
+        write!(
+            json,
+            "{{\"line\": {}, \"codePoints\": {}, \"comment\": {}, \"collationElements\": {}, \
+             \"sortKey\": {}}}",
+            self.line,
+            json_string(&self.code_points),
+            json_string(&self.comment),
+            json_string(&self.collation_elements),
+            json_string(&self.sort_key)
+        )
+        .unwrap();
+    }
+}
+
+/// `s` as a JSON string, quoted and escaped.
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c < ' ' => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// The report as text: a summary line, then the two strings of each failure.
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} lines tested, {} failed, {} skipped.",
+            self.tested,
+            self.failures.len(),
+            self.skipped.len()
+        )?;
+        for failure in &self.failures {
+            writeln!(
+                f,
+                "\nLine {} sorts before line {}:",
+                failure.current.line, failure.previous.line
+            )?;
+            for s in [&failure.previous, &failure.current] {
+                writeln!(f, "  {:>6}: {} # {}", s.line, s.code_points, s.comment)?;
+                writeln!(f, "          CEs: {}", s.collation_elements)?;
+                writeln!(f, "          key: {}", s.sort_key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `collator` orders the strings of a test file in the order of its lines, as
+/// [`Collator::compare`] and then by code points, as the identical level the files are sorted by.
+///
+/// Each line holds the code points of a string in hexadecimal, then a `;` and a comment after a
+/// `#`. The blank lines and the lines of comments only, e.g. the header, are skipped.
+///
+/// Fails if a line is ill-formed; the lines out of order are listed in the report, with the
+/// collation elements and the sort keys of their strings.
+///
+/// ```
+/// use collate_rs::conformance::{conformance_collator, run_conformance_test};
+/// use collate_rs::definitions::variable_weighting::VariableWeighting;
+///
+/// let collator = conformance_collator(VariableWeighting::NonIgnorable);
+/// let report = run_conformance_test(&collator, "0061; # a\n0062; # b\n0041; # A\n").unwrap();
+/// assert_eq!(report.tested, 2);
+/// assert_eq!(report.failures.len(), 1);
+/// assert_eq!(report.failures[0].current.line, 3);
+/// assert!(report.to_string().starts_with("2 lines tested, 1 failed, 0 skipped."));
+/// assert!(report.to_json().contains("\"codePoints\": \"0041\""));
+/// ```
+pub fn run_conformance_test(collator: &Collator, test_file: &str) -> Result<ConformanceReport> {
+    let mut report = ConformanceReport::default();
+    let mut previous: Option<(usize, String)> = None;
+    for (i, line) in test_file.lines().enumerate() {
+        let (code_points, _) = line.split_once('#').unwrap_or((line, ""));
+        let code_points = code_points.trim().trim_end_matches(';').trim();
+        if code_points.is_empty() {
+            continue;
+        }
+        let Some(s) = parse_code_points(code_points).map_err(|e| e.at_line(i + 1))? else {
+            report.skipped.push(i + 1);
+            continue;
+        };
+        if let Some((previous_line, previous)) = &previous {
+            report.tested += 1;
+            let ordering = collator
+                .compare(previous, &s)
+                .then_with(|| previous.chars().cmp(s.chars()));
+            if ordering == Ordering::Greater {
+                report.failures.push(ConformanceFailure {
+                    previous: test_string(collator, test_file, *previous_line, previous),
+                    current: test_string(collator, test_file, i + 1, &s),
+                });
+            }
+        }
+        previous = Some((i + 1, s));
+    }
+    Ok(report)
+}
+
+/// The string of hexadecimal code points, or `None` if one of them is not a scalar value.
+fn parse_code_points(code_points: &str) -> Result<Option<String>> {
+    let mut s = String::new();
+    for cp in code_points.split_whitespace() {
+        let cp = u32::from_str_radix(cp, 16)
+            .map_err(|_| CollateError::parse(format!("Invalid code point {:?}.", cp)))?;
+        match char::from_u32(cp) {
+            Some(c) => s.push(c),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(s))
+}
+
+fn test_string(collator: &Collator, test_file: &str, line: usize, s: &str) -> TestString {
+    let text = test_file.lines().nth(line - 1).unwrap_or_default();
+    let (code_points, comment) = text.split_once('#').unwrap_or((text, ""));
+    TestString {
+        line,
+        code_points: code_points.trim().trim_end_matches(';').trim().to_string(),
+        comment: comment.trim().to_string(),
+        collation_elements: collator
+            .collation_element_array(s)
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        sort_key: collator.sort_key(s).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conformance_report() {
+        let test_file = "# CollationTest_SHIFTED.txt\n\
+                         \n\
+                         0020 0021; # (' !') SPACE, EXCLAMATION MARK\n\
+                         0021;\t# ('!') EXCLAMATION MARK\n\
+                         D800 0061; # lone surrogate\n\
+                         0061 \"; # (\"a\\\")\n\
+                         0061 0301; # accent\n\
+                         00E1; # same, later code point\n\
+                         0061; # out of order\n";
+        let shifted = conformance_collator(VariableWeighting::Shifted);
+        assert!(run_conformance_test(&shifted, test_file).is_err());
+
+        let test_file = test_file.replace(" \"", "");
+        let report = run_conformance_test(&shifted, &test_file).unwrap();
+        assert_eq!(report.tested, 5);
+        assert_eq!(report.skipped, [5]);
+        assert_eq!(report.failures.len(), 1);
+        let failure = &report.failures[0];
+        assert_eq!((failure.previous.line, failure.current.line), (8, 9));
+        assert_eq!(failure.previous.code_points, "00E1");
+        assert_eq!(failure.current.comment, "out of order");
+        assert_eq!(
+            failure.current.collation_elements,
+            shifted.collation_element_array("a")[0].to_string()
+        );
+        assert_eq!(failure.current.sort_key, shifted.sort_key("a").to_string());
+        assert!(!report.passed());
+        assert!(report
+            .to_string()
+            .contains("\nLine 9 sorts before line 8:\n       8: 00E1 # same, later code point\n"));
+        assert!(report.to_json().starts_with(
+            "{\"tested\": 5, \"skipped\": [5], \"failures\": [{\"previous\": {\"line\": 8, "
+        ));
+        assert_eq!(json_string("\"a\\\"\n"), "\"\\\"a\\\\\\\"\\n\"");
+
+        // Non-ignorable, the hyphen sorts before the letters; shifted, it is ignored but at the
+        // quaternary level.
+        let test_file = "0061 002D 0063;\n0061 0062;\n";
+        let non_ignorable = conformance_collator(VariableWeighting::NonIgnorable);
+        assert!(run_conformance_test(&non_ignorable, test_file)
+            .unwrap()
+            .passed());
+        let report = run_conformance_test(&shifted, test_file).unwrap();
+        assert_eq!(report.failures.len(), 1);
+    }
+}
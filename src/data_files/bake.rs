@@ -0,0 +1,115 @@
+use crate::data_files::binary::write_table;
+use crate::data_files::parse_allkeys;
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::error::{CollateError, Result};
+use crate::tailoring::Tailoring;
+use std::path::{Path, PathBuf};
+
+/// Converts the allkeys.txt file `input` into the binary table `$OUT_DIR/<name>.bin`, for the
+/// build script of a program which bakes it with
+/// [`include_collation_data!`](crate::include_collation_data), so that the program
+/// neither parses nor reads the table at run time. Returns the path of the binary table.
+///
+/// In `build.rs`, with `collate-rs` among the `[build-dependencies]`:
+///
+/// ```ignore
+/// fn main() {
+///     collate_rs::data_files::bake::bake_table("custom", "data/custom-allkeys.txt").unwrap();
+/// }
+/// ```
+///
+/// and in the program:
+///
+/// ```ignore
+/// use collate_rs::data_files::binary::BinaryTable;
+///
+/// static CUSTOM: BinaryTable<'static> = collate_rs::include_collation_data!("custom");
+/// ```
+pub fn bake_table(name: &str, input: impl AsRef<Path>) -> Result<PathBuf> {
+    let table = parse_allkeys(&read(input.as_ref())?)?;
+    write(&out_dir()?, name, &table)
+}
+
+/// Converts the tailoring `input` of `base` (see [`Tailoring::parse`]), e.g. of the
+/// [bundled DUCET](crate::ducet::table), into the binary table of the tailored table, as
+/// [`bake_table`] does.
+pub fn bake_tailoring(
+    name: &str,
+    input: impl AsRef<Path>,
+    base: &CollationElementTable,
+) -> Result<PathBuf> {
+    let tailoring = Tailoring::parse(&read(input.as_ref())?)?;
+    write(&out_dir()?, name, &tailoring.apply(base))
+}
+
+/// Reads `path`, rebuilding when it changes.
+fn read(path: &Path) -> Result<String> {
+    println!("cargo:rerun-if-changed={}", path.display());
+    std::fs::read_to_string(path).map_err(|source| CollateError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn out_dir() -> Result<PathBuf> {
+    std::env::var_os("OUT_DIR")
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            CollateError::InvalidInput(
+                "OUT_DIR is not set: the collation data is baked by build scripts.".to_string(),
+            )
+        })
+}
+
+/// Writes the binary table of `table` to `<dir>/<name>.bin`.
+fn write(dir: &Path, name: &str, table: &CollationElementTable) -> Result<PathBuf> {
+    let path = dir.join(format!("{}.bin", name));
+    std::fs::write(&path, write_table(table)?).map_err(|source| CollateError::Io {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+/// Includes the binary table `$OUT_DIR/<name>.bin` written by
+/// [`bake_table`](crate::data_files::bake::bake_table) or
+/// [`bake_tailoring`](crate::data_files::bake::bake_tailoring) in the build script, as a constant
+/// [`BinaryTable`](crate::data_files::binary::BinaryTable) validated at compile time (see
+/// [`BinaryTable::new_const`](crate::data_files::binary::BinaryTable::new_const)).
+#[macro_export]
+macro_rules! include_collation_data {
+    ($name:literal) => {{
+        #[allow(long_running_const_eval)]
+        const TABLE: $crate::data_files::binary::BinaryTable<'static> =
+            $crate::data_files::binary::BinaryTable::new_const(include_bytes!(concat!(
+                env!("OUT_DIR"),
+                "/",
+                $name,
+                ".bin"
+            )));
+        TABLE
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_files::binary::BinaryTable;
+
+    #[test]
+    fn test_bake() {
+        let dir = std::env::temp_dir().join(format!("collate-rs-bake-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let base = parse_allkeys("0061 ; [.1C47.0020.0002]\n0062 ; [.1C60.0020.0002]\n").unwrap();
+        let tailoring = dir.join("tailoring.txt");
+        std::fs::write(&tailoring, "0062 ; [.1C46.0020.0002]\n").unwrap();
+        let tailored = Tailoring::parse(&read(&tailoring).unwrap())
+            .unwrap()
+            .apply(&base);
+        let path = write(&dir, "tailored", &tailored).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(BinaryTable::new_const(&bytes).to_table(), tailored);
+        assert!(read(&dir.join("missing.txt")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,664 @@
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::collation_wel::{CollationElement, CollationWeight, Weights};
+use crate::definitions::mappings::{CollationElementMapping, CollationElements};
+use crate::error::{CollateError, Result};
+use std::cmp::Ordering;
+
+/// Returns a [`CollateError::IllFormedTable`] with a formatted message.
+macro_rules! ill_formed {
+    ($($arg:tt)*) => {
+        return Err(CollateError::IllFormedTable(format!($($arg)*)))
+    };
+}
+
+/// Magic bytes at the start of every binary table.
+pub const MAGIC: [u8; 4] = *b"CLRS";
+
+/// Version of the binary table format.
+pub const FORMAT_VERSION: u32 = 2;
+
+const HEADER_LEN: usize = 24;
+const ENTRY_LEN: usize = 12;
+
+/// Serializes a [`CollationElementTable`] into the binary table format read by [`BinaryTable`].
+///
+/// All integers are little-endian. After a header made of the [`MAGIC`], the [`FORMAT_VERSION`],
+/// the lengths of the sections and the [variable top](CollationElementTable::variable_top) (`0`
+/// if none) as `u32`s, the sections are:
+///
+/// 1. entries, 12 bytes each, in the order of the table: the position of the first character
+///    (`u32`), the position of the first weight (`u32`), the number of characters (`u8`), the
+///    number of collation elements (`u8`), the number of weights in each collation element (`u8`)
+///    and a reserved byte;
+/// 2. the positions of the entries sorted by their characters (`u32`s), searched by
+///    [`BinaryTable::get`];
+/// 3. the characters of all entries (`u32`s);
+/// 4. the weights of all collation elements (`u16`s).
+///
+/// The collation elements whose primary weight is at most the variable top are read as variable,
+/// so that writing fails if some of them are not variable, as the table would not be read back
+/// unchanged. It also fails if a weight does not fit in 16 bits, if the collation elements of a
+/// mapping do not all have the same number of levels, if a mapping has more than 255
+/// characters or collation elements, or if it is a contextual mapping.
+pub fn write_table(table: &CollationElementTable) -> Result<Vec<u8>> {
+    let mappings = table.mappings();
+    let variable_top = table.variable_top().map_or(0, |w| w.0);
+    let mut entries = Vec::with_capacity(mappings.len() * ENTRY_LEN);
+    let mut chars = vec![];
+    let mut weights = vec![];
+    for mapping in mappings {
+        let (characters, elements) = (mapping.characters(), mapping.collation_elements());
+        if !mapping.prefix().is_empty() {
+            ill_formed!(
+                "The contextual mapping of {:?} has no binary representation.",
+                characters
+            )
+        }
+        let levels = elements.first().map(|ce| ce.0.len()).unwrap_or(0);
+        if elements.iter().any(|ce| ce.0.len() != levels) {
+            ill_formed!(
+                "The collation elements of {:?} have different numbers of levels.",
+                characters
+            )
+        }
+        let (Ok(char_len), Ok(ce_len), Ok(levels)) = (
+            u8::try_from(characters.len()),
+            u8::try_from(elements.len()),
+            u8::try_from(levels),
+        ) else {
+            ill_formed!("The mapping of {:?} is too long.", characters)
+        };
+        if let Some(ce) = elements
+            .iter()
+            .find(|ce| is_variable(ce.0.first(), variable_top) != ce.is_variable())
+        {
+            ill_formed!(
+                "The collation element {} of {:?} is not ordered with the variable ones.",
+                ce,
+                characters
+            )
+        }
+        entries.extend_from_slice(&(chars.len() as u32 / 4).to_le_bytes());
+        entries.extend_from_slice(&(weights.len() as u32 / 2).to_le_bytes());
+        entries.extend_from_slice(&[char_len, ce_len, levels, 0]);
+        for c in characters {
+            chars.extend_from_slice(&(*c as u32).to_le_bytes());
+        }
+        for w in elements.iter().flat_map(|ce| ce.0.iter()) {
+            let w = u16::try_from(w.0).map_err(|_| {
+                CollateError::IllFormedTable(format!("Weight {:04X} does not fit in 16 bits.", w.0))
+            })?;
+            weights.extend_from_slice(&w.to_le_bytes());
+        }
+    }
+
+    let mut sorted: Vec<usize> = (0..mappings.len()).collect();
+    sorted.sort_by(|&a, &b| mappings[a].characters().cmp(mappings[b].characters()));
+    if let Some(w) = sorted
+        .windows(2)
+        .find(|w| mappings[w[0]].characters() == mappings[w[1]].characters())
+    {
+        ill_formed!("Duplicate mapping for {:?}.", mappings[w[0]].characters())
+    }
+
+    let mut bytes = Vec::with_capacity(
+        HEADER_LEN + entries.len() + sorted.len() * 4 + chars.len() + weights.len(),
+    );
+    bytes.extend_from_slice(&MAGIC);
+    for n in [
+        FORMAT_VERSION,
+        mappings.len() as u32,
+        chars.len() as u32 / 4,
+        weights.len() as u32 / 2,
+        variable_top,
+    ] {
+        bytes.extend_from_slice(&n.to_le_bytes());
+    }
+    bytes.extend_from_slice(&entries);
+    for i in sorted {
+        bytes.extend_from_slice(&(i as u32).to_le_bytes());
+    }
+    bytes.extend_from_slice(&chars);
+    bytes.extend_from_slice(&weights);
+    Ok(bytes)
+}
+
+/// Whether a collation element of primary weight `primary` is variable, given the variable top.
+fn is_variable(primary: Option<&CollationWeight>, variable_top: u32) -> bool {
+    primary.is_some_and(|w| w.0 != 0 && w.0 <= variable_top)
+}
+
+/// The number of levels of the collation elements a [`BinaryElement`] holds.
+pub const BINARY_ELEMENT_LEVELS: usize = 4;
+
+/// A collation element of a [`BinaryTable`], read without allocating (see
+/// [`BinaryTable::element`]), of at most [`BINARY_ELEMENT_LEVELS`] levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryElement {
+    weights: [u16; BINARY_ELEMENT_LEVELS],
+    levels: u8,
+    variable: bool,
+}
+
+impl BinaryElement {
+    /// The weight at level `n` (counted from 1), ignorable past the last level.
+    pub const fn weight(&self, n: usize) -> u16 {
+        if n >= 1 && n <= self.levels as usize {
+            self.weights[n - 1]
+        } else {
+            0
+        }
+    }
+
+    /// The number of levels of the collation element.
+    pub const fn len_levels(&self) -> usize {
+        self.levels as usize
+    }
+
+    pub const fn is_variable(&self) -> bool {
+        self.variable
+    }
+
+    pub fn to_collation_element(&self) -> CollationElement {
+        let weights: Weights = self.weights[..self.len_levels()]
+            .iter()
+            .map(|&w| CollationWeight::from(w as u32))
+            .collect();
+        CollationElement::with_weights(weights, self.variable)
+            .expect("Variable weights are primary.")
+    }
+}
+
+const fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Why bytes are not a binary table, as found by [`BinaryTable::read`].
+#[derive(Debug, Clone, Copy)]
+enum Malformation {
+    NotATable,
+    Version(u32),
+    Length,
+    EntryBounds(usize),
+    CodePoint(usize),
+    SortedBounds(usize),
+    Unsorted,
+}
+
+impl Malformation {
+    fn into_error(self) -> CollateError {
+        CollateError::IllFormedTable(match self {
+            Self::NotATable => "Not a binary collation element table.".to_string(),
+            Self::Version(version) => format!(
+                "Unsupported binary table format version {} (expected {}).",
+                version, FORMAT_VERSION
+            ),
+            Self::Length => "Truncated or oversized binary table.".to_string(),
+            Self::EntryBounds(i) => format!("Entry {} is out of bounds.", i),
+            Self::CodePoint(i) => format!("Entry {} maps an invalid code point.", i),
+            Self::SortedBounds(i) => format!("Sorted position {} is out of bounds.", i),
+            Self::Unsorted => "Entries are not sorted.".to_string(),
+        })
+    }
+
+    /// Fails the evaluation of a constant, without the details that cannot be formatted there.
+    const fn panic(self) -> ! {
+        match self {
+            Self::NotATable => panic!("Not a binary collation element table."),
+            Self::Version(_) => panic!("Unsupported binary table format version."),
+            Self::Length => panic!("Truncated or oversized binary table."),
+            Self::EntryBounds(_) => panic!("An entry is out of bounds."),
+            Self::CodePoint(_) => panic!("An entry maps an invalid code point."),
+            Self::SortedBounds(_) => panic!("A sorted position is out of bounds."),
+            Self::Unsorted => panic!("Entries are not sorted."),
+        }
+    }
+}
+
+/// A collation element table in the binary format written by [`write_table`], read in place
+/// from borrowed bytes (e.g. `include_bytes!` or a memory-mapped file).
+///
+/// The bytes are validated once by [`BinaryTable::new`], or at compile time by
+/// [`BinaryTable::new_const`]; mappings are then decoded on demand without copying the table.
+///
+/// ## Const operations
+///
+/// Besides [`BinaryTable::new_const`], the lookups which do not decode whole mappings are `const
+/// fn`s, usable in constants and without allocating: [`BinaryTable::len`],
+/// [`BinaryTable::index_of`], [`BinaryTable::longest_match_index`],
+/// [`BinaryTable::element_count`], [`BinaryTable::element`] and [`BinaryTable::single_elements`],
+/// which builds static lookup arrays, e.g. of the ASCII characters. The operations returning
+/// [`CollationElementMapping`]s or a [`CollationElementTable`] allocate them and are not `const`.
+///
+/// ```
+/// use collate_rs::ducet;
+///
+/// let ducet = ducet::binary_table();
+/// let ascii: [_; 128] = ducet.single_elements('\0');
+/// let (a, b) = (ascii['a' as usize].unwrap(), ascii['b' as usize].unwrap());
+/// assert!(a.weight(1) < b.weight(1));
+/// assert_eq!(ascii['a' as usize], ducet.element(ducet.index_of(&['a']).unwrap(), 0));
+///
+/// // LATIN SMALL LETTER L, MIDDLE DOT
+/// let input = ['l', '\u{B7}', 'a'];
+/// let (index, len) = ducet.longest_match_index(&input).unwrap();
+/// assert_eq!((ducet.element_count(index), len), (2, 2));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryTable<'a> {
+    entries: &'a [u8],
+    sorted: &'a [u8],
+    chars: &'a [u8],
+    weights: &'a [u8],
+    /// Greatest primary weight of the variable collation elements, `0` if none.
+    variable_top: u32,
+    /// Length of the longest input of the mappings of the table.
+    max_characters: usize,
+}
+
+/// A decoded entry of a [`BinaryTable`].
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    char_start: usize,
+    weight_start: usize,
+    char_len: usize,
+    ce_len: usize,
+    levels: usize,
+}
+
+impl<'a> BinaryTable<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        Self::read(bytes).map_err(Malformation::into_error)
+    }
+
+    /// Reads `bytes` as [`BinaryTable::new`] does, in a `const` context, so that a table baked
+    /// into the program (see [`include_collation_data!`](crate::include_collation_data)) is
+    /// validated when it is compiled rather than when it is first used.
+    ///
+    /// The evaluation takes about as long as the validation by [`BinaryTable::new`], a few
+    /// seconds for the whole DUCET, over the limit of the `long_running_const_eval` lint.
+    ///
+    /// ```
+    /// use collate_rs::data_files::binary::{BinaryTable, FORMAT_VERSION, MAGIC};
+    ///
+    /// const HEADER: [u8; 24] = {
+    ///     let mut header = [0; 24];
+    ///     let (magic, version) = (MAGIC, FORMAT_VERSION.to_le_bytes());
+    ///     let mut i = 0;
+    ///     while i < 4 {
+    ///         (header[i], header[4 + i]) = (magic[i], version[i]);
+    ///         i += 1;
+    ///     }
+    ///     header
+    /// };
+    /// const EMPTY: BinaryTable<'static> = BinaryTable::new_const(&HEADER);
+    /// assert!(EMPTY.is_empty());
+    /// ```
+    ///
+    /// ## Panics
+    /// Where [`BinaryTable::new`] fails, which fails the compilation when evaluated in a constant.
+    pub const fn new_const(bytes: &'a [u8]) -> Self {
+        match Self::read(bytes) {
+            Ok(table) => table,
+            Err(malformation) => malformation.panic(),
+        }
+    }
+
+    const fn read(bytes: &'a [u8]) -> std::result::Result<Self, Malformation> {
+        if bytes.len() < HEADER_LEN
+            || bytes[0] != MAGIC[0]
+            || bytes[1] != MAGIC[1]
+            || bytes[2] != MAGIC[2]
+            || bytes[3] != MAGIC[3]
+        {
+            return Err(Malformation::NotATable);
+        }
+        let version = read_u32(bytes, 4);
+        if version != FORMAT_VERSION {
+            return Err(Malformation::Version(version));
+        }
+        let len = read_u32(bytes, 8) as usize;
+        let char_count = read_u32(bytes, 12) as usize;
+        let weight_count = read_u32(bytes, 16) as usize;
+        let variable_top = read_u32(bytes, 20);
+        let sections = [len * ENTRY_LEN, len * 4, char_count * 4, weight_count * 2];
+        if HEADER_LEN + sections[0] + sections[1] + sections[2] + sections[3] != bytes.len() {
+            return Err(Malformation::Length);
+        }
+        let (entries, rest) = bytes.split_at(HEADER_LEN).1.split_at(sections[0]);
+        let (sorted, rest) = rest.split_at(sections[1]);
+        let (chars, weights) = rest.split_at(sections[2]);
+        let mut table = Self {
+            entries,
+            sorted,
+            chars,
+            weights,
+            variable_top,
+            max_characters: 0,
+        };
+        match table.validate() {
+            Ok(max_characters) => table.max_characters = max_characters,
+            Err(malformation) => return Err(malformation),
+        }
+        Ok(table)
+    }
+
+    /// Checks that every entry points inside the character and weight sections, that every
+    /// character is a Unicode scalar value and that the entries are sorted, returning the length
+    /// of the longest input.
+    const fn validate(&self) -> std::result::Result<usize, Malformation> {
+        let mut max_characters = 0;
+        let mut i = 0;
+        while i < self.len() {
+            let e = self.entry(i);
+            if e.char_len == 0
+                || (e.char_start + e.char_len) * 4 > self.chars.len()
+                || (e.weight_start + e.ce_len * e.levels) * 2 > self.weights.len()
+            {
+                return Err(Malformation::EntryBounds(i));
+            }
+            let mut j = 0;
+            while j < e.char_len {
+                if char::from_u32(self.code_point(e.char_start + j)).is_none() {
+                    return Err(Malformation::CodePoint(i));
+                }
+                j += 1;
+            }
+            if e.char_len > max_characters {
+                max_characters = e.char_len;
+            }
+            i += 1;
+        }
+        let mut i = 0;
+        while i < self.len() {
+            let position = read_u32(self.sorted, i * 4) as usize;
+            if position >= self.len() {
+                return Err(Malformation::SortedBounds(i));
+            }
+            if i > 0
+                && !matches!(
+                    self.compare_characters(read_u32(self.sorted, (i - 1) * 4) as usize, position),
+                    Ordering::Less
+                )
+            {
+                return Err(Malformation::Unsorted);
+            }
+            i += 1;
+        }
+        Ok(max_characters)
+    }
+
+    /// Number of mappings in the table.
+    pub const fn len(&self) -> usize {
+        self.entries.len() / ENTRY_LEN
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    const fn entry(&self, i: usize) -> Entry {
+        let offset = i * ENTRY_LEN;
+        Entry {
+            char_start: read_u32(self.entries, offset) as usize,
+            weight_start: read_u32(self.entries, offset + 4) as usize,
+            char_len: self.entries[offset + 8] as usize,
+            ce_len: self.entries[offset + 9] as usize,
+            levels: self.entries[offset + 10] as usize,
+        }
+    }
+
+    const fn code_point(&self, i: usize) -> u32 {
+        read_u32(self.chars, i * 4)
+    }
+
+    const fn weight(&self, k: usize) -> u16 {
+        u16::from_le_bytes([self.weights[k * 2], self.weights[k * 2 + 1]])
+    }
+
+    fn characters(&self, e: Entry) -> impl Iterator<Item = char> + '_ {
+        (e.char_start..e.char_start + e.char_len)
+            .map(|i| char::from_u32(self.code_point(i)).expect("Validated in BinaryTable::new."))
+    }
+
+    /// Compares the inputs of the entries `a` and `b` code point by code point, as their
+    /// characters compare.
+    const fn compare_characters(&self, a: usize, b: usize) -> Ordering {
+        let (a, b) = (self.entry(a), self.entry(b));
+        let mut i = 0;
+        while i < a.char_len && i < b.char_len {
+            let (x, y) = (
+                self.code_point(a.char_start + i),
+                self.code_point(b.char_start + i),
+            );
+            if x != y {
+                return if x < y {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+            i += 1;
+        }
+        if a.char_len < b.char_len {
+            Ordering::Less
+        } else if a.char_len > b.char_len {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    /// Decodes the `i`-th mapping of the table.
+    ///
+    /// ## Panics
+    /// If `i` is out of bounds.
+    pub fn mapping(&self, i: usize) -> CollationElementMapping {
+        let e = self.entry(i);
+        let characters: Vec<char> = self.characters(e).collect();
+        let collation_elements: CollationElements = (0..e.ce_len)
+            .map(|j| {
+                let start = e.weight_start + j * e.levels;
+                let weights: Weights = (start..start + e.levels)
+                    .map(|k| CollationWeight::from(self.weight(k) as u32))
+                    .collect();
+                let variable = is_variable(weights.first(), self.variable_top);
+                CollationElement::with_weights(weights, variable)
+                    .expect("Variable weights are primary.")
+            })
+            .collect();
+        CollationElementMapping::new(&characters, &collation_elements)
+            .expect("Binary tables only contain well-formed mappings.")
+    }
+
+    /// Compares the input of the entry `e` with `characters`, as [`Self::compare_characters`].
+    const fn compare_input(&self, e: Entry, characters: &[char]) -> Ordering {
+        let mut i = 0;
+        while i < e.char_len && i < characters.len() {
+            let (x, y) = (self.code_point(e.char_start + i), characters[i] as u32);
+            if x != y {
+                return if x < y {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+            i += 1;
+        }
+        if e.char_len < characters.len() {
+            Ordering::Less
+        } else if e.char_len > characters.len() {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    /// The index of the mapping whose input is exactly `characters`, found by binary search.
+    pub const fn index_of(&self, characters: &[char]) -> Option<usize> {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            let position = read_u32(self.sorted, mid * 4) as usize;
+            match self.compare_input(self.entry(position), characters) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(position),
+            }
+        }
+        None
+    }
+
+    /// The index of the mapping for the longest initial substring of `input` listed in the table,
+    /// and the length of that substring, as [`BinaryTable::longest_match`].
+    pub const fn longest_match_index(&self, input: &[char]) -> Option<(usize, usize)> {
+        let mut n = if input.len() < self.max_characters {
+            input.len()
+        } else {
+            self.max_characters
+        };
+        while n > 0 {
+            if let Some(i) = self.index_of(input.split_at(n).0) {
+                return Some((i, n));
+            }
+            n -= 1;
+        }
+        None
+    }
+
+    /// The number of collation elements of the `i`-th mapping.
+    ///
+    /// ## Panics
+    /// If `i` is out of bounds.
+    pub const fn element_count(&self, i: usize) -> usize {
+        self.entry(i).ce_len
+    }
+
+    /// The `j`-th collation element of the `i`-th mapping, `None` if the mapping has fewer
+    /// collation elements, or if they have more than [`BINARY_ELEMENT_LEVELS`] levels.
+    ///
+    /// ## Panics
+    /// If `i` is out of bounds.
+    pub const fn element(&self, i: usize, j: usize) -> Option<BinaryElement> {
+        let e = self.entry(i);
+        if j >= e.ce_len || e.levels > BINARY_ELEMENT_LEVELS {
+            return None;
+        }
+        let mut weights = [0; BINARY_ELEMENT_LEVELS];
+        let mut k = 0;
+        while k < e.levels {
+            weights[k] = self.weight(e.weight_start + j * e.levels + k);
+            k += 1;
+        }
+        Some(BinaryElement {
+            weights,
+            levels: e.levels as u8,
+            variable: weights[0] != 0 && weights[0] as u32 <= self.variable_top,
+        })
+    }
+
+    /// The collation elements of the `N` code points from `first`, e.g. the ASCII characters
+    /// from `'\0'`, for the characters the table maps to a single collation element, `None` for
+    /// the others.
+    pub const fn single_elements<const N: usize>(&self, first: char) -> [Option<BinaryElement>; N] {
+        let mut elements = [None; N];
+        let mut k = 0;
+        while k < N {
+            if let Some(c) = char::from_u32(first as u32 + k as u32) {
+                if let Some(i) = self.index_of(&[c]) {
+                    if self.entry(i).ce_len == 1 {
+                        elements[k] = self.element(i, 0);
+                    }
+                }
+            }
+            k += 1;
+        }
+        elements
+    }
+
+    /// The mapping whose input is exactly `characters`.
+    pub fn get(&self, characters: &[char]) -> Option<CollationElementMapping> {
+        self.index_of(characters).map(|i| self.mapping(i))
+    }
+
+    /// The mapping for the longest initial substring of `input` listed in the table (UTS10-S2.1).
+    pub fn longest_match(&self, input: &[char]) -> Option<CollationElementMapping> {
+        self.longest_match_index(input)
+            .map(|(i, _)| self.mapping(i))
+    }
+
+    /// Decodes the whole table, preserving the order of the mappings.
+    pub fn to_table(&self) -> CollationElementTable {
+        CollationElementTable::new((0..self.len()).map(|i| self.mapping(i)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_files::parse_allkeys;
+
+    #[test]
+    fn test_binary_table() {
+        let table = parse_allkeys(
+            "0063  ; [.1FD6.0020.0002]\n\
+             0061  ; [.1FA2.0020.0002]\n\
+             00E9  ; [.2007.0020.0002][.0000.0024.0002]\n\
+             0063 0068 ; [.1FD7.0020.0002]\n\
+             0020  ; [*0209.0020.0002]\n",
+        )
+        .unwrap();
+        let bytes = write_table(&table).unwrap();
+        let binary = BinaryTable::new(&bytes).unwrap();
+        assert_eq!(binary.len(), 5);
+        assert_eq!(binary.to_table(), table);
+        assert_eq!(binary.get(&['c', 'h']), Some(table.mappings()[3].clone()));
+        assert_eq!(binary.get(&['h']), None);
+        let input: Vec<char> = "chc".chars().collect();
+        assert_eq!(
+            binary.longest_match(&input),
+            Some(table.mappings()[3].clone())
+        );
+        assert_eq!(
+            binary.longest_match(&input[2..]),
+            Some(table.mappings()[0].clone())
+        );
+
+        let i = binary.index_of(&['\u{E9}']).unwrap();
+        assert_eq!(binary.element_count(i), 2);
+        let elements = table.mappings()[2].collation_elements();
+        assert_eq!(
+            binary.element(i, 1).map(|ce| ce.to_collation_element()),
+            Some(elements[1].clone())
+        );
+        assert_eq!(binary.element(i, 2), None);
+        let space = binary.element(binary.index_of(&[' ']).unwrap(), 0).unwrap();
+        assert!(space.is_variable() && space.weight(1) == 0x0209 && space.weight(4) == 0);
+        let latin: [_; 8] = binary.single_elements('a');
+        assert_eq!(
+            latin.map(|ce| ce.is_some()),
+            [true, false, true, false, false, false, false, false]
+        );
+        assert_eq!(binary.longest_match_index(&input), Some((3, 2)));
+
+        assert!(BinaryTable::new(&bytes[..bytes.len() - 1]).is_err());
+        assert!(BinaryTable::new(b"ALLKEYS").is_err());
+        let mut corrupted = bytes.clone();
+        corrupted[4] = FORMAT_VERSION as u8 + 1;
+        assert!(BinaryTable::new(&corrupted).is_err());
+
+        let wide = CollationElementTable::new(vec![CollationElementMapping::new(
+            &['a'],
+            &[CollationElement::new(vec![CollationWeight::from(0x10000)])],
+        )
+        .unwrap()]);
+        assert!(write_table(&wide).is_err());
+        let unordered = parse_allkeys("0061 ; [.0201.0020.0002]\n0020 ; [*0209.0020.0002]\n");
+        assert!(write_table(&unordered.unwrap()).is_err());
+    }
+}
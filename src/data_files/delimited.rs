@@ -0,0 +1,287 @@
+use crate::algorithm::normalize::to_nfd;
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::collation_wel::{CollationElement, CollationWeight};
+use crate::definitions::mappings::{CollationElementMapping, CollationElements};
+use crate::error::{CollateError, Result};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// The weights of the levels left out of a collation element, as in most collation elements of
+/// the DUCET: the common secondary and tertiary weights.
+const DEFAULT_WEIGHTS: [u32; 3] = [0, 0x0020, 0x0002];
+
+/// Parses a table written as lines of characters and weights, separated by a tab (TSV) or a comma
+/// (CSV), friendlier to write by hand than the [allkeys.txt format](super::parse_allkeys), e.g.
+/// for the ordering of a transliteration scheme:
+///
+/// ```text
+/// # characters, collation elements
+/// a,2000
+/// ā,2000.0021
+/// kh,2010
+/// -,*0209
+/// ```
+///
+/// - The characters are written as they are, or as `\u{...}` escapes, e.g. `e\u{301}`, and mapped
+///   in their canonical decomposition (NFD), as looked up by the collators. In CSV, they may be
+///   quoted, e.g. `","`, a `""` in quotes standing for a `"`.
+/// - The collation elements are separated by spaces, each of hexadecimal weights separated by
+///   `.`, the left out secondary and tertiary weights being the common `0020` and `0002`, and
+///   `*` marking a variable collation element, e.g. `*0209`. The notation of UTS10, e.g.
+///   `[.2000.0020.0002]`, is also accepted.
+/// - Blank lines, lines starting with `#` and what follows a `#` after the weights are comments.
+///
+/// The errors give the line and the columns of the ill-formed field, counted from 1, and the
+/// lines mapping the same characters are rejected.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::data_files::delimited::parse_delimited;
+///
+/// let table = parse_delimited("a\t2000\nā\t2000.0021\nk\t2010\nkh\t2011\nl\t2012\n").unwrap();
+/// let collator = Collator::new(table);
+/// let mut words = vec!["la", "kha", "kā", "ka", "kla"];
+/// collator.sort_slice(&mut words);
+/// assert_eq!(words, ["ka", "kā", "kla", "kha", "la"]);
+///
+/// let error = parse_delimited("a\t2000\nb\t20G0\n").unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "Ill-formed entry at line 2: columns 3-6: Invalid collation weight \"20G0\"."
+/// );
+/// ```
+pub fn parse_delimited(s: &str) -> Result<CollationElementTable> {
+    let mut mappings = vec![];
+    let mut lines: HashMap<Vec<char>, usize> = HashMap::new();
+    for (i, line) in s.lines().enumerate() {
+        let Some(mapping) = parse_delimited_entry(line).map_err(|e| e.at_line(i + 1))? else {
+            continue;
+        };
+        if let Some(first) = lines.insert(mapping.characters().to_vec(), i + 1) {
+            let characters: String = mapping.characters().iter().collect();
+            return Err(CollateError::parse(format!(
+                "{:?} is already mapped at line {}.",
+                characters, first
+            ))
+            .at_line(i + 1));
+        }
+        mappings.push(mapping);
+    }
+    Ok(CollationElementTable::new(mappings))
+}
+
+/// Parses a single line of [`parse_delimited`], returning `None` for comments and blank lines.
+pub fn parse_delimited_entry(line: &str) -> Result<Option<CollationElementMapping>> {
+    let trimmed = line.trim_start();
+    if trimmed.trim_end().is_empty() || trimmed.starts_with('#') {
+        return Ok(None);
+    }
+    let (characters, weights) = split_fields(line)?;
+    let characters = to_nfd(&unescape(line, characters)?);
+    let weights = match line[weights.clone()].find('#') {
+        Some(comment) => weights.start..weights.start + comment,
+        None => weights,
+    };
+    let mut collation_elements = CollationElements::new();
+    for token in line[weights.clone()].split_whitespace() {
+        let start = token.as_ptr() as usize - line.as_ptr() as usize;
+        let ce = parse_weights(token).map_err(|e| spanned(line, start..start + token.len(), e))?;
+        collation_elements.push(ce);
+    }
+    if collation_elements.is_empty() {
+        return Err(spanned(
+            line,
+            weights,
+            "Entries must map to at least one collation element.",
+        ));
+    }
+    CollationElementMapping::new(&characters, &collation_elements).map(Some)
+}
+
+/// Splits a line at its first tab, or else at the comma after its (maybe quoted) characters,
+/// into the byte ranges of its characters, unquoted, and of its weights.
+fn split_fields(line: &str) -> Result<(Range<usize>, Range<usize>)> {
+    if let Some(tab) = line.find('\t') {
+        return Ok((0..tab, tab + 1..line.len()));
+    }
+    let start = line.len() - line.trim_start().len();
+    if line[start..].starts_with('"') {
+        let mut chars = line[start + 1..].char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c != '"' {
+                continue;
+            }
+            if chars.next_if(|&(_, c)| c == '"').is_some() {
+                continue;
+            }
+            let end = start + 1 + i;
+            let rest = &line[end + 1..];
+            return match rest.trim_start().strip_prefix(',') {
+                Some(weights) => Ok((start..end + 1, line.len() - weights.len()..line.len())),
+                None => Err(spanned(
+                    line,
+                    end + 1..line.len(),
+                    "Missing ',' after the quoted characters.",
+                )),
+            };
+        }
+        return Err(spanned(line, start..line.len(), "Unterminated quotes."));
+    }
+    match line.find(',') {
+        Some(comma) => Ok((0..comma, comma + 1..line.len())),
+        None => Err(spanned(
+            line,
+            0..line.len(),
+            "Missing tab or ',' between characters and collation elements.",
+        )),
+    }
+}
+
+/// The characters of the field `range` of `line`: unquoted, trimmed and with their escapes
+/// replaced.
+fn unescape(line: &str, range: Range<usize>) -> Result<String> {
+    let field = line[range.clone()].trim();
+    let offset = field.as_ptr() as usize - line.as_ptr() as usize;
+    let (field, offset, quoted) = match field.strip_prefix('"').and_then(|f| f.strip_suffix('"')) {
+        Some(unquoted) => (unquoted, offset + 1, true),
+        None => (field, offset, false),
+    };
+    let mut characters = String::new();
+    let mut chars = field.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' if quoted => {
+                chars.next_if(|&(_, c)| c == '"');
+                characters.push('"');
+            }
+            '\\' if field[i..].starts_with("\\u{") => {
+                let escape = match field[i..].find('}') {
+                    Some(end) => &field[i..i + end + 1],
+                    None => &field[i..],
+                };
+                let span = offset + i..offset + i + escape.len();
+                let c = escape
+                    .strip_prefix("\\u{")
+                    .and_then(|e| e.strip_suffix('}'))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| spanned(line, span, format!("Invalid escape {:?}.", escape)))?;
+                characters.push(c);
+                while chars.next_if(|&(j, _)| j < i + escape.len()).is_some() {}
+            }
+            '\\' if field[i..].starts_with("\\\\") => {
+                chars.next();
+                characters.push('\\');
+            }
+            c => characters.push(c),
+        }
+    }
+    if characters.is_empty() {
+        return Err(spanned(
+            line,
+            range,
+            "Entries must map at least one character.",
+        ));
+    }
+    Ok(characters)
+}
+
+/// Parses a collation element written as `2000.0021`, `*0209` or `[.2000.0020.0002]`.
+fn parse_weights(token: &str) -> Result<CollationElement> {
+    if token.starts_with('[') {
+        return CollationElement::try_from(token).map_err(Into::into);
+    }
+    let (variable, token) = match token.strip_prefix('*') {
+        Some(token) => (true, token),
+        None => (false, token),
+    };
+    let mut weights = token
+        .split('.')
+        .map(CollationWeight::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    for &w in DEFAULT_WEIGHTS.get(weights.len()..).unwrap_or_default() {
+        weights.push(CollationWeight::from(w));
+    }
+    if variable {
+        CollationElement::variable(weights).map_err(Into::into)
+    } else {
+        Ok(CollationElement::new(weights))
+    }
+}
+
+/// A parse error of the bytes `span` of `line`, reported by their columns.
+fn spanned(line: &str, span: Range<usize>, reason: impl std::fmt::Display) -> CollateError {
+    let start = line[..span.start].chars().count() + 1;
+    let end = start + line[span].chars().count().max(1) - 1;
+    if start == end {
+        CollateError::parse(format!("column {}: {}", start, reason))
+    } else {
+        CollateError::parse(format!("columns {}-{}: {}", start, end, reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_files::parse_allkeys;
+
+    #[test]
+    fn test_parse_delimited() {
+        let delimited = parse_delimited(
+            "# characters\tweights\n\
+             \n\
+             -\t*0209\n\
+             c\t1FD6 # c\n\
+             ch\t[.1FD7.0020.0002]\n\
+             \u{E9},2007 0.0024\n\
+             \",\",0222\n\
+             \"\"\"\" , 0223.0020.0003\n\
+             \\u{301}\\\\\t0.0025.0002\n",
+        )
+        .unwrap();
+        let allkeys = parse_allkeys(
+            "002D ; [*0209.0020.0002]\n\
+             0063 ; [.1FD6.0020.0002]\n\
+             0063 0068 ; [.1FD7.0020.0002]\n\
+             0065 0301 ; [.2007.0020.0002][.0000.0024.0002]\n\
+             002C ; [.0222.0020.0002]\n\
+             0022 ; [.0223.0020.0003]\n\
+             0301 005C ; [.0000.0025.0002]\n",
+        )
+        .unwrap();
+        assert_eq!(delimited, allkeys);
+
+        let error = |s| parse_delimited(s).unwrap_err().to_string();
+        assert_eq!(
+            error("a\t2000\na\u{301}\t2001.0025 \t2G\n"),
+            "Ill-formed entry at line 2: columns 15-16: Invalid collation weight \"2G\"."
+        );
+        assert_eq!(
+            error("a,2000\na,2001\n"),
+            "Ill-formed entry at line 2: \"a\" is already mapped at line 1."
+        );
+        assert_eq!(
+            error("a 2000"),
+            "Ill-formed entry at line 1: columns 1-6: \
+             Missing tab or ',' between characters and collation elements."
+        );
+        assert_eq!(
+            error("\\u{D800}\t2000"),
+            "Ill-formed entry at line 1: columns 1-8: Invalid escape \"\\\\u{D800}\"."
+        );
+        assert_eq!(
+            error("\"a,2000"),
+            "Ill-formed entry at line 1: columns 1-7: Unterminated quotes."
+        );
+        assert_eq!(
+            error(" \t2000"),
+            "Ill-formed entry at line 1: column 1: Entries must map at least one character."
+        );
+        assert_eq!(
+            error("a\t# none"),
+            "Ill-formed entry at line 1: column 3: \
+             Entries must map to at least one collation element."
+        );
+        assert!(error("a\t*0.0020").contains("columns 3-9: Invalid collation element"));
+    }
+}
@@ -0,0 +1,363 @@
+use crate::algorithm::KeyEncoding;
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::collation_wel::CollationElement;
+use crate::definitions::mappings::{CollationElementMapping, CollationElements};
+use crate::ducet::UnicodeVersion;
+use crate::error::{CollateError, Result};
+
+/// Defines a compact binary format for [`CollationElementTable`]s, loaded without parsing nor
+/// copying (e.g. from `include_bytes!`), as an alternative to the allkeys.txt format.
+pub mod binary;
+
+/// Defines the helpers of build scripts baking tables into a program with
+/// [`include_collation_data!`](crate::include_collation_data).
+pub mod bake;
+
+/// Defines a delimited format of tables, lines of characters and weights separated by a tab or a
+/// comma, to write tables by hand.
+pub mod delimited;
+
+/// Canonical combining classes, canonical decompositions and ranges of the implicit weights from
+/// the Unicode Character Database, generated by `build.rs`.
+pub mod ucd;
+
+/// > 12.1 Allkeys File Format
+/// >
+/// > The allkeys.txt file consists of a version line followed by a series of entries, all
+/// > separated by newlines. A '#' or '%' and any following characters on a line are comments.
+/// > Whitespace between literals is ignored. The following is an extended BNF description of the
+/// > format, where "x+" indicates one or more x's, "x\*" indicates zero or more x's, "x?"
+/// > indicates zero or one x, <char> is a hexadecimal Unicode code point value, and <weight> is a
+/// > hexadecimal collation weight value.
+/// ```text
+///     <collationElementTable> := <version>
+///                                <implicitweights>*
+///                                <entry>+
+///     <version>               := '@version' <major>.<minor>.<variant> <eol>
+///     <implicitweights>       := '@implicitweights' <char>..<char>; <base> <eol>
+///     <entry>                 := <charList> ';' <collElement>+ <eol>
+///     <charList>              := <char>+
+///     <collElement>           := "[" <alt> <weight> "." <weight> "." <weight> ("." <weight>)? "]"
+///     <alt>                   := "*" | "."
+/// ```
+/// > Collation elements marked with a "\*" are variable.
+///
+/// The `@version` line is read by [`parse_version`] and the `@implicitweights` lines are
+/// skipped. Collation elements marked
+/// with a "\*" are [variable](CollationElement::is_variable).
+///
+/// As in the rules of CLDR, the characters of an entry may follow a prefix and a `|`, e.g.
+/// `30A2 | 30FC ; [.42DA.0020.0011]`, for a
+/// [contextual mapping](CollationElementMapping::ContextualMapping).
+pub fn parse_allkeys(s: &str) -> Result<CollationElementTable> {
+    parse_entries(s).map(CollationElementTable::new)
+}
+
+/// Parses the `<version>` line of an allkeys file, e.g. `@version 15.1.0`, returning `None` if
+/// there is none, as in the tailorings and the tables written by hand.
+///
+/// Fails if the line is not `@version <major>.<minor>.<variant>`, or with
+/// [`CollateError::UnsupportedVersion`] if the version is not one of [`UnicodeVersion::ALL`],
+/// whose data the crate applies the algorithm by.
+///
+/// ```
+/// use collate_rs::data_files::parse_version;
+/// use collate_rs::ducet::UnicodeVersion;
+///
+/// let allkeys = "@version 15.1.0\n0061 ; [.2075.0020.0002]\n";
+/// assert_eq!(parse_version(allkeys).unwrap(), Some(UnicodeVersion::V15_1));
+/// assert_eq!(parse_version("0061 ; [.2075.0020.0002]\n").unwrap(), None);
+/// assert!(parse_version("@version 12.1.0\n").is_err());
+/// ```
+pub fn parse_version(s: &str) -> Result<Option<UnicodeVersion>> {
+    for (i, line) in s.lines().enumerate() {
+        let line = line.split(['#', '%']).next().unwrap_or_default().trim();
+        let Some(version) = line.strip_prefix("@version") else {
+            continue;
+        };
+        let version = version.trim();
+        let well_formed = version.split('.').count() == 3
+            && version
+                .split('.')
+                .all(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        if !well_formed {
+            return Err(CollateError::parse(format!(
+                "Invalid version {:?}, expected <major>.<minor>.<variant>.",
+                version
+            ))
+            .at_line(i + 1));
+        }
+        return version.parse().map(Some);
+    }
+    Ok(None)
+}
+
+/// Parses the `<entry>` lines of an allkeys file, skipping everything else.
+pub fn parse_entries(s: &str) -> Result<Vec<CollationElementMapping>> {
+    let mut mappings = vec![];
+    for (i, line) in s.lines().enumerate() {
+        if let Some(mapping) = parse_entry(line).map_err(|e| e.at_line(i + 1))? {
+            mappings.push(mapping);
+        }
+    }
+    Ok(mappings)
+}
+
+/// Parses a single `<entry>` line of an allkeys file, returning `None` for comments, blank lines
+/// and `@` directives.
+pub fn parse_entry(line: &str) -> Result<Option<CollationElementMapping>> {
+    let line = line.split(['#', '%']).next().unwrap_or_default().trim();
+    if line.is_empty() || line.starts_with('@') {
+        return Ok(None);
+    }
+    let (char_list, coll_elements) = line.split_once(';').ok_or_else(|| {
+        CollateError::parse("Missing ';' between characters and collation elements.")
+    })?;
+
+    let (prefix, char_list) = match char_list.split_once('|') {
+        Some((prefix, char_list)) => (parse_chars(prefix)?, char_list),
+        None => (vec![], char_list),
+    };
+    let characters = parse_chars(char_list)?;
+    if characters.is_empty() {
+        return Err(CollateError::parse(
+            "Entries must map at least one character.",
+        ));
+    }
+
+    let collation_elements = coll_elements
+        .trim()
+        .split_inclusive(']')
+        .map(parse_collation_element)
+        .collect::<Result<CollationElements>>()?;
+    if collation_elements.is_empty() {
+        return Err(CollateError::parse(
+            "Entries must map to at least one collation element.",
+        ));
+    }
+
+    CollationElementMapping::with_prefix(&prefix, &characters, &collation_elements).map(Some)
+}
+
+fn parse_chars(s: &str) -> Result<Vec<char>> {
+    s.split_whitespace().map(parse_char).collect()
+}
+
+/// Writes a table in the [allkeys.txt format](parse_allkeys), one entry per mapping in the order
+/// of the table, so that [`parse_allkeys`] reads it back unchanged, e.g. to inspect a tailored
+/// table or feed it to other tools.
+///
+/// No `@version` nor `@implicitweights` line is written, as the table does not keep them. Fails if
+/// a mapping has no character or no collation element, or if a weight does not fit in 16 bits, as
+/// they are not written in the format.
+///
+/// ```
+/// use collate_rs::data_files::{parse_allkeys, write_allkeys};
+///
+/// let allkeys = "0020 ; [*0209.0020.0002]\n\
+///                0063 0068 ; [.1FD7.0020.0002]\n\
+///                00E9 ; [.2007.0020.0002][.0000.0024.0002]\n";
+/// let table = parse_allkeys(allkeys).unwrap();
+/// assert_eq!(write_allkeys(&table).unwrap(), allkeys);
+/// ```
+pub fn write_allkeys(table: &CollationElementTable) -> Result<String> {
+    write_entries(table, " ; ", |ce, line| {
+        if let Some(w) = ce.0.iter().find(|w| w.0 > 0xFFFF) {
+            return Err(CollateError::IllFormedTable(format!(
+                "Weight {:04X} does not fit in 16 bits.",
+                w.0
+            )));
+        }
+        line.push_str(&ce.to_string());
+        Ok(())
+    })
+}
+
+/// Writes a table in the notation of the `FractionalUCA.txt` file of CLDR, e.g.
+/// `0061; [02 22, 21, 03]`: the weights of each level of a collation element are separated by
+/// commas, and written as the bytes of the [fractional encoding](KeyEncoding::Fractional) of the
+/// sort keys, left empty when ignorable.
+///
+/// The weights are those of the table, not the fractional weights allocated by CLDR, and the
+/// variable collation elements are not marked, so that the output is meant for inspection and not
+/// read back.
+///
+/// ```
+/// use collate_rs::data_files::{parse_allkeys, write_fractional_uca};
+///
+/// let table = parse_allkeys(
+///     "0061 ; [.1FA2.0020.0002]\n\
+///      0301 ; [.0000.0024.0002]\n",
+/// )
+/// .unwrap();
+/// assert_eq!(
+///     write_fractional_uca(&table).unwrap(),
+///     "0061; [9F 23, 21, 03]\n0301; [, 25, 03]\n"
+/// );
+/// ```
+pub fn write_fractional_uca(table: &CollationElementTable) -> Result<String> {
+    write_entries(table, "; ", |ce, line| {
+        let levels: Vec<String> =
+            ce.0.iter()
+                .map(|w| {
+                    let mut bytes = vec![];
+                    if w.0 != 0 {
+                        KeyEncoding::Fractional.write_weight(w, &mut bytes);
+                    }
+                    bytes
+                        .iter()
+                        .map(|b| format!("{:02X}", b))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect();
+        line.push('[');
+        line.push_str(&levels.join(", "));
+        line.push(']');
+        Ok(())
+    })
+}
+
+/// Writes a line per mapping of the table: its prefix, its characters, the `separator` and its
+/// collation elements, written by `write_ce`.
+fn write_entries(
+    table: &CollationElementTable,
+    separator: &str,
+    write_ce: impl Fn(&CollationElement, &mut String) -> Result<()>,
+) -> Result<String> {
+    let mut s = String::new();
+    for mapping in table.mappings() {
+        let (characters, ces) = (mapping.characters(), mapping.collation_elements());
+        if characters.is_empty() || ces.is_empty() {
+            return Err(CollateError::IllFormedTable(format!(
+                "The mapping of {:?} to {:?} has no entry in the format.",
+                characters, ces
+            )));
+        }
+        let chars = |chars: &[char]| {
+            chars
+                .iter()
+                .map(|&c| format!("{:04X}", c as u32))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        if !mapping.prefix().is_empty() {
+            s.push_str(&chars(mapping.prefix()));
+            s.push_str(" | ");
+        }
+        s.push_str(&chars(characters));
+        s.push_str(separator);
+        for ce in ces {
+            write_ce(ce, &mut s)?;
+        }
+        s.push('\n');
+    }
+    Ok(s)
+}
+
+fn parse_char(s: &str) -> Result<char> {
+    let cp = u32::from_str_radix(s, 16)
+        .map_err(|_| CollateError::parse(format!("Invalid code point {:?}.", s)))?;
+    char::from_u32(cp)
+        .ok_or_else(|| CollateError::parse(format!("{:04X} is not a Unicode scalar value.", cp)))
+}
+
+/// Parses a `<collElement>` such as `[.1FD6.0020.0002]` or `[*0209.0020.0002]`, keeping the
+/// variable marker.
+fn parse_collation_element(s: &str) -> Result<CollationElement> {
+    CollationElement::try_from(s.trim()).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definitions::collation_wel::CollationWeight;
+
+    #[test]
+    fn test_12_1() {
+        let table = parse_allkeys(
+            "@version 13.0.0\n\
+             \n\
+             @implicitweights 17000..18AFF; FB00 # Tangut and Tangut Components\n\
+             0020  ; [*0209.0020.0002] # SPACE\n\
+             0063  ; [.1FD6.0020.0002] # LATIN SMALL LETTER C\n\
+             00E9  ; [.2007.0020.0002][.0000.0024.0002] # LATIN SMALL LETTER E WITH ACUTE\n\
+             0063 0068 ; [.1FD7.0020.0002] # <LATIN SMALL LETTER C, LATIN SMALL LETTER H>\n",
+        )
+        .unwrap();
+        assert_eq!(table.mappings().len(), 4);
+        assert_eq!(
+            table.mappings()[0],
+            CollationElementMapping::SimpleMapping {
+                character: ' ',
+                collation_element: CollationElement::try_from("[*0209.0020.0002]").unwrap(),
+            }
+        );
+        assert!(matches!(
+            table.mappings()[2],
+            CollationElementMapping::Expansion { .. }
+        ));
+        assert!(table.mappings()[3].is_contraction());
+        assert_eq!(table.variable_top(), Some(&CollationWeight::from(0x0209)));
+        assert!(!table.mappings()[1].collation_elements()[0].is_variable());
+        assert!(parse_entry("0020 ; [*0000.0020.0002]").is_err());
+
+        assert!(parse_entry("0063 [.1FD6.0020.0002]").is_err());
+        assert!(parse_entry("0063 ; [.1FD6.0020.00G2]").is_err());
+        assert!(parse_entry("D800 ; [.1FD6.0020.0002]").is_err());
+        assert!(parse_entry("0063 ; [.1FD6.0020.10002]")
+            .unwrap_err()
+            .to_string()
+            .contains("does not fit in 16 bits"));
+        assert_eq!(
+            parse_entry("30A2 | 30FC ; [.42DA.0020.0011]").unwrap(),
+            CollationElementMapping::with_prefix(
+                &['\u{30A2}'],
+                &['\u{30FC}'],
+                &[CollationElement::try_from("[.42DA.0020.0011]").unwrap()]
+            )
+            .ok()
+        );
+        assert!(parse_entry("30A2 | ; [.42DA.0020.0011]").is_err());
+
+        // A tailored table, with a contextual mapping and weights of five levels, is written back.
+        let tailored = parse_allkeys(
+            "30A2 | 30FC ; [.42DA.0020.0011]\n\
+             30AB ; [.42E5.0020.000E.0000.0002]\n",
+        )
+        .unwrap();
+        let allkeys = write_allkeys(&tailored).unwrap();
+        assert!(allkeys.starts_with("30A2 | 30FC ; [.42DA.0020.0011]\n"));
+        assert_eq!(parse_allkeys(&allkeys).unwrap(), tailored);
+        assert!(write_fractional_uca(&tailored)
+            .unwrap()
+            .ends_with("30AB; [C2 66, 21, 0F, , 03]\n"));
+        let wide = CollationElementTable::new(vec![CollationElementMapping::new(
+            &['a'],
+            &[CollationElement::new(vec![CollationWeight::from(0x10000)])],
+        )
+        .unwrap()]);
+        assert!(write_allkeys(&wide).is_err());
+        assert!(write_fractional_uca(&wide).is_ok());
+        assert!(matches!(
+            parse_allkeys("0063 ; [.1FD6.0020.0002]\n0064 ; 1FEB"),
+            Err(CollateError::ParseError { line: Some(2), .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version("# allkeys\n@version 15.0.0 # comment\n").unwrap(),
+            Some(UnicodeVersion::V15_0)
+        );
+        assert_eq!(
+            parse_version("@version 14.0\n").unwrap_err().to_string(),
+            "Ill-formed entry at line 1: Invalid version \"14.0\", expected <major>.<minor>.<variant>."
+        );
+        assert!(matches!(
+            parse_version("@version 99.0.0\n"),
+            Err(CollateError::UnsupportedVersion(_))
+        ));
+    }
+}
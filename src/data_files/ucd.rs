@@ -0,0 +1,60 @@
+#[cfg(feature = "datagen")]
+include!(concat!(env!("OUT_DIR"), "/ucd_tables.rs"));
+#[cfg(not(feature = "datagen"))]
+include!("ucd_tables.rs");
+
+/// The canonical combining class of a character, `0` for starters.
+pub(crate) fn canonical_combining_class(c: char) -> u8 {
+    let cp = c as u32;
+    // The characters before the first non-starter, e.g. Latin-1, skip the search.
+    if CANONICAL_COMBINING_CLASSES
+        .first()
+        .is_none_or(|&(first, _, _)| cp < first)
+    {
+        return 0;
+    }
+    CANONICAL_COMBINING_CLASSES
+        .binary_search_by(|&(first, last, _)| {
+            if last < cp {
+                std::cmp::Ordering::Less
+            } else if first > cp {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .map(|i| CANONICAL_COMBINING_CLASSES[i].2)
+        .unwrap_or(0)
+}
+
+/// The full canonical decomposition of a character, if it has one. Hangul syllables are
+/// decomposed algorithmically by [`crate::algorithm::normalize`] instead.
+pub(crate) fn canonical_decomposition(c: char) -> Option<&'static [u32]> {
+    if CANONICAL_DECOMPOSITIONS
+        .first()
+        .is_none_or(|&(first, _)| (c as u32) < first)
+    {
+        return None;
+    }
+    CANONICAL_DECOMPOSITIONS
+        .binary_search_by_key(&(c as u32), |&(cp, _)| cp)
+        .ok()
+        .map(|i| CANONICAL_DECOMPOSITIONS[i].1)
+}
+
+/// The general category of a character, `"Cn"` for the unassigned ones.
+pub(crate) fn general_category(c: char) -> &'static str {
+    let cp = c as u32;
+    GENERAL_CATEGORIES
+        .binary_search_by(|&(first, last, _)| {
+            if last < cp {
+                std::cmp::Ordering::Less
+            } else if first > cp {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .map(|i| GENERAL_CATEGORIES[i].2)
+        .unwrap_or("Cn")
+}
@@ -1,77 +1,348 @@
+use crate::data_files::ucd::CANONICAL_DECOMPOSITIONS;
 use crate::definitions::collation_wel::*;
+use crate::definitions::ignorable::Ignorable;
 use crate::definitions::mappings::CollationElementMapping;
+use crate::definitions::variable_weighting::MaxVariable;
+use crate::unicode_set::UnicodeSet;
+use crate::weight_derivation::implicit_weights;
+use std::borrow::Cow;
+use std::ops::RangeInclusive;
+
+/// The least primary weight of the implicit weights, reserved from the tables.
+pub(crate) const FIRST_IMPLICIT_PRIMARY: u32 = 0xFB00;
+
+/// The least primary weight past the leading weights of the implicit weights, e.g. of U+FFFD.
+pub(crate) const FIRST_TRAILING_PRIMARY: u32 = 0xFC00;
 
 /// > UTS10-D23. Collation Element Table: A table of collation element mappings.
 /// >
 /// > The basic idea of a collation element table is that it contains the collation weight information necessary to construct sort keys for Unicode strings.
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct CollationElementTable(pub(crate) Vec<CollationElementMapping>);
 
-/// > UTS10-D24. Explicit Weight Mapping: A mapping to one (or more) collation elements which is explicitly listed in a collation element table.
-// type ExplicitWeightMapping = CollationElementTable;
-/// > UTS10-D25. Implicit Weight Mapping: A mapping to one (or more) collation elements which is not explicitly listed in a collation element table, but which is instead derived by rule.
-/// >
-/// > The convention used by the Unicode Collation Algorithm is that the mapping for any character which is not listed explicitly in a given collation element table is instead determined by the implicit weight derivation rules. This convention extends to all unassigned code points, so that all Unicode strings can have determinant sort keys constructed for them. See Section 10, Weight Derivation for the rules governing the assignment of implicit weights.
-/// >
-/// > Implementations can produce the same result using various representations of weights. In particular, while the Default Unicode Collation Element Table [Allkeys] stores weights of all levels using 16-bit integers, and such weights are shown in examples in this document, other implementations may choose to store weights in larger or smaller integer units, and may store weights of different levels in integer units of different sizes. See Section 9, Implementation Notes.
-/// >
-/// > The specific collation weight values shown in examples are illustrative only; they may not match the weights in the latest Default Unicode Collation Element Table [Allkeys].
-// type ImplicitWeightMapping = CollationElementTable;
+/// Whether the collation elements of some characters are listed in a collation element table or
+/// derived by rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MappingOrigin {
+    /// > UTS10-D24. Explicit Weight Mapping: A mapping to one (or more) collation elements which is explicitly listed in a collation element table.
+    Explicit,
+    /// > UTS10-D25. Implicit Weight Mapping: A mapping to one (or more) collation elements which is not explicitly listed in a collation element table, but which is instead derived by rule.
+    /// >
+    /// > The convention used by the Unicode Collation Algorithm is that the mapping for any character which is not listed explicitly in a given collation element table is instead determined by the implicit weight derivation rules. This convention extends to all unassigned code points, so that all Unicode strings can have determinant sort keys constructed for them. See Section 10, Weight Derivation for the rules governing the assignment of implicit weights.
+    /// >
+    /// > Implementations can produce the same result using various representations of weights. In particular, while the Default Unicode Collation Element Table [Allkeys] stores weights of all levels using 16-bit integers, and such weights are shown in examples in this document, other implementations may choose to store weights in larger or smaller integer units, and may store weights of different levels in integer units of different sizes. See Section 9, Implementation Notes.
+    /// >
+    /// > The specific collation weight values shown in examples are illustrative only; they may not match the weights in the latest Default Unicode Collation Element Table [Allkeys].
+    Implicit,
+}
 
 impl CollationElementTable {
+    pub fn new(mappings: Vec<CollationElementMapping>) -> Self {
+        Self(mappings)
+    }
+
+    /// The collation element mappings listed in the table.
+    pub fn mappings(&self) -> &[CollationElementMapping] {
+        &self.0
+    }
+
+    /// The mapping listed in the table for exactly `characters`, if any (UTS10-D24), whatever
+    /// precedes them: the [contextual mappings](CollationElementMapping::ContextualMapping) are
+    /// left out.
+    pub fn explicit_mapping(&self, characters: &[char]) -> Option<&CollationElementMapping> {
+        self.0
+            .iter()
+            .find(|m| m.prefix().is_empty() && m.characters() == characters)
+    }
+
+    /// The mapping derived by rule for `c`, if the table does not list one (UTS10-D25).
+    pub fn implicit_mapping(&self, c: char) -> Option<CollationElementMapping> {
+        if self.explicit_mapping(&[c]).is_some() {
+            return None;
+        }
+        Some(CollationElementMapping::Expansion {
+            character: c,
+            collation_elements: implicit_weights(c).into(),
+        })
+    }
+
+    /// The mapping of `characters` in the table, with its origin.
+    ///
+    /// Every single character has a mapping, explicit or implicit; a sequence of characters only
+    /// has one if the table lists it as a contraction.
+    ///
+    /// ```
+    /// use collate_rs::definitions::collation_element_tables::{CollationElementTable, MappingOrigin};
+    /// use collate_rs::definitions::collation_wel::CollationElement;
+    /// use collate_rs::definitions::mappings::CollationElementMapping;
+    ///
+    /// let ce = CollationElement::try_from("[.1FA2.0020.0002]").unwrap();
+    /// let table = CollationElementTable::new(vec![CollationElementMapping::new(&['a'], &[ce]).unwrap()]);
+    /// assert_eq!(table.lookup(&['a']).unwrap().1, MappingOrigin::Explicit);
+    /// let (mapping, origin) = table.lookup(&['\u{4E00}']).unwrap();
+    /// assert_eq!(origin, MappingOrigin::Implicit);
+    /// assert_eq!(
+    ///     mapping.collation_elements(),
+    ///     [
+    ///         CollationElement::try_from("[.FB40.0020.0002]").unwrap(),
+    ///         CollationElement::try_from("[.CE00.0000.0000]").unwrap(),
+    ///     ]
+    /// );
+    /// assert!(table.lookup(&['a', 'b']).is_none());
+    /// ```
+    pub fn lookup(
+        &self,
+        characters: &[char],
+    ) -> Option<(Cow<'_, CollationElementMapping>, MappingOrigin)> {
+        if let Some(mapping) = self.explicit_mapping(characters) {
+            return Some((Cow::Borrowed(mapping), MappingOrigin::Explicit));
+        }
+        match characters {
+            &[c] => self
+                .implicit_mapping(c)
+                .map(|mapping| (Cow::Owned(mapping), MappingOrigin::Implicit)),
+            _ => None,
+        }
+    }
+
+    /// A function telling whether a collation element is variable (UTS10-D16) in the table, that
+    /// is a primary collation element whose primary weight is at most the
+    /// [variable top](CollationElementTable::variable_top), e.g. for
+    /// [`VariableWeighting::apply`](crate::definitions::variable_weighting::VariableWeighting::apply).
+    /// The variable top is computed once, when the function is created.
+    ///
+    /// In the DUCET, the primary weights of the collation elements marked with `*` are the lowest
+    /// ones, so that it agrees with [`CollationElement::is_variable`] for the collation elements of
+    /// the table, and also classifies collation elements built elsewhere, e.g. by a tailoring.
+    ///
+    /// ```
+    /// use collate_rs::data_files::parse_allkeys;
+    /// use collate_rs::definitions::collation_wel::CollationElement;
+    ///
+    /// let table = parse_allkeys(
+    ///     "0020 ; [*0209.0020.0002] # SPACE\n\
+    ///      0061 ; [.1FA2.0020.0002] # LATIN SMALL LETTER A\n",
+    /// )
+    /// .unwrap();
+    /// let is_variable = table.variable_classifier();
+    /// assert!(is_variable(&CollationElement::try_from("[.0205.0020.0002]").unwrap()));
+    /// assert!(!is_variable(&CollationElement::try_from("[.1FA2.0020.0002]").unwrap()));
+    /// ```
+    pub fn variable_classifier(&self) -> impl Fn(&CollationElement) -> bool {
+        let top = self.variable_top().copied();
+        move |ce| match (top, ce.weight_at_level_n(1)) {
+            (Some(top), Some(primary)) => primary.0 != 0 && *primary <= top,
+            _ => false,
+        }
+    }
+
+    /// All the collation elements of the mappings of the table.
+    fn collation_elements(&self) -> impl Iterator<Item = &CollationElement> {
+        self.0
+            .iter()
+            .flat_map(CollationElementMapping::collation_elements)
+    }
+
     /// >    UTS10-D26. Minimum Weight at a Level: The least weight in any collation element in a given collation element table, at a specified level.
     /// >
     /// >>    The minimum weight at level n is abbreviated with the notation: MINn.
-    pub fn min_weight_at_l_n(&self, n: usize) -> &CollationWeight {
-        self.0
-            .iter()
-            .map(|c| c.weight_at_level_n(n - 1))
+    ///
+    /// Returns `None` if `n` is `0` or the table has no collation element.
+    pub fn min_weight_at_l_n(&self, n: usize) -> Option<&CollationWeight> {
+        self.collation_elements()
+            .filter_map(|ce| ce.weight_at_level_n(n))
             .min()
-            .unwrap()
     }
+
     /// > UTS10-D27. Maximum Weight at a Level: The greatest weight in any collation element in a given collation element table, at a specified level.
     /// >
     /// >> The maximum weight at level n is abbreviated with the notation: MAXn.
-    pub fn max_weight_at_l_n(&self, n: usize) -> &CollationWeight {
-        self.0
-            .iter()
-            .map(|c| c.weight_at_level_n(n - 1))
+    ///
+    /// Returns `None` if `n` is `0` or the table has no collation element.
+    pub fn max_weight_at_l_n(&self, n: usize) -> Option<&CollationWeight> {
+        self.collation_elements()
+            .filter_map(|ce| ce.weight_at_level_n(n))
             .max()
-            .unwrap()
     }
-}
 
-impl CollationElementMapping {
-    pub(crate) fn weight_at_level_n(&self, n: usize) -> &CollationWeight {
-        let n = if n < 1 { 1 } else { n };
-        match self {
-            Self::SimpleMapping {
-                collation_element, ..
-            }
-            | Self::ManyToOneMapping {
-                collation_element, ..
-            } => collation_element.weight_at_level_n(n - 1),
-            Self::Expansion {
-                collation_elements, ..
+    /// The least non-ignorable weight at level `n` of the primary collation elements, the weight
+    /// of the unaccented, lowercase letters at levels 2 and 3.
+    fn common_weight_at_l_n(&self, n: usize) -> Option<&CollationWeight> {
+        self.collation_elements()
+            .filter(|ce| ce.is_primary_collation_element())
+            .filter_map(|ce| ce.weight_at_level_n(n))
+            .filter(|w| !w.is_ignorable())
+            .min()
+    }
+
+    /// The common secondary weight, `0020` in the DUCET.
+    pub fn common_secondary(&self) -> Option<&CollationWeight> {
+        self.common_weight_at_l_n(2)
+    }
+
+    /// The common tertiary weight, `0002` in the DUCET.
+    pub fn common_tertiary(&self) -> Option<&CollationWeight> {
+        self.common_weight_at_l_n(3)
+    }
+
+    /// The greatest primary weight of the variable collation elements of the table, if any.
+    pub fn variable_top(&self) -> Option<&CollationWeight> {
+        self.collation_elements()
+            .filter(|ce| ce.is_variable())
+            .filter_map(|ce| ce.weight_at_level_n(1))
+            .max()
+    }
+
+    /// The greatest primary weight of the characters of the groups up to `max_variable`: the
+    /// primary weight just before the first character of the next group, or the
+    /// [`variable_top`](CollationElementTable::variable_top) of the table for
+    /// [`MaxVariable::Symbol`].
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use collate_rs::definitions::collation_wel::CollationWeight;
+    /// use collate_rs::definitions::variable_weighting::MaxVariable;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let table = collator.table();
+    /// assert_eq!(table.max_variable_top(MaxVariable::Space), Some(CollationWeight::from(0x0209)));
+    /// assert_eq!(table.max_variable_top(MaxVariable::Symbol), table.variable_top().copied());
+    /// ```
+    pub fn max_variable_top(&self, max_variable: MaxVariable) -> Option<CollationWeight> {
+        match max_variable.next_group_first() {
+            Some(c) => {
+                let first = self.explicit_mapping(&[c])?.collation_elements().first()?;
+                first
+                    .weight_at_level_n(1)?
+                    .0
+                    .checked_sub(1)
+                    .map(CollationWeight)
             }
-            | Self::ManyToManyMapping {
-                collation_elements, ..
-            } => collation_elements
-                .iter()
-                .map(|ce| ce.weight_at_level_n(n - 1))
-                .min()
-                .unwrap(),
+            None => self.variable_top().copied(),
         }
     }
-}
 
-impl CollationElement {
-    pub(crate) fn weight_at_level_n(&self, n: usize) -> &CollationWeight {
-        let n = if n < 1 { 1 } else { n };
-        if let Some(cw) = self.0.get(n - 1) {
-            cw
-        } else {
-            panic!("Unavailable level n")
+    /// The least and greatest primary weights of the variable collation elements of the table,
+    /// if any.
+    pub fn variable_range(&self) -> Option<RangeInclusive<CollationWeight>> {
+        let mut primaries = self
+            .collation_elements()
+            .filter(|ce| ce.is_variable())
+            .filter_map(|ce| ce.weight_at_level_n(1));
+        let first = *primaries.next()?;
+        let (low, high) =
+            primaries.fold((first, first), |(low, high), &w| (low.min(w), high.max(w)));
+        Some(low..=high)
+    }
+
+    /// The range of the primary weights after `weight` not used by any collation element of the
+    /// table, up to the next used primary weight, or `None` if the weight right after `weight` is
+    /// used.
+    ///
+    /// The primary weights from `FB00` are reserved for the implicit weights (see
+    /// [`implicit_weights`]), so that the range does not go past `FAFF`.
+    ///
+    /// ```
+    /// use collate_rs::data_files::parse_allkeys;
+    /// use collate_rs::definitions::collation_wel::CollationWeight;
+    ///
+    /// let table = parse_allkeys(
+    ///     "0061 ; [.1FA2.0020.0002]\n\
+    ///      0062 ; [.1FA3.0020.0002]\n\
+    ///      0063 ; [.1FD6.0020.0002]\n",
+    /// )
+    /// .unwrap();
+    /// let w = CollationWeight::from;
+    /// assert_eq!(table.free_primary_range_after(w(0x1FA2)), None);
+    /// assert_eq!(table.free_primary_range_after(w(0x1FA3)), Some(w(0x1FA4)..=w(0x1FD5)));
+    /// assert_eq!(table.free_primary_range_after(w(0x1FD6)), Some(w(0x1FD7)..=w(0xFAFF)));
+    /// ```
+    pub fn free_primary_range_after(
+        &self,
+        weight: CollationWeight,
+    ) -> Option<RangeInclusive<CollationWeight>> {
+        let first = weight.0.saturating_add(1);
+        let next_used = self
+            .collation_elements()
+            .filter_map(|ce| ce.weight_at_level_n(1))
+            .map(|w| w.0)
+            .filter(|&w| w >= first)
+            .min()
+            .map_or(FIRST_IMPLICIT_PRIMARY, |w| w.min(FIRST_IMPLICIT_PRIMARY));
+        (first < next_used).then(|| CollationWeight(first)..=CollationWeight(next_used - 1))
+    }
+
+    /// The largest range of primary weights not used by any collation element of the table,
+    /// below the implicit weights, e.g. to give new primary weights to the letters of a tailoring.
+    ///
+    /// ```
+    /// use collate_rs::data_files::parse_allkeys;
+    /// use collate_rs::definitions::collation_wel::CollationWeight;
+    ///
+    /// let table = parse_allkeys("0061 ; [.1FA2.0020.0002]\n0062 ; [.1FBC.0020.0002]\n").unwrap();
+    /// let w = CollationWeight::from;
+    /// assert_eq!(table.largest_free_primary_range(), Some(w(0x1FBD)..=w(0xFAFF)));
+    /// ```
+    pub fn largest_free_primary_range(&self) -> Option<RangeInclusive<CollationWeight>> {
+        let mut primaries: Vec<u32> = self
+            .collation_elements()
+            .filter_map(|ce| ce.weight_at_level_n(1))
+            .map(|w| w.0)
+            .filter(|&w| w < FIRST_IMPLICIT_PRIMARY)
+            .collect();
+        primaries.push(0);
+        primaries.push(FIRST_IMPLICIT_PRIMARY);
+        primaries.sort_unstable();
+        primaries
+            .windows(2)
+            .filter(|w| w[1] - w[0] > 1)
+            .max_by_key(|w| w[1] - w[0])
+            .map(|w| CollationWeight(w[0] + 1)..=CollationWeight(w[1] - 1))
+    }
+
+    /// Drops the mappings of the characters outside `repertoire`, which then weigh with their
+    /// implicit weights, e.g. to shrink the table of an embedded deployment only sorting Latin,
+    /// Greek and Cyrillic strings.
+    ///
+    /// The characters of the canonical decompositions of the characters of `repertoire` are kept
+    /// too, as the strings are normalized to NFD before the mappings are looked up. A contraction
+    /// (or a contextual mapping) is kept if all its characters are, so that the contractions of the
+    /// prefixes of the contractions kept are kept (WF5); dropping mappings keeps the other
+    /// well-formedness conditions.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use collate_rs::unicode_set::UnicodeSet;
+    ///
+    /// let root = Collator::try_new("und").unwrap();
+    /// let mut table = root.table();
+    /// table.retain_repertoire(&UnicodeSet::parse("[\\u0000-\\u024F]").unwrap());
+    /// assert!(table.mappings().len() < root.table().mappings().len() / 10);
+    /// let latin = Collator::new(table);
+    /// assert_eq!(latin.sort_key("Ärger"), root.sort_key("Ärger"));
+    /// assert_ne!(latin.sort_key("Ωμέγα"), root.sort_key("Ωμέγα"));
+    /// ```
+    pub fn retain_repertoire(&mut self, repertoire: &UnicodeSet) {
+        let mut decompositions: Vec<(u32, u32)> = CANONICAL_DECOMPOSITIONS
+            .iter()
+            .filter(|&&(cp, _)| char::from_u32(cp).is_some_and(|c| repertoire.contains(c)))
+            .flat_map(|(_, decomposition)| decomposition.iter().map(|&cp| (cp, cp)))
+            .collect();
+        // The conjoining jamo of the Hangul syllables, decomposed algorithmically.
+        let syllables = UnicodeSet::from_range('\u{AC00}'..='\u{D7A3}');
+        if !repertoire.intersection(&syllables).is_empty() {
+            decompositions.extend([(0x1100, 0x1112), (0x1161, 0x1175), (0x11A8, 0x11C2)]);
         }
+        let kept = repertoire.union(&UnicodeSet::from_code_points(decompositions));
+        self.0.retain(|mapping| {
+            mapping
+                .prefix()
+                .iter()
+                .chain(mapping.characters())
+                .all(|&c| kept.contains(c))
+        });
     }
 }
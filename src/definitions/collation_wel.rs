@@ -1,16 +1,104 @@
+use smallvec::SmallVec;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Error of the conversion of the UTS10 notation of weights and collation elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The weight is not a hexadecimal number.
+    InvalidWeight(String),
+    /// The weight does not fit in 16 bits, as the weights of the DUCET.
+    WeightOutOfRange(String),
+    /// The collation element is not of the form `[.XXXX.XXXX.XXXX]`.
+    InvalidCollationElement(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidWeight(s) => write!(f, "Invalid collation weight {:?}.", s),
+            Self::WeightOutOfRange(s) => {
+                write!(f, "Collation weight {:?} does not fit in 16 bits.", s)
+            }
+            Self::InvalidCollationElement(s) => write!(f, "Invalid collation element {:?}.", s),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
 /// > UTS10-D1. Collation Weight: A non-negative integer used in the UCA to establish a means for systematic comparison of constructed sort keys.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct CollationWeight(pub(crate) u32);
 
+/// The largest weight of the notation of UTS10, of four hexadecimal digits.
+pub(crate) const MAX_WEIGHT: u32 = 0xFFFF;
+
+impl CollationWeight {
+    /// The ignorable weight, `0000` (UTS10-D8).
+    pub const IGNORABLE: Self = Self(0);
+
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+
+    /// The weight `n` after this one, or `None` past `FFFF`, e.g. to weight the characters
+    /// following a character of a tailoring.
+    ///
+    /// ```
+    /// use collate_rs::definitions::collation_wel::CollationWeight;
+    ///
+    /// let w: CollationWeight = "1C47".parse().unwrap();
+    /// assert_eq!(w.checked_add(2).unwrap().to_string(), "1C49");
+    /// assert_eq!("FFFF".parse::<CollationWeight>().unwrap().checked_add(1), None);
+    /// assert_eq!(w.checked_sub(0x1C47), Some(CollationWeight::IGNORABLE));
+    /// ```
+    pub fn checked_add(self, n: u32) -> Option<Self> {
+        self.0.checked_add(n).filter(|&w| w <= MAX_WEIGHT).map(Self)
+    }
+
+    /// The weight `n` before this one, or `None` before `0000`.
+    pub fn checked_sub(self, n: u32) -> Option<Self> {
+        self.0.checked_sub(n).map(Self)
+    }
+}
+
+/// Displays the weight as four hexadecimal digits, as in UTS10 and allkeys.txt, e.g. `06D9`.
+impl fmt::Display for CollationWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04X}", self.0)
+    }
+}
+
 impl From<&CollationWeight> for u32 {
     fn from(cw: &CollationWeight) -> Self {
         cw.0
     }
 }
 
-impl From<&str> for CollationWeight {
-    fn from(s: &str) -> Self {
-        Self(u32::from_str_radix(s, 16).unwrap())
+/// Parses a weight written in hexadecimal, as in allkeys.txt, e.g. `"06D9"`.
+impl TryFrom<&str> for CollationWeight {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        if s.is_empty() || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseError::InvalidWeight(s.to_string()));
+        }
+        match u32::from_str_radix(s, 16) {
+            Ok(w) if w <= 0xFFFF => Ok(Self(w)),
+            _ => Err(ParseError::WeightOutOfRange(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for CollationWeight {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
     }
 }
 
@@ -20,13 +108,66 @@ impl From<u32> for CollationWeight {
     }
 }
 
+/// The weights of a collation element, inline up to four: the levels of the DUCET and the
+/// quaternary weight of the variable weighting.
+pub(crate) type Weights = SmallVec<[CollationWeight; 4]>;
+
 /// > UTS10-D2. Collation Element: An ordered list of collation weights.
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct CollationElement(pub(crate) Vec<CollationWeight>);
+///
+/// Collation elements are displayed (and debugged) in the notation of UTS10, e.g.
+/// `[.06D9.0020.0002]`, or `[*0209.0020.0002]` for a variable collation element (see
+/// [`CollationElement::is_variable`]).
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "String", try_from = "String"))]
+pub struct CollationElement(
+    pub(crate) Weights,
+    /// Whether the collation element is variable, as marked by `*` in allkeys.txt.
+    pub(crate) bool,
+);
+
+/// The weight of the levels past the last level of a collation element.
+const PADDING_WEIGHT: &CollationWeight = &CollationWeight(0);
 
 impl CollationElement {
     pub fn new(weights: Vec<CollationWeight>) -> Self {
-        Self(weights)
+        Self(weights.into(), false)
+    }
+
+    /// A variable collation element (UTS10-D16) of the given weights.
+    ///
+    /// Fails if the primary weight is ignorable, as only primary collation elements can be
+    /// variable.
+    pub fn variable(weights: Vec<CollationWeight>) -> Result<Self, ParseError> {
+        Self::with_weights(weights.into(), true)
+    }
+
+    /// Like [`CollationElement::new`] or [`CollationElement::variable`], of weights already
+    /// stored inline.
+    pub(crate) fn with_weights(weights: Weights, variable: bool) -> Result<Self, ParseError> {
+        if variable && weights.first().is_none_or(|w| w.0 == 0) {
+            return Err(ParseError::InvalidCollationElement(
+                Self(weights, true).to_string(),
+            ));
+        }
+        Ok(Self(weights, variable))
+    }
+
+    /// The number of levels of the collation element.
+    pub fn len_levels(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The weight at level `n` (counted from 1), or `None` if `n` is `0`.
+    ///
+    /// A collation element has ignorable weights at the levels past its last level, as when
+    /// building sort keys (see [`SortKey::new`](crate::algorithm::SortKey::new)): the weight at
+    /// level 4 of `[.06D9.0020.0002]` is `0000`.
+    pub fn weight_at_level_n(&self, n: usize) -> Option<&CollationWeight> {
+        match n {
+            0 => None,
+            n => Some(self.0.get(n - 1).unwrap_or(PADDING_WEIGHT)),
+        }
     }
 
     pub fn levels(&self) -> Vec<CollationLevel> {
@@ -40,14 +181,104 @@ impl CollationElement {
     }
 }
 
-impl From<&str> for CollationElement {
-    fn from(s: &str) -> Self {
-        let mut weights = vec![];
-        let s = s.trim_start_matches("[.").trim_end_matches(']');
-        for w in s.split('.') {
-            weights.push(w.try_into().unwrap());
+/// The weights of a collation element, for the steps of the algorithm shared by the
+/// [`CollationElement`]s and the packed collation elements of the
+/// [`Collator`](crate::algorithm::Collator): variable weighting and sort keys.
+pub(crate) trait Weighted {
+    fn len_levels(&self) -> usize;
+
+    /// The weight at level `n` (counted from 1), ignorable past the last level.
+    fn weight(&self, n: usize) -> u32;
+
+    /// Sets the weight at level `n` (counted from 1), adding ignorable levels up to `n`.
+    fn set_weight(&mut self, n: usize, weight: u32);
+
+    /// Marks the collation element as not variable, once weighted.
+    fn clear_variable(&mut self);
+}
+
+impl Weighted for CollationElement {
+    fn len_levels(&self) -> usize {
+        self.0.len()
+    }
+
+    fn weight(&self, n: usize) -> u32 {
+        self.0.get(n - 1).map_or(0, |w| w.0)
+    }
+
+    fn set_weight(&mut self, n: usize, weight: u32) {
+        if self.0.len() < n {
+            self.0.resize(n, CollationWeight(0));
+        }
+        self.0[n - 1] = CollationWeight(weight);
+    }
+
+    fn clear_variable(&mut self) {
+        self.1 = false;
+    }
+}
+
+impl fmt::Display for CollationElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, w) in self.0.iter().enumerate() {
+            let separator = if i == 0 && self.1 { '*' } else { '.' };
+            write!(f, "{}{}", separator, w)?;
         }
-        Self(weights)
+        write!(f, "]")
+    }
+}
+
+impl fmt::Debug for CollationElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Parses a collation element in the notation of UTS10, e.g. `"[.06D9.0020.0002]"`, or
+/// `"[*0209.0020.0002]"` for a variable collation element.
+impl TryFrom<&str> for CollationElement {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let invalid = || ParseError::InvalidCollationElement(s.to_string());
+        let (variable, weights) = match s.strip_suffix(']') {
+            Some(s) => match (s.strip_prefix("[."), s.strip_prefix("[*")) {
+                (Some(weights), _) => (false, weights),
+                (_, Some(weights)) => (true, weights),
+                _ => return Err(invalid()),
+            },
+            None => return Err(invalid()),
+        };
+        let weights = weights
+            .split('.')
+            .map(CollationWeight::try_from)
+            .collect::<Result<_, _>>()?;
+        Self::with_weights(weights, variable).map_err(|_| invalid())
+    }
+}
+
+impl FromStr for CollationElement {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<CollationElement> for String {
+    fn from(ce: CollationElement) -> Self {
+        ce.to_string()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for CollationElement {
+    type Error = ParseError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::try_from(s.as_str())
     }
 }
 
@@ -79,19 +310,30 @@ pub enum CollationLevel {
     ///
     /// >> A quaternary weight is also called the Level 4 weight. Level 4 is also abbreviated as L4.
     QuarternaryWeight(CollationWeight),
-    // > In principle, collation levels can extend past Level 4 to add additional levels, but the specification of the Unicode Collation Algorithm does not require defining more levels. In some special cases, such as support of Japanese collation, an implementation may need to define additional levels.
-    // NWeight { i: u32, w: CollationWeight },
+    /// > In principle, collation levels can extend past Level 4 to add additional levels, but the specification of the Unicode Collation Algorithm does not require defining more levels. In some special cases, such as support of Japanese collation, an implementation may need to define additional levels.
+    ///
+    /// The weight at a `level` past the fourth, e.g. the width of the kana in the Japanese
+    /// tailoring.
+    NWeight {
+        level: usize,
+        weight: CollationWeight,
+    },
 }
 
 impl From<usize> for CollationLevel {
+    /// ## Panics
+    /// If the value is `0`, as levels are counted from 1.
     fn from(value: usize) -> Self {
         match value {
+            0 => panic!("Collation levels are counted from 1."),
             1 => Self::PrimaryWeight(0.into()),
             2 => Self::SecondaryWeight(0.into()),
             3 => Self::TertiaryWeight(0.into()),
             4 => Self::QuarternaryWeight(0.into()),
-            _ => unimplemented!(),
-            // n => Ok(Self::NWeight { i: n, w: 0.into() }),
+            level => Self::NWeight {
+                level,
+                weight: 0.into(),
+            },
         }
     }
 }
@@ -99,7 +341,7 @@ impl From<usize> for CollationLevel {
 impl CollationLevel {
     pub(crate) fn set_weight<T: Into<CollationWeight>>(&mut self, w: T) {
         match self {
-            // CollationLevel::NWeight { i: _, w: weight } => *weight = w.into(),
+            CollationLevel::NWeight { weight, .. } => *weight = w.into(),
             CollationLevel::PrimaryWeight(weight) => *weight = w.into(),
             CollationLevel::SecondaryWeight(weight) => *weight = w.into(),
             CollationLevel::TertiaryWeight(weight) => *weight = w.into(),
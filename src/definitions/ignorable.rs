@@ -7,6 +7,55 @@ pub trait Ignorable {
     fn is_ignorable(&self) -> bool;
 }
 
+/// A sequence is ignorable if all its items are, e.g. the weights of a level.
+impl<T: Ignorable> Ignorable for [T] {
+    fn is_ignorable(&self) -> bool {
+        self.iter().all(Ignorable::is_ignorable)
+    }
+}
+
+/// Evaluates whether the weights of a given level are ignorable, for the parameterized
+/// expressions of UTS10-D15, e.g. whether the collation elements of a string are all primary
+/// ignorable, so that it matches nothing in a primary search (UTS10 §8).
+///
+/// It is implemented by the collation elements, whose levels past the last one are ignorable as
+/// in the sort keys, and by their sequences, e.g. the collation element arrays of strings:
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::definitions::ignorable::IgnorableAtLevel;
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// let accents = collator.collation_element_array("\u{301}\u{308}");
+/// assert!(accents.is_ignorable_at_level(1));
+/// assert!(!accents.is_ignorable_up_to_level(2));
+/// assert!(!collator.collation_element_array("a\u{301}").is_ignorable_at_level(1));
+/// ```
+pub trait IgnorableAtLevel {
+    /// Whether the weights at level `n`, counted from 1, are ignorable: `false` for `n == 0`, as
+    /// there is no weight at level 0.
+    fn is_ignorable_at_level(&self, n: usize) -> bool;
+
+    /// Whether the weights of all the levels from 1 to `n` are ignorable, e.g. at the levels of a
+    /// collator of strength `n`.
+    fn is_ignorable_up_to_level(&self, n: usize) -> bool {
+        (1..=n).all(|level| self.is_ignorable_at_level(level))
+    }
+}
+
+impl<W: Weighted> IgnorableAtLevel for W {
+    fn is_ignorable_at_level(&self, n: usize) -> bool {
+        n != 0 && self.weight(n) == 0
+    }
+}
+
+/// A sequence of collation elements is ignorable at a level if all of them are.
+impl<T: IgnorableAtLevel> IgnorableAtLevel for [T] {
+    fn is_ignorable_at_level(&self, n: usize) -> bool {
+        n != 0 && self.iter().all(|ce| ce.is_ignorable_at_level(n))
+    }
+}
+
 /// > UTS10-D8. Ignorable Weight: A collation weight whose value is zero.
 /// >
 /// >>  In the 4-digit hexadecimal format used in this specification, ignorable weights are expressed as "0000".
@@ -23,7 +72,7 @@ impl Ignorable for CollationWeight {
 impl Ignorable for CollationLevel {
     fn is_ignorable(&self) -> bool {
         let w: u32 = match self {
-            // Self::NWeight { i: _, w } => w.into(),
+            Self::NWeight { weight, .. } => weight.into(),
             Self::PrimaryWeight(w) => w.into(),
             Self::SecondaryWeight(w) => w.into(),
             Self::TertiaryWeight(w) => w.into(),
@@ -38,17 +87,11 @@ impl CollationElement {
     ///
     /// > N Collation Element: A collation element whose Level N weight is not an ignorable weight.
     ///
-    /// ## Panics
-    /// If the value `n == 0`, as N is a non-zero positive integer.
+    /// The weights past the last level are ignorable, as in
+    /// [`CollationElement::weight_at_level_n`], and there is no weight at level 0, so that no
+    /// collation element is an N collation element for these levels.
     pub fn is_n_collation_element(&self, n: usize) -> bool {
-        if n == 0 {
-            panic!("N positions should be non-zero integers.")
-        }
-        if let Some(w) = self.0.get(n - 1) {
-            !w.is_ignorable()
-        } else {
-            true
-        }
+        self.weight_at_level_n(n).is_some_and(|w| !w.is_ignorable())
     }
 
     /// > UTS10-D9. Primary Collation Element: A collation element whose Level 1 weight is not an ignorable weight.
@@ -76,25 +119,18 @@ impl CollationElement {
     /// > UTS10-D15. Level N Ignorable: A collation element which has an ignorable weight at level N, but not at level N+1.
     /// >
     /// >> This concept is useful for parameterized expressions with weight level as a parameter. For example "Level 1 ignorable" is a synonym for a secondary collation element. This alternate terminology is generally avoided in this specification, however, because of the potential for confusion.
+    ///
+    /// As in the table of UTS10-D16, the primary collation elements are Level 0 ignorable, and at
+    /// the last level, there is no level N+1 to be non-ignorable, so that a completely ignorable
+    /// collation element of four levels is Level 4 ignorable. The weights past the last level are
+    /// ignorable, as in [`CollationElement::weight_at_level_n`], so that no collation element is
+    /// Level N ignorable past its last level.
     pub fn is_n_ignorable(&self, n: usize) -> bool {
-        if n == 0 {
-            panic!("N positions should be non-zero integers.")
-        } else if n > self.0.len() {
-            panic!(
-                "N ({}) position excedes lenght of collation element ({}).",
-                n,
-                self.0.len()
-            )
-        }
-
-        if let (Some(w_n), Some(w_n1)) = (self.0.get(n - 1), self.0.get(n)) {
-            w_n.is_ignorable() && !w_n1.is_ignorable()
-        } else if let Some(w_n) = self.0.get(n - 1) {
-            assert!(n == self.0.len());
-            w_n.is_ignorable()
-        } else {
-            todo!()
-        }
+        let ignorable = |n| {
+            self.weight_at_level_n(n)
+                .is_none_or(Ignorable::is_ignorable)
+        };
+        ignorable(n) && (n == self.len_levels() || !ignorable(n + 1))
     }
 }
 
@@ -107,30 +143,27 @@ impl Ignorable for CollationElement {
     }
 }
 
-// > UTS10-D16. Variable Collation Element: A primary collation element with a low (but non-zero) value for its primary weight.
-// >
-// >> Low primary weights are generally reserved for punctuation and symbols, to enable special handling of those kinds of characters. Variable collation elements are subject to special rules when constructing sort keys. See Section 4, Variable Weighting. In the Default Unicode Collation Element Table [Allkeys](https://www.unicode.org/reports/tr10/#Allkeys) the primary weights of all variable collation elements are prefixed with an asterisk instead of a dot, so that they can be clearly identified.
-// >
-// > The relationship between these terms for patterns of ignorable weights in collation elements, together with schematic examples of the corresponding collation elements, is shown in the following table, constructed on the assumption that collation elements have four collation levels. Note that quaternary collation elements have the same schematic pattern of weights as variable collation elements which have been shifted.
-//
-// | Schematic Example      | Main Term                                | General Type  | Level Notation    |
-// |------------------------|------------------------------------------|---------------|-------------------|
-// | [.nnnn.nnnn.nnnn.nnnn] | Primary Collation Element                | Non-ignorable | Level 0 Ignorable |
-// | [*nnnn.nnnn.nnnn.nnnn] | Variable Collation Element (not shifted) | Non-ignorable | Level 0 Ignorable |
-// | [.0000.nnnn.nnnn.nnnn] | Secondary Collation Element              | Ignorable     | Level 1 Ignorable |
-// | [.0000.0000.nnnn.nnnn] | Tertiary Collation Element               | Ignorable     | Level 2 Ignorable |
-// | [.0000.0000.0000.nnnn] | Quaternary Collation Element             | Ignorable     | Level 3 Ignorable |
-// | [.0000.0000.0000.nnnn] | Variable Collation Element (shifted)     | Ignorable     | Level 3 Ignorable |
-// | [.0000.0000.0000.0000] | Completely Ignorable Collation Element   | Ignorable     | Level 4 Ignorable |
-//
-// TODO: Assert is adequate
-//
-// pub struct VariableCollationElement {
-//     collation_element: CollationElement,
-// }
-//
-// impl VariableCollationElement {
-//     pub fn collation_element(&self) -> &CollationElement {
-//         &self.collation_element
-//     }
-// }
+impl CollationElement {
+    /// > UTS10-D16. Variable Collation Element: A primary collation element with a low (but non-zero) value for its primary weight.
+    /// >
+    /// >> Low primary weights are generally reserved for punctuation and symbols, to enable special handling of those kinds of characters. Variable collation elements are subject to special rules when constructing sort keys. See Section 4, Variable Weighting. In the Default Unicode Collation Element Table [Allkeys](https://www.unicode.org/reports/tr10/#Allkeys) the primary weights of all variable collation elements are prefixed with an asterisk instead of a dot, so that they can be clearly identified.
+    /// >
+    /// > The relationship between these terms for patterns of ignorable weights in collation elements, together with schematic examples of the corresponding collation elements, is shown in the following table, constructed on the assumption that collation elements have four collation levels. Note that quaternary collation elements have the same schematic pattern of weights as variable collation elements which have been shifted.
+    ///
+    /// | Schematic Example      | Main Term                                | General Type  | Level Notation    |
+    /// |------------------------|------------------------------------------|---------------|-------------------|
+    /// | [.nnnn.nnnn.nnnn.nnnn] | Primary Collation Element                | Non-ignorable | Level 0 Ignorable |
+    /// | [*nnnn.nnnn.nnnn.nnnn] | Variable Collation Element (not shifted) | Non-ignorable | Level 0 Ignorable |
+    /// | [.0000.nnnn.nnnn.nnnn] | Secondary Collation Element              | Ignorable     | Level 1 Ignorable |
+    /// | [.0000.0000.nnnn.nnnn] | Tertiary Collation Element               | Ignorable     | Level 2 Ignorable |
+    /// | [.0000.0000.0000.nnnn] | Quaternary Collation Element             | Ignorable     | Level 3 Ignorable |
+    /// | [.0000.0000.0000.nnnn] | Variable Collation Element (shifted)     | Ignorable     | Level 3 Ignorable |
+    /// | [.0000.0000.0000.0000] | Completely Ignorable Collation Element   | Ignorable     | Level 4 Ignorable |
+    ///
+    /// Collation elements are variable if marked as such, that is parsed with `*` or built with
+    /// [`CollationElement::variable`]; see also
+    /// [`CollationElementTable::variable_classifier`](crate::definitions::collation_element_tables::CollationElementTable::variable_classifier).
+    pub fn is_variable(&self) -> bool {
+        self.1
+    }
+}
@@ -0,0 +1,145 @@
+//! The definitions of this file summarize (rather than quote) UTS10-D28-D35.
+
+use crate::data_files::ucd::canonical_combining_class;
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::mappings::CollationElementMapping;
+
+/// UTS10-D28. Input Match: the association of characters of an input string with a collation
+/// element mapping whose input is exactly those characters, in order.
+///
+/// An input match records the mapping and the positions, in the input, of the characters it
+/// matched (strictly increasing).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputMatch<'t> {
+    mapping: &'t CollationElementMapping,
+    positions: Vec<usize>,
+}
+
+impl<'t> InputMatch<'t> {
+    /// The match of `mapping` at the positions `positions` of `input`, if the characters at those
+    /// positions are the input of the mapping.
+    ///
+    /// ```
+    /// use collate_rs::definitions::collation_wel::CollationElement;
+    /// use collate_rs::definitions::input_matching::InputMatch;
+    /// use collate_rs::definitions::mappings::CollationElementMapping;
+    ///
+    /// let ce = CollationElement::try_from("[.1D19.0020.0002]").unwrap();
+    /// let ch = CollationElementMapping::new(&['c', 'h'], &[ce]).unwrap();
+    /// let input = ['c', '\u{323}', 'h'];
+    /// assert!(InputMatch::new(&ch, &input, vec![0, 2]).unwrap().is_discontiguous_match());
+    /// assert!(InputMatch::new(&ch, &input, vec![0, 1]).is_none());
+    /// ```
+    pub fn new(
+        mapping: &'t CollationElementMapping,
+        input: &[char],
+        positions: Vec<usize>,
+    ) -> Option<Self> {
+        let increasing = positions.windows(2).all(|w| w[0] < w[1]);
+        let matches = positions.len() == mapping.characters().len()
+            && positions
+                .iter()
+                .zip(mapping.characters())
+                .all(|(&i, c)| input.get(i) == Some(c));
+        (increasing && matches).then_some(Self { mapping, positions })
+    }
+
+    pub fn mapping(&self) -> &'t CollationElementMapping {
+        self.mapping
+    }
+
+    pub fn positions(&self) -> &[usize] {
+        &self.positions
+    }
+
+    /// UTS10-D29. Single Character Match: an input match of a single character.
+    pub fn is_single_character_match(&self) -> bool {
+        self.positions.len() == 1
+    }
+
+    /// UTS10-D30. Contraction Match: an input match of more than one character, i.e. of a
+    /// [contraction](CollationElementMapping::is_contraction).
+    pub fn is_contraction_match(&self) -> bool {
+        self.positions.len() > 1
+    }
+
+    /// UTS10-D31. Contiguous Match: a contraction match of characters adjacent in the input.
+    pub fn is_contiguous_match(&self) -> bool {
+        self.is_contraction_match() && self.positions.windows(2).all(|w| w[1] == w[0] + 1)
+    }
+
+    /// UTS10-D32. Discontiguous Match: a contraction match of characters that are not all adjacent
+    /// in the input, the characters skipped being unblocked non-starters.
+    pub fn is_discontiguous_match(&self) -> bool {
+        self.is_contraction_match() && !self.is_contiguous_match()
+    }
+}
+
+impl CollationElementTable {
+    /// UTS10-D33. Longest Match: the contiguous input match of the most characters at the start of
+    /// `input`.
+    ///
+    /// This is a linear search, see [`Collator`](crate::algorithm::Collator) for the indexed
+    /// lookup of the main algorithm, which also matches the
+    /// [contextual mappings](CollationElementMapping::ContextualMapping) left out here.
+    pub fn longest_match<'t>(&'t self, input: &[char]) -> Option<InputMatch<'t>> {
+        self.0
+            .iter()
+            .filter(|mapping| mapping.prefix().is_empty())
+            .filter(|mapping| input.starts_with(mapping.characters()))
+            .max_by_key(|mapping| mapping.characters().len())
+            .map(|mapping| InputMatch {
+                mapping,
+                positions: (0..mapping.characters().len()).collect(),
+            })
+    }
+}
+
+/// UTS10-D34. Non-Starter: a character whose Canonical_Combining_Class is not zero.
+///
+/// ```
+/// use collate_rs::definitions::input_matching::is_non_starter;
+///
+/// assert!(is_non_starter('\u{301}'));
+/// assert!(!is_non_starter('a'));
+/// ```
+pub fn is_non_starter(c: char) -> bool {
+    canonical_combining_class(c) != 0
+}
+
+/// UTS10-D35. Unblocked Non-Starter: a non-starter that is not blocked from the last starter
+/// before it, i.e. such that no character between them is a starter or has a canonical combining
+/// class greater than or equal to its own.
+///
+/// Returns whether `input[i]` is an unblocked non-starter of `input`.
+///
+/// ```
+/// use collate_rs::definitions::input_matching::is_unblocked_non_starter;
+///
+/// // COMBINING DOT BELOW (220), COMBINING ACUTE ACCENT (230)
+/// assert!(is_unblocked_non_starter(&['a', '\u{323}', '\u{301}'], 2));
+/// // COMBINING GRAVE ACCENT (230) is blocked by the acute accent.
+/// assert!(!is_unblocked_non_starter(&['a', '\u{301}', '\u{300}'], 2));
+/// ```
+pub fn is_unblocked_non_starter(input: &[char], i: usize) -> bool {
+    let Some(&c) = input.get(i) else {
+        return false;
+    };
+    let ccc = canonical_combining_class(c);
+    if ccc == 0 {
+        return false;
+    }
+    for previous in input[..i]
+        .iter()
+        .rev()
+        .map(|&c| canonical_combining_class(c))
+    {
+        if previous == 0 {
+            return true;
+        }
+        if previous >= ccc {
+            return false;
+        }
+    }
+    false
+}
@@ -1,5 +1,9 @@
 use crate::definitions::collation_wel::CollationElement;
-use anyhow::Result;
+use crate::error::Result;
+use smallvec::SmallVec;
+
+/// The collation elements of an expansion, inline up to two, as most expansions of the DUCET.
+pub type CollationElements = SmallVec<[CollationElement; 2]>;
 
 /// > UTS10-D17. Collation Element Mapping: A mapping from one (or more) Unicode characters to one (or more) collation elements.
 /// >
@@ -9,6 +13,7 @@ use anyhow::Result;
 /// >
 /// > Collation element mappings are divided into subtypes, based on a distinction between whether the input of the mapping constitutes a single Unicode character or a sequence of Unicode characters, and a separate distinction between whether the output of the mapping constitutes a single collation element or a sequence of collation elements.
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CollationElementMapping {
     /// > UTS10-D18. Simple Mapping: A collation element mapping from one Unicode character to one collation element.  collation_element: Vec<CollationElement>,
     SimpleMapping {
@@ -18,7 +23,7 @@ pub enum CollationElementMapping {
     /// > UTS10-D19. Expansion: A collation element mapping from one Unicode character to a sequence of more than one collation element.
     Expansion {
         character: char,
-        collation_elements: Vec<CollationElement>,
+        collation_elements: CollationElements,
     },
     /// > UTS10-D20. Many-to-One Mapping: A collation element mapping from more than one Unicode character to one collation element.
     ManyToOneMapping {
@@ -28,7 +33,16 @@ pub enum CollationElementMapping {
     /// > UTS10-D21. Many-to-Many Mapping: A collation element mapping from more than one Unicode character to a sequence of more than one collation element.
     ManyToManyMapping {
         characters: Vec<char>,
-        collation_elements: Vec<CollationElement>,
+        collation_elements: CollationElements,
+    },
+    /// A mapping of `characters` that only applies after the characters of `prefix`, as the
+    /// context-sensitive mappings `prefix | characters` of CLDR, e.g. the length mark `ー` taking
+    /// the vowel of the kana before it in Japanese. A mapping of the same characters without
+    /// prefix, if any, applies elsewhere.
+    ContextualMapping {
+        prefix: Vec<char>,
+        characters: Vec<char>,
+        collation_elements: CollationElements,
     },
 }
 
@@ -36,24 +50,98 @@ impl CollationElementMapping {
     pub fn new(characters: &[char], collation_elements: &[CollationElement]) -> Result<Self> {
         let mut cem = Self::ManyToManyMapping {
             characters: characters.to_vec(),
-            collation_elements: collation_elements.to_vec(),
+            collation_elements: collation_elements.into(),
         };
         cem.simplify()?;
         Ok(cem)
     }
 
+    /// A mapping of `characters` after `prefix` (see
+    /// [`CollationElementMapping::ContextualMapping`]), or the mapping of
+    /// [`CollationElementMapping::new`] if `prefix` is empty.
+    ///
+    /// ```
+    /// use collate_rs::definitions::collation_wel::CollationElement;
+    /// use collate_rs::definitions::mappings::CollationElementMapping;
+    ///
+    /// let ce = CollationElement::try_from("[.42DA.0020.0011]").unwrap();
+    /// // KATAKANA-HIRAGANA PROLONGED SOUND MARK after KATAKANA LETTER A
+    /// let mapping = CollationElementMapping::with_prefix(&['\u{30A2}'], &['\u{30FC}'], &[ce]).unwrap();
+    /// assert_eq!(mapping.prefix(), ['\u{30A2}']);
+    /// assert_eq!(mapping.characters(), ['\u{30FC}']);
+    /// ```
+    pub fn with_prefix(
+        prefix: &[char],
+        characters: &[char],
+        collation_elements: &[CollationElement],
+    ) -> Result<Self> {
+        if prefix.is_empty() {
+            return Self::new(characters, collation_elements);
+        }
+        Ok(Self::ContextualMapping {
+            prefix: prefix.to_vec(),
+            characters: characters.to_vec(),
+            collation_elements: collation_elements.into(),
+        })
+    }
+
     /// > UTS10-D22. Contraction: Either a many-to-one mapping or a many-to-many mapping.
+    ///
+    /// A contextual mapping is a contraction if it maps more than one character after its prefix.
     pub fn is_contraction(&self) -> bool {
-        matches!(
-            self,
-            Self::ManyToOneMapping { .. } | Self::ManyToManyMapping { .. }
-        )
+        match self {
+            Self::ManyToOneMapping { .. } | Self::ManyToManyMapping { .. } => true,
+            Self::ContextualMapping { characters, .. } => characters.len() > 1,
+            Self::SimpleMapping { .. } | Self::Expansion { .. } => false,
+        }
+    }
+
+    /// The characters that must precede the input of the mapping for it to apply, empty but for
+    /// the [`CollationElementMapping::ContextualMapping`]s.
+    pub fn prefix(&self) -> &[char] {
+        match self {
+            Self::ContextualMapping { prefix, .. } => prefix,
+            _ => &[],
+        }
+    }
+
+    /// The Unicode characters at the input of the mapping.
+    pub fn characters(&self) -> &[char] {
+        match self {
+            Self::SimpleMapping { character, .. } | Self::Expansion { character, .. } => {
+                std::slice::from_ref(character)
+            }
+            Self::ManyToOneMapping { characters, .. }
+            | Self::ManyToManyMapping { characters, .. }
+            | Self::ContextualMapping { characters, .. } => characters,
+        }
+    }
+
+    /// The collation elements at the output of the mapping.
+    pub fn collation_elements(&self) -> &[CollationElement] {
+        match self {
+            Self::SimpleMapping {
+                collation_element, ..
+            }
+            | Self::ManyToOneMapping {
+                collation_element, ..
+            } => std::slice::from_ref(collation_element),
+            Self::Expansion {
+                collation_elements, ..
+            }
+            | Self::ManyToManyMapping {
+                collation_elements, ..
+            }
+            | Self::ContextualMapping {
+                collation_elements, ..
+            } => collation_elements,
+        }
     }
 
     /// Helper function to convert misassigned [`CollationElementMapping`]s
     pub(crate) fn simplify(&mut self) -> Result<()> {
         match self {
-            Self::SimpleMapping { .. } => {}
+            Self::SimpleMapping { .. } | Self::ContextualMapping { .. } => {}
             Self::Expansion {
                 character,
                 collation_elements,
@@ -88,7 +176,7 @@ impl CollationElementMapping {
                 } else if characters.len() == 1 && collation_elements.len() > 1 {
                     *self = Self::Expansion {
                         character: characters[0],
-                        collation_elements: collation_elements.to_vec(),
+                        collation_elements: std::mem::take(collation_elements),
                     }
                 } else if characters.len() > 1 && collation_elements.len() == 1 {
                     *self = Self::ManyToOneMapping {
@@ -3,7 +3,8 @@
 /// The basic concepts of collation weights, collation elements, and collation levels are defined here first, as all other aspects of the Unicode Collation Algorithm depend fundamentally on those concepts.
 pub mod collation_wel;
 
-/// Defines the [`Ignorable`](`ignorable::Ignorable`) trait and functions related (Covering
+/// Defines the [`Ignorable`](`ignorable::Ignorable`) and
+/// [`IgnorableAtLevel`](`ignorable::IgnorableAtLevel`) traits and functions related (Covering
 /// UTS10-D8-D16).
 pub mod ignorable;
 
@@ -15,6 +16,25 @@ pub mod mappings;
 /// Defines Collation Element Tables
 pub mod collation_element_tables;
 
+/// Defines [`TableDiff`](`table_diff::TableDiff`), the differences between two collation element
+/// tables, e.g. before and after a tailoring.
+pub mod table_diff;
+
+/// Defines [`TableStats`](`table_stats::TableStats`), the counts of the mappings and collation
+/// elements of a collation element table.
+pub mod table_stats;
+
+/// Defines [`InputMatch`](`input_matching::InputMatch`), longest matches and non-starters
+/// (Covering UTS10-D28-D35).
+pub mod input_matching;
+
+/// Defines [`VariableWeighting`](`variable_weighting::VariableWeighting`) (Covering
+/// UTS10-D36-D39).
+pub mod variable_weighting;
+
+/// Defines the differences between sort keys and canonical equivalence (Covering UTS10-D40-D46).
+pub mod sort_keys;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -22,14 +42,41 @@ mod tests {
     fn test_3_1() {
         use collation_wel::*;
         // UTS10-D1
-        let a: CollationWeight = "06D9".into();
-        let b: CollationWeight = "0020".into();
-        let c: CollationWeight = "0002".into();
+        let a = CollationWeight::try_from("06D9").unwrap();
+        let b = CollationWeight::try_from("0020").unwrap();
+        let c = CollationWeight::try_from("0002").unwrap();
         // UTS10-D2
         let f1 = CollationElement::new(vec![a, b, c]);
         // UTS10-D3
-        let f2 = CollationElement::from("[.06D9.0020.0002]");
+        let f2 = CollationElement::try_from("[.06D9.0020.0002]").unwrap();
         assert_eq!(f2, f1);
+        assert_eq!(
+            CollationWeight::try_from("10000"),
+            Err(ParseError::WeightOutOfRange("10000".to_string()))
+        );
+        assert!(matches!(
+            CollationWeight::try_from("06G9"),
+            Err(ParseError::InvalidWeight(_))
+        ));
+        assert!(matches!(
+            CollationElement::try_from("[.06D9.0020"),
+            Err(ParseError::InvalidCollationElement(_))
+        ));
+        // The notation of UTS10, displayed and parsed back.
+        assert_eq!(a.to_string(), "06D9");
+        assert_eq!(CollationWeight::from(0x20).to_string(), "0020");
+        assert_eq!("06D9".parse(), Ok(a));
+        for ce in [
+            "[.06D9.0020.0002]",
+            "[*0209.0020.0002]",
+            "[.0000.0000.0000]",
+        ] {
+            assert_eq!(ce.parse::<CollationElement>().unwrap().to_string(), ce);
+        }
+        assert!("[*0000.0020.0002]".parse::<CollationElement>().is_err());
+        assert_eq!(b.checked_add(4), Some(CollationWeight::from(0x24)));
+        assert_eq!(c.checked_sub(3), None);
+        assert_eq!(CollationWeight::IGNORABLE.value(), 0);
 
         // UTS10-D4-7
         let f1 = CollationElement::new(vec![a, a, a, a]);
@@ -50,19 +97,26 @@ mod tests {
         assert_eq!(levels[1], expected[1]);
         assert_eq!(levels[2], expected[2]);
         assert_eq!(levels[3], expected[3]);
+        assert_eq!(
+            CollationElement::new(vec![a; 5]).levels()[4],
+            CollationLevel::NWeight {
+                level: 5,
+                weight: a
+            }
+        );
     }
     #[test]
     fn test_3_2() {
         use collation_wel::*;
         use ignorable::*;
         // UTS10-D8
-        let a: CollationWeight = "0000".into();
+        let a = CollationWeight::try_from("0000").unwrap();
         assert!(a.is_ignorable());
 
-        let a: CollationWeight = "06D9".into();
-        let b: CollationWeight = "0020".into();
-        let c: CollationWeight = "0002".into();
-        let d: CollationWeight = "0002".into();
+        let a = CollationWeight::try_from("06D9").unwrap();
+        let b = CollationWeight::try_from("0020").unwrap();
+        let c = CollationWeight::try_from("0002").unwrap();
+        let d = CollationWeight::try_from("0002").unwrap();
         let f1 = CollationElement::new(vec![a, b, c, d]);
         // UTS10-D9-12
         assert!(f1.is_primary_collation_element());
@@ -70,7 +124,7 @@ mod tests {
         assert!(f1.is_tertiary_collation_element());
         assert!(f1.is_quarternary_collation_element());
 
-        let a: CollationWeight = "0000".into();
+        let a = CollationWeight::try_from("0000").unwrap();
         let f1 = CollationElement::new(vec![a, a, a, a]);
         // UTS10-D13
         assert!(f1.is_completely_ignorable_collation_element());
@@ -79,12 +133,36 @@ mod tests {
         // UTS10-D15
         assert!(!f1.is_n_ignorable(1));
         assert!(f1.is_n_ignorable(4));
+        assert!(!f1.is_n_ignorable(0));
+        assert!(!f1.is_n_ignorable(5));
+        assert!(!f1.is_n_collation_element(0) && !f1.is_n_collation_element(5));
 
-        let a: CollationWeight = "0000".into();
-        let b: CollationWeight = "0020".into();
+        let a = CollationWeight::try_from("0000").unwrap();
+        let b = CollationWeight::try_from("0020").unwrap();
         let f1 = CollationElement::new(vec![a, b]);
         // UTS10-D15
         assert!(f1.is_n_ignorable(1));
+        assert!(!f1.is_n_ignorable(2));
+        assert!(!f1.is_n_ignorable(3));
+        // Past the last level, the weights are ignorable.
+        assert!(f1.is_secondary_collation_element() && !f1.is_tertiary_collation_element());
+        assert_eq!(f1.len_levels(), 2);
+        assert_eq!(f1.weight_at_level_n(2), Some(&b));
+        // Levels past the last level have ignorable weights.
+        assert_eq!(f1.weight_at_level_n(3), Some(&a));
+        assert_eq!(f1.weight_at_level_n(0), None);
+        // UTS10-D15, for sequences of collation elements.
+        assert!(f1.is_ignorable_at_level(1) && f1.is_ignorable_at_level(3));
+        assert!(!f1.is_ignorable_up_to_level(2));
+        let e = CollationElement::try_from("[.1C47.0020.0002]").unwrap();
+        assert!(e.is_n_ignorable(0) && !e.is_n_ignorable(3));
+        assert!([f1.clone(), f1.clone()].is_ignorable_at_level(1));
+        assert!(![f1.clone(), e].is_ignorable_at_level(1));
+        assert!(Vec::<CollationElement>::new().is_ignorable_up_to_level(4));
+        // There is no weight at level 0.
+        assert!(!f1.is_ignorable_at_level(0) && ![f1.clone()].is_ignorable_at_level(0));
+        assert!(f1.is_ignorable_up_to_level(0));
+        assert!([a, a].is_ignorable() && ![a, b].is_ignorable());
     }
 
     #[test]
@@ -93,7 +171,7 @@ mod tests {
         use mappings::*;
 
         let c = ['a'];
-        let ce = [CollationElement::from("[.1C47.0020.0002]")];
+        let ce = [CollationElement::try_from("[.1C47.0020.0002]").unwrap()];
         let cem = CollationElementMapping::new(&c, &ce);
         assert!(cem.is_ok());
         let cem = cem.unwrap();
@@ -102,7 +180,7 @@ mod tests {
             cem,
             CollationElementMapping::SimpleMapping {
                 character: 'a',
-                collation_element: CollationElement::from("[.1C47.0020.002]")
+                collation_element: CollationElement::try_from("[.1C47.0020.002]").unwrap()
             }
         )
     }
@@ -112,8 +190,8 @@ mod tests {
         use collation_element_tables::*;
         use collation_wel::*;
         use mappings::*;
-        let ce1 = CollationElement::from("[.0001.0001.0001.0001]");
-        let ce2 = CollationElement::from("[.0002.0002.0002.0002]");
+        let ce1 = CollationElement::try_from("[.0001.0001.0001.0001]").unwrap();
+        let ce2 = CollationElement::try_from("[.0002.0002.0002.0002]").unwrap();
         let cm1 = CollationElementMapping::SimpleMapping {
             character: 'a',
             collation_element: ce1,
@@ -124,14 +202,104 @@ mod tests {
         };
         let cet = CollationElementTable(vec![cm1, cm2]);
         let expected = CollationWeight::from(1);
-        assert_eq!(cet.min_weight_at_l_n(1), &expected);
-        assert_eq!(cet.min_weight_at_l_n(2), &expected);
-        assert_eq!(cet.min_weight_at_l_n(3), &expected);
-        assert_eq!(cet.min_weight_at_l_n(4), &expected);
+        assert_eq!(cet.min_weight_at_l_n(1), Some(&expected));
+        assert_eq!(cet.min_weight_at_l_n(2), Some(&expected));
+        assert_eq!(cet.min_weight_at_l_n(3), Some(&expected));
+        assert_eq!(cet.min_weight_at_l_n(4), Some(&expected));
         let expected = CollationWeight::from(2);
-        assert_eq!(cet.max_weight_at_l_n(1), &expected);
-        assert_eq!(cet.max_weight_at_l_n(2), &expected);
-        assert_eq!(cet.max_weight_at_l_n(3), &expected);
-        assert_eq!(cet.max_weight_at_l_n(4), &expected);
+        assert_eq!(cet.max_weight_at_l_n(1), Some(&expected));
+        assert_eq!(cet.max_weight_at_l_n(2), Some(&expected));
+        assert_eq!(cet.max_weight_at_l_n(3), Some(&expected));
+        assert_eq!(cet.max_weight_at_l_n(4), Some(&expected));
+        assert_eq!(cet.max_weight_at_l_n(0), None);
+        assert_eq!(
+            CollationElementTable::new(vec![]).min_weight_at_l_n(1),
+            None
+        );
+
+        // The weights of all the collation elements of an expansion count.
+        let expansion = CollationElementMapping::new(
+            &['c'],
+            &[
+                CollationElement::try_from("[*0003.0020.0002]").unwrap(),
+                CollationElement::try_from("[.0000.0024.0002]").unwrap(),
+            ],
+        )
+        .unwrap();
+        let cet = CollationElementTable::new(vec![expansion]);
+        assert_eq!(cet.max_weight_at_l_n(2), Some(&CollationWeight::from(0x24)));
+        assert_eq!(cet.min_weight_at_l_n(1), Some(&CollationWeight::from(0)));
+        assert_eq!(cet.common_secondary(), Some(&CollationWeight::from(0x20)));
+        assert_eq!(cet.common_tertiary(), Some(&CollationWeight::from(0x02)));
+        assert_eq!(
+            cet.variable_range(),
+            Some(CollationWeight::from(3)..=CollationWeight::from(3))
+        );
+    }
+
+    #[test]
+    fn test_3_5() {
+        use collation_element_tables::*;
+        use collation_wel::*;
+        use mappings::*;
+        use variable_weighting::*;
+        let ce = |s| CollationElement::try_from(s).unwrap();
+        // UTS10-D33
+        let c = CollationElementMapping::new(&['c'], &[ce("[.1FD2.0020.0002]")]).unwrap();
+        let ch = CollationElementMapping::new(&['c', 'h'], &[ce("[.1FD3.0020.0002]")]).unwrap();
+        let cet = CollationElementTable(vec![c, ch.clone()]);
+        let m = cet.longest_match(&['c', 'h', 'a']).unwrap();
+        assert_eq!(m.mapping(), &ch);
+        assert!(m.is_contiguous_match());
+        assert!(cet.longest_match(&['a']).is_none());
+
+        // UTS10-D39
+        let array = [ce("[.1FD2.0020.0002]"), ce("[*0209.0020.0002]")];
+        let is_variable = CollationElement::is_variable;
+        assert_eq!(
+            VariableWeighting::ShiftTrimmed.apply(&array[..1], is_variable),
+            [ce("[.1FD2.0020.0002.0000]")]
+        );
+        assert_eq!(
+            VariableWeighting::ShiftTrimmed.apply(&array, is_variable),
+            [ce("[.1FD2.0020.0002.FFFF]"), ce("[.0000.0000.0000.0209]")]
+        );
+        // The levels past the fourth are kept.
+        let array = [ce("[.42DA.0020.0011.0002.0002]"), ce("[*0209.0020.0002]")];
+        assert_eq!(
+            VariableWeighting::Shifted.apply(&array, is_variable),
+            [
+                ce("[.42DA.0020.0011.FFFF.0002]"),
+                ce("[.0000.0000.0000.0209]")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_3_repertoire() {
+        use crate::algorithm::Collator;
+        use crate::unicode_set::UnicodeSet;
+
+        let root = Collator::try_new("und").unwrap();
+        let mut table = root.table();
+        table.retain_repertoire(&UnicodeSet::parse("[\\u0400-\\u04FF\\uAC00-\\uD7A3]").unwrap());
+        // The contraction of и and the breve is kept with the breve of the decomposition of й.
+        assert!(table
+            .explicit_mapping(&['\u{438}', '\u{306}'])
+            .is_some_and(|mapping| mapping.is_contraction()));
+        assert!(table.explicit_mapping(&['a']).is_none());
+        assert!(table
+            .mappings()
+            .iter()
+            .all(|mapping| mapping
+                .characters()
+                .iter()
+                .all(|&c| ('\u{400}'..='\u{4FF}').contains(&c)
+                    || ('\u{1100}'..='\u{11FF}').contains(&c)
+                    || input_matching::is_non_starter(c))));
+        let collator = Collator::new(table);
+        for s in ["йод", "ѝ", "한국어"] {
+            assert_eq!(collator.sort_key(s), root.sort_key(s), "{}", s);
+        }
     }
 }
@@ -0,0 +1,70 @@
+//! The definitions of this file summarize (rather than quote) UTS10-D40-D46.
+//!
+//! UTS10-D40. Sort Key: an array of weights built from the collation element array of a string,
+//! level by level, suitable for binary comparison; see [`SortKey`](crate::algorithm::SortKey).
+//!
+//! UTS10-D41. Level Separator: see
+//! [`KeyEncoding::level_separator`](crate::algorithm::KeyEncoding::level_separator).
+
+use crate::algorithm::normalize::to_nfd;
+use crate::algorithm::SortKey;
+
+impl SortKey {
+    /// The first level at which the sort keys differ, or `None` if they are equal.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let key = |s| collator.sort_key(s);
+    /// assert_eq!(key("a").difference_level(&key("b")), Some(1));
+    /// assert_eq!(key("a").difference_level(&key("á")), Some(2));
+    /// assert_eq!(key("a").difference_level(&key("A")), Some(3));
+    /// assert_eq!(key("a").difference_level(&key("a")), None);
+    /// ```
+    pub fn difference_level(&self, other: &SortKey) -> Option<usize> {
+        let (a, b) = (self.levels(), other.levels());
+        (0..a.len().max(b.len()))
+            .find(|&l| a.get(l) != b.get(l))
+            .map(|l| l + 1)
+    }
+
+    /// UTS10-D42. Primary Difference: a difference between sort keys at level 1.
+    pub fn is_primary_difference(&self, other: &SortKey) -> bool {
+        self.difference_level(other) == Some(1)
+    }
+
+    /// UTS10-D43. Secondary Difference: a difference between sort keys at level 2, the keys being
+    /// equal at level 1.
+    pub fn is_secondary_difference(&self, other: &SortKey) -> bool {
+        self.difference_level(other) == Some(2)
+    }
+
+    /// UTS10-D44. Tertiary Difference: a difference between sort keys at level 3, the keys being
+    /// equal at levels 1 and 2.
+    pub fn is_tertiary_difference(&self, other: &SortKey) -> bool {
+        self.difference_level(other) == Some(3)
+    }
+
+    /// UTS10-D45. Quaternary Difference: a difference between sort keys at level 4, the keys being
+    /// equal at levels 1 to 3.
+    pub fn is_quaternary_difference(&self, other: &SortKey) -> bool {
+        self.difference_level(other) == Some(4)
+    }
+}
+
+/// UTS10-D46. Canonical Equivalence: canonically equivalent strings, having the same canonical
+/// decomposition, must collate as equal. The main algorithm guarantees it by normalizing its
+/// input (S1).
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::definitions::sort_keys::are_canonically_equivalent;
+///
+/// assert!(are_canonically_equivalent("\u{E1}", "a\u{301}"));
+/// let collator = Collator::try_new("und").unwrap();
+/// assert_eq!(collator.sort_key("\u{E1}"), collator.sort_key("a\u{301}"));
+/// ```
+pub fn are_canonically_equivalent(a: &str, b: &str) -> bool {
+    to_nfd(a) == to_nfd(b)
+}
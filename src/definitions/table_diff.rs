@@ -0,0 +1,135 @@
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::collation_wel::CollationElement;
+use crate::definitions::mappings::CollationElementMapping;
+use std::collections::HashMap;
+
+/// The differences between two [`CollationElementTable`]s, e.g. between two versions of the DUCET
+/// or between a table and its tailoring, as returned by [`CollationElementTable::diff`].
+///
+/// The mappings are matched by their characters and prefix. When a table lists several mappings
+/// of the same characters, only the first one applies (see
+/// [`Collator`](crate::algorithm::Collator)) and is compared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableDiff {
+    /// The mappings of the new table for characters the old table does not map, in the order of
+    /// the new table.
+    pub added: Vec<CollationElementMapping>,
+    /// The mappings of the old table for characters the new table does not map, in the order of
+    /// the old table.
+    pub removed: Vec<CollationElementMapping>,
+    /// The mappings of the same characters to different collation elements, in the order of the
+    /// old table.
+    pub changed: Vec<ChangedMapping>,
+}
+
+/// A mapping whose collation elements differ between two tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedMapping {
+    pub old: CollationElementMapping,
+    pub new: CollationElementMapping,
+}
+
+impl ChangedMapping {
+    /// The least level (counted from 1) at which the weights of the collation elements of the
+    /// mappings differ, ignoring the ignorable weights as when comparing sort keys: a change at
+    /// level 1 moves the characters among the others, a change at level 3 only changes e.g. their
+    /// case.
+    ///
+    /// Returns `None` if the weights only differ by ignorable weights or by which collation
+    /// elements are variable.
+    pub fn level(&self) -> Option<usize> {
+        let (old, new) = (self.old.collation_elements(), self.new.collation_elements());
+        let levels = old.iter().chain(new).map(CollationElement::len_levels);
+        (1..=levels.max().unwrap_or(0))
+            .find(|&n| nonzero_weights(old, n) != nonzero_weights(new, n))
+    }
+}
+
+fn nonzero_weights(ces: &[CollationElement], n: usize) -> Vec<u32> {
+    ces.iter()
+        .filter_map(|ce| ce.weight_at_level_n(n))
+        .map(|w| w.0)
+        .filter(|&w| w != 0)
+        .collect()
+}
+
+impl TableDiff {
+    /// Whether the tables map the same characters to the same collation elements.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The changed mappings whose weights first differ at level `n` (see
+    /// [`ChangedMapping::level`]), e.g. the primary weight shifts for `n = 1`.
+    pub fn changed_at_level(&self, n: usize) -> impl Iterator<Item = &ChangedMapping> {
+        self.changed
+            .iter()
+            .filter(move |change| change.level() == Some(n))
+    }
+}
+
+/// The first mapping of the table for each prefix and characters, with its position.
+fn first_mappings(table: &CollationElementTable) -> HashMap<(&[char], &[char]), usize> {
+    let mut first = HashMap::new();
+    for (i, mapping) in table.mappings().iter().enumerate() {
+        first
+            .entry((mapping.prefix(), mapping.characters()))
+            .or_insert(i);
+    }
+    first
+}
+
+impl CollationElementTable {
+    /// The mappings added, removed and changed from `self` to `other`.
+    ///
+    /// ```
+    /// use collate_rs::data_files::parse_allkeys;
+    ///
+    /// let old = parse_allkeys(
+    ///     "0061 ; [.1FA2.0020.0002]\n\
+    ///      0062 ; [.1FBC.0020.0002]\n\
+    ///      0063 ; [.1FD6.0020.0002]\n",
+    /// )
+    /// .unwrap();
+    /// let new = parse_allkeys(
+    ///     "0061 ; [.1FA2.0020.0002]\n\
+    ///      0062 ; [.1FBC.0020.0003]\n\
+    ///      0063 0068 ; [.1FD7.0020.0002]\n",
+    /// )
+    /// .unwrap();
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.added, [new.mappings()[2].clone()]);
+    /// assert_eq!(diff.removed, [old.mappings()[2].clone()]);
+    /// assert_eq!(diff.changed.len(), 1);
+    /// assert_eq!(diff.changed[0].new, new.mappings()[1]);
+    /// assert_eq!(diff.changed[0].level(), Some(3));
+    /// assert!(old.diff(&old).is_empty());
+    /// ```
+    pub fn diff(&self, other: &CollationElementTable) -> TableDiff {
+        let (old, new) = (first_mappings(self), first_mappings(other));
+        let mut diff = TableDiff::default();
+        for (i, mapping) in self.mappings().iter().enumerate() {
+            let key = (mapping.prefix(), mapping.characters());
+            if old[&key] != i {
+                continue;
+            }
+            match new.get(&key).map(|&j| &other.mappings()[j]) {
+                None => diff.removed.push(mapping.clone()),
+                Some(changed) if changed.collation_elements() != mapping.collation_elements() => {
+                    diff.changed.push(ChangedMapping {
+                        old: mapping.clone(),
+                        new: changed.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for (j, mapping) in other.mappings().iter().enumerate() {
+            let key = (mapping.prefix(), mapping.characters());
+            if new[&key] == j && !old.contains_key(&key) {
+                diff.added.push(mapping.clone());
+            }
+        }
+        diff
+    }
+}
@@ -0,0 +1,155 @@
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::collation_wel::{CollationElement, CollationWeight};
+use crate::definitions::mappings::CollationElementMapping;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Counts of the mappings and collation elements of a [`CollationElementTable`], as returned by
+/// [`CollationElementTable::stats`], e.g. to size the data structures looking them up.
+///
+/// Displays as a report of a line per count:
+///
+/// ```
+/// use collate_rs::data_files::parse_allkeys;
+///
+/// let table = parse_allkeys(
+///     "0020 ; [*0209.0020.0002]\n\
+///      0061 ; [.1FA2.0020.0002]\n\
+///      00E6 ; [.1FA2.0020.0004][.0000.0110.0004][.1FF8.0020.0004]\n\
+///      0063 0068 ; [.1FD7.0020.0002]\n",
+/// )
+/// .unwrap();
+/// let stats = table.stats();
+/// assert_eq!(stats.simple_mappings, 2);
+/// assert_eq!(stats.expansions, 1);
+/// assert_eq!(stats.contractions_by_length[&2], 1);
+/// assert_eq!(stats.longest_contraction, Some(vec!['c', 'h']));
+/// assert_eq!(
+///     stats.to_string(),
+///     "Mappings: 4\n\
+///      Simple mappings: 2\n\
+///      Expansions: 1\n\
+///      Contractions of 2 characters: 1\n\
+///      Contextual mappings: 0\n\
+///      Longest contraction: <0063 0068>\n\
+///      Collation elements: 6 (1 variable)\n\
+///      Level 1 weights: 0209..1FF8\n\
+///      Level 2 weights: 0020..0110\n\
+///      Level 3 weights: 0002..0004\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableStats {
+    pub mappings: usize,
+    /// The mappings of one character to one collation element (UTS10-D18).
+    pub simple_mappings: usize,
+    /// The mappings of one character to several collation elements (UTS10-D19).
+    pub expansions: usize,
+    /// The number of contractions (UTS10-D22) of each number of characters, the contextual
+    /// mappings left out.
+    pub contractions_by_length: BTreeMap<usize, usize>,
+    /// The [contextual mappings](CollationElementMapping::ContextualMapping).
+    pub contextual_mappings: usize,
+    /// The characters of the first contraction of the most characters, if any.
+    pub longest_contraction: Option<Vec<char>>,
+    /// The number of collation elements of all the mappings.
+    pub collation_elements: usize,
+    /// The number of variable collation elements (UTS10-D16) of all the mappings.
+    pub variable_elements: usize,
+    /// The least and the greatest non-ignorable weight of each level, from level 1, `None` for
+    /// the levels whose weights are all ignorable.
+    pub weight_ranges: Vec<Option<RangeInclusive<CollationWeight>>>,
+}
+
+impl CollationElementTable {
+    /// The counts of the mappings and collation elements of the table.
+    pub fn stats(&self) -> TableStats {
+        let mut stats = TableStats {
+            mappings: self.mappings().len(),
+            ..Default::default()
+        };
+        for mapping in self.mappings() {
+            match mapping {
+                CollationElementMapping::SimpleMapping { .. } => stats.simple_mappings += 1,
+                CollationElementMapping::Expansion { .. } => stats.expansions += 1,
+                CollationElementMapping::ContextualMapping { .. } => stats.contextual_mappings += 1,
+                CollationElementMapping::ManyToOneMapping { characters, .. }
+                | CollationElementMapping::ManyToManyMapping { characters, .. } => {
+                    *stats
+                        .contractions_by_length
+                        .entry(characters.len())
+                        .or_default() += 1;
+                    if stats
+                        .longest_contraction
+                        .as_ref()
+                        .is_none_or(|longest| longest.len() < characters.len())
+                    {
+                        stats.longest_contraction = Some(characters.clone());
+                    }
+                }
+            }
+            for ce in mapping.collation_elements() {
+                stats.collation_elements += 1;
+                stats.variable_elements += usize::from(ce.is_variable());
+                include_weights(&mut stats.weight_ranges, ce);
+            }
+        }
+        stats
+    }
+}
+
+fn include_weights(
+    ranges: &mut Vec<Option<RangeInclusive<CollationWeight>>>,
+    ce: &CollationElement,
+) {
+    if ranges.len() < ce.len_levels() {
+        ranges.resize(ce.len_levels(), None);
+    }
+    for (range, &w) in ranges.iter_mut().zip(&ce.0) {
+        if w.0 == 0 {
+            continue;
+        }
+        *range = Some(match range.take() {
+            Some(range) => (*range.start()).min(w)..=(*range.end()).max(w),
+            None => w..=w,
+        });
+    }
+}
+
+impl fmt::Display for TableStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Mappings: {}", self.mappings)?;
+        writeln!(f, "Simple mappings: {}", self.simple_mappings)?;
+        writeln!(f, "Expansions: {}", self.expansions)?;
+        for (len, count) in &self.contractions_by_length {
+            writeln!(f, "Contractions of {} characters: {}", len, count)?;
+        }
+        writeln!(f, "Contextual mappings: {}", self.contextual_mappings)?;
+        if let Some(longest) = &self.longest_contraction {
+            let code_points: Vec<String> = longest
+                .iter()
+                .map(|&c| format!("{:04X}", c as u32))
+                .collect();
+            writeln!(f, "Longest contraction: <{}>", code_points.join(" "))?;
+        }
+        writeln!(
+            f,
+            "Collation elements: {} ({} variable)",
+            self.collation_elements, self.variable_elements
+        )?;
+        for (i, range) in self.weight_ranges.iter().enumerate() {
+            match range {
+                Some(range) => writeln!(
+                    f,
+                    "Level {} weights: {:04X}..{:04X}",
+                    i + 1,
+                    range.start().0,
+                    range.end().0
+                )?,
+                None => writeln!(f, "Level {} weights: all ignorable", i + 1)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! The definitions of this file summarize (rather than quote) UTS10-D36-D39, the options for
+//! weighting variable collation elements described in Section 4, Variable Weighting.
+
+use crate::definitions::collation_wel::{CollationElement, Weighted};
+
+/// The fourth-level weight of the non-variable, non-ignorable collation elements when shifting.
+const SHIFTED_HIGH_WEIGHT: u32 = 0xFFFF;
+
+/// How variable collation elements (UTS10-D16) are weighted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VariableWeighting {
+    /// UTS10-D36. Non-ignorable: variable collation elements are left unchanged, and weighted as
+    /// any other primary collation element.
+    #[default]
+    NonIgnorable,
+    /// UTS10-D37. Blanked: variable collation elements, and the ignorable collation elements
+    /// following them, get ignorable weights at all levels.
+    Blanked,
+    /// UTS10-D38. Shifted: variable collation elements get ignorable weights at levels 1 to 3 and
+    /// their primary weight at level 4; the ignorable collation elements following them become
+    /// completely ignorable; the other collation elements get a level 4 weight of `FFFF`, or
+    /// `0000` if completely ignorable.
+    ///
+    /// The weights of the levels past the fourth, if any, are kept, except for the collation
+    /// elements made ignorable.
+    Shifted,
+    /// UTS10-D39. Shift-Trimmed: as [`VariableWeighting::Shifted`], trimming the trailing `FFFF`
+    /// weights at level 4, so that strings without variable collation elements sort as if level 4
+    /// were absent.
+    ShiftTrimmed,
+}
+
+/// The last group of characters whose primary collation elements are variable, as the
+/// `maxVariable` setting of CLDR. The groups follow each other in the DUCET: spaces, punctuation,
+/// symbols, currency symbols and then digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MaxVariable {
+    /// Only the spaces are variable.
+    Space,
+    /// The spaces and the punctuation, the default of CLDR.
+    Punct,
+    /// The spaces, the punctuation and the symbols: the variable collation elements of the
+    /// DUCET.
+    Symbol,
+    /// Every primary collation element before the digits, currency symbols included.
+    Currency,
+}
+
+impl MaxVariable {
+    /// The first character, in the DUCET, of the group following this one, if the group does
+    /// not end with the variable collation elements of the table.
+    pub(crate) fn next_group_first(&self) -> Option<char> {
+        match self {
+            Self::Space => Some('\u{203E}'),
+            Self::Punct => Some('`'),
+            Self::Symbol => None,
+            Self::Currency => Some('0'),
+        }
+    }
+}
+
+impl VariableWeighting {
+    /// Applies the weighting to a collation element array, `is_variable` telling which collation
+    /// elements are variable, e.g. [`CollationElement::is_variable`] or
+    /// the function of
+    /// [`CollationElementTable::variable_classifier`](crate::definitions::collation_element_tables::CollationElementTable::variable_classifier).
+    ///
+    /// ```
+    /// use collate_rs::definitions::collation_wel::CollationElement;
+    /// use collate_rs::definitions::variable_weighting::VariableWeighting;
+    ///
+    /// let ce = |s| CollationElement::try_from(s).unwrap();
+    /// // SPACE, COMBINING ACUTE ACCENT, LATIN SMALL LETTER A
+    /// let array = [ce("[*0209.0020.0002]"), ce("[.0000.0024.0002]"), ce("[.1FA2.0020.0002]")];
+    /// assert_eq!(
+    ///     VariableWeighting::Shifted.apply(&array, CollationElement::is_variable),
+    ///     [
+    ///         ce("[.0000.0000.0000.0209]"),
+    ///         ce("[.0000.0000.0000.0000]"),
+    ///         ce("[.1FA2.0020.0002.FFFF]"),
+    ///     ]
+    /// );
+    /// assert_eq!(
+    ///     VariableWeighting::Blanked.apply(&array, CollationElement::is_variable),
+    ///     [ce("[.0000.0000.0000]"), ce("[.0000.0000.0000]"), ce("[.1FA2.0020.0002]")]
+    /// );
+    /// ```
+    pub fn apply<F>(&self, array: &[CollationElement], is_variable: F) -> Vec<CollationElement>
+    where
+        F: Fn(&CollationElement) -> bool,
+    {
+        let mut weighted = array.to_vec();
+        self.apply_in_place(&mut weighted, is_variable);
+        weighted
+    }
+
+    /// Like [`VariableWeighting::apply`], changing the collation elements of `array` in place.
+    pub(crate) fn apply_in_place<E, F>(&self, array: &mut [E], is_variable: F)
+    where
+        E: Weighted,
+        F: Fn(&E) -> bool,
+    {
+        let mut after_variable = false;
+        for ce in array.iter_mut() {
+            let variable = is_variable(ce);
+            let ignorable = ce.weight(1) == 0;
+            let blank = variable || (after_variable && ignorable);
+            if !ignorable {
+                after_variable = variable;
+            }
+            match self {
+                Self::NonIgnorable => {}
+                Self::Blanked if blank => {
+                    (1..=ce.len_levels()).for_each(|n| ce.set_weight(n, 0));
+                    ce.clear_variable();
+                }
+                Self::Blanked => {}
+                Self::Shifted | Self::ShiftTrimmed => {
+                    let completely_ignorable = (1..=ce.len_levels()).all(|n| ce.weight(n) == 0);
+                    let l4 = if variable {
+                        ce.weight(1)
+                    } else if blank || completely_ignorable {
+                        0
+                    } else {
+                        SHIFTED_HIGH_WEIGHT
+                    };
+                    if variable || blank {
+                        (1..=ce.len_levels()).for_each(|n| ce.set_weight(n, 0));
+                    }
+                    ce.set_weight(4, l4);
+                    ce.clear_variable();
+                }
+            }
+        }
+        if *self == Self::ShiftTrimmed {
+            for ce in array.iter_mut().rev() {
+                match ce.weight(4) {
+                    SHIFTED_HIGH_WEIGHT => ce.set_weight(4, 0),
+                    0 => {}
+                    _ => break,
+                }
+            }
+        }
+    }
+}
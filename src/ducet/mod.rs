@@ -0,0 +1,290 @@
+use crate::algorithm::TableData;
+#[cfg(not(feature = "datagen"))]
+use crate::data_files::binary::BinaryTable;
+use crate::data_files::{parse_allkeys, parse_version};
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::error::{CollateError, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// Defines the [`UnicodeVersion`]s a DUCET can be selected by.
+pub mod unicode_version;
+
+pub use unicode_version::UnicodeVersion;
+
+/// Environment variable naming the directory searched for the DUCETs that are not bundled with
+/// the crate, laid out as `<version>/allkeys.txt` (e.g. `15.1.0/allkeys.txt`).
+pub const DUCET_DIR_VAR: &str = "COLLATE_RS_DUCET_DIR";
+
+/// The tables loaded by [`table_data`], in the order of [`UnicodeVersion::ALL`].
+static TABLES: [OnceLock<Arc<TableData>>; UnicodeVersion::ALL.len()] =
+    [const { OnceLock::new() }; UnicodeVersion::ALL.len()];
+
+/// The version of the bundled DUCET.
+#[cfg(not(feature = "datagen"))]
+pub fn version() -> UnicodeVersion {
+    UnicodeVersion::V13_0
+}
+
+/// The version of the DUCET read from `COLLATE_RS_UCD_DIR` at build time.
+#[cfg(feature = "datagen")]
+pub fn version() -> UnicodeVersion {
+    env!("COLLATE_RS_DUCET_VERSION")
+        .parse()
+        .expect("build.rs only accepts supported versions.")
+}
+
+/// The allkeys.txt file of the Default Unicode Collation Element Table bundled with the crate
+/// (version 13.0.0).
+#[cfg(not(feature = "datagen"))]
+pub const ALLKEYS: &str = include_str!("../../data/allkeys.txt");
+
+/// The allkeys.txt file of the Default Unicode Collation Element Table read from
+/// `COLLATE_RS_UCD_DIR` at build time (see `build.rs`).
+#[cfg(feature = "datagen")]
+pub const ALLKEYS: &str = include_str!(concat!(env!("OUT_DIR"), "/allkeys.txt"));
+
+/// [`ALLKEYS`] precompiled to the [binary table format](crate::data_files::binary), regenerated
+/// with `cargo run --bin collate-rs-datagen -- data/allkeys.txt data/ducet.bin`.
+#[cfg(not(feature = "datagen"))]
+pub const ALLKEYS_BINARY: &[u8] = include_bytes!("../../data/ducet.bin");
+
+/// The bundled DUCET, read in place from [`ALLKEYS_BINARY`].
+#[cfg(not(feature = "datagen"))]
+pub fn binary_table() -> BinaryTable<'static> {
+    BinaryTable::new(ALLKEYS_BINARY).expect("The bundled ducet.bin is well-formed.")
+}
+
+/// > The Default Unicode Collation Element Table is provided in [Allkeys]. This table provides a
+/// > mapping from characters to collation elements for all the explicitly weighted characters.
+///
+/// Decodes the bundled [`ALLKEYS_BINARY`] on each call.
+#[cfg(not(feature = "datagen"))]
+pub fn table() -> CollationElementTable {
+    binary_table().to_table()
+}
+
+/// > The Default Unicode Collation Element Table is provided in [Allkeys]. This table provides a
+/// > mapping from characters to collation elements for all the explicitly weighted characters.
+///
+/// Parses the generated [`ALLKEYS`] on each call.
+#[cfg(feature = "datagen")]
+pub fn table() -> CollationElementTable {
+    parse_allkeys(ALLKEYS).expect("The generated allkeys.txt is well-formed.")
+}
+
+/// Loads the DUCET of `version` from `<dir>/<version>/allkeys.txt`, checking that the file
+/// declares that version.
+pub fn load_table(version: UnicodeVersion, dir: &Path) -> Result<CollationElementTable> {
+    let path: PathBuf = [dir, Path::new(version.as_str()), Path::new("allkeys.txt")]
+        .iter()
+        .collect();
+    let allkeys = match std::fs::read_to_string(&path) {
+        Ok(allkeys) => allkeys,
+        Err(source) => return Err(CollateError::Io { path, source }),
+    };
+    match parse_version(&allkeys)? {
+        Some(declared) if declared != version => Err(CollateError::VersionMismatch {
+            found: declared.to_string(),
+            expected: version.to_string(),
+            path,
+        }),
+        _ => parse_allkeys(&allkeys),
+    }
+}
+
+/// The DUCET of `version`: the bundled one if `version` is [`version()`], otherwise the one
+/// loaded from the directory named by [`DUCET_DIR_VAR`] (see [`load_table`]).
+///
+/// The DUCETs of the other versions are only tables, e.g. to compare the mappings of two
+/// versions: the collators are only created for the version of the Unicode data of the crate
+/// (see [`Collator::for_unicode_version`](crate::algorithm::Collator::for_unicode_version)).
+pub fn table_for(version: UnicodeVersion) -> Result<CollationElementTable> {
+    if version == self::version() {
+        return Ok(table());
+    }
+    let dir = std::env::var_os(DUCET_DIR_VAR).ok_or_else(|| {
+        CollateError::UnsupportedVersion(format!(
+            "The DUCET {} is not bundled (only {} is); set {} to a directory with {}/allkeys.txt.",
+            version,
+            self::version(),
+            DUCET_DIR_VAR,
+            version
+        ))
+    })?;
+    load_table(version, Path::new(&dir))
+}
+
+/// The versions [`table_for`] can load: the bundled one, and those found in the directory named
+/// by [`DUCET_DIR_VAR`], oldest first.
+pub fn available_versions() -> Vec<UnicodeVersion> {
+    let dir = std::env::var_os(DUCET_DIR_VAR).map(PathBuf::from);
+    UnicodeVersion::ALL
+        .into_iter()
+        .filter(|&v| {
+            v == version()
+                || dir
+                    .as_ref()
+                    .is_some_and(|dir| dir.join(v.as_str()).join("allkeys.txt").is_file())
+        })
+        .collect()
+}
+
+/// Fails unless `version` is the [`version()`] of the Unicode data of the crate: the canonical
+/// decompositions and the implicit weights of the other versions are not bundled, so the orderings
+/// of their DUCETs cannot be reproduced.
+pub(crate) fn check_version(version: UnicodeVersion) -> Result<()> {
+    if version != self::version() {
+        return Err(CollateError::UnsupportedVersion(format!(
+            "The Unicode data of the crate is version {}, not {}.",
+            self::version(),
+            version
+        )));
+    }
+    Ok(())
+}
+
+/// The table of `version`, built on first use and shared afterwards.
+pub(crate) fn table_data(version: UnicodeVersion) -> Result<Arc<TableData>> {
+    let i = UnicodeVersion::ALL
+        .iter()
+        .position(|v| *v == version)
+        .expect("ALL lists every version.");
+    if let Some(data) = TABLES[i].get() {
+        return Ok(data.clone());
+    }
+    check_version(version)?;
+    let data = Arc::new(TableData::versioned(table_for(version)?, version)?);
+    Ok(TABLES[i].get_or_init(|| data).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "datagen"))]
+    #[test]
+    fn test_binary_ducet_is_up_to_date() {
+        use crate::data_files::binary::write_table;
+        use crate::definitions::collation_wel::CollationWeight;
+
+        let parsed = parse_allkeys(ALLKEYS).unwrap();
+        assert_eq!(write_table(&parsed).unwrap(), ALLKEYS_BINARY);
+        assert_eq!(table(), parsed);
+
+        let binary = binary_table();
+        assert_eq!(binary.len(), parsed.mappings().len());
+        // LATIN SMALL LETTER E WITH ACUTE
+        assert_eq!(
+            binary.get(&['\u{E9}']).unwrap().collation_elements().len(),
+            2
+        );
+
+        let w = CollationWeight::from;
+        assert_eq!(parsed.common_secondary(), Some(&w(0x0020)));
+        assert_eq!(parsed.common_tertiary(), Some(&w(0x0002)));
+        assert_eq!(parsed.variable_range(), Some(w(0x0201)..=w(0x1F45)));
+    }
+
+    #[test]
+    fn test_export_ducet() {
+        use crate::data_files::{write_allkeys, write_fractional_uca};
+
+        let table = table();
+        let allkeys = write_allkeys(&table).unwrap();
+        assert_eq!(parse_allkeys(&allkeys).unwrap(), table);
+        let fractional = write_fractional_uca(&table).unwrap();
+        assert_eq!(fractional.lines().count(), table.mappings().len());
+        // LATIN SMALL LETTER E WITH ACUTE
+        assert!(allkeys.contains("\n00E9 ; [.2007.0020.0002][.0000.0024.0002]\n"));
+        assert!(fractional.contains("\n00E9; [9F 88, 21, 03][, 25, 03]\n"));
+    }
+
+    #[test]
+    fn test_ducet_stats() {
+        use crate::definitions::collation_wel::CollationWeight;
+
+        let stats = table().stats();
+        assert_eq!(stats.mappings, table().mappings().len());
+        assert_eq!(
+            stats.simple_mappings
+                + stats.expansions
+                + stats.contractions_by_length.values().sum::<usize>(),
+            stats.mappings
+        );
+        assert_eq!(stats.contextual_mappings, 0);
+        // The decomposition of KANNADA VOWEL SIGN OO
+        assert_eq!(
+            stats.longest_contraction,
+            Some(vec!['\u{CC6}', '\u{CC2}', '\u{CD5}'])
+        );
+        assert_eq!(stats.contractions_by_length.keys().max(), Some(&3));
+        assert_eq!(stats.weight_ranges.len(), 3);
+        assert_eq!(
+            stats.weight_ranges[1],
+            Some(CollationWeight::from(0x0020)..=CollationWeight::from(0x011C))
+        );
+        assert!(stats.to_string().starts_with("Mappings: "));
+    }
+
+    #[test]
+    fn test_unicode_versions() {
+        assert_eq!(
+            "15.1".parse::<UnicodeVersion>().unwrap(),
+            UnicodeVersion::V15_1
+        );
+        assert_eq!(
+            "14.0.0".parse::<UnicodeVersion>().unwrap(),
+            UnicodeVersion::V14_0
+        );
+        assert!("12.1.0".parse::<UnicodeVersion>().is_err());
+        assert_eq!(UnicodeVersion::V16_0.to_string(), "16.0.0");
+        assert_eq!(table_for(version()).unwrap(), table());
+        assert!(available_versions().contains(&version()));
+        let collator = crate::algorithm::Collator::for_unicode_version(version()).unwrap();
+        assert_eq!(collator.unicode_version(), Some(version()));
+        // The collators of the other versions would mix their DUCET with the Unicode data of the
+        // crate.
+        let other = UnicodeVersion::ALL
+            .into_iter()
+            .find(|v| *v != version())
+            .unwrap();
+        assert!(matches!(
+            crate::algorithm::Collator::for_unicode_version(other),
+            Err(CollateError::UnsupportedVersion(_))
+        ));
+        let allkeys = format!("@version {}\n0061 ; [.2075.0020.0002]\n", other);
+        assert!(crate::algorithm::Collator::from_allkeys(&allkeys).is_err());
+        assert_eq!(parse_version(ALLKEYS).unwrap(), Some(version()));
+        // The Unicode data normalizing the strings is of the version of the DUCET.
+        assert_eq!(
+            crate::data_files::ucd::UNICODE_VERSION
+                .parse::<UnicodeVersion>()
+                .unwrap(),
+            version()
+        );
+
+        let dir = std::env::temp_dir().join(format!("collate-rs-ducet-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("15.1.0")).unwrap();
+        std::fs::write(
+            dir.join("15.1.0/allkeys.txt"),
+            "@version 15.1.0\n0061 ; [.2075.0020.0002]\n",
+        )
+        .unwrap();
+        let table = load_table(UnicodeVersion::V15_1, &dir).unwrap();
+        assert_eq!(table.mappings().len(), 1);
+        assert!(load_table(UnicodeVersion::V16_0, &dir).is_err());
+        std::fs::create_dir_all(dir.join("16.0.0")).unwrap();
+        std::fs::write(dir.join("16.0.0/allkeys.txt"), "@version 15.1.0\n").unwrap();
+        assert!(matches!(
+            load_table(UnicodeVersion::V16_0, &dir),
+            Err(CollateError::VersionMismatch { .. })
+        ));
+        std::fs::write(dir.join("16.0.0/allkeys.txt"), "@version 12.1.0\n").unwrap();
+        assert!(matches!(
+            load_table(UnicodeVersion::V16_0, &dir),
+            Err(CollateError::UnsupportedVersion(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
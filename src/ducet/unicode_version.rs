@@ -0,0 +1,62 @@
+use crate::error::CollateError;
+use std::fmt;
+use std::str::FromStr;
+
+/// A version of the Unicode Standard, and thus of the DUCET and of the UCA.
+///
+/// Orderings change between versions, as characters are added and weights reassigned, so an
+/// application that must reproduce the orderings of a past release builds the crate with the data
+/// of that version (feature `datagen`), and requests it explicitly to check that it is the one in
+/// use (see [`Collator::for_unicode_version`](crate::algorithm::Collator::for_unicode_version)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnicodeVersion {
+    V13_0,
+    V14_0,
+    V15_0,
+    V15_1,
+    V16_0,
+}
+
+impl UnicodeVersion {
+    /// Every supported version, oldest first.
+    pub const ALL: [UnicodeVersion; 5] = [
+        UnicodeVersion::V13_0,
+        UnicodeVersion::V14_0,
+        UnicodeVersion::V15_0,
+        UnicodeVersion::V15_1,
+        UnicodeVersion::V16_0,
+    ];
+
+    /// The version as written in the `@version` line of allkeys.txt, e.g. `15.1.0`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnicodeVersion::V13_0 => "13.0.0",
+            UnicodeVersion::V14_0 => "14.0.0",
+            UnicodeVersion::V15_0 => "15.0.0",
+            UnicodeVersion::V15_1 => "15.1.0",
+            UnicodeVersion::V16_0 => "16.0.0",
+        }
+    }
+}
+
+impl fmt::Display for UnicodeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UnicodeVersion {
+    type Err = CollateError;
+
+    /// Parses `15.1.0`, or `15.1` with the update number omitted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Self::ALL
+            .into_iter()
+            .find(|v| v.as_str() == s || v.as_str().strip_suffix(".0") == Some(s))
+            .ok_or_else(|| {
+                CollateError::UnsupportedVersion(format!("Unsupported Unicode version {:?}.", s))
+            })
+    }
+}
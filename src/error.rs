@@ -0,0 +1,98 @@
+use crate::algorithm::KeyEncoding;
+use crate::definitions::collation_wel;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors of the public API of the crate.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CollateError {
+    /// A data file, or a weight or collation element in the notation of UTS10, is ill-formed.
+    #[error("{}", parse_message(.line, .reason))]
+    ParseError {
+        /// The line of the data file, counted from 1, if the error comes from a file.
+        line: Option<usize>,
+        reason: String,
+    },
+    /// The contents of a collation element table are inconsistent, or cannot be represented in the
+    /// requested format.
+    #[error("Ill-formed collation element table: {0}")]
+    IllFormedTable(String),
+    /// A collation level beyond the levels available was requested.
+    #[error("Level {level} is not supported (the maximum level is {max}).")]
+    UnsupportedLevel { level: usize, max: usize },
+    /// A tailoring could not be applied.
+    #[error("Tailoring error: {0}")]
+    TailoringError(String),
+    /// No collation data is available for the locale.
+    #[error("No collation data for the locale {0:?}.")]
+    UnknownLocale(String),
+    /// The Unicode version is not supported, or its data is not available.
+    #[error("{0}")]
+    UnsupportedVersion(String),
+    /// A data file declares another Unicode version than the one requested.
+    #[error("{path:?} declares version {found}, expected {expected}.")]
+    VersionMismatch {
+        path: PathBuf,
+        expected: String,
+        found: String,
+    },
+    /// A data file could not be read.
+    #[error("Could not read {path:?}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The bytes are not a sort key.
+    #[error("Invalid sort key: {0}")]
+    InvalidSortKey(String),
+    /// Sort keys of different [`KeyEncoding`]s cannot be compared.
+    #[error("Cannot compare a {left:?} sort key with a {right:?} sort key.")]
+    IncompatibleSortKeys {
+        left: KeyEncoding,
+        right: KeyEncoding,
+    },
+    /// A string is ill-formed, e.g. a UTF-16 string with an unpaired surrogate collated with
+    /// [`IllFormedInput::Error`](crate::algorithm::IllFormedInput::Error).
+    #[error("Ill-formed input: {0}")]
+    InvalidInput(String),
+    /// An option has an unknown value.
+    #[error("{0}")]
+    InvalidOption(String),
+}
+
+fn parse_message(line: &Option<usize>, reason: &str) -> String {
+    match line {
+        Some(line) => format!("Ill-formed entry at line {}: {}", line, reason),
+        None => reason.to_string(),
+    }
+}
+
+impl CollateError {
+    /// Attaches the line of a data file to a [`CollateError::ParseError`].
+    pub(crate) fn at_line(self, line: usize) -> Self {
+        match self {
+            Self::ParseError { reason, .. } => Self::ParseError {
+                line: Some(line),
+                reason,
+            },
+            e => e,
+        }
+    }
+
+    pub(crate) fn parse(reason: impl Into<String>) -> Self {
+        Self::ParseError {
+            line: None,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl From<collation_wel::ParseError> for CollateError {
+    fn from(e: collation_wel::ParseError) -> Self {
+        Self::parse(e.to_string())
+    }
+}
+
+/// A [`Result`](std::result::Result) whose error defaults to [`CollateError`].
+pub type Result<T, E = CollateError> = std::result::Result<T, E>;
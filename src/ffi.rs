@@ -0,0 +1,239 @@
+//! The header of the API is `include/collate_rs.h`. Built with `cargo build --release --features
+//! ffi`, the `cdylib` can be loaded by any language with a C FFI, e.g. from Python:
+//!
+//! ```python
+//! lib = ctypes.CDLL("target/release/libcollate_rs.so")
+//! lib.collate_open.restype = ctypes.c_void_p
+//! status = ctypes.c_int32()
+//! coll = ctypes.c_void_p(lib.collate_open(b"sv", ctypes.byref(status)))
+//! lib.collate_strcoll(coll, "ä".encode(), -1, b"z", -1, ctypes.byref(status))  # 1
+//! lib.collate_close(coll)
+//! ```
+//!
+//! Strings are UTF-8, given as a pointer and a length in bytes, or a length of `-1` for
+//! NUL-terminated strings. Every fallible function reports its outcome through a `status` out
+//! parameter, set to one of the `COLLATE_*` codes.
+
+use crate::algorithm::Collator;
+use std::cmp::Ordering;
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+/// The call succeeded.
+pub const COLLATE_OK: i32 = 0;
+/// A required pointer argument was null.
+pub const COLLATE_NULL_ARGUMENT: i32 = 1;
+/// A string argument was not valid UTF-8.
+pub const COLLATE_INVALID_UTF8: i32 = 2;
+/// No collation data is bundled for the requested locale.
+pub const COLLATE_UNKNOWN_LOCALE: i32 = 3;
+/// A length argument was negative (other than `-1`).
+pub const COLLATE_INVALID_LENGTH: i32 = 4;
+
+/// An opaque collator, created by [`collate_open`] and released by [`collate_close`].
+pub struct CollateCollator(Collator);
+
+/// Writes `code` to `status` if it is not null.
+unsafe fn set_status(status: *mut i32, code: i32) {
+    if !status.is_null() {
+        *status = code;
+    }
+}
+
+/// Reads a UTF-8 string of `len` bytes, or NUL-terminated if `len` is `-1`.
+unsafe fn read_str<'a>(s: *const c_char, len: i32) -> Result<&'a str, i32> {
+    if s.is_null() {
+        return Err(COLLATE_NULL_ARGUMENT);
+    }
+    let bytes = match len {
+        -1 => CStr::from_ptr(s).to_bytes(),
+        len if len < 0 => return Err(COLLATE_INVALID_LENGTH),
+        len => std::slice::from_raw_parts(s.cast::<u8>(), len as usize),
+    };
+    std::str::from_utf8(bytes).map_err(|_| COLLATE_INVALID_UTF8)
+}
+
+/// Opens a collator for one of the bundled locales (a NUL-terminated identifier such as `"sv"` or
+/// `"de-u-co-phonebk"`; `"und"` or null for the root collation), or returns null on failure.
+///
+/// # Safety
+/// `locale` must be null or a valid NUL-terminated string, and `status` null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn collate_open(
+    locale: *const c_char,
+    status: *mut i32,
+) -> *mut CollateCollator {
+    let locale = if locale.is_null() {
+        "und"
+    } else {
+        match read_str(locale, -1) {
+            Ok(locale) => locale,
+            Err(code) => {
+                set_status(status, code);
+                return ptr::null_mut();
+            }
+        }
+    };
+    match Collator::try_new(locale) {
+        Ok(collator) => {
+            set_status(status, COLLATE_OK);
+            Box::into_raw(Box::new(CollateCollator(collator)))
+        }
+        Err(_) => {
+            set_status(status, COLLATE_UNKNOWN_LOCALE);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Compares two strings, returning `-1`, `0` or `1`, or `0` with an error `status`.
+///
+/// # Safety
+/// `coll` must come from [`collate_open`] and not be closed; `a` and `b` must be valid for reads
+/// of `a_len` and `b_len` bytes (or NUL-terminated if the length is `-1`); `status` must be null
+/// or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn collate_strcoll(
+    coll: *const CollateCollator,
+    a: *const c_char,
+    a_len: i32,
+    b: *const c_char,
+    b_len: i32,
+    status: *mut i32,
+) -> i32 {
+    let Some(coll) = coll.as_ref() else {
+        set_status(status, COLLATE_NULL_ARGUMENT);
+        return 0;
+    };
+    match (read_str(a, a_len), read_str(b, b_len)) {
+        (Ok(a), Ok(b)) => {
+            set_status(status, COLLATE_OK);
+            match coll.0.compare(a, b) {
+                Ordering::Less => -1,
+                Ordering::Equal => 0,
+                Ordering::Greater => 1,
+            }
+        }
+        (Err(code), _) | (_, Err(code)) => {
+            set_status(status, code);
+            0
+        }
+    }
+}
+
+/// Computes the sort key of a string and returns its length in bytes. The key is written to
+/// `result` only if it fits in `result_len` bytes, so a first call with a null `result` gives the
+/// size of the buffer to allocate, as with `ucol_getSortKey`. Returns `0` with an error `status`.
+///
+/// # Safety
+/// `coll` must come from [`collate_open`] and not be closed; `s` must be valid for reads of `len`
+/// bytes (or NUL-terminated if `len` is `-1`); `result` must be null or valid for writes of
+/// `result_len` bytes; `status` must be null or valid for writes.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub unsafe extern "C" fn collate_getSortKey(
+    coll: *const CollateCollator,
+    s: *const c_char,
+    len: i32,
+    result: *mut u8,
+    result_len: i32,
+    status: *mut i32,
+) -> i32 {
+    let Some(coll) = coll.as_ref() else {
+        set_status(status, COLLATE_NULL_ARGUMENT);
+        return 0;
+    };
+    let s = match read_str(s, len) {
+        Ok(s) => s,
+        Err(code) => {
+            set_status(status, code);
+            return 0;
+        }
+    };
+    let key = coll.0.sort_key(s);
+    let bytes = key.as_bytes();
+    if !result.is_null() && result_len >= 0 && bytes.len() <= result_len as usize {
+        ptr::copy_nonoverlapping(bytes.as_ptr(), result, bytes.len());
+    }
+    set_status(status, COLLATE_OK);
+    bytes.len() as i32
+}
+
+/// Releases a collator. Does nothing if `coll` is null.
+///
+/// # Safety
+/// `coll` must be null or come from [`collate_open`], and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn collate_close(coll: *mut CollateCollator) {
+    if !coll.is_null() {
+        drop(Box::from_raw(coll));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi() {
+        unsafe {
+            let mut status = -1;
+            let coll = collate_open(c"sv".as_ptr(), &mut status);
+            assert_eq!(status, COLLATE_OK);
+            assert!(!coll.is_null());
+
+            let (a, z) = ("ä", "z");
+            let cmp = |a: &str, b: &str, status: &mut i32| {
+                collate_strcoll(
+                    coll,
+                    a.as_ptr().cast(),
+                    a.len() as i32,
+                    b.as_ptr().cast(),
+                    b.len() as i32,
+                    status,
+                )
+            };
+            assert_eq!(cmp(a, z, &mut status), 1);
+            assert_eq!(cmp(z, a, &mut status), -1);
+            assert_eq!(
+                collate_strcoll(coll, c"a".as_ptr(), -1, c"a".as_ptr(), -1, &mut status),
+                0
+            );
+            assert_eq!(status, COLLATE_OK);
+            let invalid = [0x61u8, 0xFF];
+            collate_strcoll(
+                coll,
+                invalid.as_ptr().cast(),
+                2,
+                c"a".as_ptr(),
+                -1,
+                &mut status,
+            );
+            assert_eq!(status, COLLATE_INVALID_UTF8);
+            collate_strcoll(coll, ptr::null(), 0, c"a".as_ptr(), -1, &mut status);
+            assert_eq!(status, COLLATE_NULL_ARGUMENT);
+
+            let len =
+                collate_getSortKey(coll, c"abc".as_ptr(), -1, ptr::null_mut(), 0, &mut status);
+            assert_eq!(status, COLLATE_OK);
+            let mut key = vec![0u8; len as usize];
+            assert_eq!(
+                collate_getSortKey(
+                    coll,
+                    c"abc".as_ptr(),
+                    -1,
+                    key.as_mut_ptr(),
+                    len,
+                    &mut status
+                ),
+                len
+            );
+            assert_eq!(key, (*coll).0.sort_key("abc").as_bytes());
+            collate_close(coll);
+
+            assert!(collate_open(c"xx".as_ptr(), &mut status).is_null());
+            assert_eq!(status, COLLATE_UNKNOWN_LOCALE);
+            collate_close(ptr::null_mut());
+        }
+    }
+}
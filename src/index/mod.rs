@@ -0,0 +1,413 @@
+use crate::algorithm::{Collator, CollatorOptions, SortKey, Strength};
+use crate::error::Result;
+use crate::locales::fallbacks;
+
+/// The label of the underflow and the overflow buckets.
+pub const OTHERS_LABEL: &str = "…";
+
+/// The Latin alphabet, the labels of the locales without more specific ones.
+const LATIN: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// The first letter after the Latin script in the DUCET, starting the overflow bucket of the
+/// indexes of the Latin alphabets.
+const LATIN_OVERFLOW: &str = "α";
+
+/// The labels of the bundled locales whose labels are the letters of an alphabet, in the CLDR
+/// index exemplars.
+const ALPHABETS: [(&str, &str); 7] = [
+    ("da", "ABCDEFGHIJKLMNOPQRSTUVWXYZÆØÅ"),
+    ("es", "ABCDEFGHIJKLMNÑOPQRSTUVWXYZ"),
+    ("ja", "あかさたなはまやらわ"),
+    ("ko", "ㄱㄴㄷㄹㅁㅂㅅㅇㅈㅊㅋㅌㅍㅎ"),
+    ("nb", "ABCDEFGHIJKLMNOPQRSTUVWXYZÆØÅ"),
+    ("sv", "ABCDEFGHIJKLMNOPQRSTUVWXYZÅÄÖ"),
+    ("tr", "ABCÇDEFGĞHIİJKLMNOÖPQRSŞTUÜVWXYZ"),
+];
+
+/// The labels of the Han ideographs in the pinyin collation, with the first ideograph of each,
+/// from the data of Unicode::Collate::CJK::Pinyin.
+const PINYIN: [(&str, &str); 23] = [
+    ("A", "阿"),
+    ("B", "八"),
+    ("C", "嚓"),
+    ("D", "咑"),
+    ("E", "妸"),
+    ("F", "发"),
+    ("G", "旮"),
+    ("H", "哈"),
+    ("J", "丌"),
+    ("K", "咔"),
+    ("L", "垃"),
+    ("M", "呣"),
+    ("N", "嗯"),
+    ("O", "喔"),
+    ("P", "妑"),
+    ("Q", "七"),
+    ("R", "呥"),
+    ("S", "仨"),
+    ("T", "他"),
+    ("W", "穵"),
+    ("X", "夕"),
+    ("Y", "丫"),
+    ("Z", "帀"),
+];
+
+/// The labels of the Han ideographs in the stroke collation, by number of strokes, with the
+/// first ideograph of each, from the data of Unicode::Collate::CJK::Stroke.
+const STROKE: [(&str, &str); 37] = [
+    ("1劃", "一"),
+    ("2劃", "丁"),
+    ("3劃", "万"),
+    ("4劃", "不"),
+    ("5劃", "丗"),
+    ("6劃", "㐁"),
+    ("7劃", "丣"),
+    ("8劃", "並"),
+    ("9劃", "临"),
+    ("10劃", "𠀾"),
+    ("11劃", "㐢"),
+    ("12劃", "𠁆"),
+    ("13劃", "亂"),
+    ("14劃", "𠁎"),
+    ("15劃", "㒓"),
+    ("16劃", "亸"),
+    ("17劃", "償"),
+    ("18劃", "儭"),
+    ("19劃", "㐦"),
+    ("20劃", "㒥"),
+    ("21劃", "㒧"),
+    ("22劃", "亹"),
+    ("23劃", "儽"),
+    ("24劃", "儾"),
+    ("25劃", "囔"),
+    ("26劃", "㔶"),
+    ("27劃", "灥"),
+    ("28劃", "囖"),
+    ("29劃", "爨"),
+    ("30劃", "厵"),
+    ("31劃", "灩"),
+    ("32劃", "灪"),
+    ("33劃", "𡤻"),
+    ("35劃", "齾"),
+    ("36劃", "齉"),
+    ("39劃", "靐"),
+    ("48劃", "龘"),
+];
+
+/// The labels of the Han ideographs in the zhuyin collation, with the first ideograph of each,
+/// from the data of Unicode::Collate::CJK::Zhuyin.
+const ZHUYIN: [(&str, &str); 36] = [
+    ("ㄅ", "八"),
+    ("ㄆ", "妑"),
+    ("ㄇ", "呣"),
+    ("ㄈ", "发"),
+    ("ㄉ", "咑"),
+    ("ㄊ", "他"),
+    ("ㄋ", "嗯"),
+    ("ㄌ", "垃"),
+    ("ㄍ", "旮"),
+    ("ㄎ", "咔"),
+    ("ㄏ", "噷"),
+    ("ㄐ", "丌"),
+    ("ㄑ", "七"),
+    ("ㄒ", "夕"),
+    ("ㄓ", "之"),
+    ("ㄔ", "吃"),
+    ("ㄕ", "尸"),
+    ("ㄖ", "⺜"),
+    ("ㄗ", "乲"),
+    ("ㄘ", "呲"),
+    ("ㄙ", "厶"),
+    ("ㄚ", "呵"),
+    ("ㄛ", "喔"),
+    ("ㄜ", "妸"),
+    ("ㄞ", "哀"),
+    ("ㄟ", "诶"),
+    ("ㄠ", "凹"),
+    ("ㄡ", "讴"),
+    ("ㄢ", "安"),
+    ("ㄣ", "奀"),
+    ("ㄤ", "肮"),
+    ("ㄥ", "鞥"),
+    ("ㄦ", "儿"),
+    ("ㄧ", "一"),
+    ("ㄨ", "乌"),
+    ("ㄩ", "扜"),
+];
+
+/// The letters of an alphabet, each the label and the first string of its bucket.
+fn letters(alphabet: &'static str) -> impl Iterator<Item = (&'static str, &'static str)> {
+    alphabet
+        .char_indices()
+        .map(move |(i, c)| &alphabet[i..i + c.len_utf8()])
+        .map(|letter| (letter, letter))
+}
+
+/// The labels of a bundled locale with the first string of their buckets, and the first string
+/// of its overflow bucket, if any.
+#[allow(clippy::type_complexity)]
+fn index_data(locale: &str) -> Option<(Vec<(&'static str, &'static str)>, Option<&'static str>)> {
+    let han: &[(&str, &str)] = match locale {
+        "zh-u-co-pinyin" => &PINYIN,
+        "zh-u-co-stroke" => &STROKE,
+        "zh-u-co-zhuyin" => &ZHUYIN,
+        "und" => &[],
+        _ => {
+            let (_, alphabet) = ALPHABETS.iter().find(|(name, _)| *name == locale)?;
+            let overflow = alphabet.starts_with('A').then_some(LATIN_OVERFLOW);
+            return Some((letters(alphabet).collect(), overflow));
+        }
+    };
+    Some((
+        letters(LATIN).chain(han.iter().copied()).collect(),
+        Some(LATIN_OVERFLOW),
+    ))
+}
+
+/// The kind of a [`Bucket`] of an [`AlphabeticIndex`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BucketKind {
+    /// The records sorting before the first label.
+    Underflow,
+    /// The records of a label.
+    Label,
+    /// The records of the scripts sorting after the labels of the index, e.g. Greek in an index
+    /// of the Latin alphabet.
+    Overflow,
+}
+
+/// The records of an [`AlphabeticIndex`] under a label, in collation order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bucket<S> {
+    pub label: String,
+    pub kind: BucketKind,
+    pub records: Vec<S>,
+}
+
+/// Distributes records in buckets under labels, e.g. `A` to `Z`, `Å`, `Ä` and `Ö` for Swedish or
+/// the numbers of strokes for Chinese, as a contact list does, in the manner of the
+/// AlphabeticIndex of ICU.
+///
+/// A record belongs to the bucket of the last label whose first string sorts before it, or is
+/// equal to it, at the primary strength: `Ärla` is under `Ä` in Swedish and under `A` in German.
+/// The records sorting before the first label go to an underflow bucket, and those of the scripts
+/// after the labels, if the index knows where they start, to an overflow bucket, both labelled
+/// [`OTHERS_LABEL`].
+///
+/// ```
+/// use collate_rs::index::AlphabeticIndex;
+///
+/// # #[cfg(feature = "locales")]
+/// # {
+/// let index = AlphabeticIndex::for_locale("sv-SE").unwrap();
+/// let buckets = index.buckets(["Örjan", "Anna", "Åsa", "Ärla", "Zelda", "Ωmega", "42"]);
+/// let buckets: Vec<(&str, Vec<&str>)> = buckets
+///     .iter()
+///     .filter(|bucket| !bucket.records.is_empty())
+///     .map(|bucket| (bucket.label.as_str(), bucket.records.clone()))
+///     .collect();
+/// assert_eq!(
+///     buckets,
+///     [
+///         ("…", vec!["42"]),
+///         ("A", vec!["Anna"]),
+///         ("Z", vec!["Zelda"]),
+///         ("Å", vec!["Åsa"]),
+///         ("Ä", vec!["Ärla"]),
+///         ("Ö", vec!["Örjan"]),
+///         ("…", vec!["Ωmega"]),
+///     ]
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AlphabeticIndex {
+    /// The collator ordering the records of the buckets.
+    collator: Collator,
+    /// The labels of the buckets with their kinds, in order.
+    buckets: Vec<(String, BucketKind)>,
+    /// The primary sort key of the first string of each bucket but the underflow one, sorted,
+    /// with the position of its bucket.
+    boundaries: Vec<(SortKey, usize)>,
+    /// The primary strength clone of `collator`, computing the keys of the boundaries.
+    primary: Collator,
+}
+
+impl AlphabeticIndex {
+    /// An index of `labels`, each the first string of its bucket, with an underflow bucket and no
+    /// overflow bucket.
+    pub fn new<L: AsRef<str>>(collator: &Collator, labels: &[L]) -> Self {
+        let labels: Vec<(&str, &str)> = labels
+            .iter()
+            .map(|label| (label.as_ref(), label.as_ref()))
+            .collect();
+        Self::with_boundaries(collator, &labels, None)
+    }
+
+    /// An index of labels with the first string of their buckets, e.g. `("2劃", "丁")` for the
+    /// ideographs of two strokes, and where the overflow bucket starts, if any. The buckets of the
+    /// same label are merged.
+    pub fn with_boundaries(
+        collator: &Collator,
+        labels: &[(&str, &str)],
+        overflow: Option<&str>,
+    ) -> Self {
+        let primary = collator.clone_with_options(CollatorOptions {
+            strength: Strength::Primary,
+            ..collator.options().clone()
+        });
+        let mut buckets = vec![(OTHERS_LABEL.to_string(), BucketKind::Underflow)];
+        let mut boundaries = vec![];
+        for (label, first) in labels {
+            let i = match buckets
+                .iter()
+                .position(|(l, kind)| l == label && *kind == BucketKind::Label)
+            {
+                Some(i) => i,
+                None => {
+                    buckets.push((label.to_string(), BucketKind::Label));
+                    buckets.len() - 1
+                }
+            };
+            boundaries.push((primary.sort_key(first), i));
+        }
+        if let Some(first) = overflow {
+            buckets.push((OTHERS_LABEL.to_string(), BucketKind::Overflow));
+            boundaries.push((primary.sort_key(first), buckets.len() - 1));
+        }
+        boundaries.sort_by(|a, b| a.0.weights().cmp(b.0.weights()));
+        Self {
+            collator: collator.clone(),
+            buckets,
+            boundaries,
+            primary,
+        }
+    }
+
+    /// The index of the labels of a BCP 47 language tag, collated by
+    /// [`Collator::for_locale`]. The locales without bundled labels get the Latin alphabet.
+    pub fn for_locale(locale: &str) -> Result<Self> {
+        let collator = Collator::for_locale(locale)?;
+        let (labels, overflow) = fallbacks(locale)?
+            .iter()
+            .find_map(|locale| index_data(locale))
+            .expect("The root locale has labels.");
+        Ok(Self::with_boundaries(&collator, &labels, overflow))
+    }
+
+    pub fn collator(&self) -> &Collator {
+        &self.collator
+    }
+
+    /// The labels of the buckets, in order, [`OTHERS_LABEL`] included.
+    pub fn labels(&self) -> Vec<&str> {
+        self.buckets
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .collect()
+    }
+
+    /// The position, in [`AlphabeticIndex::labels`], of the bucket of `record`.
+    pub fn bucket_index(&self, record: &str) -> usize {
+        let key = self.primary.sort_key(record);
+        match self
+            .boundaries
+            .partition_point(|(first, _)| first.weights() <= key.weights())
+        {
+            0 => 0,
+            n => self.boundaries[n - 1].1,
+        }
+    }
+
+    /// The label of the bucket of `record`.
+    pub fn bucket_label(&self, record: &str) -> &str {
+        &self.buckets[self.bucket_index(record)].0
+    }
+
+    /// Sorts `records` and distributes them in every bucket of the index, empty buckets included.
+    pub fn buckets<I, S>(&self, records: I) -> Vec<Bucket<S>>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut records: Vec<S> = records.into_iter().collect();
+        self.collator.sort_slice(&mut records);
+        let mut buckets: Vec<Bucket<S>> = self
+            .buckets
+            .iter()
+            .map(|(label, kind)| Bucket {
+                label: label.clone(),
+                kind: *kind,
+                records: vec![],
+            })
+            .collect();
+        for record in records {
+            let i = self.bucket_index(record.as_ref());
+            buckets[i].records.push(record);
+        }
+        buckets
+    }
+}
+
+#[cfg(all(test, feature = "locales"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_buckets() {
+        let sv = AlphabeticIndex::for_locale("sv").unwrap();
+        assert_eq!(sv.bucket_label("Östen"), "Ö");
+        assert_eq!(sv.bucket_label("Åsa"), "Å");
+        assert_eq!(sv.bucket_label("ärla"), "Ä");
+        assert_eq!(sv.bucket_label("Wilhelm"), "W");
+        let de = AlphabeticIndex::for_locale("de").unwrap();
+        assert_eq!(de.bucket_label("Ärla"), "A");
+        assert_eq!(de.bucket_label("123"), OTHERS_LABEL);
+        assert_eq!(de.buckets(["123"])[0].kind, BucketKind::Underflow);
+        let en = AlphabeticIndex::for_locale("en").unwrap();
+        let buckets = en.buckets(["Ωmega"]);
+        assert_eq!(buckets.len(), 28);
+        assert_eq!(buckets[27].kind, BucketKind::Overflow);
+        assert_eq!(buckets[27].records, ["Ωmega"]);
+        let tr = AlphabeticIndex::for_locale("tr").unwrap();
+        assert_eq!(tr.bucket_label("ılık"), "I");
+        assert_eq!(tr.bucket_label("iki"), "İ");
+        assert_eq!(tr.bucket_label("ğ"), "Ğ");
+        let ja = AlphabeticIndex::for_locale("ja").unwrap();
+        assert_eq!(ja.bucket_label("カタカナ"), "か");
+        assert_eq!(ja.bucket_label("ん"), "わ");
+        let ko = AlphabeticIndex::for_locale("ko").unwrap();
+        assert_eq!(ko.bucket_label("가나"), "ㄱ");
+        assert_eq!(ko.bucket_label("한국"), "ㅎ");
+    }
+
+    #[test]
+    fn test_han_buckets() {
+        let stroke = AlphabeticIndex::for_locale("zh-u-co-stroke").unwrap();
+        assert_eq!(stroke.bucket_label("一"), "1劃");
+        assert_eq!(stroke.bucket_label("丁"), "2劃");
+        assert_eq!(stroke.bucket_label("中国"), "4劃");
+        assert_eq!(stroke.bucket_label("Zoe"), "Z");
+        let buckets = stroke.buckets(["中", "一", "乙", "丁"]);
+        let two = buckets.iter().find(|b| b.label == "2劃").unwrap();
+        assert_eq!(two.records, ["丁"]);
+        let one = buckets.iter().find(|b| b.label == "1劃").unwrap();
+        assert_eq!(one.records, ["一", "乙"]);
+        let pinyin = AlphabeticIndex::for_locale("zh-u-co-pinyin").unwrap();
+        assert_eq!(pinyin.bucket_label("北京"), "B");
+        assert_eq!(pinyin.bucket_label("Beijing"), "B");
+        assert_eq!(pinyin.labels().iter().filter(|l| **l == "B").count(), 1);
+        let zhuyin = AlphabeticIndex::for_locale("zh-u-co-zhuyin").unwrap();
+        assert_eq!(zhuyin.bucket_label("八"), "ㄅ");
+    }
+
+    #[test]
+    fn test_new() {
+        let collator = Collator::try_new("und").unwrap();
+        let index = AlphabeticIndex::new(&collator, &["a", "m"]);
+        assert_eq!(index.labels(), ["…", "a", "m"]);
+        assert_eq!(index.bucket_label("Lima"), "a");
+        assert_eq!(index.bucket_label("Mike"), "m");
+        assert_eq!(index.bucket_label("Ωmega"), "m");
+    }
+}
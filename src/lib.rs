@@ -3,7 +3,9 @@
 #[cfg(feature = "todo")]
 pub mod introduction {}
 // 2
-#[cfg(feature = "todo")]
+/// # Conformance
+///
+/// The conformance tests of the Unicode Collation Algorithm: the files `CollationTest_NON_IGNORABLE.txt` and `CollationTest_SHIFTED.txt` list strings in the order of the DUCET, one per line. The lines out of order are reported with the collation elements and the sort keys of their strings.
 pub mod conformance;
 // 3
 /// # Definitions and Notation
@@ -27,7 +29,7 @@ pub mod conformance;
 ///  - [x] UTS10-D13
 ///  - [x] UTS10-D14
 ///  - [x] UTS10-D15
-///  - [ ] UTS10-D16
+///  - [x] UTS10-D16
 ///  - [x] UTS10-D17
 ///  - [x] UTS10-D18
 ///  - [x] UTS10-D19
@@ -35,29 +37,29 @@ pub mod conformance;
 ///  - [x] UTS10-D21
 ///  - [x] UTS10-D22
 ///  - [x] UTS10-D23
-///  - [ ] UTS10-D24
+///  - [x] UTS10-D24
 ///  - [x] UTS10-D25
 ///  - [x] UTS10-D26
-///  - [ ] UTS10-D27
-///  - [ ] UTS10-D28
-///  - [ ] UTS10-D29
-///  - [ ] UTS10-D30
-///  - [ ] UTS10-D31
-///  - [ ] UTS10-D32
-///  - [ ] UTS10-D33
-///  - [ ] UTS10-D34
-///  - [ ] UTS10-D35
-///  - [ ] UTS10-D36
-///  - [ ] UTS10-D37
-///  - [ ] UTS10-D38
-///  - [ ] UTS10-D39
-///  - [ ] UTS10-D40
-///  - [ ] UTS10-D41
-///  - [ ] UTS10-D42
-///  - [ ] UTS10-D43
-///  - [ ] UTS10-D44
-///  - [ ] UTS10-D45
-///  - [ ] UTS10-D46
+///  - [x] UTS10-D27
+///  - [x] UTS10-D28
+///  - [x] UTS10-D29
+///  - [x] UTS10-D30
+///  - [x] UTS10-D31
+///  - [x] UTS10-D32
+///  - [x] UTS10-D33
+///  - [x] UTS10-D34
+///  - [x] UTS10-D35
+///  - [x] UTS10-D36
+///  - [x] UTS10-D37
+///  - [x] UTS10-D38
+///  - [x] UTS10-D39
+///  - [x] UTS10-D40
+///  - [x] UTS10-D41
+///  - [x] UTS10-D42
+///  - [x] UTS10-D43
+///  - [x] UTS10-D44
+///  - [x] UTS10-D45
+///  - [x] UTS10-D46
 ///
 pub mod definitions;
 // 4
@@ -67,23 +69,104 @@ pub mod variable_weighting;
 #[cfg(feature = "todo")]
 pub mod well_formadness;
 // 6
-#[cfg(feature = "todo")]
+/// # Default Unicode Collation Element Table
+///
+/// > The Default Unicode Collation Element Table is provided in [Allkeys]. This table provides a mapping from characters to collation elements for all the explicitly weighted characters. The mapping lists characters in the order that they are weighted. Any code points that are not explicitly mentioned in this table are given a derived collation element, as described in Section 10, Weight Derivation.
 pub mod ducet;
 // 7
-#[cfg(feature = "todo")]
+/// # Main Algorithm
+///
+/// > The main algorithm has four steps. First is to normalize each input string, second is to produce an array of collation elements for each string, and third is to produce a sort key for each string from the collation elements. Two sort keys can then be compared with a binary comparison; the result is the ordering for the original strings.
+///
+/// ## List of implemented steps:
+///
+///  - [x] S1 Normalize each input string
+///  - [x] S2 Produce an array of collation elements for each string
+///  - [x] S3 Produce a sort key for each string from the collation elements
+///  - [x] S4 Compare the sort keys
+///
 pub mod algorithm;
+
+pub use algorithm::{cmp, default_collator};
 // 8
-#[cfg(feature = "todo")]
+/// # Tailoring
+///
+/// > Tailoring consists of any well-defined change in the Collation Element Table and/or any well-defined change in the behavior of the algorithm.
 pub mod tailoring;
 // 9
 #[cfg(feature = "todo")]
 pub mod implementation_notes {}
 // 10
-#[cfg(feature = "todo")]
+/// # Weight Derivation
+///
+/// > This section describes the generation of collation elements that are not explicitly listed in a collation element table, for code points that are not explicitly mentioned in it.
 pub mod weight_derivation;
 // 11
-#[cfg(feature = "todo")]
+/// # Searching and Matching
+///
+/// Language-sensitive searching and matching by the collation elements of a collator, so that
+/// the strings equal at a strength, e.g. differing only by accents, match at that strength.
 pub mod search_and_matching;
 // 12
-#[cfg(feature = "todo")]
+/// # Data Files
+///
+/// Parsing of the data files of the Unicode Collation Algorithm.
 pub mod data_files;
+
+/// # Errors
+///
+/// The [`CollateError`](error::CollateError) returned by the fallible functions of the crate.
+pub mod error;
+
+pub use error::{CollateError, Result};
+
+/// # Locales
+///
+/// Collation data bundled with the crate for specific locales, loaded lazily. The tailorings of
+/// the locales other than the root are only bundled with the `locales` feature (enabled by
+/// default).
+pub mod locales;
+
+pub use locales::{human_compare, human_sort};
+
+/// # Alphabetic Index
+///
+/// Buckets of records under labels, e.g. the letters of an alphabet, for the indexes of contact
+/// lists.
+pub mod index;
+
+/// # UnicodeSet
+///
+/// Sets of characters written in the UnicodeSet notation of ICU and CLDR, e.g. `[a-z]` or
+/// `[:Lu:]`, as in the settings of the tailorings.
+pub mod unicode_set;
+
+/// # Storage
+///
+/// Keys of the strings for the ordered key-value stores, e.g. sled or RocksDB, sorting in the
+/// order of a collator and from which the strings can be recovered.
+pub mod storage;
+
+/// # Bulk
+///
+/// Sorting of the datasets larger than memory, spilling sorted runs to temporary files and
+/// merging them.
+pub mod bulk;
+
+/// # WebAssembly
+///
+/// JavaScript bindings of the [`Collator`](algorithm::Collator), built with wasm-bindgen.
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// # Test Support
+///
+/// Generators of the corpora of the benchmarks.
+#[cfg(feature = "test-support")]
+pub mod test_support;
+
+/// # C API
+///
+/// Functions exported to C, in the style of the `ucol_*` functions of ICU4C.
+#[cfg(feature = "ffi")]
+pub mod ffi;
@@ -0,0 +1,1014 @@
+use crate::algorithm::{CaseFirst, Collator, CollatorOptions, Strength, TableData};
+use crate::definitions::variable_weighting::{MaxVariable, VariableWeighting};
+use crate::ducet::{self, UnicodeVersion};
+use crate::error::{CollateError, Result};
+use crate::tailoring::Tailoring;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Defines the [`DataProvider`] trait, the sources of the tables collators can be created from
+/// other than the bundled data.
+pub mod provider;
+pub use provider::{BakedProvider, DataProvider, FsProvider, StaticProvider};
+
+/// Locales whose CLDR collation is the root collation, i.e. the DUCET without tailoring.
+pub const ROOT_LOCALES: [&str; 9] = ["und", "root", "de", "de-at", "en", "fr", "it", "nl", "pt"];
+
+/// Tailorings bundled with the crate, by locale identifier, in the allkeys.txt format.
+#[cfg(feature = "locales")]
+pub const TAILORED_LOCALES: [(&str, &str); 16] = [
+    ("da", include_str!("../../data/tailorings/da.txt")),
+    (
+        "de-at-u-co-phonebk",
+        include_str!("../../data/tailorings/de-at-u-co-phonebk.txt"),
+    ),
+    (
+        "de-u-co-phonebk",
+        include_str!("../../data/tailorings/de-u-co-phonebk.txt"),
+    ),
+    ("es", include_str!("../../data/tailorings/es.txt")),
+    (
+        "es-u-co-trad",
+        include_str!("../../data/tailorings/es-u-co-trad.txt"),
+    ),
+    ("grc", include_str!("../../data/tailorings/grc.txt")),
+    ("ja", include_str!("../../data/tailorings/ja.txt")),
+    ("ko", include_str!("../../data/tailorings/ko.txt")),
+    ("nb", include_str!("../../data/tailorings/nb.txt")),
+    ("sa-latn", include_str!("../../data/tailorings/sa-latn.txt")),
+    ("sv", include_str!("../../data/tailorings/sv.txt")),
+    ("tr", include_str!("../../data/tailorings/tr.txt")),
+    ("zh", include_str!("../../data/tailorings/zh.txt")),
+    (
+        "zh-u-co-pinyin",
+        include_str!("../../data/tailorings/zh-u-co-pinyin.txt"),
+    ),
+    (
+        "zh-u-co-stroke",
+        include_str!("../../data/tailorings/zh-u-co-stroke.txt"),
+    ),
+    (
+        "zh-u-co-zhuyin",
+        include_str!("../../data/tailorings/zh-u-co-zhuyin.txt"),
+    ),
+];
+
+/// Tailorings bundled with the crate, none without the `locales` feature.
+#[cfg(not(feature = "locales"))]
+pub const TAILORED_LOCALES: [(&str, &str); 0] = [];
+
+/// Locales whose CLDR collation is the root collation with backward secondary weights (see
+/// [`CollatorOptions::backward_secondary`]).
+#[cfg(feature = "locales")]
+pub const BACKWARD_SECONDARY_LOCALES: [&str; 1] = ["fr-ca"];
+
+/// Locales with backward secondary weights, none without the `locales` feature.
+#[cfg(not(feature = "locales"))]
+pub const BACKWARD_SECONDARY_LOCALES: [&str; 0] = [];
+
+/// Locales whose CLDR collation sorts the uppercase letters first (see
+/// [`CollatorOptions::case_first`]).
+#[cfg(feature = "locales")]
+pub const UPPER_FIRST_LOCALES: [&str; 1] = ["da"];
+
+/// Locales sorting the uppercase letters first, none without the `locales` feature.
+#[cfg(not(feature = "locales"))]
+pub const UPPER_FIRST_LOCALES: [&str; 0] = [];
+
+/// The tailored tables, built on first use, in the order of [`TAILORED_LOCALES`].
+static TAILORED: [OnceLock<Arc<TableData>>; TAILORED_LOCALES.len()] =
+    [const { OnceLock::new() }; TAILORED_LOCALES.len()];
+
+fn root() -> Arc<TableData> {
+    ducet::table_data(ducet::version()).expect("The bundled DUCET is always available.")
+}
+
+/// Lowercases the identifier and replaces `_` by `-`, so that `sv_SE` and `sv-se` are equivalent.
+fn normalize_locale(locale: &str) -> String {
+    locale.replace('_', "-").to_lowercase()
+}
+
+/// The table for a bundled locale, built the first time it is requested and shared by all the
+/// collators of that locale afterwards.
+pub(crate) fn table_data(locale: &str) -> Result<Arc<TableData>> {
+    let locale = normalize_locale(locale);
+    if ROOT_LOCALES.contains(&locale.as_str())
+        || BACKWARD_SECONDARY_LOCALES.contains(&locale.as_str())
+    {
+        return Ok(root());
+    }
+    let i = TAILORED_LOCALES
+        .iter()
+        .position(|(name, _)| *name == locale)
+        .ok_or(CollateError::UnknownLocale(locale))?;
+    let data = TAILORED[i].get_or_init(|| {
+        let tailoring = Tailoring::parse(TAILORED_LOCALES[i].1)
+            .expect("The bundled tailorings are well-formed.");
+        Arc::new(
+            TableData::versioned(tailoring.apply(&root().table()), ducet::version())
+                .expect("The bundled DUCET is of the version of the Unicode data."),
+        )
+    });
+    Ok(data.clone())
+}
+
+/// The options of the collation of a bundled locale.
+fn options(locale: &str) -> CollatorOptions {
+    let locale = normalize_locale(locale);
+    let language = locale.split(COLLATION_EXTENSION).next().unwrap_or_default();
+    CollatorOptions {
+        backward_secondary: BACKWARD_SECONDARY_LOCALES.contains(&locale.as_str()),
+        case_first: if UPPER_FIRST_LOCALES.contains(&language) {
+            CaseFirst::Upper
+        } else {
+            CaseFirst::Off
+        },
+        ..Default::default()
+    }
+}
+
+/// The extension introducing the collation types of a locale, as in `de-u-co-phonebk`.
+const COLLATION_EXTENSION: &str = "-u-co-";
+
+/// What the crate provides for a locale, as listed by [`Collator::available_locales`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocaleInfo {
+    /// The locale identifier, without extensions, e.g. `de`.
+    pub locale: &'static str,
+    /// Whether the standard collation of the locale tailors the root collation.
+    pub tailored: bool,
+    /// The collation types available besides `standard`, e.g. `phonebk` for `de`.
+    pub collations: Vec<&'static str>,
+    /// Version of the DUCET the collations of the locale are built on.
+    pub unicode_version: UnicodeVersion,
+}
+
+impl LocaleInfo {
+    /// Every identifier accepted by [`Collator::try_new`] for the locale, e.g. `de` and
+    /// `de-u-co-phonebk`.
+    pub fn identifiers(&self) -> Vec<String> {
+        std::iter::once(self.locale.to_string())
+            .chain(
+                self.collations
+                    .iter()
+                    .map(|co| format!("{}{}{}", self.locale, COLLATION_EXTENSION, co)),
+            )
+            .collect()
+    }
+}
+
+/// Whether [`Collator::try_new`] accepts `locale`, without building its table.
+pub fn is_available(locale: &str) -> bool {
+    let locale = normalize_locale(locale);
+    ROOT_LOCALES.contains(&locale.as_str())
+        || BACKWARD_SECONDARY_LOCALES.contains(&locale.as_str())
+        || TAILORED_LOCALES.iter().any(|(name, _)| *name == locale)
+}
+
+/// The bundled locale identifiers to try for `locale`, from the most specific: the subtags of
+/// the language tag are removed one by one from the end, first keeping the collation type of the
+/// `-u-co-` extension and then without it, down to the root locale.
+///
+/// e.g. `de-CH-u-co-phonebk` falls back to `de-ch-u-co-phonebk`, `de-u-co-phonebk`, `de-ch`, `de`
+/// and `und`.
+pub(crate) fn fallbacks(locale: &str) -> Result<Vec<String>> {
+    let locale = normalize_locale(locale);
+    let (tag, extension) = match locale.split_once("-u-") {
+        // The subtags after the `x` singleton are private use, even a `u`.
+        Some((tag, extension)) if !tag.split('-').any(|subtag| subtag == "x") => {
+            (tag, Some(extension))
+        }
+        _ => (locale.as_str(), None),
+    };
+    let subtags: Vec<&str> = tag.split('-').collect();
+    let well_formed = |subtag: &str| {
+        (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+    };
+    if !subtags.iter().copied().all(well_formed)
+        || !extension.is_none_or(|e| e.split('-').all(well_formed))
+    {
+        return Err(CollateError::UnknownLocale(locale));
+    }
+    let tags: Vec<String> = (1..=subtags.len())
+        .rev()
+        .map(|n| subtags[..n].join("-"))
+        .collect();
+    let mut fallbacks = vec![];
+    if let Some(co) = extension.and_then(collation_type) {
+        fallbacks.extend(
+            tags.iter()
+                .map(|tag| format!("{}{}{}", tag, COLLATION_EXTENSION, co)),
+        );
+    }
+    fallbacks.extend(tags);
+    fallbacks.push("und".to_string());
+    Ok(fallbacks)
+}
+
+/// The type of the `co` keyword of a `-u-` extension (without the `u-` singleton), e.g.
+/// `phonebk` in `co-phonebk-ka-shifted`.
+fn collation_type(extension: &str) -> Option<&str> {
+    let subtags: Vec<&str> = extension
+        .split('-')
+        .take_while(|&subtag| subtag != "x")
+        .collect();
+    let i = subtags.iter().position(|&subtag| subtag == "co")?;
+    subtags
+        .get(i + 1)
+        .copied()
+        .filter(|subtag| subtag.len() > 2)
+}
+
+/// The keywords of the `-u-` extension of a language tag, with their types, e.g. `("ka",
+/// "shifted")` and `("kn", "true")` for `de-u-ka-shifted-kn`, a keyword without type being `true`.
+fn extension_keywords(locale: &str) -> Vec<(&str, String)> {
+    let subtags: Vec<&str> = locale.split('-').collect();
+    let Some(u) = subtags
+        .iter()
+        .take_while(|&&subtag| subtag != "x")
+        .position(|&subtag| subtag == "u")
+    else {
+        return vec![];
+    };
+    let mut keywords: Vec<(&str, Vec<&str>)> = vec![];
+    for &subtag in subtags[u + 1..]
+        .iter()
+        .take_while(|subtag| subtag.len() > 1)
+    {
+        match keywords.last_mut() {
+            _ if subtag.len() == 2 => keywords.push((subtag, vec![])),
+            Some((_, types)) => types.push(subtag),
+            // The attributes preceding the first keyword.
+            None => {}
+        }
+    }
+    keywords
+        .into_iter()
+        .map(|(key, types)| match types.is_empty() {
+            true => (key, "true".to_string()),
+            false => (key, types.join("-")),
+        })
+        .collect()
+}
+
+impl CollatorOptions {
+    /// The options set by the collation keywords of the Unicode extension (`-u-`) of a BCP 47
+    /// language tag, e.g. `de-DE-u-co-phonebk-ka-shifted-kn-true`:
+    ///
+    /// - `ka` (`noignore`, `shifted`): [`CollatorOptions::variable_weighting`];
+    /// - `kb` (`true`, `false`): [`CollatorOptions::backward_secondary`];
+    /// - `kc` (`true`, `false`): [`CollatorOptions::case_level`];
+    /// - `kf` (`upper`, `lower`, `false`): [`CollatorOptions::case_first`];
+    /// - `kn` (`true`, `false`): [`CollatorOptions::numeric`];
+    /// - `ks` (`level1` to `level4`): [`CollatorOptions::strength`];
+    /// - `kv` (`space`, `punct`, `symbol`, `currency`): [`CollatorOptions::max_variable`];
+    /// - `kr`: only `others`, the default order of the scripts, is supported.
+    ///
+    /// The collation type `co` selects the table of the collator instead (see
+    /// [`Collator::for_locale`]), and the keywords of other services, e.g. `nu`, are ignored.
+    /// Fails with [`CollateError::InvalidOption`] on an unsupported type.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::{CaseFirst, CollatorOptions};
+    /// use collate_rs::definitions::variable_weighting::VariableWeighting;
+    ///
+    /// let options = CollatorOptions::from_locale_extensions("de-DE-u-co-phonebk-ka-shifted-kn")
+    ///     .unwrap();
+    /// assert_eq!(options.variable_weighting, VariableWeighting::Shifted);
+    /// assert!(options.numeric);
+    /// assert_eq!(
+    ///     CollatorOptions::from_locale_extensions("en-u-kf-upper").unwrap().case_first,
+    ///     CaseFirst::Upper
+    /// );
+    /// assert!(CollatorOptions::from_locale_extensions("en-u-ks-level5").is_err());
+    /// ```
+    pub fn from_locale_extensions(locale: &str) -> Result<Self> {
+        let mut options = Self::default();
+        options.apply_locale_extensions(locale)?;
+        Ok(options)
+    }
+
+    /// Overrides the options set by the keywords of the `-u-` extension of `locale`.
+    fn apply_locale_extensions(&mut self, locale: &str) -> Result<()> {
+        let locale = normalize_locale(locale);
+        for (key, value) in extension_keywords(&locale) {
+            let invalid = || {
+                CollateError::InvalidOption(format!(
+                    "Unsupported value {:?} for the keyword {:?}.",
+                    value, key
+                ))
+            };
+            let boolean = || match value.as_str() {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                _ => Err(invalid()),
+            };
+            match key {
+                "ka" => {
+                    self.variable_weighting = match value.as_str() {
+                        "noignore" => VariableWeighting::NonIgnorable,
+                        "shifted" => VariableWeighting::Shifted,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "kb" => self.backward_secondary = boolean()?,
+                "kc" => self.case_level = boolean()?,
+                "kf" => {
+                    self.case_first = match value.as_str() {
+                        "upper" => CaseFirst::Upper,
+                        "lower" => CaseFirst::Lower,
+                        "false" => CaseFirst::Off,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "kn" => self.numeric = boolean()?,
+                "ks" => {
+                    self.strength = match value.as_str() {
+                        "level1" => Strength::Primary,
+                        "level2" => Strength::Secondary,
+                        "level3" => Strength::Tertiary,
+                        "level4" | "quarter" => Strength::Quaternary,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "kv" => {
+                    self.max_variable = Some(match value.as_str() {
+                        "space" => MaxVariable::Space,
+                        "punct" => MaxVariable::Punct,
+                        "symbol" => MaxVariable::Symbol,
+                        "currency" => MaxVariable::Currency,
+                        _ => return Err(invalid()),
+                    })
+                }
+                "kr" if value != "others" => return Err(invalid()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Collator {
+    /// The bundled locales, sorted by identifier, with their collation types and data version,
+    /// e.g. to populate a locale picker or check a configuration at startup.
+    pub fn available_locales() -> Vec<LocaleInfo> {
+        let mut locales: Vec<LocaleInfo> = ROOT_LOCALES
+            .iter()
+            .map(|&locale| (locale, false))
+            .chain(
+                BACKWARD_SECONDARY_LOCALES
+                    .iter()
+                    .map(|&locale| (locale, true)),
+            )
+            .chain(
+                TAILORED_LOCALES
+                    .iter()
+                    .filter(|(name, _)| !name.contains(COLLATION_EXTENSION))
+                    .map(|&(locale, _)| (locale, true)),
+            )
+            .map(|(locale, tailored)| LocaleInfo {
+                locale,
+                tailored,
+                collations: TAILORED_LOCALES
+                    .iter()
+                    .filter_map(|(name, _)| name.split_once(COLLATION_EXTENSION))
+                    .filter(|(base, _)| *base == locale)
+                    .map(|(_, co)| co)
+                    .collect(),
+                unicode_version: ducet::version(),
+            })
+            .collect();
+        locales.sort_by_key(|info| info.locale);
+        locales
+    }
+
+    /// Creates a collator for one of the bundled locales ([`ROOT_LOCALES`] and
+    /// [`TAILORED_LOCALES`]).
+    ///
+    /// The table of each locale is built once, on the first call for that locale, and shared by
+    /// every collator created for it afterwards, from any thread.
+    pub fn try_new(locale: &str) -> Result<Self> {
+        table_data(locale).map(|data| Self::from_data(data, options(locale)))
+    }
+
+    /// Creates a collator for any BCP 47 language tag, falling back to the closest bundled locale,
+    /// e.g. `sv` for `sv-SE`, and to the root collation for the languages without bundled data.
+    ///
+    /// The collation keywords of the `-u-` extension of the tag override the options of the
+    /// locale, as described in [`CollatorOptions::from_locale_extensions`].
+    ///
+    /// Fails if `locale` is not a well-formed language tag, or if its keywords are not supported.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use std::cmp::Ordering;
+    ///
+    /// # #[cfg(feature = "locales")]
+    /// # {
+    /// let swedish = Collator::for_locale("sv-SE").unwrap();
+    /// assert_eq!(swedish.compare("ä", "z"), Ordering::Greater);
+    /// let phonebook = Collator::for_locale("de-CH-u-co-phonebk").unwrap();
+    /// assert_eq!(phonebook.compare("ä", "ad"), Ordering::Greater);
+    /// # }
+    /// assert!(Collator::for_locale("xx-YY").is_ok());
+    /// assert!(Collator::for_locale("sv--SE").is_err());
+    ///
+    /// // Shifted punctuation, numeric ordering and uppercase first.
+    /// let collator = Collator::for_locale("en-u-ka-shifted-kn-kf-upper").unwrap();
+    /// assert_eq!(collator.compare("de-luge", "delta"), Ordering::Greater);
+    /// assert_eq!(collator.compare("de-luge", "deluge"), Ordering::Equal);
+    /// assert_eq!(collator.compare("de-luge", "Deluge"), Ordering::Greater);
+    /// assert_eq!(collator.compare("file2", "file10"), Ordering::Less);
+    /// ```
+    pub fn for_locale(locale: &str) -> Result<Self> {
+        let (fallback, options) = resolve_locale(locale)?;
+        table_data(&fallback).map(|data| Self::from_data(data, options))
+    }
+}
+
+/// The bundled locale [`Collator::for_locale`] falls back to for `locale`, with the options of
+/// its table overridden by the keywords of the tag.
+fn resolve_locale(locale: &str) -> Result<(String, CollatorOptions)> {
+    let fallback = fallbacks(locale)?
+        .into_iter()
+        .find(|locale| is_available(locale))
+        .expect("The root locale is always available.");
+    let mut options = options(&fallback);
+    options.apply_locale_extensions(locale)?;
+    Ok((fallback, options))
+}
+
+/// The collators of [`human_compare`], created on first use, by the bundled locale the tags
+/// resolve to and then by options, so that the tags resolving to the same collator, e.g. `EN`,
+/// `en` and `en-US`, share one entry, whatever the tags the callers pass.
+static HUMAN_COLLATORS: OnceLock<RwLock<HashMap<String, Vec<Collator>>>> = OnceLock::new();
+
+/// The collator of [`human_compare`] for `locale`: the one of [`Collator::for_locale`], or of the
+/// root locale if the identifier is ill-formed, with numeric ordering, the spaces and the
+/// punctuation shifted, the quaternary strength and the code point tiebreak.
+fn human_collator(locale: &str) -> Collator {
+    let (fallback, options) =
+        resolve_locale(locale).unwrap_or_else(|_| ("und".to_string(), options("und")));
+    let options = CollatorOptions {
+        strength: Strength::Quaternary,
+        variable_weighting: VariableWeighting::Shifted,
+        max_variable: Some(MaxVariable::Punct),
+        numeric: true,
+        code_point_tiebreak: true,
+        ..options
+    };
+    let collators = HUMAN_COLLATORS.get_or_init(Default::default);
+    let cached = |collators: &HashMap<String, Vec<Collator>>| {
+        collators
+            .get(&fallback)?
+            .iter()
+            .find(|collator| *collator.options() == options)
+            .cloned()
+    };
+    if let Some(collator) = cached(&collators.read().unwrap()) {
+        return collator;
+    }
+    let mut collators = collators.write().unwrap();
+    if let Some(collator) = cached(&collators) {
+        return collator;
+    }
+    let data = table_data(&fallback).expect("The fallbacks are bundled locales.");
+    let collator = Collator::from_data(data, options);
+    collators
+        .entry(fallback)
+        .or_default()
+        .push(collator.clone());
+    collator
+}
+
+/// Compares `a` and `b` in the order of the file explorers, for the programs that need no other
+/// option: the digits are compared by their numeric values, the spaces and the punctuation are
+/// ignored but to break the ties, after the case and the accents, and the strings still equal are
+/// ordered by their code points, so that only identical strings compare equal. The collators are
+/// created once by resolved locale and share the cached tables of [`Collator::try_new`]; an
+/// ill-formed `locale` compares as the root locale.
+///
+/// ```
+/// use collate_rs::human_compare;
+/// use std::cmp::Ordering;
+///
+/// let mut files = ["file10.txt", "File2.txt", "file-1.txt", "file2.txt"];
+/// files.sort_by(|a, b| human_compare(a, b, "en"));
+/// assert_eq!(files, ["file-1.txt", "file2.txt", "File2.txt", "file10.txt"]);
+/// # #[cfg(feature = "locales")]
+/// assert_eq!(human_compare("å", "z", "sv"), Ordering::Greater);
+/// ```
+pub fn human_compare(a: &str, b: &str, locale: &str) -> Ordering {
+    human_collator(locale).compare(a, b)
+}
+
+/// Sorts `slice` in the order of [`human_compare`], computing the sort key of each string once.
+pub fn human_sort<S: AsRef<str>>(slice: &mut [S], locale: &str) {
+    human_collator(locale).sort_slice(slice);
+}
+
+#[cfg(all(test, feature = "locales"))]
+mod tests {
+    use super::*;
+    use crate::definitions::collation_wel::Weighted;
+
+    #[test]
+    fn test_lazy_locale_tables() {
+        let de = Collator::try_new("de").unwrap();
+        let root = Collator::try_new("und").unwrap();
+        assert!(de.shares_table_with(&root));
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| std::thread::spawn(|| Collator::try_new("sv")))
+            .collect();
+        let collators: Vec<Collator> = threads
+            .into_iter()
+            .map(|t| t.join().unwrap().unwrap())
+            .collect();
+        assert!(collators.windows(2).all(|w| w[0].shares_table_with(&w[1])));
+        assert!(!collators[0].shares_table_with(&root));
+
+        // Swedish sorts "ä" after "z", the root collation next to "a".
+        assert_eq!(collators[0].compare("ä", "z"), Ordering::Greater);
+        assert_eq!(root.compare("ä", "z"), Ordering::Less);
+
+        assert_eq!(collators[0].unicode_version(), Some(ducet::version()));
+        assert!(Collator::try_new("ES-U-CO-TRAD").is_ok());
+        assert!(matches!(
+            Collator::try_new("xx"),
+            Err(CollateError::UnknownLocale(_))
+        ));
+    }
+
+    #[test]
+    fn test_human_compare() {
+        assert_eq!(human_compare("file2", "file10", "und"), Ordering::Less);
+        assert_eq!(human_compare("de-luge", "deluge", "und"), Ordering::Less);
+        assert_eq!(human_compare("deluge", "Deluge", "und"), Ordering::Less);
+        assert_eq!(human_compare("ä", "z", "sv"), Ordering::Greater);
+        // The symbols are not ignored.
+        assert_ne!(human_compare("a+b", "ab", "und"), Ordering::Equal);
+        // The ill-formed identifiers compare as the root locale.
+        assert_eq!(human_compare("ä", "z", "sv_"), Ordering::Less);
+        assert!(human_collator("sv").shares_table_with(&Collator::try_new("sv").unwrap()));
+
+        let mut names = vec!["b 10", "B 9", "a-2", "a1"];
+        human_sort(&mut names, "en-u-kf-upper");
+        assert_eq!(names, ["a1", "a-2", "B 9", "b 10"]);
+
+        // Only identical names compare equal.
+        let names = [
+            "file-1.txt",
+            "file1.txt",
+            "file01.txt",
+            "a b",
+            "ab",
+            "a\u{301}",
+            "\u{E1}",
+            "Ab",
+        ];
+        for a in names {
+            for b in names {
+                assert_eq!(human_compare(a, b, "en") == Ordering::Equal, a == b);
+            }
+        }
+        // The tags resolving to the same collator share it.
+        let cached = || {
+            let collators = HUMAN_COLLATORS.get().unwrap().read().unwrap();
+            collators.values().map(Vec::len).sum::<usize>()
+        };
+        human_compare("a", "b", "EN");
+        let count = cached();
+        for locale in ["en", "en-US", "en_us-x-private", "en-u-xx-yy"] {
+            human_compare("a", "b", locale);
+        }
+        assert_eq!(cached(), count);
+    }
+
+    #[test]
+    fn test_danish_norwegian() {
+        // The orders of ICU 78, where aa sorts as å, after z, and the uppercase letters sort first
+        // in da. "aA" is not a contraction, and ę and œ are tailored in nb only.
+        let da = [
+            "A", "a", "aA", "Abel", "Daa", "ę", "Hå", "Haag", "kå", "kaa", "Kåre", "Kaare", "œ",
+            "Saab", "Zebra", "Ærø", "ærø", "Øresund", "Å", "AA", "Aa", "å", "aa", "Aabenraa",
+            "Ålborg", "AALBORG", "Aalborg", "aalborg", "Århus", "Aarhus", "århus", "Åse", "Aase",
+        ];
+        let nb = [
+            "a", "A", "aA", "Abel", "Daa", "Hå", "Haag", "kå", "kaa", "Kåre", "Kaare", "Saab",
+            "Zebra", "ę", "ærø", "Ærø", "œ", "Øresund", "å", "Å", "aa", "Aa", "AA", "Aabenraa",
+            "Ålborg", "aalborg", "Aalborg", "AALBORG", "århus", "Århus", "Aarhus", "Åse", "Aase",
+        ];
+        for (locale, sorted) in [("da", da), ("nb", nb)] {
+            let collator = Collator::try_new(locale).unwrap();
+            let mut words = sorted.to_vec();
+            words.reverse();
+            collator.sort_slice(&mut words);
+            assert_eq!(words, sorted, "{}", locale);
+            assert!(sorted
+                .windows(2)
+                .all(|pair| collator.compare(pair[0], pair[1]) == Ordering::Less));
+        }
+        assert_eq!(
+            Collator::try_new("da").unwrap().options().case_first,
+            CaseFirst::Upper
+        );
+        assert_eq!(
+            Collator::try_new("nb").unwrap().options().case_first,
+            CaseFirst::Off
+        );
+
+        // The case options of the extensions override the default of da.
+        let sorted_with = |locale: &str| {
+            let mut words = ["aa", "Aa", "AA", "å", "Å"];
+            Collator::for_locale(locale).unwrap().sort_slice(&mut words);
+            words
+        };
+        assert_eq!(sorted_with("da-u-kf-false"), ["å", "Å", "aa", "Aa", "AA"]);
+        assert_eq!(sorted_with("da-DK"), ["Å", "AA", "Aa", "å", "aa"]);
+    }
+
+    #[test]
+    fn test_german_phonebook() {
+        // The orders of ICU 78, where the phonebook collation expands ä, ö and ü as ae, oe and ue,
+        // with a secondary difference.
+        let standard = [
+            "ä", "Ä", "ad", "ae", "æ", "Aepfel", "Aerger", "af", "Apfel", "Äpfel", "Arger",
+            "Ärger", "Gobel", "Göbel", "Goebel", "Goethe", "Göthe", "Mueller", "Muller", "Müller",
+            "Ubel", "Übel", "Uebel",
+        ];
+        let phonebook = [
+            "ad", "ae", "ä", "Ä", "æ", "Aepfel", "Äpfel", "Aerger", "Ärger", "af", "Apfel",
+            "Arger", "Gobel", "Goebel", "Göbel", "Goethe", "Göthe", "Mueller", "Müller", "Muller",
+            "Ubel", "Uebel", "Übel",
+        ];
+        for (locale, sorted) in [("de", standard), ("de-u-co-phonebk", phonebook)] {
+            let collator = Collator::try_new(locale).unwrap();
+            let mut words = sorted;
+            words.reverse();
+            collator.sort_slice(&mut words);
+            assert_eq!(words, sorted, "{}", locale);
+        }
+
+        // The expansions have the primary weights of the letters they expand to.
+        let collator = Collator::for_locale("de-DE-u-co-phonebk").unwrap();
+        let primaries = |s: &str| -> Vec<u32> {
+            collator
+                .collation_element_array(s)
+                .iter()
+                .map(|ce| ce.weight(1))
+                .filter(|&w| w != 0)
+                .collect()
+        };
+        for (letter, expansion) in [("ä", "ae"), ("Ö", "OE"), ("u\u{308}", "ue")] {
+            assert_eq!(collator.collation_element_array(letter).len(), 2);
+            assert_eq!(primaries(letter), primaries(expansion));
+        }
+        let primary = collator.clone_with_options(CollatorOptions {
+            strength: Strength::Primary,
+            ..Default::default()
+        });
+        assert_eq!(primary.compare("Müller", "Mueller"), Ordering::Equal);
+        assert_ne!(
+            Collator::try_new("de")
+                .unwrap()
+                .clone_with_options(CollatorOptions {
+                    strength: Strength::Primary,
+                    ..Default::default()
+                })
+                .compare("Müller", "Mueller"),
+            Ordering::Equal
+        );
+        // The phonebook collation of de-AT sorts ä, ö and ü as letters after a, o and u.
+        let austrian = Collator::for_locale("de-AT-u-co-phonebk").unwrap();
+        assert_eq!(austrian.compare("Müller", "Muz"), Ordering::Greater);
+        assert_eq!(austrian.compare("Müller", "Mv"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_spanish_traditional() {
+        // The orders of ICU 78, where the traditional collation sorts ch after c and ll after l,
+        // as letters of their own. cH is not a contraction.
+        let modern = [
+            "calle", "calor", "cana", "canoa", "caña", "ch", "chico", "cHico", "Chile", "CHILE",
+            "coche", "cocina", "corte", "cuna", "cz", "dado", "ll", "llama", "Llosa", "lo", "luz",
+            "lz",
+        ];
+        let traditional = [
+            "calor", "calle", "cana", "canoa", "caña", "cHico", "cocina", "coche", "corte", "cuna",
+            "cz", "ch", "chico", "Chile", "CHILE", "dado", "lo", "luz", "lz", "ll", "llama",
+            "Llosa",
+        ];
+        for (locale, sorted) in [("es", modern), ("es-u-co-trad", traditional)] {
+            let collator = Collator::try_new(locale).unwrap();
+            let mut words = sorted;
+            words.reverse();
+            collator.sort_slice(&mut words);
+            assert_eq!(words, sorted, "{}", locale);
+        }
+
+        // The contractions weigh as one letter, of one collation element; ch still sorts before r,
+        // so coche sorts before corte in both collations, and after cocina in the traditional one
+        // only.
+        let collator = Collator::for_locale("es-ES-u-co-trad").unwrap();
+        assert_eq!(collator.collation_element_array("ch").len(), 1);
+        assert_eq!(collator.collation_element_array("LL").len(), 1);
+        assert_eq!(collator.collation_element_array("cH").len(), 2);
+        assert_eq!(collator.compare("coche", "corte"), Ordering::Less);
+        assert_eq!(collator.compare("coche", "cocina"), Ordering::Greater);
+        let modern = Collator::for_locale("es-ES").unwrap();
+        assert_eq!(modern.compare("coche", "cocina"), Ordering::Less);
+        assert_eq!(modern.collation_element_array("ch").len(), 2);
+    }
+
+    #[test]
+    fn test_turkish() {
+        // The orders of ICU 78, where ı and I are a letter before i and İ, with the case of each
+        // pair at the tertiary level only.
+        let with_strength = |strength| {
+            Collator::try_new("tr")
+                .unwrap()
+                .clone_with_options(CollatorOptions {
+                    strength,
+                    ..Default::default()
+                })
+        };
+        let tertiary = with_strength(Strength::Tertiary);
+        let sorted = [
+            "hz",
+            "ıi",
+            "ılık",
+            "Ilık",
+            "ILIK",
+            "ISTANBUL",
+            "ilik",
+            "İlik",
+            "istanbul",
+            "İstanbul",
+            "jale",
+            "kılıç",
+            "KILIÇ",
+            "kilit",
+        ];
+        let mut words = sorted;
+        words.reverse();
+        tertiary.sort_slice(&mut words);
+        assert_eq!(words, sorted);
+        let compare = |collator: &Collator, pairs: [(&str, &str); 4]| {
+            pairs.map(|(a, b)| collator.compare(a, b))
+        };
+        let pairs = [("I", "ı"), ("İ", "i"), ("I", "i"), ("ı", "i")];
+        use Ordering::{Equal, Greater, Less};
+        assert_eq!(compare(&tertiary, pairs), [Greater, Greater, Less, Less]);
+        let secondary = with_strength(Strength::Secondary);
+        assert_eq!(compare(&secondary, pairs), [Equal, Equal, Less, Less]);
+        let root = Collator::try_new("und")
+            .unwrap()
+            .clone_with_options(CollatorOptions {
+                strength: Strength::Secondary,
+                ..Default::default()
+            });
+        assert_eq!(compare(&root, pairs), [Less, Greater, Equal, Greater]);
+
+        // The default case folding, which Rust's to_lowercase follows, pairs I with i and
+        // decomposes İ as i and a dot above, where the tr collation pairs I with ı and İ with i.
+        assert_eq!("ILIK".to_lowercase(), "ilik");
+        assert_ne!(secondary.compare("ILIK", "ilik"), Equal);
+        assert_eq!(secondary.compare("ILIK", "ılık"), Equal);
+        assert_eq!("İstanbul".to_lowercase(), "i\u{307}stanbul");
+        assert_eq!(secondary.compare("İstanbul", "istanbul"), Equal);
+        // The dot above of the folded string is a secondary difference, ignored at the primary
+        // level only.
+        assert_eq!(secondary.compare("İstanbul", "i\u{307}stanbul"), Less);
+        let primary = with_strength(Strength::Primary);
+        assert_eq!(primary.compare("İstanbul", "i\u{307}stanbul"), Equal);
+    }
+
+    #[test]
+    fn test_available_locales() {
+        let locales = Collator::available_locales();
+        let de = locales.iter().find(|info| info.locale == "de").unwrap();
+        assert!(!de.tailored);
+        assert_eq!(de.collations, ["phonebk"]);
+        assert_eq!(de.identifiers(), ["de", "de-u-co-phonebk"]);
+        let sv = locales.iter().find(|info| info.locale == "sv").unwrap();
+        assert!(sv.tailored && sv.collations.is_empty());
+        assert!(locales.windows(2).all(|w| w[0].locale < w[1].locale));
+
+        let identifiers: Vec<String> = locales.iter().flat_map(LocaleInfo::identifiers).collect();
+        assert_eq!(
+            identifiers.len(),
+            ROOT_LOCALES.len() + BACKWARD_SECONDARY_LOCALES.len() + TAILORED_LOCALES.len()
+        );
+        assert!(identifiers.iter().all(|id| is_available(id)));
+        assert!(is_available("ES_u_co_TRAD"));
+        assert!(!is_available("xx"));
+    }
+
+    #[test]
+    fn test_for_locale() {
+        assert_eq!(
+            fallbacks("de-CH-u-co-phonebk-ka-shifted").unwrap(),
+            [
+                "de-ch-u-co-phonebk",
+                "de-u-co-phonebk",
+                "de-ch",
+                "de",
+                "und"
+            ]
+        );
+        assert_eq!(fallbacks("sv_SE").unwrap(), ["sv-se", "sv", "und"]);
+        assert!(fallbacks("").is_err());
+        // A `u` after the `x` singleton is private use.
+        assert_eq!(
+            fallbacks("de-x-u-co-phonebk").unwrap()[..2],
+            ["de-x-u-co-phonebk", "de-x-u-co"]
+        );
+        assert!(!Collator::for_locale("de-x-u-kn").unwrap().options().numeric);
+        assert_eq!(fallbacks("de-u-kn-x-co-phonebk").unwrap()[0], "de");
+        assert!(
+            Collator::for_locale("de-u-kn-x-u-kn-false")
+                .unwrap()
+                .options()
+                .numeric
+        );
+
+        let root = Collator::try_new("und").unwrap();
+        let sv = Collator::for_locale("sv-SE").unwrap();
+        assert!(sv.shares_table_with(&Collator::try_new("sv").unwrap()));
+        assert!(Collator::for_locale("xx").unwrap().shares_table_with(&root));
+
+        // French Canadian compares the accents from the end of the words.
+        let fr_ca = Collator::for_locale("fr-CA").unwrap();
+        let mut words = ["côté", "coté", "côte", "cote"];
+        root.sort_slice(&mut words);
+        assert_eq!(words, ["cote", "coté", "côte", "côté"]);
+        fr_ca.sort_slice(&mut words);
+        assert_eq!(words, ["cote", "côte", "coté", "côté"]);
+
+        // Pinyin: "ān" < "zhōng", whereas the root collation follows the code points.
+        let pinyin = Collator::for_locale("zh-u-co-pinyin").unwrap();
+        assert_eq!(pinyin.compare("安", "中"), Ordering::Less);
+        assert_eq!(root.compare("安", "中"), Ordering::Greater);
+        // Stroke: "乙" has one stroke, "丁" two.
+        let stroke = Collator::for_locale("zh-TW-u-co-stroke").unwrap();
+        assert_eq!(stroke.compare("乙", "丁"), Ordering::Less);
+        assert_eq!(root.compare("乙", "丁"), Ordering::Greater);
+        // Zhuyin: "八" (ㄅㄚ) < "阿" (ㄚ), whereas "ā" < "bā" in pinyin.
+        let zhuyin = Collator::for_locale("zh-u-co-zhuyin").unwrap();
+        assert_eq!(zhuyin.compare("八", "阿"), Ordering::Less);
+        assert_eq!(pinyin.compare("八", "阿"), Ordering::Greater);
+        // Japanese orders the kanji of JIS X 0208 by their reading: "a" < "ichi".
+        let ja = Collator::for_locale("ja-JP").unwrap();
+        assert_eq!(ja.compare("亜", "一"), Ordering::Less);
+        assert_eq!(root.compare("亜", "一"), Ordering::Greater);
+        // Kana differ by their kind at level 4 and by their width at level 5.
+        let ja = ja.clone_with_options(CollatorOptions {
+            strength: Strength::Quinary,
+            ..Default::default()
+        });
+        assert_eq!(ja.compare("あ", "ア"), Ordering::Less);
+        assert_eq!(ja.compare("ア", "ｱ"), Ordering::Less);
+        assert_eq!(ja.compare("ｱ", "い"), Ordering::Less);
+        let key = ja.sort_key("ｶﾞｰ");
+        assert_eq!(key.levels().len(), 5);
+        assert_eq!(key.levels()[4], [0x0002, 0x0002]);
+        assert_eq!(key.difference_level(&ja.sort_key("ガー")), Some(5));
+        let ko = Collator::for_locale("ko-KR").unwrap();
+        assert_eq!(ko.compare("家", "나"), Ordering::Less);
+
+        // Polytonic Greek: oxia (U+1F71) and tonos (U+03AC) are the same, the breathings and
+        // accents differ at the secondary level, and the spacing breathings as the combining ones.
+        let grc = Collator::for_locale("grc").unwrap();
+        assert_eq!(grc.compare("\u{1F71}", "\u{3AC}"), Ordering::Equal);
+        let mut words = ["ἅμα", "ἄμα", "ἀμά", "αμα"];
+        grc.sort_slice(&mut words);
+        assert_eq!(words, ["αμα", "ἀμά", "ἄμα", "ἅμα"]);
+        let secondary = Collator::for_locale("grc-u-ks-level2").unwrap();
+        assert_eq!(secondary.compare("\u{1FBF}Α", "\u{313}Α"), Ordering::Equal);
+        assert_eq!(
+            secondary.compare("\u{1FFE}Α", "\u{1FBF}Α"),
+            Ordering::Greater
+        );
+        // At the primary strength the diacritics are ignored, but not the iota subscript, which
+        // sorts as an iota adscript.
+        let primary = Collator::for_locale("grc-u-ks-level1").unwrap();
+        assert_eq!(primary.compare("ᾅδης", "αδης"), Ordering::Greater);
+        assert_eq!(primary.compare("ᾅδης", "αιδης"), Ordering::Equal);
+        assert_eq!(primary.compare("ἄνθρωπος", "ανθρωπος"), Ordering::Equal);
+        assert_eq!(grc.compare("ᾳ", "αι"), Ordering::Greater);
+        let root_primary = root.clone_with_options(primary.options().clone());
+        assert_eq!(root_primary.compare("ᾅδης", "αδης"), Ordering::Equal);
+
+        // Sanskrit in IAST follows the order of the alphabet, the aspirates after their consonant.
+        let iast = Collator::for_locale("sa-Latn-IN").unwrap();
+        let mut words = [
+            "hari",
+            "kṣatriya",
+            "ṛṣi",
+            "Kṛṣṇa",
+            "khaga",
+            "ācārya",
+            "candra",
+            "ṭīkā",
+            "aiśvarya",
+            "ojas",
+            "saṃskṛta",
+            "śiva",
+            "agni",
+            "zebra",
+        ];
+        iast.sort_slice(&mut words);
+        assert_eq!(
+            words,
+            [
+                "agni",
+                "ācārya",
+                "ṛṣi",
+                "aiśvarya",
+                "ojas",
+                "Kṛṣṇa",
+                "kṣatriya",
+                "khaga",
+                "candra",
+                "ṭīkā",
+                "śiva",
+                "saṃskṛta",
+                "hari",
+                "zebra",
+            ]
+        );
+        assert_eq!(iast.compare("Kha", "kha"), Ordering::Greater);
+        assert_eq!(iast.compare("saṁskṛta", "saṃskṛta"), Ordering::Greater);
+        // Contractions of Devanagari, e.g. क्ष sorted as a letter after ह, are declared alike.
+        let ksa = Tailoring::parse("0915 094D 0937 ; [.5C80.0020.0002]\n").unwrap();
+        let tailored = Collator::new(ksa.apply(&iast.table()));
+        assert_eq!(tailored.compare("क्षमा", "हरि"), Ordering::Greater);
+        assert_eq!(root.compare("क्षमा", "हरि"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_locale_extensions() {
+        assert_eq!(
+            extension_keywords("de-de-u-attr-co-phonebk-ka-shifted-kn-x-private"),
+            [
+                ("co", "phonebk".to_string()),
+                ("ka", "shifted".to_string()),
+                ("kn", "true".to_string())
+            ]
+        );
+        assert!(extension_keywords("de-de").is_empty());
+
+        let options =
+            CollatorOptions::from_locale_extensions("en-u-kb-kc-false-ks-level2").unwrap();
+        assert!(options.backward_secondary && !options.case_level);
+        assert_eq!(options.strength, Strength::Secondary);
+        assert_eq!(
+            CollatorOptions::from_locale_extensions("en-u-nu-arab").unwrap(),
+            CollatorOptions::default()
+        );
+        for invalid in [
+            "en-u-ka-blanked",
+            "en-u-kn-yes",
+            "en-u-kr-grek",
+            "en-u-ks-identic",
+        ] {
+            assert!(matches!(
+                CollatorOptions::from_locale_extensions(invalid),
+                Err(CollateError::InvalidOption(_))
+            ));
+        }
+
+        // "$" is only variable up to the currency symbols, and "_" only up to the punctuation.
+        let shifted =
+            |kv: &str| Collator::for_locale(&format!("en-u-ka-shifted-kv-{}", kv)).unwrap();
+        assert_eq!(shifted("currency").compare("a$b", "ab"), Ordering::Equal);
+        assert_eq!(shifted("symbol").compare("a$b", "ab"), Ordering::Less);
+        assert_eq!(shifted("punct").compare("a_b", "ab"), Ordering::Equal);
+        assert_eq!(shifted("punct").compare("a+b", "ab"), Ordering::Less);
+        assert_eq!(shifted("space").compare("a b", "ab"), Ordering::Equal);
+        assert_eq!(shifted("space").compare("a_b", "ab"), Ordering::Less);
+        assert!(CollatorOptions::from_locale_extensions("en-u-kv-digit").is_err());
+
+        // The keywords override the options of the locale.
+        assert!(
+            Collator::for_locale("fr-CA")
+                .unwrap()
+                .options()
+                .backward_secondary
+        );
+        assert!(
+            !Collator::for_locale("fr-CA-u-kb-false")
+                .unwrap()
+                .options()
+                .backward_secondary
+        );
+        let phonebook = Collator::for_locale("de-DE-u-co-phonebk-ka-shifted-kn-true").unwrap();
+        assert!(phonebook.shares_table_with(&Collator::try_new("de-u-co-phonebk").unwrap()));
+        assert_eq!(phonebook.compare("a 10", "a9"), Ordering::Greater);
+        // The hyphen and the leading zero are ignored below the quaternary strength.
+        assert_eq!(phonebook.compare("a-09", "a9"), Ordering::Equal);
+        assert_eq!(phonebook.compare("Ä1", "ae1"), Ordering::Greater);
+    }
+}
@@ -0,0 +1,239 @@
+use crate::algorithm::{Collator, TableData};
+use crate::data_files::binary::BinaryTable;
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::ducet::{self, UnicodeVersion};
+use crate::error::{CollateError, Result};
+use crate::locales::{
+    normalize_locale, options, BACKWARD_SECONDARY_LOCALES, ROOT_LOCALES, TAILORED_LOCALES,
+};
+use crate::tailoring::Tailoring;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// A source of collation data, as the data providers of ICU4X: the root table and the tailorings
+/// of the locales, from which [`Collator::try_new_with_provider`] builds collators.
+///
+/// The data bundled with the crate is the [`BakedProvider`]; the [`FsProvider`] reads the files of
+/// a directory, and the [`StaticProvider`] the bytes embedded by the program, e.g. a table
+/// compiled to the [binary format](crate::data_files::binary) by `collate-rs-datagen`. Other
+/// sources, e.g. a crate of data or a database, implement the trait.
+pub trait DataProvider {
+    /// The version of the DUCET the root table is, or is derived from, if known.
+    fn unicode_version(&self) -> Option<UnicodeVersion>;
+
+    /// The root table, e.g. the DUCET.
+    fn root_table(&self) -> Result<CollationElementTable>;
+
+    /// The tailoring of the root table for `locale`, a lowercase identifier with `-` separators,
+    /// e.g. `de-u-co-phonebk`: `None` if the locale sorts as the root table. Fails with
+    /// [`CollateError::UnknownLocale`] if the provider has no data for the locale.
+    fn tailoring(&self, locale: &str) -> Result<Option<Tailoring>>;
+}
+
+/// Whether the bundled data sorts `locale` as the root table, possibly with other options.
+fn is_root_locale(locale: &str) -> bool {
+    ROOT_LOCALES.contains(&locale) || BACKWARD_SECONDARY_LOCALES.contains(&locale)
+}
+
+/// The data bundled with the crate: the DUCET of [`ducet::version`] and the
+/// [`TAILORED_LOCALES`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BakedProvider;
+
+impl DataProvider for BakedProvider {
+    fn unicode_version(&self) -> Option<UnicodeVersion> {
+        Some(ducet::version())
+    }
+
+    fn root_table(&self) -> Result<CollationElementTable> {
+        Ok(ducet::table())
+    }
+
+    fn tailoring(&self, locale: &str) -> Result<Option<Tailoring>> {
+        if is_root_locale(locale) {
+            return Ok(None);
+        }
+        match TAILORED_LOCALES.iter().find(|(name, _)| *name == locale) {
+            Some((_, entries)) => Tailoring::parse(entries).map(Some),
+            None => Err(CollateError::UnknownLocale(locale.to_string())),
+        }
+    }
+}
+
+/// The data of a directory laid out as `data/` in the repository of the crate: the DUCET of
+/// `version` in `<dir>/<version>/allkeys.txt` (see [`ducet::load_table`]), and the tailoring of
+/// each locale in `<dir>/tailorings/<locale>.txt`, in the allkeys.txt format.
+///
+/// The locales without a file sort as the root table if they are among the [`ROOT_LOCALES`].
+#[derive(Debug, Clone)]
+pub struct FsProvider {
+    pub dir: PathBuf,
+    pub version: UnicodeVersion,
+}
+
+impl FsProvider {
+    pub fn new(dir: impl Into<PathBuf>, version: UnicodeVersion) -> Self {
+        Self {
+            dir: dir.into(),
+            version,
+        }
+    }
+}
+
+impl DataProvider for FsProvider {
+    fn unicode_version(&self) -> Option<UnicodeVersion> {
+        Some(self.version)
+    }
+
+    fn root_table(&self) -> Result<CollationElementTable> {
+        ducet::load_table(self.version, &self.dir)
+    }
+
+    fn tailoring(&self, locale: &str) -> Result<Option<Tailoring>> {
+        let path = self.dir.join("tailorings").join(format!("{}.txt", locale));
+        match std::fs::read_to_string(&path) {
+            Ok(entries) => Tailoring::parse(&entries).map(Some),
+            Err(_) if ROOT_LOCALES.contains(&locale) => Ok(None),
+            Err(source) if source.kind() == std::io::ErrorKind::NotFound => {
+                Err(CollateError::UnknownLocale(locale.to_string()))
+            }
+            Err(source) => Err(CollateError::Io { path, source }),
+        }
+    }
+}
+
+/// Data embedded in the program: a root table in the [binary format](crate::data_files::binary)
+/// and tailorings in the allkeys.txt format, e.g. included with `include_bytes!` and
+/// `include_str!`, so that a program supplying its own tables does not read files.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::data_files::binary::write_table;
+/// use collate_rs::data_files::parse_allkeys;
+/// use collate_rs::locales::provider::StaticProvider;
+/// use std::cmp::Ordering;
+///
+/// let ducet = parse_allkeys("0061 ; [.1C47.0020.0002]\n0062 ; [.1C60.0020.0002]\n").unwrap();
+/// let bytes = write_table(&ducet).unwrap();
+/// let provider = StaticProvider {
+///     root_table: &bytes,
+///     unicode_version: None,
+///     tailorings: &[("x-reversed", "0061 ; [.1C61.0020.0002]\n")],
+/// };
+/// let root = Collator::try_new_with_provider("und", &provider).unwrap();
+/// assert_eq!(root.compare("a", "b"), Ordering::Less);
+/// let reversed = Collator::try_new_with_provider("x-reversed", &provider).unwrap();
+/// assert_eq!(reversed.compare("a", "b"), Ordering::Greater);
+/// assert!(Collator::try_new_with_provider("sv", &provider).is_err());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct StaticProvider<'a> {
+    pub root_table: &'a [u8],
+    pub unicode_version: Option<UnicodeVersion>,
+    /// The tailorings, by lowercase locale identifier.
+    pub tailorings: &'a [(&'a str, &'a str)],
+}
+
+impl DataProvider for StaticProvider<'_> {
+    fn unicode_version(&self) -> Option<UnicodeVersion> {
+        self.unicode_version
+    }
+
+    fn root_table(&self) -> Result<CollationElementTable> {
+        BinaryTable::new(self.root_table).map(|table| table.to_table())
+    }
+
+    fn tailoring(&self, locale: &str) -> Result<Option<Tailoring>> {
+        match self.tailorings.iter().find(|(name, _)| *name == locale) {
+            Some((_, entries)) => Tailoring::parse(entries).map(Some),
+            None if ROOT_LOCALES.contains(&locale) => Ok(None),
+            None => Err(CollateError::UnknownLocale(locale.to_string())),
+        }
+    }
+}
+
+impl Collator {
+    /// Creates a collator for `locale` from the data of `provider`, with the options of the
+    /// locale as [`Collator::try_new`] sets them, e.g. the backward secondary weights of `fr-CA`.
+    ///
+    /// Unlike [`Collator::try_new`], the table is built on each call, from the tables the provider
+    /// returns: the collators created for the same locale should be cloned instead, as the clones
+    /// share their table. Fails with [`CollateError::UnsupportedVersion`] if the provider declares
+    /// another version than the one of the Unicode data of the crate ([`ducet::version`]).
+    pub fn try_new_with_provider(locale: &str, provider: &dyn DataProvider) -> Result<Self> {
+        let locale = normalize_locale(locale);
+        let root = provider.root_table()?;
+        let table = match provider.tailoring(&locale)? {
+            Some(tailoring) => tailoring.apply(&root),
+            None => root,
+        };
+        let data = match provider.unicode_version() {
+            Some(version) => TableData::versioned(table, version)?,
+            None => TableData::new(table),
+        };
+        Ok(Self::from_data(Arc::new(data), options(&locale)))
+    }
+}
+
+#[cfg(all(test, feature = "locales"))]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_baked_provider() {
+        for locale in ["und", "fr-CA", "sv", "de-u-co-phonebk"] {
+            let provided = Collator::try_new_with_provider(locale, &BakedProvider).unwrap();
+            let bundled = Collator::try_new(locale).unwrap();
+            assert_eq!(provided.version(), bundled.version(), "{}", locale);
+            assert_eq!(provided.options(), bundled.options());
+            assert!(!provided.shares_table_with(&bundled));
+        }
+        assert!(matches!(
+            Collator::try_new_with_provider("xx", &BakedProvider),
+            Err(CollateError::UnknownLocale(_))
+        ));
+    }
+
+    #[test]
+    fn test_fs_provider() {
+        let dir = std::env::temp_dir().join(format!("collate-rs-provider-{}", std::process::id()));
+        let version = crate::ducet::version();
+        std::fs::create_dir_all(dir.join(version.as_str())).unwrap();
+        std::fs::create_dir_all(dir.join("tailorings")).unwrap();
+        std::fs::write(
+            dir.join(version.as_str()).join("allkeys.txt"),
+            format!(
+                "@version {}\n0061 ; [.2075.0020.0002]\n0062 ; [.2076.0020.0002]\n",
+                version
+            ),
+        )
+        .unwrap();
+        std::fs::write(dir.join("tailorings/xx.txt"), "0062 ; [.2074.0020.0002]\n").unwrap();
+        let provider = FsProvider::new(&dir, version);
+        let root = Collator::try_new_with_provider("und", &provider).unwrap();
+        assert_eq!(root.compare("a", "b"), Ordering::Less);
+        assert_eq!(root.unicode_version(), Some(version));
+        let tailored = Collator::try_new_with_provider("XX", &provider).unwrap();
+        assert_eq!(tailored.compare("a", "b"), Ordering::Greater);
+        assert!(matches!(
+            Collator::try_new_with_provider("sv", &provider),
+            Err(CollateError::UnknownLocale(_))
+        ));
+        let missing = FsProvider::new(&dir, UnicodeVersion::V16_0);
+        assert!(Collator::try_new_with_provider("und", &missing).is_err());
+        // The tables of another version than the one of the Unicode data of the crate are
+        // rejected.
+        std::fs::create_dir_all(dir.join("16.0.0")).unwrap();
+        std::fs::write(
+            dir.join("16.0.0/allkeys.txt"),
+            "@version 16.0.0\n0061 ; [.2075.0020.0002]\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            Collator::try_new_with_provider("und", &missing),
+            Err(CollateError::UnsupportedVersion(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
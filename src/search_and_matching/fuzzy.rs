@@ -0,0 +1,104 @@
+use crate::algorithm::scratch::with_thread_scratch;
+use crate::algorithm::Collator;
+use crate::definitions::collation_wel::Weighted;
+
+/// The non-ignorable primary weights of the collation element array of `s`.
+fn primary_weights(collator: &Collator, s: &str) -> Vec<u32> {
+    with_thread_scratch(|scratch| {
+        collator.collation_elements_into(s, scratch);
+        scratch
+            .elements
+            .as_slice()
+            .iter()
+            .map(|ce| ce.weight(1))
+            .filter(|&w| w != 0)
+            .collect()
+    })
+}
+
+/// The Levenshtein distance of `a` and `b` over their primary weights rather than their code
+/// points: the number of insertions, deletions and substitutions of primary collation elements
+/// turning one into the other.
+///
+/// The differences of accents, case and width are thus ignored, as are the variable characters
+/// with a [`Shifted`](crate::definitions::variable_weighting::VariableWeighting::Shifted)
+/// collator, e.g. to deduplicate a list of names.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::search_and_matching::fuzzy::edit_distance;
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// assert_eq!(edit_distance(&collator, "Muller", "Müller"), 0);
+/// assert_eq!(edit_distance(&collator, "Mueller", "Müller"), 1);
+/// assert_eq!(edit_distance(&collator, "ｶﾀｶﾅ", "カタカナ"), 0);
+/// assert_eq!(edit_distance(&collator, "Schmidt", "Schmitt"), 1);
+/// ```
+pub fn edit_distance(collator: &Collator, a: &str, b: &str) -> usize {
+    let (a, b) = (primary_weights(collator, a), primary_weights(collator, b));
+    levenshtein(&a, &b, usize::MAX)
+}
+
+/// Whether the [`edit_distance`] of `a` and `b` is at most `max_distance`, stopping as soon as it
+/// is known to be greater.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::search_and_matching::fuzzy::is_within_distance;
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// assert!(is_within_distance(&collator, "Jürgen", "Jurgen", 0));
+/// assert!(!is_within_distance(&collator, "Jürgen", "Jorge", 1));
+/// ```
+pub fn is_within_distance(collator: &Collator, a: &str, b: &str, max_distance: usize) -> bool {
+    let (a, b) = (primary_weights(collator, a), primary_weights(collator, b));
+    a.len().abs_diff(b.len()) <= max_distance && levenshtein(&a, &b, max_distance) <= max_distance
+}
+
+/// The Levenshtein distance of `a` and `b`, or some distance greater than `max` once every
+/// alignment costs more.
+fn levenshtein(a: &[u32], b: &[u32], max: usize) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, wa) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, wb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(wa != wb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().all(|&d| d > max) {
+            return max.saturating_add(1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::CollatorOptions;
+    use crate::definitions::variable_weighting::VariableWeighting;
+
+    #[test]
+    fn test_11_fuzzy() {
+        assert_eq!(levenshtein(&[1, 2, 3], &[1, 3], usize::MAX), 1);
+        assert_eq!(levenshtein(&[], &[1, 2], usize::MAX), 2);
+        assert_eq!(levenshtein(&[1, 2, 3, 4], &[5, 6, 7, 8], 1), 2);
+
+        let collator = Collator::try_new("und").unwrap();
+        assert_eq!(edit_distance(&collator, "", ""), 0);
+        assert_eq!(edit_distance(&collator, "kitten", "sitting"), 3);
+        assert_eq!(edit_distance(&collator, "ÉCOLE", "ecole"), 0);
+        assert_eq!(edit_distance(&collator, "Jean-Luc", "Jean Luc"), 1);
+        let shifted = collator.clone_with_options(CollatorOptions {
+            variable_weighting: VariableWeighting::Shifted,
+            ..Default::default()
+        });
+        assert_eq!(edit_distance(&shifted, "Jean-Luc", "JeanLuc"), 0);
+
+        assert!(is_within_distance(&collator, "kitten", "sitting", 3));
+        assert!(!is_within_distance(&collator, "kitten", "sitting", 2));
+        assert!(!is_within_distance(&collator, "a", "abcd", 2));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::data_files::ucd::general_category;
+
+/// The Grapheme_Cluster_Break property values of UAX #29, as derived from the general categories
+/// and the Hangul jamo ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GraphemeBreak {
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Prepend,
+    SpacingMark,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    Other,
+}
+
+fn grapheme_break(c: char) -> GraphemeBreak {
+    use GraphemeBreak::*;
+    match c as u32 {
+        0x000D => return Cr,
+        0x000A => return Lf,
+        0x200C => return Extend,
+        0x200D => return Zwj,
+        // The emoji modifiers and the halfwidth voiced sound marks, of other categories.
+        0x1F3FB..=0x1F3FF | 0xFF9E..=0xFF9F | 0xE0020..=0xE007F => return Extend,
+        0x1F1E6..=0x1F1FF => return RegionalIndicator,
+        // The prepended concatenation marks.
+        0x0600..=0x0605 | 0x06DD | 0x070F | 0x0890..=0x0891 | 0x08E2 | 0x110BD | 0x110CD => {
+            return Prepend
+        }
+        0x1100..=0x115F | 0xA960..=0xA97C => return L,
+        0x1160..=0x11A7 | 0xD7B0..=0xD7C6 => return V,
+        0x11A8..=0x11FF | 0xD7CB..=0xD7FB => return T,
+        cp @ 0xAC00..=0xD7A3 if (cp - 0xAC00) % 28 == 0 => return Lv,
+        0xAC00..=0xD7A3 => return Lvt,
+        _ => {}
+    }
+    match general_category(c) {
+        "Mn" | "Me" => Extend,
+        "Mc" => SpacingMark,
+        "Cc" | "Cf" | "Zl" | "Zp" => Control,
+        _ => Other,
+    }
+}
+
+/// Whether `c` is an Extended_Pictographic character, approximated by the blocks of the emoji
+/// and the pictographic symbols.
+fn is_extended_pictographic(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x00A9
+            | 0x00AE
+            | 0x203C
+            | 0x2049
+            | 0x2122
+            | 0x2139
+            | 0x2194..=0x21AA
+            | 0x2300..=0x23FF
+            | 0x25A0..=0x27BF
+            | 0x2B00..=0x2BFF
+            | 0x1F000..=0x1F0FF
+            | 0x1F10D..=0x1F1AD
+            | 0x1F200..=0x1FAFF
+            | 0x1FC00..=0x1FFFD
+    )
+}
+
+/// The byte offsets of the extended grapheme cluster boundaries (UAX #29) of `s`, from `0` to
+/// `s.len()`, e.g. to never split a base character from its combining marks.
+///
+/// The Grapheme_Cluster_Break property is derived from the general categories and the
+/// Extended_Pictographic property is approximated by the emoji blocks, which is close enough to
+/// keep the combining sequences, the Hangul syllables, the flags and the emoji ZWJ sequences
+/// whole. A full segmenter can be plugged into a search by
+/// [`MatchBoundaries::Custom`](crate::search_and_matching::search::MatchBoundaries::Custom).
+///
+/// ```
+/// use collate_rs::search_and_matching::graphemes::grapheme_boundaries;
+///
+/// assert_eq!(grapheme_boundaries("ab"), vec![0, 1, 2]);
+/// assert_eq!(grapheme_boundaries("a\u{308}b"), vec![0, 3, 4]);
+/// assert_eq!(grapheme_boundaries("\u{1100}\u{1161}\u{11A8}"), vec![0, 9]);
+/// assert_eq!(grapheme_boundaries("\r\n"), vec![0, 2]);
+/// assert_eq!(grapheme_boundaries(""), vec![0]);
+/// ```
+pub fn grapheme_boundaries(s: &str) -> Vec<usize> {
+    use GraphemeBreak::*;
+    let mut boundaries = vec![0];
+    let mut previous: Option<GraphemeBreak> = None;
+    // The number of regional indicators right before, and whether the characters before are an
+    // Extended_Pictographic character followed by Extend characters and a ZWJ.
+    let mut regional_indicators = 0;
+    let mut pictographic = false;
+    let mut pictographic_zwj = false;
+    for (i, c) in s.char_indices() {
+        let current = grapheme_break(c);
+        let boundary = match (previous, current) {
+            (None, _) => false,
+            // GB3, GB4 and GB5.
+            (Some(Cr), Lf) => false,
+            (Some(Cr | Lf | Control), _) | (_, Cr | Lf | Control) => true,
+            // GB6, GB7 and GB8.
+            (Some(L), L | V | Lv | Lvt) => false,
+            (Some(Lv | V), V | T) => false,
+            (Some(Lvt | T), T) => false,
+            // GB9, GB9a and GB9b.
+            (_, Extend | Zwj | SpacingMark) => false,
+            (Some(Prepend), _) => false,
+            // GB11.
+            (Some(Zwj), _) if pictographic_zwj && is_extended_pictographic(c) => false,
+            // GB12 and GB13.
+            (Some(RegionalIndicator), RegionalIndicator) => regional_indicators % 2 == 0,
+            _ => true,
+        };
+        if boundary {
+            boundaries.push(i);
+        }
+        regional_indicators = match current {
+            RegionalIndicator => regional_indicators + 1,
+            _ => 0,
+        };
+        pictographic_zwj = pictographic && current == Zwj;
+        pictographic = is_extended_pictographic(c) || (pictographic && current == Extend);
+        previous = Some(current);
+    }
+    if !s.is_empty() {
+        boundaries.push(s.len());
+    }
+    boundaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_11_graphemes() {
+        // Two flags, the regional indicators paired.
+        let flags = "\u{1F1EB}\u{1F1F7}\u{1F1E9}\u{1F1EA}";
+        assert_eq!(grapheme_boundaries(flags), vec![0, 8, 16]);
+        // A family emoji ZWJ sequence, then a skin tone modifier.
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        assert_eq!(grapheme_boundaries(family), vec![0, family.len()]);
+        assert_eq!(grapheme_boundaries("\u{1F44D}\u{1F3FD}"), vec![0, 8]);
+        // A ZWJ joins no letter.
+        assert_eq!(grapheme_boundaries("a\u{200D}b"), vec![0, 4, 5]);
+        // A precomposed LV syllable takes a trailing jamo but no leading one.
+        assert_eq!(grapheme_boundaries("\u{AC00}\u{11A8}"), vec![0, 6]);
+        assert_eq!(grapheme_boundaries("\u{AC00}\u{1100}"), vec![0, 3, 6]);
+        // A spacing mark, and a combining mark after a control.
+        assert_eq!(grapheme_boundaries("\u{915}\u{93F}"), vec![0, 6]);
+        assert_eq!(grapheme_boundaries("\n\u{301}"), vec![0, 1, 3]);
+        // A prepended concatenation mark.
+        assert_eq!(grapheme_boundaries("\u{600}\u{661}"), vec![0, 4]);
+    }
+}
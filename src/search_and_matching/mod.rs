@@ -0,0 +1,9 @@
+/// Defines the [`edit_distance`](`fuzzy::edit_distance`) of strings over their primary weights, for
+/// fuzzy matching.
+pub mod fuzzy;
+/// Defines the [`grapheme_boundaries`](`graphemes::grapheme_boundaries`) of strings, where the
+/// matches of a search may be restricted to start and end.
+pub mod graphemes;
+/// Defines the [`StringSearch`](`search::StringSearch`) of the substrings equal to a pattern by a
+/// collator.
+pub mod search;
@@ -0,0 +1,337 @@
+use crate::algorithm::scratch::with_thread_scratch;
+use crate::algorithm::sort_key::SortKey;
+use crate::algorithm::Collator;
+use crate::definitions::ignorable::IgnorableAtLevel;
+use crate::search_and_matching::graphemes::grapheme_boundaries;
+use std::ops::Range;
+
+/// Where the matches of a [`StringSearch`] may start and end.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum MatchBoundaries {
+    /// At any character boundary, so that a search for "a" matches the "a" of "a\u{308}".
+    #[default]
+    Characters,
+    /// At the extended grapheme cluster boundaries of
+    /// [`grapheme_boundaries`](crate::search_and_matching::graphemes::grapheme_boundaries), so
+    /// that no match splits a base character from its combining marks.
+    Graphemes,
+    /// At the byte offsets returned by a function, e.g. to plug in a full UAX #29 segmenter. The
+    /// offsets must be character boundaries in increasing order, from `0` to the length of the
+    /// text.
+    Custom(fn(&str) -> Vec<usize>),
+}
+
+impl MatchBoundaries {
+    fn boundaries(self, text: &str) -> Vec<usize> {
+        match self {
+            MatchBoundaries::Characters => text
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(text.len()))
+                .collect(),
+            MatchBoundaries::Graphemes => grapheme_boundaries(text),
+            MatchBoundaries::Custom(boundaries) => boundaries(text),
+        }
+    }
+}
+
+/// Whether the characters ignorable at the strength of the collator, e.g. the combining marks of a
+/// primary search, belong to the matches they are next to, as the minimal and maximal matches of
+/// UTS10 §8.2.
+///
+/// For a primary search of "ab" in "\u{301}ab\u{308}c", the minimal match is "ab", the default
+/// one "\u{301}ab" and the maximal one "\u{301}ab\u{308}".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchStyle {
+    /// The ignorable characters at the start and at the end of the matches are left out.
+    Minimal,
+    /// The matches start at the first boundary where one starts, the ignorable characters before
+    /// them included, and end at the first boundary where they end.
+    #[default]
+    Default,
+    /// The matches also extend over the ignorable characters after them.
+    Maximal,
+}
+
+/// The options of a [`StringSearch`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchOptions {
+    pub boundaries: MatchBoundaries,
+    pub style: MatchStyle,
+}
+
+/// A match of a [`StringSearch`], with its offsets in the units of the usual text APIs, e.g. to
+/// highlight it in a user interface, where the expansions and the contractions of the collation
+/// make its length differ from the one of the pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// The offsets of the match in the UTF-8 bytes of the text.
+    pub bytes: Range<usize>,
+    /// The offsets of the match in the characters (code points) of the text.
+    pub chars: Range<usize>,
+    /// The number of collation elements of the match, ignorable ones included.
+    pub collation_elements: usize,
+}
+
+/// A search for the substrings of texts equal to a pattern by a collator, at its strength.
+///
+/// By default, a match is the shortest substring equal to the pattern from the first boundary
+/// where one starts (see [`MatchStyle`]), and the next match is searched from its end.
+///
+/// ```
+/// use collate_rs::algorithm::{Collator, CollatorOptions, Strength};
+/// use collate_rs::search_and_matching::search::{MatchBoundaries, SearchOptions, StringSearch};
+///
+/// let collator = Collator::try_new("und").unwrap().clone_with_options(CollatorOptions {
+///     strength: Strength::Primary,
+///     ..Default::default()
+/// });
+/// let search = StringSearch::new(&collator, "cafe");
+/// assert_eq!(search.find("Un CAFÉ, deux cafés"), Some(3..8));
+/// assert_eq!(search.find_iter("Un CAFÉ, deux cafés").count(), 2);
+///
+/// let text = "ba\u{308}a";
+/// assert_eq!(StringSearch::new(&collator, "a").find(text), Some(1..2));
+/// let whole = SearchOptions {
+///     boundaries: MatchBoundaries::Graphemes,
+///     ..Default::default()
+/// };
+/// let search = StringSearch::with_options(&collator, "a", whole);
+/// assert_eq!(search.find(text), Some(1..4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct StringSearch<'c> {
+    collator: &'c Collator,
+    key: SortKey,
+    primaries: usize,
+    options: SearchOptions,
+}
+
+impl<'c> StringSearch<'c> {
+    pub fn new(collator: &'c Collator, pattern: &str) -> Self {
+        Self::with_options(collator, pattern, SearchOptions::default())
+    }
+
+    pub fn with_options(collator: &'c Collator, pattern: &str, options: SearchOptions) -> Self {
+        let (key, primaries, _) = key_and_primaries(collator, pattern);
+        StringSearch {
+            collator,
+            key,
+            primaries,
+            options,
+        }
+    }
+
+    /// The byte range of the first match in `text`, if any.
+    pub fn find(&self, text: &str) -> Option<Range<usize>> {
+        self.find_iter(text).next()
+    }
+
+    /// The byte ranges of the successive non-overlapping matches in `text`.
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+        self.matches(text).map(|found| found.bytes)
+    }
+
+    /// The first match in `text`, if any, with its byte and character offsets.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use collate_rs::search_and_matching::search::StringSearch;
+    ///
+    /// let collator = Collator::try_new("und").unwrap();
+    /// let found = StringSearch::new(&collator, "\u{E9}t\u{E9}").find_match("l'e\u{301}te\u{301}");
+    /// let found = found.unwrap();
+    /// assert_eq!(found.bytes, 2..9);
+    /// assert_eq!(found.chars, 2..7);
+    /// assert_eq!(found.collation_elements, 5);
+    /// ```
+    pub fn find_match(&self, text: &str) -> Option<SearchMatch> {
+        self.matches(text).next()
+    }
+
+    /// The successive non-overlapping matches in `text`, with their byte and character offsets.
+    pub fn matches<'a>(&'a self, text: &'a str) -> impl Iterator<Item = SearchMatch> + 'a {
+        let boundaries = self.options.boundaries.boundaries(text);
+        // The characters before `boundaries[counted]`.
+        let (mut counted, mut chars) = (0, 0);
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            while start < boundaries.len() {
+                match self.match_at(text, &boundaries, start) {
+                    Some((end, collation_elements)) => {
+                        let (first, end, collation_elements) =
+                            self.styled(text, &boundaries, start..end, collation_elements);
+                        let mut char_offset = |to: usize| {
+                            chars += text[boundaries[counted]..boundaries[to]].chars().count();
+                            counted = to;
+                            chars
+                        };
+                        let chars = char_offset(first)..char_offset(end);
+                        let bytes = boundaries[first]..boundaries[end];
+                        start = end;
+                        return Some(SearchMatch {
+                            bytes,
+                            chars,
+                            collation_elements,
+                        });
+                    }
+                    None => start += 1,
+                }
+            }
+            None
+        })
+    }
+
+    /// The match of `boundaries[range]`, found by [`StringSearch::match_at`], with the
+    /// ignorable characters at its ends left out or added according to the [`MatchStyle`]: the
+    /// boundaries it starts and ends at, and the number of its collation elements.
+    fn styled(
+        &self,
+        text: &str,
+        boundaries: &[usize],
+        range: Range<usize>,
+        collation_elements: usize,
+    ) -> (usize, usize, usize) {
+        let (mut start, mut end, mut collation_elements) =
+            (range.start, range.end, collation_elements);
+        let matches = |start: usize, end: usize| {
+            let (key, _, elements) =
+                key_and_primaries(self.collator, &text[boundaries[start]..boundaries[end]]);
+            (key == self.key).then_some(elements)
+        };
+        match self.options.style {
+            MatchStyle::Minimal => {
+                while start + 1 < end {
+                    match matches(start + 1, end) {
+                        Some(elements) => (start, collation_elements) = (start + 1, elements),
+                        None => break,
+                    }
+                }
+            }
+            MatchStyle::Default => {}
+            MatchStyle::Maximal => {
+                while end + 1 < boundaries.len() {
+                    match matches(start, end + 1) {
+                        Some(elements) => (end, collation_elements) = (end + 1, elements),
+                        None => break,
+                    }
+                }
+            }
+        }
+        (start, end, collation_elements)
+    }
+
+    /// The index in `boundaries` of the end of the shortest match starting at
+    /// `boundaries[start]`, if any, and the number of its collation elements. The matches are
+    /// non-empty and end once the substring has more primary collation elements than the
+    /// pattern.
+    fn match_at(&self, text: &str, boundaries: &[usize], start: usize) -> Option<(usize, usize)> {
+        for end in start + 1..boundaries.len() {
+            let (key, primaries, elements) =
+                key_and_primaries(self.collator, &text[boundaries[start]..boundaries[end]]);
+            if primaries > self.primaries {
+                return None;
+            }
+            if key == self.key {
+                return Some((end, elements));
+            }
+        }
+        None
+    }
+}
+
+/// The sort key of `s`, the number of its non-ignorable primary collation elements and the
+/// number of all of them.
+fn key_and_primaries(collator: &Collator, s: &str) -> (SortKey, usize, usize) {
+    with_thread_scratch(|scratch| {
+        collator.collation_elements_into(s, scratch);
+        let elements = scratch.elements.as_slice();
+        let primaries = elements
+            .iter()
+            .filter(|ce| !ce.is_ignorable_at_level(1))
+            .count();
+        let key = collator.sort_key_from_elements(elements);
+        (key, primaries, elements.len())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{CollatorOptions, Strength};
+
+    #[test]
+    fn test_11_search() {
+        let collator = Collator::try_new("und").unwrap();
+        let search = StringSearch::new(&collator, "ab");
+        assert_eq!(search.find(""), None);
+        assert_eq!(search.find("xxab"), Some(2..4));
+        assert_eq!(
+            search.find_iter("ababxab").collect::<Vec<_>>(),
+            vec![0..2, 2..4, 5..7]
+        );
+        // At the tertiary strength, the case and the accents differ.
+        assert_eq!(search.find("AB áb"), None);
+        // The canonically equivalent strings match.
+        let search = StringSearch::new(&collator, "\u{E1}");
+        assert_eq!(search.find("xa\u{301}"), Some(1..4));
+
+        // The combining marks stay with their base character.
+        let graphemes = SearchOptions {
+            boundaries: MatchBoundaries::Graphemes,
+            ..Default::default()
+        };
+        let search = StringSearch::with_options(&collator, "a", graphemes);
+        assert_eq!(search.find("a\u{308}"), None);
+        assert_eq!(search.find("a\u{308}a"), Some(3..4));
+        let search = StringSearch::with_options(&collator, "\u{E4}", graphemes);
+        assert_eq!(search.find("a\u{308}"), Some(0..3));
+
+        // Custom boundaries, here at the spaces only.
+        fn words(text: &str) -> Vec<usize> {
+            let mut boundaries = vec![0];
+            boundaries.extend(text.match_indices(' ').flat_map(|(i, _)| [i, i + 1]));
+            boundaries.push(text.len());
+            boundaries
+        }
+        let options = SearchOptions {
+            boundaries: MatchBoundaries::Custom(words),
+            ..Default::default()
+        };
+        let search = StringSearch::with_options(&collator, "cat", options);
+        assert_eq!(search.find("concat cat"), Some(7..10));
+
+        // The character offsets of the matches past multibyte characters and expansions.
+        let search = StringSearch::new(&collator, "\u{E6}");
+        let matches: Vec<SearchMatch> = search.matches("\u{E6}\u{E9}\u{E6} \u{E6}").collect();
+        let chars: Vec<Range<usize>> = matches.iter().map(|m| m.chars.clone()).collect();
+        assert_eq!(chars, [0..1, 2..3, 4..5]);
+        assert_eq!(matches[2].bytes, 7..9);
+        assert_eq!(matches[0].collation_elements, 3);
+        assert_eq!(search.find_match("ae"), None);
+
+        // The ignorable characters at the ends of the matches, by match style.
+        let primary = collator.clone_with_options(CollatorOptions {
+            strength: Strength::Primary,
+            ..Default::default()
+        });
+        let text = "x\u{301}ab\u{308}\u{301}c ab";
+        let found = |style| {
+            let options = SearchOptions {
+                style,
+                ..Default::default()
+            };
+            let search = StringSearch::with_options(&primary, "ab", options);
+            search.matches(text).collect::<Vec<_>>()
+        };
+        let minimal = found(MatchStyle::Minimal);
+        assert_eq!(minimal[0].bytes, 3..5);
+        assert_eq!(minimal[0].collation_elements, 2);
+        assert_eq!(found(MatchStyle::Default)[0].bytes, 1..5);
+        let maximal = found(MatchStyle::Maximal);
+        assert_eq!(maximal[0].bytes, 1..9);
+        assert_eq!(maximal[0].chars, 1..6);
+        assert_eq!(maximal[0].collation_elements, 5);
+        assert_eq!(maximal[1].bytes, 11..13);
+    }
+}
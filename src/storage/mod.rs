@@ -0,0 +1,181 @@
+use crate::algorithm::{Collator, CollatorOptions, KeyEncoding};
+use crate::error::{CollateError, Result};
+use std::cmp::Ordering;
+
+/// The byte between the sort key and the string in an encoded key. No weight nor level separator
+/// of the [`KeyEncoding::Fractional`] encoding starts with a `00` byte, so it is lower than the
+/// byte following a sort key in any sort key it is a prefix of.
+const SEPARATOR: u8 = 0x00;
+
+/// The collator computing the keys: `collator` with the [`KeyEncoding::Fractional`] encoding.
+fn fractional(collator: &Collator) -> Collator {
+    collator.clone_with_options(CollatorOptions {
+        key_encoding: KeyEncoding::Fractional,
+        ..collator.options().clone()
+    })
+}
+
+/// The key of `s` for an ordered key-value store, e.g. sled or RocksDB: its sort key, a `00`
+/// byte and its UTF-8 bytes, so that the keys sort in the order of the collator and `s` can be
+/// recovered by [`decode`] from the keys returned by the iterators of the store.
+///
+/// The sort key is computed in the [`KeyEncoding::Fractional`] encoding, whatever the encoding of
+/// the collator, as its weights never start with a `00` byte. The strings equal for the collator
+/// are ordered by their bytes, so that distinct strings have distinct keys.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::storage;
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// let mut keys: Vec<Vec<u8>> = ["zulu", "Élan", "elan"]
+///     .iter()
+///     .map(|s| storage::encode(&collator, s))
+///     .collect();
+/// keys.sort();
+/// let strings: Vec<&str> = keys.iter().map(|key| storage::decode(key).unwrap()).collect();
+/// assert_eq!(strings, ["elan", "Élan", "zulu"]);
+/// ```
+pub fn encode(collator: &Collator, s: &str) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(collator.sort_key_len_hint(s) + 1 + s.len());
+    encode_into(collator, s, &mut buffer);
+    buffer
+}
+
+/// Like [`encode`], appending the key to `buffer`, e.g. to reuse the buffer of a batch of
+/// writes.
+pub fn encode_into(collator: &Collator, s: &str, buffer: &mut Vec<u8>) {
+    fractional(collator).append_sort_key(s, buffer);
+    buffer.push(SEPARATOR);
+    buffer.extend_from_slice(s.as_bytes());
+}
+
+/// Splits a key of [`encode`] into the bytes of its sort key and its string.
+pub fn split(key: &[u8]) -> Result<(&[u8], &str)> {
+    if key.first() != Some(&KeyEncoding::Fractional.header()) {
+        return Err(CollateError::InvalidSortKey(
+            "The sort key is not of the Fractional encoding.".into(),
+        ));
+    }
+    let len = sort_key_len(key)
+        .ok_or_else(|| CollateError::InvalidSortKey("No separator after the sort key.".into()))?;
+    let s = std::str::from_utf8(&key[len + 1..])
+        .map_err(|e| CollateError::InvalidSortKey(format!("Invalid UTF-8 string: {}.", e)))?;
+    Ok((&key[..len], s))
+}
+
+/// The length of the sort key of a key of [`encode`], the index of the separator following it,
+/// if any. The weights may have `00` bytes past their first one, so the key is read weight by
+/// weight.
+fn sort_key_len(key: &[u8]) -> Option<usize> {
+    let mut i = 1;
+    while i < key.len() {
+        if key[i] == SEPARATOR {
+            return Some(i);
+        }
+        i += KeyEncoding::Fractional.unit_len(key[i]);
+    }
+    None
+}
+
+/// The string of a key of [`encode`].
+pub fn decode(key: &[u8]) -> Result<&str> {
+    split(key).map(|(_, s)| s)
+}
+
+/// The comparison of the keys of [`encode`], for the stores taking a custom comparator: the
+/// binary comparison of their bytes, which the stores comparing bytes use by default.
+pub fn compare(a: &[u8], b: &[u8]) -> Ordering {
+    a.cmp(b)
+}
+
+/// The comparison of the keys of [`encode`] by their sort keys only, ignoring their strings: the
+/// order of the collator, in which the strings equal for the collator are equal. The bytes that
+/// are not keys of [`encode`] are compared as if they were all sort key.
+pub fn compare_sort_keys(a: &[u8], b: &[u8]) -> Ordering {
+    sort_key_part(a).cmp(sort_key_part(b))
+}
+
+/// The bytes of a key before its separator, all of them if it has none.
+fn sort_key_part(key: &[u8]) -> &[u8] {
+    &key[..sort_key_len(key).unwrap_or(key.len())]
+}
+
+/// The least key of [`encode`] of the strings equal to `s` for the collator: the bound from which
+/// to iterate over them, or over the strings following them.
+///
+/// ```
+/// use collate_rs::algorithm::{Collator, CollatorOptions, Strength};
+/// use collate_rs::storage;
+/// use std::collections::BTreeSet;
+///
+/// let collator = Collator::try_new("und").unwrap().clone_with_options(CollatorOptions {
+///     strength: Strength::Primary,
+///     ..Default::default()
+/// });
+/// let store: BTreeSet<Vec<u8>> = ["Ana", "ANA", "Anaïs", "ana", "Bob"]
+///     .iter()
+///     .map(|s| storage::encode(&collator, s))
+///     .collect();
+/// let range = storage::lower_bound(&collator, "ana")..storage::upper_bound(&collator, "ana");
+/// let equal: Vec<&str> = store
+///     .range(range)
+///     .map(|key| storage::decode(key).unwrap())
+///     .collect();
+/// assert_eq!(equal, ["ANA", "Ana", "ana"]);
+/// ```
+pub fn lower_bound(collator: &Collator, s: &str) -> Vec<u8> {
+    let mut bound = fractional(collator).sort_key(s).into_bytes();
+    bound.push(SEPARATOR);
+    bound
+}
+
+/// The least key of [`encode`] greater than the keys of the strings equal to `s` for the
+/// collator: the exclusive end of the iteration over them.
+pub fn upper_bound(collator: &Collator, s: &str) -> Vec<u8> {
+    let mut bound = fractional(collator).sort_key(s).into_bytes();
+    bound.push(SEPARATOR + 1);
+    bound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_files::parse_allkeys;
+
+    #[test]
+    fn test_storage() {
+        let collator = Collator::try_new("und").unwrap();
+        let key = encode(&collator, "a\u{301}b");
+        let (sort_key, s) = split(&key).unwrap();
+        assert_eq!(s, "a\u{301}b");
+        let fractional = fractional(&collator);
+        assert_eq!(sort_key, fractional.sort_key("a\u{301}b").as_bytes());
+
+        // The weights with a 00 byte past their first one, here 027F, are not the separator.
+        let table = parse_allkeys("0061 ; [.027F.0020.0002]\n0062 ; [.0280.0020.0002]\n").unwrap();
+        let zeros = Collator::new(table);
+        let key = encode(&zeros, "ab");
+        assert_eq!(&key[..3], [0x02, 0x82, 0x00]);
+        assert_eq!(decode(&key).unwrap(), "ab");
+        assert!(encode(&zeros, "a") < key);
+        assert!(key < encode(&zeros, "b"));
+
+        // The strings equal for the collator are ordered by their bytes.
+        let (composed, decomposed) = (encode(&collator, "\u{E1}"), encode(&collator, "a\u{301}"));
+        assert_eq!(compare(&composed, &decomposed), Ordering::Greater);
+        assert_eq!(compare_sort_keys(&composed, &decomposed), Ordering::Equal);
+        assert_eq!(
+            compare_sort_keys(&encode(&collator, "a"), &encode(&collator, "ab")),
+            Ordering::Less
+        );
+
+        let mut buffer = b"batch".to_vec();
+        encode_into(&collator, "", &mut buffer);
+        assert_eq!(decode(&buffer[5..]).unwrap(), "");
+
+        assert!(decode(b"\x02\x21").is_err());
+        assert!(decode(b"\x01\x21\x00a").is_err());
+        assert!(decode(b"\x02\x21\x00\xFF").is_err());
+    }
+}
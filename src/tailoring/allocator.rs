@@ -0,0 +1,173 @@
+use crate::error::{CollateError, Result};
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+
+/// Allocates new weights of a level between the weights a table uses, e.g. the primary weights of
+/// the letters a tailoring inserts after others.
+///
+/// The weights allocated after a weight are spread over the gap up to the next used weight, so that
+/// later insertions into the same gap still find free weights. When the gap is too small, the used
+/// weights following it are moved up to widen it, which must then be applied to the table with
+/// [`Respread::apply`]. The allocation fails once the weights would move past the end of the range
+/// of the level.
+///
+/// ```
+/// use collate_rs::tailoring::WeightAllocator;
+///
+/// let mut allocator = WeightAllocator::new([0x10, 0x14, 0x15], 0x01..=0xFF);
+/// let allocation = allocator.allocate_after(0x10, 1).unwrap();
+/// assert_eq!(allocation.weights, [0x12]);
+/// assert_eq!(allocation.respread, None);
+///
+/// // No weight is free between 14 and 15, so 15 moves up to 18.
+/// let allocation = allocator.allocate_after(0x14, 1).unwrap();
+/// assert_eq!(allocation.weights, [0x16]);
+/// assert_eq!(allocation.respread.unwrap().apply(0x15), 0x18);
+/// assert_eq!(allocation.respread.unwrap().apply(0x12), 0x12);
+/// ```
+#[derive(Debug, Clone)]
+pub struct WeightAllocator {
+    used: BTreeSet<u32>,
+    range: RangeInclusive<u32>,
+}
+
+/// The weights allocated by [`WeightAllocator::allocate_after`], in increasing order, and the
+/// weights moved to make room for them, if any.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allocation {
+    pub weights: Vec<u32>,
+    pub respread: Option<Respread>,
+}
+
+/// Used weights moved up by a [`WeightAllocator`]: the weights from `from` to `to`, the greatest
+/// used weight of the range, are increased by `shift`, keeping their order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Respread {
+    pub from: u32,
+    pub to: u32,
+    pub shift: u32,
+}
+
+impl Respread {
+    /// The weight `weight` moved to, itself if it is not moved.
+    pub fn apply(&self, weight: u32) -> u32 {
+        if (self.from..=self.to).contains(&weight) {
+            weight + self.shift
+        } else {
+            weight
+        }
+    }
+}
+
+impl WeightAllocator {
+    /// An allocator of the weights of `range`, where the weights of `used` are taken. The used
+    /// weights outside `range` are ignored.
+    pub fn new(used: impl IntoIterator<Item = u32>, range: RangeInclusive<u32>) -> Self {
+        let used = used.into_iter().filter(|w| range.contains(w)).collect();
+        Self { used, range }
+    }
+
+    /// Whether `weight` is used, or allocated.
+    pub fn is_used(&self, weight: u32) -> bool {
+        self.used.contains(&weight)
+    }
+
+    /// Allocates `n` weights greater than `after` and less than the used weight following it,
+    /// spread evenly over the gap between both.
+    ///
+    /// If the gap has fewer than `n` weights, the used weights following it are first moved up
+    /// far enough to leave a free weight around each new one, as described by the
+    /// [`Allocation::respread`]. Fails with [`CollateError::TailoringError`] if `after` is not
+    /// below the end of the range, or if the weights cannot move that far.
+    pub fn allocate_after(&mut self, after: u32, n: usize) -> Result<Allocation> {
+        let exhausted = || {
+            CollateError::TailoringError(format!(
+                "No weight of {:04X}..={:04X} is free after {:04X}.",
+                self.range.start(),
+                self.range.end(),
+                after
+            ))
+        };
+        let end = *self.range.end();
+        if after >= end {
+            return Err(exhausted());
+        }
+        let n = n as u32;
+        let first = after.max(self.range.start().saturating_sub(1)) + 1;
+        let next = self.used.range(first..).next().copied();
+        let gap = next.unwrap_or(end + 1) - first;
+        let mut respread = None;
+        let gap = if gap >= n {
+            gap
+        } else {
+            let (Some(from), Some(&to)) = (next, self.used.last()) else {
+                return Err(exhausted());
+            };
+            let shift = 2 * n + 1 - gap;
+            if to.checked_add(shift).is_none_or(|to| to > end) {
+                return Err(exhausted());
+            }
+            let moved: Vec<u32> = self.used.range(from..).copied().collect();
+            moved.iter().for_each(|w| {
+                self.used.remove(w);
+            });
+            self.used.extend(moved.iter().map(|w| w + shift));
+            respread = Some(Respread { from, to, shift });
+            gap + shift
+        };
+        let step = (gap + 1) / (n + 1);
+        let weights: Vec<u32> = (1..=n).map(|k| first - 1 + k * step).collect();
+        self.used.extend(&weights);
+        Ok(Allocation { weights, respread })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weight_allocator() {
+        let mut allocator = WeightAllocator::new([0x02, 0x06, 0x07, 0x100], 0x01..=0x0F);
+        assert!(!allocator.is_used(0x100));
+        // Spread over the gap of 03 to 05.
+        let allocation = allocator.allocate_after(0x02, 3).unwrap();
+        assert_eq!(allocation.weights, [0x03, 0x04, 0x05]);
+        // The gap of 03 to 05 is full, so 06 and 07 move up.
+        let allocation = allocator.allocate_after(0x03, 1).unwrap();
+        let respread = allocation.respread.unwrap();
+        assert_eq!(
+            respread,
+            Respread {
+                from: 0x04,
+                to: 0x07,
+                shift: 3
+            }
+        );
+        assert_eq!(allocation.weights, [0x05]);
+        assert_eq!(
+            [0x03, 0x04, 0x05, 0x06, 0x07].map(|w| respread.apply(w)),
+            [0x03, 0x07, 0x08, 0x09, 0x0A]
+        );
+        assert!(allocator.is_used(0x0A) && !allocator.is_used(0x06));
+
+        // After the last used weight, up to the end of the range.
+        let allocation = allocator.allocate_after(0x0A, 2).unwrap();
+        assert_eq!(allocation.weights, [0x0C, 0x0E]);
+        assert_eq!(allocation.respread, None);
+        // Below the first used weight.
+        assert_eq!(allocator.allocate_after(0x00, 1).unwrap().weights, [0x01]);
+
+        // The exhaustion of the range.
+        assert!(allocator.allocate_after(0x0F, 1).is_err());
+        assert!(allocator.allocate_after(0x0E, 1).is_ok());
+        assert!(allocator.allocate_after(0x0E, 1).is_err());
+        assert_eq!(allocator.allocate_after(0x03, 1).unwrap().weights, [0x04]);
+        assert!(allocator.allocate_after(0x03, 1).is_err());
+        assert!(allocator
+            .allocate_after(0x03, 0)
+            .unwrap()
+            .weights
+            .is_empty());
+    }
+}
@@ -0,0 +1,766 @@
+use crate::algorithm::normalize::to_nfd;
+use crate::algorithm::Strength;
+use crate::definitions::collation_element_tables::{
+    CollationElementTable, FIRST_IMPLICIT_PRIMARY, FIRST_TRAILING_PRIMARY,
+};
+use crate::definitions::collation_wel::{CollationElement, CollationWeight, Weighted};
+use crate::definitions::input_matching::is_non_starter;
+use crate::definitions::mappings::CollationElementMapping;
+use crate::error::{CollateError, Result};
+use crate::tailoring::allocator::{Respread, WeightAllocator};
+use crate::unicode_set::UnicodeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Edits a [`CollationElementTable`] in place, e.g. the [table of a
+/// collator](crate::algorithm::Collator::table), with the operations of the tailoring rules of
+/// CLDR: placing a string after another at a given strength, suppressing a contraction and setting
+/// the variable top, without writing rules or collation elements.
+///
+/// Each edit checks that it keeps the table well-formed (UTS10 Section 5) with the bounds of the
+/// weights kept up to date from one edit to the next, rather than by checking the whole table
+/// again, and leaves the table unchanged if it fails. Only the conditions the table meets are
+/// checked: the bundled tailorings do not all meet them.
+///
+/// ```
+/// use collate_rs::algorithm::{Collator, Strength};
+/// use collate_rs::tailoring::TableEditor;
+/// use std::cmp::Ordering;
+///
+/// let root = Collator::try_new("und").unwrap();
+/// let mut editor = TableEditor::new(root.table());
+/// // &c < ch, as in traditional Spanish.
+/// editor.add_rule("c", Strength::Primary, "ch").unwrap();
+/// let collator = Collator::new(editor.into_table());
+/// assert_eq!(collator.compare("cz", "ch"), Ordering::Less);
+/// assert_eq!(collator.compare("ch", "d"), Ordering::Less);
+/// ```
+#[derive(Debug)]
+pub struct TableEditor {
+    table: CollationElementTable,
+    bounds: WeightBounds,
+}
+
+/// The weights the well-formedness conditions WF2 and WF3 bound, and the greatest weight of each
+/// of the first three levels.
+#[derive(Debug, Clone, Default)]
+struct WeightBounds {
+    max: [u32; 3],
+    /// The greatest secondary weight of the primary collation elements.
+    max_primary_secondary: u32,
+    /// The least secondary weight of the secondary collation elements.
+    min_secondary_secondary: Option<u32>,
+    /// The greatest tertiary weight of the primary and secondary collation elements.
+    max_non_tertiary_tertiary: u32,
+    /// The least tertiary weight of the tertiary collation elements.
+    min_tertiary_tertiary: Option<u32>,
+}
+
+impl WeightBounds {
+    fn include(&mut self, ce: &CollationElement) {
+        let [p, s, t] = [1, 2, 3].map(|n| ce.weight(n));
+        for (max, w) in self.max.iter_mut().zip([p, s, t]) {
+            *max = (*max).max(w);
+        }
+        if p != 0 {
+            self.max_primary_secondary = self.max_primary_secondary.max(s);
+        } else if s != 0 {
+            self.min_secondary_secondary =
+                Some(self.min_secondary_secondary.map_or(s, |m| m.min(s)));
+        } else if t != 0 {
+            self.min_tertiary_tertiary = Some(self.min_tertiary_tertiary.map_or(t, |m| m.min(t)));
+        }
+        if p != 0 || s != 0 {
+            self.max_non_tertiary_tertiary = self.max_non_tertiary_tertiary.max(t);
+        }
+    }
+
+    /// Whether WF2 holds: the secondary weights of the secondary collation elements are greater
+    /// than the secondary weights of all the primary collation elements.
+    fn wf2(&self) -> bool {
+        self.min_secondary_secondary
+            .is_none_or(|min| min > self.max_primary_secondary)
+    }
+
+    /// Whether WF3 holds: the tertiary weights of the tertiary collation elements are greater than
+    /// the tertiary weights of all the primary and secondary collation elements.
+    fn wf3(&self) -> bool {
+        self.min_tertiary_tertiary
+            .is_none_or(|min| min > self.max_non_tertiary_tertiary)
+    }
+
+    /// Checks that adding `ce` to the table keeps WF1, and WF2 and WF3 if they hold.
+    ///
+    /// WF1 is checked on the first three levels: a weight is only ignorable if the weights of the
+    /// levels before it are, e.g. the tertiary weight of a primary collation element is not
+    /// ignorable.
+    fn check(&self, ce: &CollationElement) -> Result<()> {
+        let ill_formed = |condition: &str| {
+            Err(CollateError::TailoringError(format!(
+                "The collation element {} would break the well-formedness condition {}.",
+                ce, condition
+            )))
+        };
+        if (2..=3).any(|n| ce.weight(n) == 0 && ce.weight(n - 1) != 0) {
+            return ill_formed("WF1");
+        }
+        let mut bounds = self.clone();
+        bounds.include(ce);
+        if self.wf2() && !bounds.wf2() {
+            return ill_formed("WF2");
+        }
+        if self.wf3() && !bounds.wf3() {
+            return ill_formed("WF3");
+        }
+        Ok(())
+    }
+}
+
+/// A logical reset position of the rules of CLDR, e.g. `&[last variable] < x`: the first or last
+/// collation element of a kind of the table, where the tailorings place characters without naming
+/// a character of the table.
+///
+/// Parses from, and displays as, the notation of the rules, e.g. `[first regular]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogicalPosition {
+    /// The first completely ignorable collation element, `[.0000.0000.0000]`.
+    FirstTertiaryIgnorable,
+    /// The last completely ignorable collation element, the same as the first.
+    LastTertiaryIgnorable,
+    /// The first collation element ignorable at the first two levels only.
+    FirstSecondaryIgnorable,
+    LastSecondaryIgnorable,
+    /// The first collation element ignorable at the primary level only, e.g. of an accent.
+    FirstPrimaryIgnorable,
+    LastPrimaryIgnorable,
+    /// The first variable collation element, e.g. of a tab.
+    FirstVariable,
+    LastVariable,
+    /// The first collation element of primary weight neither variable, implicit nor trailing.
+    FirstRegular,
+    LastRegular,
+    /// The first collation element of primary weight past the implicit weights, e.g. of U+FFFD.
+    FirstTrailing,
+}
+
+impl LogicalPosition {
+    const ALL: [(Self, &'static str); 11] = [
+        (Self::FirstTertiaryIgnorable, "first tertiary ignorable"),
+        (Self::LastTertiaryIgnorable, "last tertiary ignorable"),
+        (Self::FirstSecondaryIgnorable, "first secondary ignorable"),
+        (Self::LastSecondaryIgnorable, "last secondary ignorable"),
+        (Self::FirstPrimaryIgnorable, "first primary ignorable"),
+        (Self::LastPrimaryIgnorable, "last primary ignorable"),
+        (Self::FirstVariable, "first variable"),
+        (Self::LastVariable, "last variable"),
+        (Self::FirstRegular, "first regular"),
+        (Self::LastRegular, "last regular"),
+        (Self::FirstTrailing, "first trailing"),
+    ];
+
+    fn name(&self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(position, _)| position == self)
+            .map(|(_, name)| *name)
+            .expect("Every position is named.")
+    }
+
+    /// Whether the position is the first collation element of its kind, rather than the last.
+    fn is_first(&self) -> bool {
+        self.name().starts_with("first")
+    }
+
+    /// Whether `ce`, a collation element of the table that is not the second of implicit
+    /// weights, is of the kind of the position.
+    fn contains(&self, ce: &CollationElement) -> bool {
+        let [p, s, t] = [1, 2, 3].map(|n| ce.weight(n));
+        match self {
+            Self::FirstTertiaryIgnorable | Self::LastTertiaryIgnorable => {
+                p == 0 && s == 0 && t == 0
+            }
+            Self::FirstSecondaryIgnorable | Self::LastSecondaryIgnorable => {
+                p == 0 && s == 0 && t != 0
+            }
+            Self::FirstPrimaryIgnorable | Self::LastPrimaryIgnorable => p == 0 && s != 0,
+            Self::FirstVariable | Self::LastVariable => ce.is_variable(),
+            Self::FirstRegular | Self::LastRegular => {
+                p != 0 && !ce.is_variable() && p < FIRST_IMPLICIT_PRIMARY
+            }
+            Self::FirstTrailing => p >= FIRST_TRAILING_PRIMARY,
+        }
+    }
+}
+
+impl fmt::Display for LogicalPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.name())
+    }
+}
+
+impl FromStr for LogicalPosition {
+    type Err = CollateError;
+
+    /// Parses the notation of the rules, e.g. `[last variable]`, with any spaces between the words.
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s
+            .trim()
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "));
+        Self::ALL
+            .iter()
+            .find(|(_, n)| Some(*n) == name.as_deref())
+            .map(|(position, _)| *position)
+            .ok_or_else(|| CollateError::parse(format!("Unknown logical position {:?}.", s)))
+    }
+}
+
+/// Whether the collation element `i` of `ces` is the second one of implicit weights, whose
+/// primary weight is not a primary weight of the table.
+fn is_implicit_trail(ces: &[CollationElement], i: usize) -> bool {
+    i > 0 && (FIRST_IMPLICIT_PRIMARY..FIRST_TRAILING_PRIMARY).contains(&ces[i - 1].weight(1))
+}
+
+/// The collation elements of `mappings`, but the second ones of implicit weights.
+fn explicit_elements(
+    mappings: &[CollationElementMapping],
+) -> impl Iterator<Item = &CollationElement> {
+    mappings.iter().flat_map(|mapping| {
+        let ces = mapping.collation_elements();
+        ces.iter()
+            .enumerate()
+            .filter_map(move |(i, ce)| (!is_implicit_trail(ces, i)).then_some(ce))
+    })
+}
+
+/// Moves the primary weights of `ces` as a [`WeightAllocator`] moved them.
+fn respread_primaries(ces: &mut [CollationElement], respread: Respread) {
+    for i in 0..ces.len() {
+        if !is_implicit_trail(ces, i) {
+            let primary = ces[i].weight(1);
+            ces[i].set_weight(1, respread.apply(primary));
+        }
+    }
+}
+
+impl TableEditor {
+    pub fn new(table: CollationElementTable) -> Self {
+        let mut bounds = WeightBounds::default();
+        table
+            .mappings()
+            .iter()
+            .flat_map(CollationElementMapping::collation_elements)
+            .for_each(|ce| bounds.include(ce));
+        Self { table, bounds }
+    }
+
+    pub fn table(&self) -> &CollationElementTable {
+        &self.table
+    }
+
+    pub fn into_table(self) -> CollationElementTable {
+        self.table
+    }
+
+    /// Maps `s` after `reset` at `strength`, as the rule `&reset < s` (or `<<`, `<<<`) of CLDR:
+    /// `s` weighs as `reset` up to the level before `strength`, and more at `strength`.
+    ///
+    /// - At [`Strength::Primary`], the last collation element of `reset` gets a primary weight
+    ///   [allocated](WeightAllocator::allocate_after) after it, before the primary weight
+    ///   following it in the table, which moves up with the greater ones if no weight is free
+    ///   between both. `s` thus sorts before the letter following `reset`, and a rule after the
+    ///   same `reset` needs another `reset`, as in the rules `&a < x &x < y`.
+    /// - At [`Strength::Secondary`] and [`Strength::Tertiary`], `s` gets the collation elements
+    ///   of `reset` followed by a secondary (or tertiary) collation element weighing more than
+    ///   all the others, so that it sorts after the accents (or cases) of `reset` mapped so far.
+    ///
+    /// `reset` may be a [`LogicalPosition`] rather than characters, e.g. `[last regular]` to sort
+    /// `s` after the letters of all the scripts. No primary weight is free after the ignorable
+    /// positions, nor after `[first trailing]`.
+    ///
+    /// Replaces the mapping of `s`, if any. Fails with [`CollateError::TailoringError`] if a
+    /// string is empty, if no weight is free, at another strength, or if the mapping would leave
+    /// the table ill-formed, e.g. a contraction of a non-starter whose prefix is not mapped
+    /// (WF5).
+    pub fn add_rule(&mut self, reset: &str, strength: Strength, s: &str) -> Result<()> {
+        let characters = to_nfd(s);
+        let mut ces = match reset.parse::<LogicalPosition>() {
+            Ok(position) => vec![self.logical_position(position).ok_or_else(|| {
+                CollateError::TailoringError(format!(
+                    "The table has no collation element at {}.",
+                    position
+                ))
+            })?],
+            Err(_) => self.collation_elements(&to_nfd(reset)),
+        };
+        if characters.is_empty() || ces.is_empty() {
+            return Err(CollateError::TailoringError(
+                "The rules apply to non-empty strings.".to_string(),
+            ));
+        }
+        let last = ces.len() - 1;
+        let secondary = self.table.common_secondary().map_or(0x0020, |w| w.0);
+        let tertiary = self.table.common_tertiary().map_or(0x0002, |w| w.0);
+        let ce = match strength {
+            Strength::Primary => {
+                let no_free = |ce: &CollationElement| {
+                    CollateError::TailoringError(format!("No primary weight is free after {}.", ce))
+                };
+                let primary = ces[last].weight(1);
+                if primary == 0 {
+                    return Err(no_free(&ces[last]));
+                }
+                let primaries = explicit_elements(&self.table.0).map(|ce| ce.weight(1));
+                let allocation = WeightAllocator::new(primaries, 1..=FIRST_IMPLICIT_PRIMARY - 1)
+                    .allocate_after(primary, 1)
+                    .map_err(|_| no_free(&ces[last]))?;
+                if let Some(respread) = allocation.respread {
+                    self.respread_primaries(respread);
+                    respread_primaries(&mut ces, respread);
+                    self.bounds.max[0] = respread.apply(self.bounds.max[0]);
+                }
+                let weight = allocation.weights[0];
+                let weights = vec![
+                    CollationWeight(weight),
+                    CollationWeight(secondary),
+                    CollationWeight(tertiary),
+                ];
+                let variable = self.table.variable_top().is_some_and(|top| weight <= top.0);
+                let ce = CollationElement::with_weights(weights.into(), variable)
+                    .expect("The primary weight is not ignorable.");
+                ces[last] = ce.clone();
+                ce
+            }
+            Strength::Secondary | Strength::Tertiary => {
+                let n = strength.level();
+                let weight = self.bounds.max[n - 1] + 1;
+                if weight > 0xFFFF {
+                    return Err(CollateError::TailoringError(format!(
+                        "No weight is free at level {}.",
+                        n
+                    )));
+                }
+                let mut ce = CollationElement::new(vec![CollationWeight(0); n]);
+                ce.set_weight(n, weight);
+                if n == 2 {
+                    ce.set_weight(3, tertiary);
+                }
+                // The completely ignorable reset, e.g. [last tertiary ignorable], weighs nothing.
+                ces.retain(|ce| (1..=3).any(|n| ce.weight(n) != 0));
+                ces.push(ce.clone());
+                ce
+            }
+            _ => {
+                return Err(CollateError::TailoringError(format!(
+                    "Rules at the strength {:?} are not supported.",
+                    strength
+                )))
+            }
+        };
+        self.bounds.check(&ce)?;
+        let mapping = CollationElementMapping::new(&characters, &ces)?;
+        self.check_contraction_prefix(&characters)?;
+        self.bounds.include(&ce);
+        match self.position(&characters) {
+            Some(i) => self.table.0[i] = mapping,
+            None => self.table.0.push(mapping),
+        }
+        Ok(())
+    }
+
+    /// Removes the contraction of `characters`, so that they weigh as the characters they are
+    /// made of, e.g. `"й"` of the Cyrillic contractions of the DUCET.
+    ///
+    /// Fails with [`CollateError::TailoringError`] if the table has no such contraction, or if a
+    /// longer contraction of a non-starter needs it (WF5).
+    pub fn suppress_contraction(&mut self, characters: &str) -> Result<()> {
+        let characters = to_nfd(characters);
+        let Some(i) = self
+            .position(&characters)
+            .filter(|&i| self.table.0[i].is_contraction())
+        else {
+            return Err(CollateError::TailoringError(format!(
+                "The table has no contraction of {:?}.",
+                characters.iter().collect::<String>()
+            )));
+        };
+        if let Some(longer) = self.table.0.iter().find(|mapping| {
+            mapping.prefix().is_empty()
+                && mapping.characters().len() == characters.len() + 1
+                && mapping.characters().starts_with(&characters)
+                && mapping
+                    .characters()
+                    .last()
+                    .copied()
+                    .is_some_and(is_non_starter)
+        }) {
+            return Err(CollateError::TailoringError(format!(
+                "The contraction of {:?} needs the contraction of its prefix (WF5).",
+                longer.characters().iter().collect::<String>()
+            )));
+        }
+        self.table.0.remove(i);
+        Ok(())
+    }
+
+    /// Removes the contractions starting with a character of `set`, as the `suppressContractions`
+    /// setting of CLDR, e.g. `[Ѐ-ӿ]` for the Cyrillic contractions of the DUCET. The set is written
+    /// in the notation of [`UnicodeSet`].
+    ///
+    /// Returns the number of contractions removed. Removing all the contractions starting with the
+    /// same character keeps the prefixes of the others (WF5). Fails with
+    /// [`CollateError::ParseError`] if the set is not well written.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use collate_rs::tailoring::TableEditor;
+    ///
+    /// let root = Collator::try_new("und").unwrap();
+    /// let mut editor = TableEditor::new(root.table());
+    /// assert!(editor.suppress_contractions("[\\u0400-\\u04FF]").unwrap() > 0);
+    /// let collator = Collator::new(editor.into_table());
+    /// assert_eq!(
+    ///     collator.collation_element_array("й"),
+    ///     collator.collation_element_array("и\u{306}"),
+    /// );
+    /// assert_ne!(root.collation_element_array("й"), collator.collation_element_array("й"));
+    /// ```
+    pub fn suppress_contractions(&mut self, set: &str) -> Result<usize> {
+        let set = UnicodeSet::parse(set)?;
+        let len = self.table.0.len();
+        self.table.0.retain(|mapping| {
+            !(mapping.prefix().is_empty()
+                && mapping.is_contraction()
+                && set.contains(mapping.characters()[0]))
+        });
+        Ok(len - self.table.0.len())
+    }
+
+    /// Makes variable (UTS10-D16) the primary collation elements of primary weight at most `top`,
+    /// and only them, e.g. to include the symbols as with
+    /// [`MaxVariable::Symbol`](crate::definitions::variable_weighting::MaxVariable::Symbol).
+    ///
+    /// The ignorable collation elements stay not variable, so that the table stays well-formed.
+    pub fn set_variable_top(&mut self, top: CollationWeight) {
+        for mapping in &mut self.table.0 {
+            let ces: Vec<CollationElement> = mapping
+                .collation_elements()
+                .iter()
+                .map(|ce| {
+                    let primary = ce.weight(1);
+                    CollationElement(ce.0.clone(), primary != 0 && primary <= top.0)
+                })
+                .collect();
+            if ces != mapping.collation_elements() {
+                *mapping = CollationElementMapping::with_prefix(
+                    mapping.prefix(),
+                    mapping.characters(),
+                    &ces,
+                )
+                .expect("The mapping has the same characters.");
+            }
+        }
+    }
+
+    /// The collation element of the table at `position`, if the table has one of its kind.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use collate_rs::tailoring::{LogicalPosition, TableEditor};
+    ///
+    /// let root = Collator::try_new("und").unwrap();
+    /// let editor = TableEditor::new(root.table());
+    /// let position: LogicalPosition = "[first trailing]".parse().unwrap();
+    /// let trailing = editor.logical_position(position).unwrap();
+    /// assert_eq!(trailing, root.collation_element_array("\u{FFFD}")[0]);
+    /// ```
+    pub fn logical_position(&self, position: LogicalPosition) -> Option<CollationElement> {
+        let key = |ce: &&CollationElement| [1, 2, 3].map(|n| ce.weight(n));
+        let ces = explicit_elements(&self.table.0).filter(|ce| position.contains(ce));
+        let ce = if position.is_first() {
+            ces.min_by_key(key)
+        } else {
+            ces.max_by_key(key)
+        };
+        ce.cloned()
+    }
+
+    /// Moves the primary weights of the table as a [`WeightAllocator`] moved them.
+    fn respread_primaries(&mut self, respread: Respread) {
+        for mapping in &mut self.table.0 {
+            let mut ces = mapping.collation_elements().to_vec();
+            respread_primaries(&mut ces, respread);
+            if ces != mapping.collation_elements() {
+                *mapping = CollationElementMapping::with_prefix(
+                    mapping.prefix(),
+                    mapping.characters(),
+                    &ces,
+                )
+                .expect("The mapping has the same characters.");
+            }
+        }
+    }
+
+    /// The position of the mapping of exactly `characters`, without prefix.
+    fn position(&self, characters: &[char]) -> Option<usize> {
+        self.table
+            .0
+            .iter()
+            .position(|mapping| mapping.prefix().is_empty() && mapping.characters() == characters)
+    }
+
+    /// The collation elements of `characters` by longest matches, without the discontiguous
+    /// matches of the main algorithm, as the characters of a reset are not expected to be
+    /// reordered.
+    fn collation_elements(&self, characters: &[char]) -> Vec<CollationElement> {
+        let mut ces = vec![];
+        let mut i = 0;
+        while i < characters.len() {
+            let len = match self.table.longest_match(&characters[i..]) {
+                Some(m) => {
+                    ces.extend_from_slice(m.mapping().collation_elements());
+                    m.positions().len()
+                }
+                None => {
+                    let (mapping, _) = self
+                        .table
+                        .lookup(&characters[i..=i])
+                        .expect("Every character has a mapping.");
+                    ces.extend_from_slice(mapping.collation_elements());
+                    1
+                }
+            };
+            i += len;
+        }
+        ces
+    }
+
+    /// Checks WF5 for a new mapping of `characters`: a contraction of more than two characters
+    /// ending with a non-starter needs a contraction of its characters but the last.
+    fn check_contraction_prefix(&self, characters: &[char]) -> Result<()> {
+        match characters {
+            [prefix @ .., last] if prefix.len() > 1 && is_non_starter(*last) => {
+                match self.position(prefix) {
+                    Some(_) => Ok(()),
+                    None => Err(CollateError::TailoringError(format!(
+                        "The contraction of {:?} needs a contraction of {:?} (WF5).",
+                        characters.iter().collect::<String>(),
+                        prefix.iter().collect::<String>()
+                    ))),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{Collator, CollatorOptions};
+    use crate::definitions::variable_weighting::VariableWeighting;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_8_editor() {
+        let root = Collator::try_new("und").unwrap();
+        let mut editor = TableEditor::new(root.table());
+        editor.add_rule("a", Strength::Tertiary, "ⓐ").unwrap();
+        editor.add_rule("a", Strength::Secondary, "æ").unwrap();
+        editor.add_rule("o", Strength::Primary, "ø").unwrap();
+        editor.add_rule("ø", Strength::Primary, "œ").unwrap();
+        assert!(editor.add_rule("", Strength::Primary, "x").is_err());
+        assert!(editor.add_rule("a", Strength::Quaternary, "x").is_err());
+        // WF5: the contraction of a, b and the acute accent needs a contraction of "ab".
+        assert!(editor
+            .add_rule("a", Strength::Primary, "ab\u{301}")
+            .is_err());
+
+        // The contraction of и and the breve (й) of the DUCET.
+        editor.suppress_contraction("й").unwrap();
+        assert!(editor.suppress_contraction("й").is_err());
+        assert!(editor.suppress_contraction("a").is_err());
+
+        let top = editor.table().variable_top().copied();
+        let space = root.collation_element_array(" ")[0].weight(1);
+        editor.set_variable_top(CollationWeight(space));
+        assert_eq!(editor.table().variable_top(), Some(&CollationWeight(space)));
+        assert_ne!(editor.table().variable_top().copied(), top);
+
+        let collator = Collator::new(editor.into_table());
+        let mut words = ["p", "œ", "A", "o", "ø", "æ", "á", "ⓐ", "a"];
+        collator.sort_slice(&mut words);
+        assert_eq!(words, ["a", "ⓐ", "A", "á", "æ", "o", "ø", "œ", "p"]);
+        assert_eq!(
+            collator.collation_element_array("й"),
+            collator.collation_element_array("и\u{306}")
+        );
+        assert_ne!(
+            root.collation_element_array("й"),
+            collator.collation_element_array("й")
+        );
+    }
+
+    #[test]
+    fn test_8_case_pairs() {
+        // The Turkish rules &h < ı <<< I &i <<< İ: the dotless and dotted letters are distinct
+        // letters, each with its own uppercase form.
+        let root = Collator::try_new("und").unwrap();
+        let mut editor = TableEditor::new(root.table());
+        editor.add_rule("h", Strength::Primary, "ı").unwrap();
+        editor.add_rule("ı", Strength::Tertiary, "I").unwrap();
+        editor.add_rule("i", Strength::Tertiary, "İ").unwrap();
+        let collator = Collator::new(editor.into_table());
+        let mut words = ["İlik", "ilik", "Ilık", "ılık", "Ílık", "hz", "jale"];
+        collator.sort_slice(&mut words);
+        assert_eq!(
+            words,
+            ["hz", "ılık", "Ilık", "Ílık", "ilik", "İlik", "jale"]
+        );
+        let secondary = collator.clone_with_options(CollatorOptions {
+            strength: Strength::Secondary,
+            ..Default::default()
+        });
+        assert_eq!(secondary.compare("I", "ı"), Ordering::Equal);
+        assert_eq!(secondary.compare("İ", "i"), Ordering::Equal);
+        assert_eq!(secondary.compare("I", "i"), Ordering::Less);
+        // The accents of I, decomposed, are the ones of the root collation.
+        assert_eq!(secondary.compare("Í", "ı\u{301}"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_8_logical_positions() {
+        let root = Collator::try_new("und").unwrap();
+        let mut editor = TableEditor::new(root.table());
+        let position = |s: &str| s.parse::<LogicalPosition>().unwrap();
+        assert_eq!(
+            position("[ last   variable ]"),
+            LogicalPosition::LastVariable
+        );
+        assert_eq!(LogicalPosition::FirstRegular.to_string(), "[first regular]");
+        assert!("[last implicit]".parse::<LogicalPosition>().is_err());
+        assert!("last variable".parse::<LogicalPosition>().is_err());
+
+        let at = |s: &str| editor.logical_position(position(s)).unwrap();
+        assert_eq!(
+            at("[first variable]"),
+            root.collation_element_array("\t")[0]
+        );
+        assert!(at("[last variable]").is_variable());
+        assert!(!at("[first regular]").is_variable());
+        assert_eq!(
+            at("[first regular]").weight(1),
+            at("[last variable]").weight(1) + 1
+        );
+        assert_eq!(at("[last tertiary ignorable]").weight(3), 0);
+        assert!(
+            at("[first primary ignorable]").weight(2) < at("[last primary ignorable]").weight(2)
+        );
+        // The DUCET has no collation element ignorable at the first two levels only, and the
+        // second collation elements of the implicit weights are not trailing.
+        assert_eq!(
+            editor.logical_position(LogicalPosition::FirstSecondaryIgnorable),
+            None
+        );
+        assert_eq!(at("[first trailing]").weight(1), 0xFFFD);
+        assert!(editor
+            .add_rule("[last secondary ignorable]", Strength::Tertiary, "x")
+            .is_err());
+
+        editor
+            .add_rule("[last regular]", Strength::Primary, "ꙮ")
+            .unwrap();
+        editor
+            .add_rule("[last tertiary ignorable]", Strength::Tertiary, "\u{2063}")
+            .unwrap();
+        editor
+            .add_rule("[first variable]", Strength::Tertiary, "\u{2060}")
+            .unwrap();
+        // No primary weight is free after the last variable one: the primary weights of the
+        // regular collation elements move up.
+        editor
+            .add_rule("[last variable]", Strength::Primary, "\u{E000}")
+            .unwrap();
+        assert!(editor
+            .add_rule("[first trailing]", Strength::Primary, "x")
+            .is_err());
+        let collator = Collator::new(editor.into_table());
+        let mut words = ["\u{4E00}", "ꙮ", "z", "\u{10FFFF}", "ω"];
+        collator.sort_slice(&mut words);
+        assert_eq!(words, ["z", "ω", "ꙮ", "\u{4E00}", "\u{10FFFF}"]);
+        // The invisible separator only weighs at the tertiary level.
+        assert_eq!(collator.compare("a\u{2063}", "a"), Ordering::Greater);
+        assert_eq!(
+            collator
+                .clone_with_options(CollatorOptions {
+                    strength: Strength::Secondary,
+                    ..Default::default()
+                })
+                .compare("a\u{2063}", "a"),
+            Ordering::Equal
+        );
+        assert_eq!(collator.compare("\u{2060}", "\t"), Ordering::Greater);
+        assert_eq!(collator.compare("\u{2060}", " "), Ordering::Less);
+        let shifted = collator.clone_with_options(CollatorOptions {
+            variable_weighting: VariableWeighting::Shifted,
+            ..Default::default()
+        });
+        // The last variable character of the DUCET is U+1D371, a counting rod digit.
+        assert_ne!(shifted.compare("a\u{E000}b", "ab"), Ordering::Equal);
+        assert_eq!(shifted.compare("\u{E000}", "\u{1D371}"), Ordering::Greater);
+        assert_eq!(shifted.compare("\u{E000}", "0"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_8_respread() {
+        // The DUCET has three primary weights free after a: the fourth letter inserted after it
+        // moves the weights of b and of the following letters up.
+        let root = Collator::try_new("und").unwrap();
+        let mut editor = TableEditor::new(root.table());
+        let letters = ["α", "β", "γ", "δ", "ε", "ζ"];
+        let mut reset = "a";
+        for letter in letters {
+            editor.add_rule(reset, Strength::Primary, letter).unwrap();
+            reset = letter;
+        }
+        let collator = Collator::new(editor.into_table());
+        let mut words = ["b", "ζ", "ε", "δ", "γ", "β", "α", "a", "z", "ω", "\u{4E00}"];
+        collator.sort_slice(&mut words);
+        assert_eq!(
+            words,
+            ["a", "α", "β", "γ", "δ", "ε", "ζ", "b", "z", "ω", "\u{4E00}"]
+        );
+        let primary = |s: &str| collator.collation_element_array(s)[0].weight(1);
+        assert!(primary("b") > root.collation_element_array("b")[0].weight(1));
+        assert_eq!(primary("\u{4E00}"), 0xFB40);
+        assert_eq!(primary(" "), root.collation_element_array(" ")[0].weight(1));
+        // The accents and cases of the letters moved up stay theirs.
+        assert!(collator.compare("b", "B") == Ordering::Less);
+        assert!(collator.compare("B", "b\u{301}") == Ordering::Less);
+    }
+
+    #[test]
+    fn test_8_suppress_contractions() {
+        let root = Collator::try_new("und").unwrap();
+        let mut editor = TableEditor::new(root.table());
+        let removed = editor.suppress_contractions("[Ѐ-ӿ]").unwrap();
+        assert!(removed > 0);
+        assert_eq!(editor.suppress_contractions("[Ѐ-ӿ]").unwrap(), 0);
+        assert!(editor
+            .table()
+            .mappings()
+            .iter()
+            .filter(|mapping| mapping.is_contraction())
+            .all(|mapping| !('Ѐ'..='ӿ').contains(&mapping.characters()[0])));
+        let collator = Collator::new(editor.into_table());
+        assert_eq!(
+            collator.table().mappings().len(),
+            root.table().mappings().len() - removed
+        );
+        assert_eq!(
+            collator.collation_element_array("ѐ"),
+            collator.collation_element_array("е\u{300}")
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use crate::algorithm::normalize::to_nfd;
+use crate::data_files::parse_entries;
+use crate::definitions::collation_element_tables::CollationElementTable;
+use crate::definitions::collation_wel::{CollationElement, CollationWeight};
+use crate::definitions::mappings::CollationElementMapping;
+use crate::ducet;
+use crate::error::{CollateError, Result};
+use std::collections::{HashMap, HashSet};
+
+/// Defines the [`WeightAllocator`](`allocator::WeightAllocator`), allocating the weights of the
+/// characters inserted between others.
+pub mod allocator;
+
+/// Defines the [`TableEditor`](`editor::TableEditor`), editing a table with the operations of the
+/// tailoring rules.
+pub mod editor;
+
+pub use allocator::WeightAllocator;
+pub use editor::{LogicalPosition, TableEditor};
+
+/// > Tailoring consists of any well-defined change in the Collation Element Table and/or any
+/// > well-defined change in the behavior of the algorithm. Typically, a tailoring is expressed by
+/// > means of a formal syntax which allows detailed manipulation of values in a Collation Element
+/// > Table, with or without an additional collection of parametric settings which modify specific
+/// > aspects of the behavior of the algorithm.
+///
+/// A tailoring is currently expressed as a list of collation element mappings overriding (or
+/// extending) the mappings of a base table.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Tailoring(pub(crate) Vec<CollationElementMapping>);
+
+impl Tailoring {
+    pub fn new(mappings: Vec<CollationElementMapping>) -> Self {
+        Self(mappings)
+    }
+
+    /// Parses a tailoring written as entries in the [allkeys.txt format](crate::data_files).
+    pub fn parse(entries: &str) -> Result<Self> {
+        parse_entries(entries).map(Self)
+    }
+
+    pub fn mappings(&self) -> &[CollationElementMapping] {
+        &self.0
+    }
+
+    /// A tailoring of the DUCET ordering the graphemes of `alphabet` as listed, each with a
+    /// primary weight of its own, without writing the collation elements by hand.
+    ///
+    /// The graphemes are written in lowercase; their uppercase forms (and the titlecase forms of
+    /// the graphemes of several characters, e.g. `Ch`) get the same primary weight with the
+    /// tertiary weight of the capitals. The graphemes of several characters become contractions.
+    /// The primary weights are taken from the [largest free
+    /// range](CollationElementTable::largest_free_primary_range) of the DUCET, so that the
+    /// alphabet sorts after the letters of all the scripts, and thus after the characters not
+    /// listed.
+    ///
+    /// Fails with [`CollateError::TailoringError`] if a grapheme is empty or listed twice.
+    ///
+    /// ```
+    /// use collate_rs::algorithm::Collator;
+    /// use collate_rs::ducet;
+    /// use collate_rs::tailoring::Tailoring;
+    /// use std::cmp::Ordering;
+    ///
+    /// let tailoring = Tailoring::from_alphabet(&["a", "á", "b", "c", "č", "ch", "d"]).unwrap();
+    /// let collator = Collator::new(tailoring.apply(&ducet::table()));
+    /// let mut words = ["dub", "chata", "čas", "cena", "ábel", "bod", "Chata", "auto"];
+    /// collator.sort_slice(&mut words);
+    /// assert_eq!(words, ["auto", "ábel", "bod", "cena", "čas", "chata", "Chata", "dub"]);
+    /// ```
+    pub fn from_alphabet<S: AsRef<str>>(alphabet: &[S]) -> Result<Self> {
+        let free = ducet::table()
+            .largest_free_primary_range()
+            .expect("The DUCET has free primary weights.");
+        let (first, last) = (free.start().0, free.end().0);
+        if alphabet.len() > (last - first + 1) as usize {
+            return Err(CollateError::TailoringError(format!(
+                "The alphabet of {} graphemes has more letters than free primary weights.",
+                alphabet.len()
+            )));
+        }
+        let mut mappings = vec![];
+        let mut seen = HashSet::new();
+        for (grapheme, primary) in alphabet.iter().map(AsRef::as_ref).zip(first..) {
+            if grapheme.is_empty() || !seen.insert(to_nfd(grapheme)) {
+                return Err(CollateError::TailoringError(format!(
+                    "The grapheme {:?} of the alphabet is empty or listed twice.",
+                    grapheme
+                )));
+            }
+            let mut title: String = grapheme
+                .chars()
+                .take(1)
+                .flat_map(char::to_uppercase)
+                .collect();
+            title.extend(grapheme.chars().skip(1));
+            let upper = grapheme.to_uppercase();
+            let mut cases = vec![(grapheme.to_string(), 0x0002)];
+            if title != grapheme && title != upper {
+                cases.push((title, 0x0007));
+            }
+            if upper != grapheme {
+                cases.push((upper, 0x0008));
+            }
+            let len = to_nfd(grapheme).len();
+            for (s, tertiary) in cases {
+                let (chars, nfd): (Vec<char>, Vec<char>) = (s.chars().collect(), to_nfd(&s));
+                // The case mappings changing the number of characters, e.g. "ß" to "SS", would
+                // tailor other graphemes.
+                if nfd.len() != len {
+                    continue;
+                }
+                let ce = [CollationElement::new(vec![
+                    CollationWeight::from(primary),
+                    CollationWeight::from(0x0020),
+                    CollationWeight::from(tertiary),
+                ])];
+                // As in the bundled tailorings, the precomposed form and its decomposition.
+                mappings.push(CollationElementMapping::new(&chars, &ce)?);
+                if nfd != chars {
+                    mappings.push(CollationElementMapping::new(&nfd, &ce)?);
+                }
+            }
+        }
+        Ok(Self(mappings))
+    }
+
+    /// Builds a new table from `base`, replacing the mappings with the same characters (and
+    /// prefix, for the contextual mappings) as a mapping of the tailoring and appending the other
+    /// mappings of the tailoring.
+    ///
+    /// Takes a time linear in the sizes of the table and of the tailoring, so that the tailorings
+    /// of tens of thousands of mappings, e.g. the orderings of the Han ideographs of
+    /// `zh-u-co-pinyin`, apply in milliseconds.
+    pub fn apply(&self, base: &CollationElementTable) -> CollationElementTable {
+        fn key(mapping: &CollationElementMapping) -> (&[char], &[char]) {
+            (mapping.prefix(), mapping.characters())
+        }
+        let mut tailored: HashMap<(&[char], &[char]), &CollationElementMapping> = self
+            .0
+            .iter()
+            .map(|mapping| (key(mapping), mapping))
+            .collect();
+        let mut mappings: Vec<CollationElementMapping> = base
+            .mappings()
+            .iter()
+            .map(|mapping| tailored.remove(&key(mapping)).unwrap_or(mapping).clone())
+            .collect();
+        mappings.extend(
+            self.0
+                .iter()
+                .filter(|mapping| tailored.contains_key(&key(mapping)))
+                .cloned(),
+        );
+        CollationElementTable::new(mappings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::Collator;
+    use crate::data_files::parse_allkeys;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_8() {
+        let base = parse_allkeys(
+            "0061 ; [.1FA2.0020.0002]\n\
+             0063 ; [.1FD6.0020.0002]\n\
+             0064 ; [.1FEB.0020.0002]\n\
+             0068 ; [.2076.0020.0002]\n",
+        )
+        .unwrap();
+        let tailoring = Tailoring::parse(
+            "# Spanish traditional\n\
+             0063 0068 ; [.1FD7.0020.0002] # <LATIN SMALL LETTER C, LATIN SMALL LETTER H>\n\
+             0061 ; [.1FA2.0020.0003]\n",
+        )
+        .unwrap();
+        let table = tailoring.apply(&base);
+        assert_eq!(table.mappings().len(), 5);
+        assert_eq!(table.mappings()[0], tailoring.mappings()[1]);
+        assert_eq!(table.mappings()[4], tailoring.mappings()[0]);
+        let diff = base.diff(&table);
+        assert_eq!(diff.added, [tailoring.mappings()[0].clone()]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed_at_level(3).count(), 1);
+        assert_eq!(diff.changed[0].new, tailoring.mappings()[1]);
+
+        let collator = Collator::new(table);
+        assert_eq!(collator.compare("cz", "ch"), Ordering::Less);
+        assert_eq!(collator.compare("ch", "d"), Ordering::Less);
+        assert!(Tailoring::parse("0063 0068 [.1FD7.0020.0002]").is_err());
+    }
+
+    #[test]
+    fn test_8_contextual() {
+        let root = Collator::try_new("und").unwrap();
+        // The length mark after KATAKANA LETTER A weighs as a tertiary variant of the letter.
+        let a = root.collation_element_array("\u{30A2}")[0].clone();
+        let (primary, tertiary) = (
+            a.weight_at_level_n(1).unwrap().0,
+            a.weight_at_level_n(3).unwrap().0,
+        );
+        let tailoring = Tailoring::parse(&format!(
+            "30A2 | 30FC ; [.{primary:04X}.0020.{:04X}]\n",
+            tertiary + 1
+        ))
+        .unwrap();
+        let table = tailoring.apply(&root.table());
+        assert_eq!(table.mappings().len(), root.table().mappings().len() + 1);
+        assert!(table
+            .explicit_mapping(&['\u{30FC}'])
+            .unwrap()
+            .prefix()
+            .is_empty());
+
+        let collator = Collator::new(table);
+        let primary_strength = collator.clone_with_options(crate::algorithm::CollatorOptions {
+            strength: crate::algorithm::Strength::Primary,
+            ..Default::default()
+        });
+        assert_eq!(
+            primary_strength.compare("\u{30A2}\u{30FC}", "\u{30A2}\u{30A2}"),
+            Ordering::Equal
+        );
+        assert_eq!(
+            collator.compare("\u{30A2}\u{30FC}", "\u{30A2}\u{30A2}"),
+            Ordering::Greater
+        );
+        // Elsewhere the length mark keeps its weights of the DUCET.
+        assert_eq!(
+            collator.explain("\u{30A4}\u{30FC}"),
+            root.explain("\u{30A4}\u{30FC}")
+        );
+        assert_ne!(
+            collator.explain("\u{30A2}\u{30FC}"),
+            root.explain("\u{30A2}\u{30FC}")
+        );
+    }
+
+    #[test]
+    fn test_8_alphabet() {
+        let tailoring = Tailoring::from_alphabet(&["z", "ß", "dz", "a\u{301}", "a"]).unwrap();
+        let characters: Vec<String> = tailoring
+            .mappings()
+            .iter()
+            .map(|mapping| mapping.characters().iter().collect())
+            .collect();
+        // The uppercase of "ß" is "SS", which is left out.
+        assert_eq!(
+            characters,
+            ["z", "Z", "ß", "dz", "Dz", "DZ", "a\u{301}", "A\u{301}", "a", "A"]
+        );
+        assert!(tailoring.mappings()[3].is_contraction());
+
+        let collator = Collator::new(tailoring.apply(&crate::ducet::table()));
+        let mut words = ["a", "dzwon", "á", "ßa", "Zebra", "zebra", "b"];
+        collator.sort_slice(&mut words);
+        assert_eq!(words, ["b", "zebra", "Zebra", "ßa", "dzwon", "\u{E1}", "a"]);
+
+        for invalid in [&["a", ""][..], &["a", "b", "a"], &["\u{E1}", "a\u{301}"]] {
+            assert!(matches!(
+                Tailoring::from_alphabet(invalid),
+                Err(CollateError::TailoringError(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_8_serde() {
+        use crate::algorithm::{CollatorOptions, KeyEncoding};
+
+        let tailoring = Tailoring::parse(
+            "0063 0068 ; [.1FD7.0020.0002]\n\
+             00E4 ; [.1FA2.0020.0002][.0000.0025.0002]\n",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&tailoring).unwrap();
+        assert_eq!(serde_json::from_str::<Tailoring>(&json).unwrap(), tailoring);
+        assert!(json.starts_with(r#"[{"ManyToOneMapping":{"characters":["c","h"]"#));
+
+        let options = CollatorOptions {
+            key_encoding: KeyEncoding::Fractional,
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            json,
+            r#"{"key_encoding":"Fractional","strength":"Tertiary","backward_secondary":false,"variable_weighting":"NonIgnorable","max_variable":null,"case_level":false,"case_first":"Off","numeric":false,"ill_formed_input":"Replace","quaternary":"Weights","code_point_tiebreak":false}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<CollatorOptions>(&json).unwrap(),
+            options
+        );
+        assert_eq!(
+            serde_json::from_str::<CollatorOptions>("{}").unwrap(),
+            CollatorOptions::default()
+        );
+
+        let table = tailoring.apply(&CollationElementTable::new(vec![]));
+        let json = serde_json::to_string(&table).unwrap();
+        let collator = Collator::with_options(serde_json::from_str(&json).unwrap(), options);
+        assert_eq!(collator.table(), table);
+    }
+}
@@ -0,0 +1,123 @@
+//! Deterministic generators of strings to measure the collation, shared by the benchmarks (see
+//! `benches/collation.rs`) so that optimizations are measured on the same inputs.
+
+/// A family of generated strings, by script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corpus {
+    /// Words of ASCII letters, with some capitals, digits and punctuation.
+    Ascii,
+    /// Words of the letters of Latin-1, accented ones included.
+    Latin1,
+    /// Words of Greek letters, with and without tonos.
+    Greek,
+    /// Words of CJK unified ideographs and kana.
+    Cjk,
+    /// Words of any of the other corpora.
+    Mixed,
+}
+
+impl Corpus {
+    pub const ALL: [Self; 5] = [
+        Self::Ascii,
+        Self::Latin1,
+        Self::Greek,
+        Self::Cjk,
+        Self::Mixed,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ascii => "ascii",
+            Self::Latin1 => "latin1",
+            Self::Greek => "greek",
+            Self::Cjk => "cjk",
+            Self::Mixed => "mixed",
+        }
+    }
+
+    /// `count` words of the corpus, the same for the same `seed`.
+    pub fn generate(&self, count: usize, seed: u64) -> Vec<String> {
+        let mut rng = XorShift(seed.max(1));
+        (0..count).map(|_| self.word(&mut rng)).collect()
+    }
+
+    fn word(&self, rng: &mut XorShift) -> String {
+        let corpus = match self {
+            Self::Mixed => Self::ALL[rng.below(4)],
+            corpus => *corpus,
+        };
+        let len = 3 + rng.below(10);
+        (0..len).map(|i| corpus.char(rng, i == 0)).collect()
+    }
+
+    fn char(&self, rng: &mut XorShift, first: bool) -> char {
+        let pick = |rng: &mut XorShift, chars: &[char]| chars[rng.below(chars.len())];
+        match self {
+            Self::Ascii => match rng.below(20) {
+                0 if first => pick(rng, &['A', 'E', 'M', 'S', 'Z']),
+                1 => pick(rng, &['0', '1', '7', '9']),
+                2 if !first => pick(rng, &['-', '.', '\'', ' ']),
+                _ => char_in(rng, 'a', 'z'),
+            },
+            Self::Latin1 => match rng.below(4) {
+                0 => loop {
+                    let c = char_in(rng, '\u{C0}', '\u{FF}');
+                    if c != '\u{D7}' && c != '\u{F7}' {
+                        break c;
+                    }
+                },
+                _ => char_in(rng, 'a', 'z'),
+            },
+            Self::Greek => match rng.below(6) {
+                0 => pick(rng, &['ά', 'έ', 'ή', 'ί', 'ό', 'ύ', 'ώ']),
+                1 if first => char_in(rng, 'Α', 'Ρ'),
+                _ => char_in(rng, 'α', 'ω'),
+            },
+            Self::Cjk => match rng.below(4) {
+                0 => char_in(rng, 'ぁ', 'ゖ'),
+                _ => char_in(rng, '\u{4E00}', '\u{9FFF}'),
+            },
+            Self::Mixed => unreachable!("Mixed words pick the corpus of each word."),
+        }
+    }
+}
+
+/// A character between `first` and `last`, both included, of a range without surrogates.
+fn char_in(rng: &mut XorShift, first: char, last: char) -> char {
+    let offset = rng.below((last as usize) - (first as usize) + 1) as u32;
+    char::from_u32(first as u32 + offset).expect("The ranges have no surrogates.")
+}
+
+/// The xorshift64* generator, to generate the corpora without depending on a random crate.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A number below `n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() >> 32) as usize % n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpora() {
+        for corpus in Corpus::ALL {
+            let words = corpus.generate(100, 42);
+            assert_eq!(words, corpus.generate(100, 42));
+            assert_ne!(words, corpus.generate(100, 43));
+            assert!(words.iter().all(|w| (3..13).contains(&w.chars().count())));
+        }
+        let greek = Corpus::Greek.generate(100, 1);
+        assert!(greek.iter().flat_map(|w| w.chars()).all(|c| !c.is_ascii()));
+    }
+}
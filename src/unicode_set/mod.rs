@@ -0,0 +1,463 @@
+use crate::data_files::ucd::GENERAL_CATEGORIES;
+use crate::error::{CollateError, Result};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// The code points of the surrogates, which are not characters.
+const SURROGATES: RangeInclusive<u32> = 0xD800..=0xDFFF;
+
+/// The groups of general categories, e.g. `L` for the letters.
+const CATEGORY_GROUPS: [(&str, &[&str]); 8] = [
+    ("L", &["Lu", "Ll", "Lt", "Lm", "Lo"]),
+    ("LC", &["Lu", "Ll", "Lt"]),
+    ("M", &["Mn", "Mc", "Me"]),
+    ("N", &["Nd", "Nl", "No"]),
+    ("P", &["Pc", "Pd", "Ps", "Pe", "Pi", "Pf", "Po"]),
+    ("S", &["Sm", "Sc", "Sk", "So"]),
+    ("Z", &["Zs", "Zl", "Zp"]),
+    ("C", &["Cc", "Cf", "Cs", "Co", "Cn"]),
+];
+
+/// A set of characters written in the UnicodeSet notation of ICU and CLDR, as in the
+/// `suppressContractions` setting of the tailorings, e.g. `[Ѐ-ӿ]`.
+///
+/// The notation supported is a subset of the one of ICU:
+///
+/// - characters and ranges of characters in brackets, `[a-z]`, the set of the characters not
+///   listed with `[^a-z]`;
+/// - characters escaped with `\`, as `\-` or `\]`, or written as code points with `\uXXXX`,
+///   `\UXXXXXXXX` or `\x{X}`;
+/// - the general categories, as `[:Lu:]` or `\p{Lu}`, their groups, as `[:L:]`, and their
+///   complements, as `[:^Lu:]` or `\P{Lu}`;
+/// - the union of nested sets, `[[a-z][0-9]]`, their intersection, `[[a-z]&[aeiou]]`, and their
+///   difference, `[[a-z]-[aeiou]]`, from left to right.
+///
+/// The whitespace between the items is ignored. The strings of ICU, as `{ch}`, are not
+/// supported.
+///
+/// ```
+/// use collate_rs::unicode_set::UnicodeSet;
+///
+/// let consonants = UnicodeSet::parse("[[a-z]-[aeiou]]").unwrap();
+/// assert!(consonants.contains('b'));
+/// assert!(!consonants.contains('a'));
+/// assert_eq!(consonants.len(), 21);
+///
+/// let uppercase = UnicodeSet::parse("[:Lu:]").unwrap();
+/// assert!(uppercase.contains('É'));
+/// assert!(!uppercase.contains('é'));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UnicodeSet {
+    /// Sorted ranges, neither overlapping nor adjacent, without the surrogates.
+    ranges: Vec<RangeInclusive<char>>,
+}
+
+impl UnicodeSet {
+    /// The empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a set written in the UnicodeSet notation (see [`UnicodeSet`]).
+    ///
+    /// Fails with [`CollateError::ParseError`] if the set is not well written, or names an unknown
+    /// property.
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let mut parser = Parser {
+            pattern,
+            chars: pattern.chars().collect(),
+            i: 0,
+        };
+        let set = parser.parse_set()?;
+        parser.skip_whitespace();
+        match parser.i < parser.chars.len() {
+            true => Err(parser.error("unexpected characters after the set")),
+            false => Ok(set),
+        }
+    }
+
+    /// The set of the characters of `range`.
+    pub fn from_range(range: RangeInclusive<char>) -> Self {
+        Self::from_code_points(vec![(*range.start() as u32, *range.end() as u32)])
+    }
+
+    /// The set of the ranges of code points, in any order, the surrogates left out.
+    pub(crate) fn from_code_points(mut ranges: Vec<(u32, u32)>) -> Self {
+        ranges.retain(|(first, last)| first <= last);
+        ranges.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = vec![];
+        for (first, last) in ranges {
+            match merged.last_mut() {
+                Some((_, end)) if first <= end.saturating_add(1) => *end = (*end).max(last),
+                _ => merged.push((first, last)),
+            }
+        }
+        let mut chars = vec![];
+        for (first, last) in merged {
+            let pieces = [
+                (first, last.min(SURROGATES.start() - 1)),
+                (first.max(SURROGATES.end() + 1), last),
+            ];
+            for (first, last) in pieces.into_iter().filter(|(first, last)| first <= last) {
+                let to_char = |cp| char::from_u32(cp).expect("Not a surrogate.");
+                chars.push(to_char(first)..=to_char(last));
+            }
+        }
+        Self { ranges: chars }
+    }
+
+    fn code_points(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.ranges
+            .iter()
+            .map(|range| (*range.start() as u32, *range.end() as u32))
+    }
+
+    /// The ranges of the characters of the set, in order.
+    pub fn ranges(&self) -> &[RangeInclusive<char>] {
+        &self.ranges
+    }
+
+    /// The characters of the set, in order.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.ranges.iter().flat_map(RangeInclusive::clone)
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.ranges
+            .binary_search_by(|range| {
+                if *range.end() < c {
+                    std::cmp::Ordering::Less
+                } else if *range.start() > c {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of characters of the set.
+    pub fn len(&self) -> usize {
+        self.code_points()
+            .map(|(first, last)| (last - first + 1) as usize)
+            .sum()
+    }
+
+    pub fn union(&self, other: &UnicodeSet) -> Self {
+        Self::from_code_points(self.code_points().chain(other.code_points()).collect())
+    }
+
+    /// The characters not in the set.
+    pub fn complement(&self) -> Self {
+        let mut gaps = vec![];
+        let mut next = 0;
+        for (first, last) in self.code_points() {
+            if first > next {
+                gaps.push((next, first - 1));
+            }
+            next = last + 1;
+        }
+        gaps.push((next, char::MAX as u32));
+        Self::from_code_points(gaps)
+    }
+
+    pub fn intersection(&self, other: &UnicodeSet) -> Self {
+        self.complement().union(&other.complement()).complement()
+    }
+
+    pub fn difference(&self, other: &UnicodeSet) -> Self {
+        self.intersection(&other.complement())
+    }
+
+    /// The characters of the general category `name`, e.g. `Lu`, or of a group of categories,
+    /// e.g. `L`.
+    fn general_category(name: &str) -> Option<Self> {
+        let categories: &[&str] =
+            match CATEGORY_GROUPS.iter().find(|(group, _)| *group == name) {
+                Some((_, categories)) => categories,
+                None => std::slice::from_ref(CATEGORY_GROUPS[..].iter().find_map(
+                    |(_, categories)| categories.iter().find(|&&category| category == name),
+                )?),
+            };
+        let assigned = Self::from_code_points(
+            GENERAL_CATEGORIES
+                .iter()
+                .map(|&(first, last, _)| (first, last))
+                .collect(),
+        );
+        let set = Self::from_code_points(
+            GENERAL_CATEGORIES
+                .iter()
+                .filter(|(_, _, category)| categories.contains(category))
+                .map(|&(first, last, _)| (first, last))
+                .collect(),
+        );
+        Some(match categories.contains(&"Cn") {
+            true => set.union(&assigned.complement()),
+            false => set,
+        })
+    }
+}
+
+impl FromStr for UnicodeSet {
+    type Err = CollateError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+struct Parser<'p> {
+    pattern: &'p str,
+    chars: Vec<char>,
+    i: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, reason: &str) -> CollateError {
+        CollateError::parse(format!(
+            "Invalid UnicodeSet {:?} at offset {}: {}.",
+            self.pattern, self.i, reason
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.i).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.i += 1;
+        }
+    }
+
+    /// Whether a set starts at the current position, rather than a character.
+    fn at_set(&self) -> bool {
+        matches!(
+            (self.peek(), self.chars.get(self.i + 1)),
+            (Some('['), _) | (Some('\\'), Some('p' | 'P'))
+        )
+    }
+
+    fn parse_set(&mut self) -> Result<UnicodeSet> {
+        self.skip_whitespace();
+        if self.chars[self.i..].starts_with(&['[', ':']) {
+            return self.parse_property(":]");
+        }
+        if self.at_set() && self.peek() == Some('\\') {
+            return self.parse_property("}");
+        }
+        if self.peek() != Some('[') {
+            return Err(self.error("expected a set"));
+        }
+        self.i += 1;
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.i += 1;
+        }
+        let mut set = UnicodeSet::new();
+        let mut operator = None;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Err(self.error("the set is not closed")),
+                Some(']') => {
+                    self.i += 1;
+                    break;
+                }
+                Some('{') => return Err(self.error("strings are not supported")),
+                Some(c @ ('&' | '-')) if !set.is_empty() && operator.is_none() => {
+                    self.i += 1;
+                    self.skip_whitespace();
+                    match self.at_set() {
+                        true => operator = Some(c),
+                        false if c == '-' && self.peek() == Some(']') => {
+                            set = set.union(&UnicodeSet::from_range('-'..='-'))
+                        }
+                        false => return Err(self.error("expected a set after the operator")),
+                    }
+                }
+                _ if self.at_set() => {
+                    let nested = self.parse_set()?;
+                    set = match operator.take() {
+                        Some('&') => set.intersection(&nested),
+                        Some(_) => set.difference(&nested),
+                        None => set.union(&nested),
+                    };
+                }
+                _ if operator.is_some() => {
+                    return Err(self.error("expected a set after the operator"))
+                }
+                _ => {
+                    let first = self.parse_char()?;
+                    self.skip_whitespace();
+                    let range = match (self.peek(), self.chars.get(self.i + 1)) {
+                        (Some('-'), Some(&next)) if next != ']' => {
+                            self.i += 1;
+                            self.skip_whitespace();
+                            let last = self.parse_char()?;
+                            if last < first {
+                                return Err(self.error("the range is reversed"));
+                            }
+                            first..=last
+                        }
+                        _ => first..=first,
+                    };
+                    set = set.union(&UnicodeSet::from_range(range));
+                }
+            }
+        }
+        if operator.is_some() {
+            return Err(self.error("expected a set after the operator"));
+        }
+        Ok(match negated {
+            true => set.complement(),
+            false => set,
+        })
+    }
+
+    /// A property written `[:name:]` or `\p{name}`, the current position at `[` or `\`.
+    fn parse_property(&mut self, end: &str) -> Result<UnicodeSet> {
+        let mut negated = self.chars.get(self.i + 1) == Some(&'P');
+        self.i += 2;
+        if end == "}" {
+            if self.peek() != Some('{') {
+                return Err(self.error("expected `{`"));
+            }
+            self.i += 1;
+        }
+        if self.peek() == Some('^') {
+            negated = !negated;
+            self.i += 1;
+        }
+        let rest: String = self.chars[self.i..].iter().collect();
+        let Some(len) = rest.find(end) else {
+            return Err(self.error("the property is not closed"));
+        };
+        let name = rest[..len].trim();
+        let name = ["gc=", "General_Category="]
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix))
+            .unwrap_or(name);
+        let set = UnicodeSet::general_category(name)
+            .ok_or_else(|| self.error(&format!("unknown property {:?}", name)))?;
+        self.i += rest[..len + end.len()].chars().count();
+        Ok(match negated {
+            true => set.complement(),
+            false => set,
+        })
+    }
+
+    fn parse_char(&mut self) -> Result<char> {
+        let c = self
+            .peek()
+            .ok_or_else(|| self.error("expected a character"))?;
+        self.i += 1;
+        if c != '\\' {
+            return Ok(c);
+        }
+        let escaped = self
+            .peek()
+            .ok_or_else(|| self.error("expected a character"))?;
+        self.i += 1;
+        let hex = match escaped {
+            'u' => self.take_hex(4),
+            'U' => self.take_hex(8),
+            'x' if self.peek() == Some('{') => {
+                self.i += 1;
+                let hex = self.take_hex(6);
+                match self.peek() {
+                    Some('}') => {
+                        self.i += 1;
+                        hex
+                    }
+                    _ => None,
+                }
+            }
+            'x' => self.take_hex(2),
+            c => return Ok(c),
+        };
+        hex.and_then(char::from_u32)
+            .ok_or_else(|| self.error("invalid code point"))
+    }
+
+    /// Up to `n` hexadecimal digits, exactly `n` but after `\x{`.
+    fn take_hex(&mut self, n: usize) -> Option<u32> {
+        let digits: String = self.chars[self.i..]
+            .iter()
+            .take(n)
+            .take_while(|c| c.is_ascii_hexdigit())
+            .collect();
+        let braced = self.chars.get(self.i - 1) == Some(&'{');
+        if digits.is_empty() || (!braced && digits.len() != n) {
+            return None;
+        }
+        self.i += digits.len();
+        u32::from_str_radix(&digits, 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unicode_set() {
+        let set = |pattern| UnicodeSet::parse(pattern).unwrap();
+        assert_eq!(
+            set("[a-c\\u00E0 \\-]").ranges(),
+            ['-'..='-', 'a'..='c', 'à'..='à']
+        );
+        assert_eq!(set("[a-]").ranges(), ['-'..='-', 'a'..='a']);
+        assert_eq!(
+            set("[\\x{1F600}\\U0001F601\\x41]").ranges(),
+            ['A'..='A', '😀'..='😁']
+        );
+        assert_eq!(set("[\\u0300-\\u036F]").len(), 0x70);
+        assert_eq!(set("[[a-z]&[x-\\u00FF]]").ranges(), ['x'..='z']);
+        assert_eq!(set("[[a-z] - [b-y]]").ranges(), ['a'..='a', 'z'..='z']);
+        assert_eq!(set("[^\\u0000-\\U0010FFFF]"), UnicodeSet::new());
+        assert_eq!(set("[^a]").len(), 0x110000 - 0x800 - 1);
+        assert_eq!(set("[Ѐ-ӿ]"), UnicodeSet::from_range('\u{400}'..='\u{4FF}'));
+
+        let upper = set("[:Lu:]");
+        assert_eq!(upper, set("\\p{Lu}"));
+        assert_eq!(upper, set("[:gc=Lu:]"));
+        assert!(upper.contains('A') && upper.contains('Σ') && !upper.contains('a'));
+        assert!(!upper.contains('Ⓐ'));
+        assert_eq!(set("[:^Lu:]"), upper.complement());
+        assert_eq!(set("\\P{Lu}"), upper.complement());
+        assert!(!set("[[:L:]-[:Ll:]]").contains('a'));
+        assert!(set("[:Cn:]").contains('\u{378}'));
+        assert!(set("[:C:]").contains('\u{378}'));
+        assert!(set("[[:Mn:]&[\\u0300-\\u036F]]").contains('\u{301}'));
+        assert_eq!(set("[:Cs:]"), UnicodeSet::new());
+
+        for invalid in [
+            "a-c",
+            "[c-a]",
+            "[a",
+            "[\\u00G0]",
+            "[:Xx:]",
+            "[[a]&b]",
+            "[[a]&]",
+            "[{ch}]",
+            "[a]b",
+            "\\p{Lu",
+        ] {
+            assert!(
+                matches!(
+                    UnicodeSet::parse(invalid),
+                    Err(CollateError::ParseError { .. })
+                ),
+                "{}",
+                invalid
+            );
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Built with `wasm-pack build --features wasm`, the bindings are used from JavaScript as
+//!
+//! ```js
+//! const collator = new Collator("sv", { keyEncoding: "fractional" });
+//! collator.compare("ä", "z"); // 1
+//! collator.sort(["z", "ä", "a"]); // ["a", "z", "ä"]
+//! collator.sortKey("ä"); // Uint8Array
+//! ```
+//!
+//! so that client and server can share the same deterministic ordering, independently of the
+//! `Intl.Collator` of each JavaScript engine.
+
+use crate::algorithm::{Collator, CollatorOptions, KeyEncoding};
+use crate::error::{CollateError, Result};
+use js_sys::Reflect;
+use std::cmp::Ordering;
+use wasm_bindgen::prelude::*;
+
+/// Parses the `keyEncoding` of a JavaScript options object.
+fn parse_key_encoding(name: &str) -> Result<KeyEncoding> {
+    match name {
+        "u16BigEndian" => Ok(KeyEncoding::U16BigEndian),
+        "fractional" => Ok(KeyEncoding::Fractional),
+        _ => Err(CollateError::InvalidOption(format!(
+            "Unknown keyEncoding {:?}, expected \"u16BigEndian\" or \"fractional\".",
+            name
+        ))),
+    }
+}
+
+/// Reads a JavaScript options object `{ keyEncoding?: "u16BigEndian" | "fractional" }`, the
+/// counterpart of [`CollatorOptions`].
+fn parse_options(options: &JsValue) -> Result<CollatorOptions, JsError> {
+    let mut parsed = CollatorOptions::default();
+    if options.is_undefined() || options.is_null() {
+        return Ok(parsed);
+    }
+    if !options.is_object() {
+        return Err(JsError::new("The collator options must be an object."));
+    }
+    let key_encoding = Reflect::get(options, &JsValue::from_str("keyEncoding"))
+        .map_err(|_| JsError::new("Could not read options.keyEncoding."))?;
+    if !key_encoding.is_undefined() {
+        let name = key_encoding
+            .as_string()
+            .ok_or_else(|| JsError::new("options.keyEncoding must be a string."))?;
+        parsed.key_encoding =
+            parse_key_encoding(&name).map_err(|e| JsError::new(&e.to_string()))?;
+    }
+    Ok(parsed)
+}
+
+/// A [`Collator`] for one of the bundled locales, exported to JavaScript as `Collator`.
+#[wasm_bindgen(js_name = Collator)]
+pub struct WasmCollator(Collator);
+
+#[wasm_bindgen(js_class = Collator)]
+impl WasmCollator {
+    /// `new Collator(locale?, options?)`, with the root collation if `locale` is omitted.
+    #[wasm_bindgen(constructor)]
+    pub fn new(locale: Option<String>, options: JsValue) -> Result<WasmCollator, JsError> {
+        let options = parse_options(&options)?;
+        let collator = Collator::try_new(locale.as_deref().unwrap_or("und"))
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(Self(collator.clone_with_options(options)))
+    }
+
+    /// Returns `-1`, `0` or `1`, as `Intl.Collator.prototype.compare`.
+    pub fn compare(&self, a: &str, b: &str) -> i32 {
+        match self.0.compare(a, b) {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        }
+    }
+
+    /// The bytes of the sort key of `s`, comparable with other keys of the same collator.
+    #[wasm_bindgen(js_name = sortKey)]
+    pub fn sort_key(&self, s: &str) -> Vec<u8> {
+        self.0.sort_key(s).into_bytes()
+    }
+
+    /// Returns the strings sorted, computing the sort key of each string once.
+    pub fn sort(&self, mut strings: Vec<String>) -> Vec<String> {
+        self.0.sort_slice(&mut strings);
+        strings
+    }
+
+    /// The locales accepted by the constructor.
+    #[wasm_bindgen(js_name = availableLocales)]
+    pub fn available_locales() -> Vec<String> {
+        Collator::available_locales()
+            .iter()
+            .flat_map(|info| info.identifiers())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_options() {
+        assert_eq!(
+            parse_key_encoding("fractional").unwrap(),
+            KeyEncoding::Fractional
+        );
+        assert_eq!(
+            parse_key_encoding("u16BigEndian").unwrap(),
+            KeyEncoding::U16BigEndian
+        );
+        assert!(parse_key_encoding("utf8").is_err());
+        assert!(WasmCollator::available_locales().contains(&"de-u-co-phonebk".to_string()));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::data_files::ucd::{CORE_HAN_BLOCKS, SINIFORM_SCRIPTS, UNIFIED_IDEOGRAPHS};
+use crate::definitions::collation_wel::{CollationElement, CollationWeight};
+use smallvec::smallvec;
+use std::fmt;
+
+/// Whether the code point has the Unified_Ideograph property in the Unicode data of the crate.
+fn is_unified_ideograph(cp: u32) -> bool {
+    UNIFIED_IDEOGRAPHS
+        .iter()
+        .any(|&(first, last)| (first..=last).contains(&cp))
+}
+
+/// Whether the code point belongs to the CJK Unified Ideographs or CJK Compatibility Ideographs
+/// blocks.
+fn is_core_han_block(cp: u32) -> bool {
+    CORE_HAN_BLOCKS
+        .iter()
+        .any(|&(first, last)| (first..=last).contains(&cp))
+}
+
+/// > 10.1.3 Implicit Weights
+/// >
+/// > This section describes the generation of implicit weights for code points that are not
+/// > explicitly mentioned in the collation element table.
+/// >
+/// > A character is mapped to an implicit weight of the following form:
+/// ```text
+///     [.AAAA.0020.0002][.BBBB.0000.0000]
+/// ```
+/// > where `AAAA` is the base (or the siniform base) plus `CP >> 15`, and
+/// > `BBBB = (CP & 0x7FFF) | 0x8000`.
+///
+/// | Type                                              | AAAA                    | BBBB                           |
+/// |---------------------------------------------------|-------------------------|--------------------------------|
+/// | Tangut, Nushu, Khitan Small Script                | FB00, FB01, FB02        | `(CP - FIRST) \| 0x8000`       |
+/// | Unified_Ideograph in the core Han blocks          | `FB40 + (CP >> 15)`     | `(CP & 0x7FFF) \| 0x8000`      |
+/// | Any other Unified_Ideograph                       | `FB80 + (CP >> 15)`     | `(CP & 0x7FFF) \| 0x8000`      |
+/// | Any other code point                              | `FBC0 + (CP >> 15)`     | `(CP & 0x7FFF) \| 0x8000`      |
+pub fn implicit_weights(c: char) -> [CollationElement; 2] {
+    let (aaaa, bbbb) = implicit_primaries(c);
+    [
+        CollationElement(
+            smallvec![
+                CollationWeight::from(aaaa),
+                CollationWeight::from(0x0020),
+                CollationWeight::from(0x0002),
+            ],
+            false,
+        ),
+        CollationElement(
+            smallvec![
+                CollationWeight::from(bbbb),
+                CollationWeight::from(0x0000),
+                CollationWeight::from(0x0000),
+            ],
+            false,
+        ),
+    ]
+}
+
+/// The primary weights `AAAA` and `BBBB` of the collation elements of [`implicit_weights`].
+pub(crate) fn implicit_primaries(c: char) -> (u32, u32) {
+    implicit_code_point_primaries(c as u32)
+}
+
+/// Like [`implicit_primaries`], for any code point, e.g. the unpaired surrogates of ill-formed
+/// UTF-16 strings, which are not characters.
+pub(crate) fn implicit_code_point_primaries(cp: u32) -> (u32, u32) {
+    if let Some(&(_, _, base, offset)) = SINIFORM_SCRIPTS
+        .iter()
+        .find(|&&(first, last, _, _)| (first..=last).contains(&cp))
+    {
+        (base, (cp - offset) | 0x8000)
+    } else {
+        let base = if is_unified_ideograph(cp) && is_core_han_block(cp) {
+            0xFB40
+        } else if is_unified_ideograph(cp) {
+            0xFB80
+        } else {
+            0xFBC0
+        };
+        (base + (cp >> 15), (cp & 0x7FFF) | 0x8000)
+    }
+}
+
+/// The policy deriving the implicit weights of the code points a table has no mapping for, which
+/// a [`Collator`](crate::algorithm::Collator) consults whenever a lookup misses, e.g. to give the
+/// private-use characters of a legacy encoding or of a fictional script an order of their own
+/// without listing them all in the table.
+///
+/// The policy returns the primary weights `AAAA` and `BBBB` of the two collation elements
+/// `[.AAAA.0020.0002][.BBBB.0000.0000]` of the code point, as [`implicit_weights`]; the default
+/// one, [`UcaWeightDerivation`], follows 10.1.3.
+///
+/// ```
+/// use collate_rs::algorithm::Collator;
+/// use collate_rs::weight_derivation::{UcaWeightDerivation, WeightDerivation};
+/// use std::cmp::Ordering;
+///
+/// /// Sorts the private-use characters of the BMP before the explicit weights of the table.
+/// #[derive(Debug)]
+/// struct PrivateUseFirst;
+///
+/// impl WeightDerivation for PrivateUseFirst {
+///     fn implicit_primaries(&self, cp: u32) -> (u32, u32) {
+///         match cp {
+///             0xE000..=0xF8FF => (0x0100, 0x8000 | (cp - 0xE000)),
+///             _ => UcaWeightDerivation.implicit_primaries(cp),
+///         }
+///     }
+///
+///     fn fingerprint(&self) -> u64 {
+///         1
+///     }
+/// }
+///
+/// let collator = Collator::try_new("und").unwrap();
+/// assert_eq!(collator.compare("\u{E000}", "a"), Ordering::Greater);
+/// let collator = collator.with_weight_derivation(PrivateUseFirst);
+/// assert_eq!(collator.compare("\u{E000}", "a"), Ordering::Less);
+/// assert_eq!(collator.compare("\u{E000}", "\u{E001}"), Ordering::Less);
+/// ```
+pub trait WeightDerivation: fmt::Debug + Send + Sync {
+    /// The primary weights `AAAA` and `BBBB` of the implicit collation elements of `cp`, a code
+    /// point missing from the table, e.g. an unassigned one, or an unpaired surrogate with
+    /// [`IllFormedInput::Implicit`](crate::algorithm::IllFormedInput::Implicit).
+    ///
+    /// Both weights must be in `0001..=FFFF`, the weights the sort keys of every
+    /// [`KeyEncoding`](crate::algorithm::KeyEncoding) hold: the collator saturates the others to
+    /// that range, and panics on them in the debug builds.
+    fn implicit_primaries(&self, cp: u32) -> (u32, u32);
+
+    /// A hash of the weights of the policy, hashed into the
+    /// [`Collator::version`](crate::algorithm::Collator::version): two policies deriving different
+    /// weights must return different values, and a policy must return a new one when its weights
+    /// change, so that the sort keys stored with the former version are recomputed.
+    fn fingerprint(&self) -> u64;
+}
+
+/// The implicit weights of 10.1.3, used unless a collator is given another [`WeightDerivation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UcaWeightDerivation;
+
+impl WeightDerivation for UcaWeightDerivation {
+    fn implicit_primaries(&self, cp: u32) -> (u32, u32) {
+        implicit_code_point_primaries(cp)
+    }
+
+    fn fingerprint(&self) -> u64 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::source::Utf16Source;
+    use crate::algorithm::{Collator, CollatorOptions, IllFormedInput};
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_10_1_3() {
+        // CJK UNIFIED IDEOGRAPH-4E00
+        let [a, b] = implicit_weights('\u{4E00}');
+        assert_eq!(a, CollationElement::try_from("[.FB40.0020.0002]").unwrap());
+        assert_eq!(b, CollationElement::try_from("[.CE00.0000.0000]").unwrap());
+        // CJK UNIFIED IDEOGRAPH-20000 (Extension B)
+        let [a, b] = implicit_weights('\u{20000}');
+        assert_eq!(a, CollationElement::try_from("[.FB84.0020.0002]").unwrap());
+        assert_eq!(b, CollationElement::try_from("[.8000.0000.0000]").unwrap());
+        // TANGUT IDEOGRAPH-17001
+        let [a, b] = implicit_weights('\u{17001}');
+        assert_eq!(a, CollationElement::try_from("[.FB00.0020.0002]").unwrap());
+        assert_eq!(b, CollationElement::try_from("[.8001.0000.0000]").unwrap());
+        // Unassigned code point
+        let [a, b] = implicit_weights('\u{E0080}');
+        assert_eq!(a, CollationElement::try_from("[.FBDC.0020.0002]").unwrap());
+        assert_eq!(b, CollationElement::try_from("[.8080.0000.0000]").unwrap());
+    }
+
+    /// Sorts the private-use characters in reverse, and the surrogates after them.
+    #[derive(Debug)]
+    struct ReversedPrivateUse;
+
+    impl WeightDerivation for ReversedPrivateUse {
+        fn implicit_primaries(&self, cp: u32) -> (u32, u32) {
+            match cp {
+                0xE000..=0xF8FF => (0xFBC0, 0x8000 | (0xF8FF - cp)),
+                0xD800..=0xDFFF => (0xFBC1, 0x8000 | cp),
+                _ => UcaWeightDerivation.implicit_primaries(cp),
+            }
+        }
+
+        fn fingerprint(&self) -> u64 {
+            1
+        }
+    }
+
+    #[test]
+    fn test_weight_derivation() {
+        for c in ['\u{4E00}', '\u{17001}', '\u{E000}', '\u{E0080}'] {
+            assert_eq!(
+                UcaWeightDerivation.implicit_primaries(c.into()),
+                implicit_primaries(c)
+            );
+        }
+
+        let collator = Collator::try_new("und").unwrap();
+        assert_eq!(collator.compare("\u{E000}", "\u{E001}"), Ordering::Less);
+        let reversed = collator.clone().with_weight_derivation(ReversedPrivateUse);
+        assert_eq!(reversed.compare("\u{E000}", "\u{E001}"), Ordering::Greater);
+        assert_eq!(reversed.compare("a\u{E000}", "b"), Ordering::Less);
+        // The explicit mappings of the table are left as they are.
+        assert_eq!(
+            reversed.sort_key("a\u{4E00}"),
+            collator.sort_key("a\u{4E00}")
+        );
+        assert_eq!(reversed.weight_derivation().fingerprint(), 1);
+        assert_ne!(reversed.version(), collator.version());
+
+        let implicit = reversed.clone_with_options(CollatorOptions {
+            ill_formed_input: IllFormedInput::Implicit,
+            ..Default::default()
+        });
+        // The policy is kept by the clones with other options.
+        let options = implicit.options().clone();
+        assert_ne!(
+            implicit.version(),
+            collator.clone_with_options(options).version()
+        );
+        let surrogate = [0x61, 0xD800];
+        assert_eq!(
+            implicit
+                .compare_source(Utf16Source::new(&surrogate), "a\u{E000}".chars())
+                .unwrap(),
+            Ordering::Greater
+        );
+    }
+
+    #[derive(Debug)]
+    struct OutOfRange;
+
+    impl WeightDerivation for OutOfRange {
+        fn implicit_primaries(&self, cp: u32) -> (u32, u32) {
+            (0x1_0000 + cp, 0)
+        }
+
+        fn fingerprint(&self) -> u64 {
+            2
+        }
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "out of 0001..=FFFF"))]
+    fn test_weight_derivation_out_of_range() {
+        let collator = Collator::try_new("und")
+            .unwrap()
+            .with_weight_derivation(OutOfRange);
+        // Saturated to FFFF and 0001.
+        assert_eq!(collator.compare("\u{E000}", "\u{E001}"), Ordering::Equal);
+        assert_eq!(collator.sort_key("\u{E000}").levels()[0], [0xFFFF, 0x0001]);
+    }
+}
@@ -0,0 +1,82 @@
+//! Counts the allocations of the comparisons, which reuse scratch buffers once they have grown to
+//! the size of the strings compared.
+
+use collate_rs::algorithm::{Collator, CollatorOptions, CollatorScratch};
+use collate_rs::definitions::variable_weighting::VariableWeighting;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Counts the allocations of the current thread, as the test harness allocates on its own.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_comparisons_do_not_allocate() {
+    let collator = Collator::try_new("und").unwrap();
+    let shifted = collator.clone_with_options(CollatorOptions {
+        variable_weighting: VariableWeighting::Shifted,
+        ..Default::default()
+    });
+    let words = [
+        "résumé",
+        "Resume",
+        "re-sume",
+        "RÉSUMÉ",
+        "co-op",
+        "coop",
+        "Ωmega",
+        "한국어",
+    ];
+    let mut scratch = CollatorScratch::new();
+    for collator in [&collator, &shifted] {
+        // Grows the buffers.
+        for a in words {
+            for b in words {
+                collator.compare(a, b);
+                collator.compare_with(a, b, &mut scratch);
+            }
+        }
+        for a in words {
+            for b in words {
+                let count = allocations(|| {
+                    collator.compare(a, b);
+                });
+                assert_eq!(count, 0);
+                let count = allocations(|| {
+                    collator.compare_with(a, b, &mut scratch);
+                });
+                assert_eq!(count, 0);
+            }
+        }
+        // Unlike the sort keys themselves.
+        assert!(allocations(|| drop(collator.sort_key("résumé"))) > 0);
+    }
+}
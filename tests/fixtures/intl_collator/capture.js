@@ -0,0 +1,69 @@
+// Captures the orderings of `Intl.Collator` for the corpus below.
+//
+// Usage: node tests/fixtures/intl_collator/capture.js
+//
+// Every fixture lists the corpus in the order produced by the engine, one string per line,
+// prefixed by its relation to the previous line ("<" or "=") and a tab.
+const fs = require("fs");
+const path = require("path");
+
+const LOCALES = {
+  root: "en",
+  de: "de",
+  "de-u-co-phonebk": "de-u-co-phonebk",
+  da: "da",
+  nb: "nb",
+  es: "es",
+  "es-u-co-trad": "es-u-co-trad",
+  sv: "sv",
+  tr: "tr",
+};
+
+const CORPUS = [
+  // Case and accents
+  "cote", "côte", "coté", "côté", "Cote", "COTE", "Côte", "resume", "résumé", "Resume",
+  "a", "A", "á", "Á", "à", "â", "ä", "Ä", "ã", "å", "Å", "ā", "ă", "ą", "æ", "Æ",
+  "b", "B", "c", "C", "ç", "č", "e", "é", "è", "ê", "ë", "ě", "ę", "z", "Z", "ž", "ź",
+  // Punctuation, symbols and digits
+  "death", "de ath", "de-ath", "deluge", "de luge", "co-op", "coop", "co op", "cooperate",
+  "!", "?", "-", "_", "@", "#", "$", "€", "£", "%", "&", "*", "(", ")", "+", "=",
+  "0", "1", "2", "9", "10", "11", "20", "100", "a1", "a2", "a10", "1a", "½", "²",
+  // German
+  "Apfel", "Äpfel", "Aepfel", "Müller", "Mueller", "Muller", "Öl", "Oel", "Ofen", "Übel",
+  "Ufer", "Uebel", "Straße", "Strasse", "Strase", "Göbel", "Goethe", "Goldmann",
+  // Scandinavian
+  "Aalborg", "Aarhus", "Århus", "Ærø", "Ørsted", "Oslo", "Øresund", "Zürich", "Zebra",
+  "ångström", "ärlig", "öl", "visby", "wisby", "Vilhelm", "William", "yxa", "ü", "y",
+  "aa", "Aa", "AA", "aA", "å", "Å", "AALBORG", "aalborg", "Ålborg", "Haag", "Hå", "kaa", "Kåre",
+  "ę", "œ",
+  // Spanish
+  "chico", "cosa", "cuchara", "coche", "corte", "llama", "luz", "lobo", "ñu", "nube",
+  "nada", "Ñandú", "canción", "cancion",
+  // Turkish
+  "ılık", "ıslak", "iki", "İstanbul", "Istanbul", "Iğdır", "ipek", "ırmak", "çay", "cam",
+  "şeker", "sabah", "ğ", "g", "h",
+  // Greek
+  "άλφα", "αλφα", "Άλφα", "ΑΛΦΑ", "βήτα", "γάμμα", "ωμέγα", "ὤμος", "ᾅδης", "ἀγαθός",
+  // Cyrillic
+  "ёж", "еж", "Ёлка", "елка", "жук", "яблоко", "Яблоко", "арбуз", "ї", "і",
+  // Thai and Lao, whose prevowels sort after the following consonant
+  "ก", "กา", "เก", "แก", "ไก่", "ข", "ขาว", "เขา", "ข้าว", "เข้า", "ใหม่", "ไหม", "น้ำ", "นำ",
+  "ກ", "ເກ", "ໄກ", "ຂ", "ຂ້າວ", "ເຂົ້າ",
+  // Other scripts
+  "नमस्ते", "संस्कृत", "中文", "日本", "漢字", "カタカナ", "かたかな", "ひらがな", "한국어", "ஆ",
+];
+
+const outDir = __dirname;
+for (const [name, locale] of Object.entries(LOCALES)) {
+  const collator = new Intl.Collator(locale);
+  const sorted = [...CORPUS].sort(collator.compare);
+  const lines = [
+    `# Intl.Collator(${JSON.stringify(locale)}) resolved as ${JSON.stringify(collator.resolvedOptions().locale)}`,
+    `# node ${process.versions.node}, ICU ${process.versions.icu}, Unicode ${process.versions.unicode}`,
+  ];
+  sorted.forEach((s, i) => {
+    const relation = i > 0 && collator.compare(sorted[i - 1], s) === 0 ? "=" : "<";
+    lines.push(`${relation}\t${s}`);
+  });
+  fs.writeFileSync(path.join(outDir, `${name}.txt`), lines.join("\n") + "\n");
+}
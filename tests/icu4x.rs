@@ -0,0 +1,124 @@
+//! Comparative tests of the orderings of the collator of ICU4X (feature `icu-interop`), at the
+//! root locale and at the bundled tailorings.
+//!
+//! The sort keys of ICU4X are not comparable to ours, so the orderings are compared: every pair of
+//! successive strings of the generated corpora, in their random order and in the order of ICU4X,
+//! must compare alike, but for the pairs concerned by one of the [`DIVERGENCES`]. Run
+//! `cargo test --features icu-interop --test icu4x -- --nocapture` to print the agreement report.
+#![cfg(feature = "icu-interop")]
+
+use collate_rs::algorithm::Collator;
+use collate_rs::test_support::Corpus;
+use icu_collator::options::CollatorOptions;
+use icu_collator::{CollatorBorrowed, CollatorPreferences};
+use icu_locale_core::Locale;
+
+const LOCALES: [&str; 9] = [
+    "und",
+    "de",
+    "de-u-co-phonebk",
+    "da",
+    "nb",
+    "es",
+    "es-u-co-trad",
+    "sv",
+    "tr",
+];
+
+/// The number of words generated of each corpus.
+const WORDS: usize = 2000;
+
+const SEED: u64 = 0x5EED;
+
+/// An intentional divergence from ICU4X: the locales it concerns, why, and whether a pair of
+/// strings is concerned.
+struct Divergence {
+    locales: &'static [&'static str],
+    reason: &'static str,
+    applies: fn(&str, &str) -> bool,
+}
+
+const DIVERGENCES: [Divergence; 2] = [
+    Divergence {
+        locales: &["sv"],
+        reason: "The sv tailoring of Unicode::Collate::Locale, which the bundled one is derived \
+            from, sorts v and w as variants of the same letter, as CLDR did before the letters were \
+            separated.",
+        applies: |a, b| [a, b].iter().all(|s| s.contains(['v', 'w', 'V', 'W'])),
+    },
+    Divergence {
+        locales: &["da", "nb"],
+        reason: "The contractions of aa are matched in the NFD of the strings, as in S2.1 of \
+            UTS10, so the aa of a\u{E2} (a, a, U+0302) sorts as a\u{30A}; ICU4X does not match \
+            the contractions ending with a letter carrying a combining mark.",
+        applies: |a, b| [a, b].iter().any(|s| has_accented_aa(s)),
+    },
+];
+
+/// Whether `s` has an a followed by an a with an accent, in either case.
+fn has_accented_aa(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().collect();
+    chars.windows(2).any(|pair| {
+        matches!(pair[0], 'a' | 'A') && matches!(pair[1], '\u{C0}'..='\u{C5}' | '\u{E0}'..='\u{E5}')
+    })
+}
+
+fn icu_collator(locale: &str) -> CollatorBorrowed<'static> {
+    let locale: Locale = locale.parse().unwrap();
+    icu_collator::Collator::try_new(
+        CollatorPreferences::from(&locale),
+        CollatorOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn icu4x_agreement() {
+    let mut report = vec![];
+    let mut unexplained = vec![];
+    for locale in LOCALES {
+        let collator = Collator::try_new(locale).unwrap();
+        let icu = icu_collator(locale);
+        let divergences: Vec<&Divergence> = DIVERGENCES
+            .iter()
+            .filter(|divergence| divergence.locales.contains(&locale))
+            .collect();
+        for corpus in Corpus::ALL {
+            let random = corpus.generate(WORDS, SEED);
+            let mut sorted = random.clone();
+            sorted.sort_by(|a, b| icu.compare(a, b));
+            let (mut total, mut agreeing) = (0, 0);
+            for pair in random.windows(2).chain(sorted.windows(2)) {
+                let (a, b) = (pair[0].as_str(), pair[1].as_str());
+                total += 1;
+                if collator.compare(a, b) == icu.compare(a, b) {
+                    agreeing += 1;
+                } else if !divergences
+                    .iter()
+                    .any(|divergence| (divergence.applies)(a, b))
+                {
+                    unexplained.push(format!("{} {}: {:?} {:?}", locale, corpus.name(), a, b));
+                }
+            }
+            report.push(format!(
+                "{:<16} {:<8} {:>6.2}%",
+                locale,
+                corpus.name(),
+                agreeing as f64 * 100.0 / total as f64
+            ));
+        }
+    }
+    println!("Agreement with ICU4X:");
+    for line in report {
+        println!("  {}", line);
+    }
+    println!("Intentional divergences:");
+    for divergence in &DIVERGENCES {
+        println!("  {}: {}", divergence.locales.join(", "), divergence.reason);
+    }
+    assert!(
+        unexplained.is_empty(),
+        "Orderings differing from ICU4X:\n{}",
+        unexplained.join("\n")
+    );
+}
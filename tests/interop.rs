@@ -0,0 +1,83 @@
+//! Golden interop tests against the orderings of `Intl.Collator` (V8/ICU).
+//!
+//! The fixtures in `tests/fixtures/intl_collator` are captured with `capture.js`. Run
+//! `cargo test --test interop -- --nocapture` to print the agreement report.
+//!
+//! The fixtures of the locales need their bundled tailorings, of the `locales` feature.
+
+#![cfg(feature = "locales")]
+
+use collate_rs::algorithm::Collator;
+use std::cmp::Ordering;
+use std::path::Path;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/intl_collator");
+
+/// Minimum agreement with ICU for every fixture. The remaining pairs differ because of the CLDR
+/// root modifications of DUCET and the Unicode version of the bundled data.
+const MIN_AGREEMENT: f64 = 0.99;
+
+/// A fixture: the strings in the engine's order, and whether each one compared equal to the
+/// previous one.
+fn read_fixture(name: &str) -> Vec<(bool, String)> {
+    let path = Path::new(FIXTURES).join(format!("{}.txt", name));
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Could not read {}: {}", path.display(), e))
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| {
+            let (relation, s) = line.split_once('\t').expect("Ill-formed fixture line.");
+            (relation == "=", s.to_string())
+        })
+        .collect()
+}
+
+/// Fraction of the pairs of strings of the fixture ordered as the engine orders them.
+fn agreement(collator: &Collator, fixture: &[(bool, String)]) -> f64 {
+    let keys: Vec<_> = fixture.iter().map(|(_, s)| collator.sort_key(s)).collect();
+    let mut agreeing = 0;
+    let mut total = 0;
+    for i in 0..keys.len() {
+        let mut expected = Ordering::Equal;
+        for j in i + 1..keys.len() {
+            if !fixture[j].0 {
+                expected = Ordering::Less;
+            }
+            total += 1;
+            if keys[i].try_cmp(&keys[j]).unwrap() == expected {
+                agreeing += 1;
+            }
+        }
+    }
+    agreeing as f64 / total as f64
+}
+
+#[test]
+fn intl_collator_agreement() {
+    let mut report = vec![];
+    for name in [
+        "root",
+        "de",
+        "de-u-co-phonebk",
+        "da",
+        "nb",
+        "es",
+        "es-u-co-trad",
+        "sv",
+        "tr",
+    ] {
+        let collator = Collator::try_new(name).unwrap();
+        let agreement = agreement(&collator, &read_fixture(name));
+        report.push(format!("{:<16} {:>6.2}%", name, agreement * 100.0));
+        assert!(
+            agreement >= MIN_AGREEMENT,
+            "Agreement with ICU for {} dropped to {:.2}%.",
+            name,
+            agreement * 100.0
+        );
+    }
+    println!("Agreement with Intl.Collator:");
+    for line in report {
+        println!("  {}", line);
+    }
+}
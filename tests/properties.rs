@@ -0,0 +1,128 @@
+//! Property tests of the comparisons: the comparisons of the collator agree with the binary
+//! comparison of its sort keys, which agree with the keys built from its collation element arrays,
+//! and with the keys of `collate_rs::storage`, and the ordering is a total order.
+
+use collate_rs::algorithm::{
+    CaseFirst, Collator, CollatorOptions, CollatorScratch, KeyEncoding, SortKey, Strength,
+};
+use collate_rs::definitions::variable_weighting::VariableWeighting;
+use collate_rs::storage;
+use proptest::prelude::*;
+use std::cmp::Ordering;
+use std::sync::OnceLock;
+
+/// The collators of the DUCET under test, one per path of the algorithm worth covering.
+fn collators() -> &'static [Collator] {
+    static COLLATORS: OnceLock<Vec<Collator>> = OnceLock::new();
+    COLLATORS.get_or_init(|| {
+        let root = Collator::try_new("und").unwrap();
+        [
+            CollatorOptions::default(),
+            CollatorOptions {
+                variable_weighting: VariableWeighting::Shifted,
+                strength: Strength::Quaternary,
+                ..Default::default()
+            },
+            CollatorOptions {
+                key_encoding: KeyEncoding::Fractional,
+                variable_weighting: VariableWeighting::Blanked,
+                ..Default::default()
+            },
+            CollatorOptions {
+                strength: Strength::Primary,
+                case_level: true,
+                ..Default::default()
+            },
+            CollatorOptions {
+                backward_secondary: true,
+                case_first: CaseFirst::Upper,
+                numeric: true,
+                ..Default::default()
+            },
+        ]
+        .into_iter()
+        .map(|options| root.clone_with_options(options))
+        .collect()
+    })
+}
+
+/// Arbitrary strings, and strings of characters that often tie or interact: cases, accents,
+/// combining marks, contractions, digits, spaces and punctuation.
+fn string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<String>(),
+        "[aAáÁbBcChHlL·0-9 \\-'\u{300}\u{301}\u{308}\u{327}\u{E01}\u{E40}\u{4E00}]{0,8}",
+    ]
+}
+
+/// Whether the collator builds its sort keys with the levels of its collation element arrays
+/// as they are, as [`SortKey::with_max_level`] does.
+fn plain_levels(options: &CollatorOptions) -> bool {
+    !options.backward_secondary && !options.case_level && options.case_first == CaseFirst::Off
+}
+
+proptest! {
+    #[test]
+    fn compare_agrees_with_sort_keys(a in string(), b in string()) {
+        let mut scratch = CollatorScratch::new();
+        for collator in collators() {
+            let keys = collator.sort_key(&a).as_bytes().cmp(collator.sort_key(&b).as_bytes());
+            prop_assert_eq!(collator.compare(&a, &b), keys);
+            prop_assert_eq!(collator.compare_with(&a, &b, &mut scratch), keys);
+            prop_assert_eq!(collator.eq_str(&a, &b), keys == Ordering::Equal);
+        }
+    }
+
+    #[test]
+    fn sort_keys_agree_with_collation_element_arrays(s in string()) {
+        for collator in collators() {
+            let options = collator.options();
+            let key = collator.sort_key(&s);
+            if plain_levels(options) {
+                let array = collator.collation_element_array(&s);
+                let expected = SortKey::with_max_level(
+                    &array,
+                    options.key_encoding,
+                    options.strength.level(),
+                );
+                prop_assert_eq!(&key, &expected);
+            }
+            let mut buffer = vec![];
+            collator.append_sort_key(&s, &mut buffer);
+            prop_assert_eq!(key.as_bytes(), buffer.as_slice());
+        }
+    }
+
+    #[test]
+    fn storage_keys_round_trip_and_sort_as_strings(a in string(), b in string()) {
+        for collator in collators() {
+            let (key_a, key_b) = (storage::encode(collator, &a), storage::encode(collator, &b));
+            prop_assert_eq!(storage::decode(&key_a).unwrap(), a.as_str());
+            let expected = collator.compare(&a, &b);
+            prop_assert_eq!(storage::compare_sort_keys(&key_a, &key_b), expected);
+            prop_assert_eq!(
+                storage::compare(&key_a, &key_b),
+                expected.then(a.as_bytes().cmp(b.as_bytes()))
+            );
+        }
+    }
+
+    #[test]
+    fn ordering_is_total(a in string(), b in string(), c in string()) {
+        for collator in collators() {
+            let (ab, bc, ac) = (
+                collator.compare(&a, &b),
+                collator.compare(&b, &c),
+                collator.compare(&a, &c),
+            );
+            prop_assert_eq!(collator.compare(&a, &a), Ordering::Equal);
+            prop_assert_eq!(collator.compare(&b, &a), ab.reverse());
+            if ab == bc {
+                prop_assert_eq!(ac, ab);
+            }
+            if ab == Ordering::Equal {
+                prop_assert_eq!(ac, bc);
+            }
+        }
+    }
+}